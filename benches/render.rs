@@ -0,0 +1,60 @@
+//! Render-path benchmarks: one timer frame's worth of text, rebuilt from
+//! scratch as the draw loop used to do versus served from the frame cache.
+//! Run with `cargo bench`.
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+
+// The module's unit tests aren't built into the bench harness, and the
+// benches don't use every helper the timer does
+#[allow(unused_imports, dead_code)]
+#[path = "../src/frame.rs"]
+mod frame;
+
+use frame::{BarCache, TimeText};
+
+const BAR_WIDTH: u16 = 30;
+const TOTAL_SECS: u64 = 25 * 60;
+
+/// The pre-cache draw path: format both times and build both bar halves.
+fn uncached_frame(remaining_secs: u64) -> usize {
+    let fmt = |secs: u64| format!("{:02}:{:02}", secs / 60, secs % 60);
+    let remaining = fmt(remaining_secs);
+    let elapsed = fmt(TOTAL_SECS - remaining_secs);
+    let filled = (frame::progress_at((TOTAL_SECS - remaining_secs) * 1000, TOTAL_SECS) * BAR_WIDTH as f64) as u16;
+    let bar_filled = "\u{2588}".repeat(filled as usize);
+    let bar_empty = "\u{2591}".repeat((BAR_WIDTH - filled) as usize);
+    remaining.len() + elapsed.len() + bar_filled.len() + bar_empty.len()
+}
+
+fn render_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_text");
+
+    // Four ticks per second: most frames repeat the previous second
+    group.bench_function("uncached", |b| {
+        let mut tick = 0u64;
+        b.iter(|| {
+            tick += 1;
+            black_box(uncached_frame(TOTAL_SECS - (tick / 4) % TOTAL_SECS))
+        })
+    });
+
+    group.bench_function("cached", |b| {
+        let mut bar = BarCache::new(BAR_WIDTH);
+        let mut remaining = TimeText::default();
+        let mut elapsed = TimeText::default();
+        let mut tick = 0u64;
+        b.iter(|| {
+            tick += 1;
+            let remaining_secs = TOTAL_SECS - (tick / 4) % TOTAL_SECS;
+            let (filled, empty) = bar.get(frame::progress_at((TOTAL_SECS - remaining_secs) * 1000, TOTAL_SECS));
+            black_box(
+                remaining.get(remaining_secs as i64).len() + elapsed.get((TOTAL_SECS - remaining_secs) as i64).len() + filled.len() + empty.len(),
+            )
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, render_text);
+criterion_main!(benches);
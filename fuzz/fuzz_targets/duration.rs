@@ -0,0 +1,23 @@
+//! Duration parsing on arbitrary input: it must never panic, and anything
+//! it accepts must be within `Duration::MAX` and read back from its spec.
+//! Run with `cargo +nightly fuzz run duration` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The module's unit tests aren't built into the fuzz target
+#[allow(dead_code)]
+#[path = "../../src/duration.rs"]
+mod duration;
+
+use duration::Duration;
+
+fuzz_target!(|input: &str| {
+    if let Ok(d) = Duration::parse(input) {
+        assert!(d <= Duration::MAX);
+        assert_eq!(Duration::parse(&d.to_spec()), Ok(d));
+    }
+    let _ = Duration::smaller_unit(input);
+    let _ = Duration::until_clock(input, chrono::NaiveTime::MIN);
+});
@@ -0,0 +1,162 @@
+//! Work-round length suggestions from the session log: how often rounds of
+//! each length were finished, and a length you tend to finish. Shown when a
+//! session starts; with `adaptive = true` the suggestion is applied.
+
+use crate::duration::Duration;
+use crate::log::SessionRecord;
+
+/// Rounds planned at a length before its completion rate counts.
+const MIN_ROUNDS: u32 = 8;
+/// Completion rate a length should reach.
+const TARGET_RATE: f64 = 0.8;
+/// How far to step down when no shorter length has enough history.
+const STEP_SECS: u64 = 5 * 60;
+/// Never suggest work rounds shorter than this.
+const MIN_WORK_SECS: u64 = 10 * 60;
+
+/// Rounds finished out of rounds planned, for one work length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub work_secs: u64,
+    pub completed: u32,
+    pub planned: u32,
+}
+
+impl Rate {
+    fn value(&self) -> f64 {
+        f64::from(self.completed) / f64::from(self.planned.max(1))
+    }
+
+    fn percent(&self) -> u32 {
+        (self.value() * 100.0).round() as u32
+    }
+
+    fn enough(&self) -> bool {
+        self.planned >= MIN_ROUNDS
+    }
+}
+
+/// A different work length to try, and why.
+#[derive(Debug, PartialEq)]
+pub struct Suggestion {
+    pub work_secs: u64,
+    pub current: Rate,
+    /// History at the suggested length, if there is any.
+    pub suggested: Option<Rate>,
+}
+
+impl Suggestion {
+    pub fn reason(&self) -> String {
+        let current = Duration::from_secs(self.current.work_secs).to_spec();
+        let mut reason = format!("you finish {}% of {current} rounds", self.current.percent());
+        if let Some(rate) = self.suggested {
+            let suggested = Duration::from_secs(rate.work_secs).to_spec();
+            reason.push_str(&format!(" and {}% of {suggested} ones", rate.percent()));
+        }
+        reason
+    }
+}
+
+/// Completion rates per work length, shortest first. Sessions from before
+/// lengths were recorded, and sequences, have none and are left out.
+pub fn rates(sessions: &[SessionRecord]) -> Vec<Rate> {
+    let mut rates: Vec<Rate> = Vec::new();
+    for session in sessions.iter().filter(|s| s.work_secs > 0) {
+        let index = match rates.iter().position(|r| r.work_secs == session.work_secs) {
+            Some(index) => index,
+            None => {
+                rates.push(Rate { work_secs: session.work_secs, completed: 0, planned: 0 });
+                rates.len() - 1
+            }
+        };
+        rates[index].completed += session.rounds_completed;
+        rates[index].planned += session.rounds_planned;
+    }
+    rates.sort_by_key(|r| r.work_secs);
+    rates
+}
+
+/// The longest length finished often enough, when it isn't `work_secs`. If
+/// rounds at `work_secs` are usually left unfinished and nothing shorter has
+/// a record, one step shorter.
+pub fn suggest(sessions: &[SessionRecord], work_secs: u64) -> Option<Suggestion> {
+    let rates = rates(sessions);
+    let current = *rates.iter().find(|r| r.work_secs == work_secs && r.enough())?;
+    let best = rates.iter().rev().find(|r| r.enough() && r.value() >= TARGET_RATE);
+    match best {
+        Some(best) if best.work_secs == work_secs => None,
+        // Only move up from a length that's going well
+        Some(best) if best.work_secs > work_secs && current.value() < TARGET_RATE => None,
+        Some(best) => Some(Suggestion { work_secs: best.work_secs, current, suggested: Some(*best) }),
+        None if work_secs > MIN_WORK_SECS => Some(Suggestion {
+            work_secs: work_secs.saturating_sub(STEP_SECS).max(MIN_WORK_SECS),
+            current,
+            suggested: None,
+        }),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::SessionEnd;
+    use chrono::Local;
+
+    fn session(work_mins: u64, completed: u32, planned: u32) -> SessionRecord {
+        SessionRecord {
+            name: "pomodoro".to_string(),
+            rounds_planned: planned,
+            rounds_completed: completed,
+            rounds_skipped: 0,
+            focus_secs: 0,
+            work_secs: work_mins * 60,
+            note: None,
+            started_at: Local::now(),
+            ended_at: Local::now(),
+            end: SessionEnd::Completed,
+        }
+    }
+
+    #[test]
+    fn rates_grouped_by_length() {
+        let sessions = [session(25, 2, 4), session(50, 1, 2), session(25, 4, 4), session(0, 4, 4)];
+        assert_eq!(
+            rates(&sessions),
+            [Rate { work_secs: 1500, completed: 6, planned: 8 }, Rate { work_secs: 3000, completed: 1, planned: 2 }]
+        );
+    }
+
+    #[test]
+    fn shorter_length_suggested_when_current_unfinished() {
+        let sessions = [session(25, 2, 4), session(25, 2, 4), session(20, 4, 4), session(20, 4, 4)];
+        let suggestion = suggest(&sessions, 1500).unwrap();
+        assert_eq!(suggestion.work_secs, 1200);
+        assert_eq!(suggestion.reason(), "you finish 50% of 25m rounds and 100% of 20m ones");
+        assert_eq!(suggest(&sessions, 1200), None);
+    }
+
+    #[test]
+    fn longer_length_only_from_a_finished_one() {
+        let sessions = [session(25, 4, 4), session(25, 4, 4), session(50, 8, 8)];
+        assert_eq!(suggest(&sessions, 1500).map(|s| s.work_secs), Some(3000));
+        let sessions = [session(25, 1, 4), session(25, 1, 4), session(50, 8, 8)];
+        assert_eq!(suggest(&sessions, 1500), None);
+    }
+
+    #[test]
+    fn steps_down_without_history() {
+        let sessions = [session(25, 1, 4), session(25, 2, 4)];
+        let suggestion = suggest(&sessions, 1500).unwrap();
+        assert_eq!((suggestion.work_secs, suggestion.suggested), (1200, None));
+        assert_eq!(suggestion.reason(), "you finish 38% of 25m rounds");
+        let sessions = [session(10, 1, 4), session(10, 2, 4)];
+        assert_eq!(suggest(&sessions, 600), None);
+    }
+
+    #[test]
+    fn too_little_history_no_suggestion() {
+        assert_eq!(suggest(&[session(25, 0, 4)], 1500), None);
+        assert_eq!(suggest(&[], 1500), None);
+    }
+}
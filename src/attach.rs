@@ -0,0 +1,137 @@
+//! `tik attach`: a stream of the running timer for editor statuslines and
+//! other plugins. One line is written whenever what it shows changes: a JSON
+//! object by default, or the `tik status --compact` text. When no timer is
+//! running it says so and keeps waiting, so a plugin can stay attached across
+//! timers, restarts and daemon timers alike.
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::io::Write;
+
+use crate::duration::TimeStyle;
+use crate::state::RuntimeState;
+use crate::timer::TimerContext;
+
+/// How often the state file is read.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// `{"running":true,"name":"pomodoro","phase":"work",...}`, or
+    /// `{"running":false}`.
+    Json,
+    /// "work 24m", or an empty line.
+    Line,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct Update<'a> {
+    running: bool,
+    #[serde(flatten)]
+    timer: Option<TimerUpdate<'a>>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct TimerUpdate<'a> {
+    name: &'a str,
+    phase: TimerContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    round: Option<(u32, u32)>,
+    remaining_secs: u64,
+    total_secs: u64,
+    paused: bool,
+}
+
+/// The line to write for `state` at `now`.
+fn line(state: Option<&RuntimeState>, format: Format, now: DateTime<Local>) -> String {
+    match format {
+        Format::Line => state.map_or_else(String::new, |s| crate::state::status_line(s, now, TimeStyle::Compact, None)),
+        Format::Json => {
+            let update = Update {
+                running: state.is_some(),
+                timer: state.map(|s| TimerUpdate {
+                    name: &s.name,
+                    phase: s.phase,
+                    round: s.round,
+                    remaining_secs: s.remaining_at(now),
+                    total_secs: s.total_secs,
+                    paused: s.paused,
+                }),
+            };
+            serde_json::to_string(&update).unwrap_or_default()
+        }
+    }
+}
+
+/// Write an update line whenever the running timer changes, until stdout
+/// is closed. With `once`, write the current line and return.
+pub async fn run(format: Format, once: bool) {
+    let mut stdout = std::io::stdout();
+    let mut last = None;
+    loop {
+        let current = line(crate::state::read().as_ref(), format, Local::now());
+        if last.as_ref() != Some(&current) {
+            // A closed pipe means the plugin detached
+            if writeln!(stdout, "{current}").and_then(|()| stdout.flush()).is_err() {
+                return;
+            }
+            last = Some(current);
+        }
+        if once || reader_gone() {
+            return;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Whether the other end of stdout has been closed. Without this a plugin
+/// that detaches while no timer runs would leave tik waiting for a change to
+/// write before noticing.
+#[cfg(unix)]
+fn reader_gone() -> bool {
+    let mut fd = libc::pollfd { fd: libc::STDOUT_FILENO, events: 0, revents: 0 };
+    // SAFETY: one valid pollfd, zero timeout
+    let ready = unsafe { libc::poll(&mut fd, 1, 0) };
+    ready > 0 && fd.revents & (libc::POLLERR | libc::POLLHUP) != 0
+}
+
+#[cfg(not(unix))]
+fn reader_gone() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(now: DateTime<Local>) -> RuntimeState {
+        RuntimeState {
+            version: crate::state::SCHEMA_VERSION,
+            pid: 1,
+            name: "pomodoro".to_string(),
+            phase: TimerContext::Work,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 1440,
+            paused: false,
+            updated_at: now - chrono::Duration::seconds(30),
+        }
+    }
+
+    #[test]
+    fn json_lines_for_a_timer_and_for_none() {
+        let now = Local::now();
+        assert_eq!(
+            line(Some(&state(now)), Format::Json, now),
+            r#"{"running":true,"name":"pomodoro","phase":"work","round":[2,4],"remaining_secs":1410,"total_secs":1500,"paused":false}"#
+        );
+        assert_eq!(line(None, Format::Json, now), r#"{"running":false}"#);
+    }
+
+    #[test]
+    fn text_lines_match_compact_status() {
+        let now = Local::now();
+        assert_eq!(line(Some(&state(now)), Format::Line, now), "work 23m");
+        assert_eq!(line(None, Format::Line, now), "");
+    }
+}
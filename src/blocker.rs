@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::BlockerConfig;
+
+const BEGIN_MARKER: &str = "# >>> tik focus block >>>";
+const END_MARKER: &str = "# <<< tik focus block <<<";
+
+#[cfg(windows)]
+const DEFAULT_HOSTS_FILE: &str = r"C:\Windows\System32\drivers\etc\hosts";
+#[cfg(not(windows))]
+const DEFAULT_HOSTS_FILE: &str = "/etc/hosts";
+
+/// Blocks distracting sites for the lifetime of a work phase: domains are
+/// pointed at 0.0.0.0 in the hosts file and `start_command` runs; dropping
+/// the guard removes the entries and runs `stop_command`.
+pub struct FocusBlock<'a> {
+    config: &'a BlockerConfig,
+    hosts_edited: bool,
+}
+
+impl<'a> FocusBlock<'a> {
+    pub fn start(config: &'a BlockerConfig) -> Self {
+        let mut hosts_edited = false;
+        if !config.domains.is_empty() {
+            match edit_hosts(&hosts_path(config), |contents| add_block(contents, &config.domains)) {
+                Ok(()) => hosts_edited = true,
+                Err(e) => crate::messages::warn(format!("Focus blocking: {e}")),
+            }
+        }
+        if let Some(command) = &config.start_command {
+            crate::hooks::spawn(command);
+        }
+        FocusBlock { config, hosts_edited }
+    }
+}
+
+impl Drop for FocusBlock<'_> {
+    fn drop(&mut self) {
+        if self.hosts_edited
+            && let Err(e) = edit_hosts(&hosts_path(self.config), remove_block)
+        {
+            crate::messages::warn(format!("Focus blocking: {e}"));
+        }
+        if let Some(command) = &self.config.stop_command {
+            crate::hooks::spawn(command);
+        }
+    }
+}
+
+fn hosts_path(config: &BlockerConfig) -> PathBuf {
+    PathBuf::from(config.hosts_file.as_deref().unwrap_or(DEFAULT_HOSTS_FILE))
+}
+
+fn edit_hosts(path: &Path, edit: impl FnOnce(&str) -> Result<String, String>) -> Result<(), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("can't read {}: {e}", path.display()))?;
+    let updated = edit(&contents).map_err(|e| format!("{} left as it is: {e}", path.display()))?;
+    if updated != contents {
+        replace_file(path, &updated).map_err(|e| format!("can't update {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Write a temporary file beside `path` and rename it over it, so a crash
+/// or a full disk never leaves the hosts file half written. A symlinked
+/// hosts file has its target replaced, and the permissions are kept.
+fn replace_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let path = std::fs::canonicalize(path)?;
+    let permissions = std::fs::metadata(&path)?.permissions();
+    let name = path.file_name().map_or_else(|| "hosts".into(), |name| name.to_string_lossy());
+    let tmp = path.with_file_name(format!(".{name}.tik-{}.tmp", std::process::id()));
+    let written = (|| {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        std::fs::set_permissions(&tmp, permissions)?;
+        std::fs::rename(&tmp, &path)
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Append a marked block of entries, replacing any block left by an earlier run.
+fn add_block(contents: &str, domains: &[String]) -> Result<String, String> {
+    let mut out = remove_block(contents)?;
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(BEGIN_MARKER);
+    out.push('\n');
+    for domain in domains {
+        let domain = domain.trim();
+        out.push_str(&format!("0.0.0.0 {domain}\n"));
+        if !domain.starts_with("www.") {
+            out.push_str(&format!("0.0.0.0 www.{domain}\n"));
+        }
+    }
+    out.push_str(END_MARKER);
+    out.push('\n');
+    Ok(out)
+}
+
+/// The contents without the marked block. A begin marker with no end
+/// marker after it is an error: where the block ends is anyone's guess, and
+/// dropping everything after it would take the user's own entries too.
+fn remove_block(contents: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(contents.len());
+    let mut in_block = false;
+    for line in contents.split_inclusive('\n') {
+        match line.trim_end() {
+            BEGIN_MARKER => in_block = true,
+            END_MARKER => in_block = false,
+            _ if !in_block => out.push_str(line),
+            _ => {}
+        }
+    }
+    if in_block {
+        return Err(format!("it has a '{BEGIN_MARKER}' line but no '{END_MARKER}' after it; remove the block by hand"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTS: &str = "127.0.0.1 localhost\n::1 localhost\n";
+
+    #[test]
+    fn add_block_appends_marked_entries() {
+        let updated = add_block(HOSTS, &["twitter.com".to_string(), "www.reddit.com".to_string()]).unwrap();
+        assert_eq!(
+            updated,
+            "127.0.0.1 localhost\n::1 localhost\n\
+             # >>> tik focus block >>>\n\
+             0.0.0.0 twitter.com\n\
+             0.0.0.0 www.twitter.com\n\
+             0.0.0.0 www.reddit.com\n\
+             # <<< tik focus block <<<\n"
+        );
+    }
+
+    #[test]
+    fn remove_block_restores_original() {
+        let updated = add_block(HOSTS, &["twitter.com".to_string()]).unwrap();
+        assert_eq!(remove_block(&updated), Ok(HOSTS.to_string()));
+    }
+
+    #[test]
+    fn add_block_replaces_stale_block() {
+        let once = add_block(HOSTS, &["a.com".to_string()]).unwrap();
+        let twice = add_block(&once, &["b.com".to_string()]).unwrap();
+        assert!(!twice.contains("a.com"));
+        assert_eq!(twice.matches(BEGIN_MARKER).count(), 1);
+    }
+
+    #[test]
+    fn add_block_handles_missing_trailing_newline() {
+        let updated = add_block("127.0.0.1 localhost", &["a.com".to_string()]).unwrap();
+        assert!(updated.starts_with("127.0.0.1 localhost\n# >>>"));
+    }
+
+    #[test]
+    fn unterminated_block_is_left_alone() {
+        let dir = std::env::temp_dir().join(format!("pomitik-hosts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hosts");
+        let broken = format!("{HOSTS}{BEGIN_MARKER}\n0.0.0.0 a.com\n192.168.1.2 nas\n");
+        std::fs::write(&path, &broken).unwrap();
+        assert!(edit_hosts(&path, remove_block).unwrap_err().contains("no '# <<< tik focus block <<<'"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), broken);
+
+        std::fs::write(&path, HOSTS).unwrap();
+        edit_hosts(&path, |contents| add_block(contents, &["a.com".to_string()])).unwrap();
+        edit_hosts(&path, remove_block).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), HOSTS);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,479 @@
+//! `tik log browse`: the log as a scrollable list, newest first, with the
+//! selected block's details underneath. Blocks can be filtered, given a
+//! different task or tags, or deleted; each change is written to the log as
+//! soon as it's made.
+
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+use std::io::{self, Write};
+
+use crate::dates::DateRange;
+use crate::input::Input;
+use crate::log::{self, LogEntry};
+use crate::render::{self, Screen};
+use crate::text;
+
+const LIST_HINTS: &str = "[↑↓] move  [/] filter  [e] task  [t] tags  [d] delete  [q] quit";
+const EDIT_HINTS: &str = "[enter] save  [esc] cancel";
+const DELETE_HINTS: &str = "[y] delete  [n] keep";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Task,
+    Tags,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    List,
+    /// Typing the filter; the list narrows as it changes.
+    Filter,
+    Edit(Field, String),
+    ConfirmDelete,
+}
+
+/// What a key asks the run loop to do.
+#[derive(Debug, PartialEq)]
+enum Step {
+    Stay,
+    Quit,
+    /// Replace the block with the new one, or delete it.
+    Write(Box<(LogEntry, Option<LogEntry>)>),
+}
+
+struct Browser {
+    /// As in the file, oldest first.
+    entries: Vec<LogEntry>,
+    filter: String,
+    /// Position in `visible()`.
+    selected: usize,
+    /// First list position on screen.
+    top: usize,
+    mode: Mode,
+    /// Shown in place of the hints until the next key.
+    message: Option<String>,
+}
+
+impl Browser {
+    fn new(entries: Vec<LogEntry>) -> Self {
+        Browser { entries, filter: String::new(), selected: 0, top: 0, mode: Mode::List, message: None }
+    }
+
+    /// Indices into `entries` that match the filter, newest first.
+    fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        let mut visible: Vec<usize> =
+            (0..self.entries.len()).filter(|&i| log::entry_matches(&self.entries[i], &filter)).collect();
+        visible.sort_by_key(|&i| std::cmp::Reverse(self.entries[i].completed_at));
+        visible
+    }
+
+    fn current(&self) -> Option<&LogEntry> {
+        self.visible().get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    /// Swap in the log as written, keeping the selection in range.
+    fn reload(&mut self, entries: Vec<LogEntry>) {
+        self.entries = entries;
+        self.selected = self.selected.min(self.visible().len().saturating_sub(1));
+    }
+
+    /// Move the selection by `by` rows, stopping at either end.
+    fn move_by(&mut self, by: isize) {
+        let last = self.visible().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    /// Scroll so the selection is among the `height` rows on screen.
+    fn scroll(&mut self, height: usize) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if height > 0 && self.selected >= self.top + height {
+            self.top = self.selected + 1 - height;
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent, page: usize) -> Step {
+        self.message = None;
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Step::Quit;
+        }
+        match std::mem::replace(&mut self.mode, Mode::List) {
+            Mode::List => return self.list_key(key.code, page),
+            Mode::Filter => match key.code {
+                KeyCode::Enter => {}
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.selected = 0;
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.selected = 0;
+                    self.mode = Mode::Filter;
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.selected = 0;
+                    self.mode = Mode::Filter;
+                }
+                _ => self.mode = Mode::Filter,
+            },
+            Mode::Edit(field, mut value) => match key.code {
+                KeyCode::Enter => return self.edited(field, &value),
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    value.pop();
+                    self.mode = Mode::Edit(field, value);
+                }
+                KeyCode::Char(c) => {
+                    value.push(c);
+                    self.mode = Mode::Edit(field, value);
+                }
+                _ => self.mode = Mode::Edit(field, value),
+            },
+            Mode::ConfirmDelete => {
+                if let (KeyCode::Char('y'), Some(entry)) = (key.code, self.current()) {
+                    return Step::Write(Box::new((entry.clone(), None)));
+                }
+            }
+        }
+        Step::Stay
+    }
+
+    fn list_key(&mut self, code: KeyCode, page: usize) -> Step {
+        let page = page.max(1) as isize;
+        match code {
+            KeyCode::Char('q') => return Step::Quit,
+            KeyCode::Esc if self.filter.is_empty() => return Step::Quit,
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.selected = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-page),
+            KeyCode::PageDown => self.move_by(page),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX),
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('e') => {
+                if let Some(entry) = self.current() {
+                    self.mode = Mode::Edit(Field::Task, entry.task.clone().unwrap_or_default());
+                }
+            }
+            KeyCode::Char('t') => {
+                if let Some(entry) = self.current() {
+                    self.mode = Mode::Edit(Field::Tags, entry.tags.join(" "));
+                }
+            }
+            KeyCode::Char('d') if self.current().is_some() => self.mode = Mode::ConfirmDelete,
+            _ => {}
+        }
+        Step::Stay
+    }
+
+    /// The selected block with the edited field set from `value`.
+    fn edited(&self, field: Field, value: &str) -> Step {
+        let Some(old) = self.current() else {
+            return Step::Stay;
+        };
+        let mut new = old.clone();
+        match field {
+            Field::Task => new.task = Some(value.trim().to_string()).filter(|t| !t.is_empty()),
+            Field::Tags => new.tags = parse_tags(value),
+        }
+        if new == *old {
+            return Step::Stay;
+        }
+        Step::Write(Box::new((old.clone(), Some(new))))
+    }
+
+    /// The bottom line: a prompt while typing, else a message or the key hints.
+    fn status(&self) -> (String, &'static str) {
+        match &self.mode {
+            Mode::List => (self.message.clone().unwrap_or_default(), LIST_HINTS),
+            Mode::Filter => (format!("Filter: {}_", self.filter), EDIT_HINTS),
+            Mode::Edit(Field::Task, value) => (format!("Task: {value}_"), EDIT_HINTS),
+            Mode::Edit(Field::Tags, value) => (format!("Tags: {value}_"), EDIT_HINTS),
+            Mode::ConfirmDelete => ("Delete this block?".to_string(), DELETE_HINTS),
+        }
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        let mut stdout = io::stdout();
+        execute!(stdout, ResetColor, terminal::Clear(ClearType::All))?;
+        if render::draw_too_small(&mut crate::surface::Terminal::new(), cols, rows)? {
+            return stdout.flush();
+        }
+
+        let visible = self.visible();
+        let details = self.current().map(detail_lines).unwrap_or_default();
+        // Heading, blank, separator and status; the details take at most half the rest
+        let room = rows.saturating_sub(4) as usize;
+        let detail_height = details.len().min(room / 2);
+        let list_height = room - detail_height;
+        self.scroll(list_height);
+
+        let mut heading = format!("Log: {} block{}", visible.len(), if visible.len() == 1 { "" } else { "s" });
+        if !self.filter.is_empty() {
+            heading.push_str(&format!(" matching '{}'", self.filter));
+        }
+        execute!(
+            stdout,
+            cursor::MoveTo(0, 0),
+            SetAttribute(Attribute::Bold),
+            Print(text::truncate(&heading, cols)),
+            SetAttribute(Attribute::Reset),
+        )?;
+        if visible.is_empty() {
+            let empty = if self.entries.is_empty() { "No blocks logged yet." } else { "Nothing matches the filter." };
+            execute!(stdout, cursor::MoveTo(0, 2), SetForegroundColor(Color::DarkGrey), Print(empty), ResetColor)?;
+        }
+        for (row, position) in (self.top..visible.len()).take(list_height).enumerate() {
+            let line = text::truncate(&row_text(&self.entries[visible[position]]), cols).into_owned();
+            let line = format!("{line:<width$}", width = cols as usize);
+            execute!(stdout, cursor::MoveTo(0, 2 + row as u16))?;
+            if position == self.selected {
+                execute!(stdout, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset))?;
+            } else {
+                execute!(stdout, Print(line))?;
+            }
+        }
+
+        let separator = 2 + list_height as u16;
+        execute!(
+            stdout,
+            cursor::MoveTo(0, separator),
+            SetForegroundColor(Color::DarkGrey),
+            Print("─".repeat(cols as usize)),
+            ResetColor,
+        )?;
+        for (i, line) in details.iter().take(detail_height).enumerate() {
+            execute!(stdout, cursor::MoveTo(0, separator + 1 + i as u16), Print(text::truncate(line, cols)))?;
+        }
+
+        let (prompt, hints) = self.status();
+        let hints = if prompt.is_empty() { hints.to_string() } else { format!("{prompt}  {hints}") };
+        execute!(
+            stdout,
+            cursor::MoveTo(0, rows - 1),
+            SetForegroundColor(Color::DarkGrey),
+            Print(text::truncate(&hints, cols)),
+            ResetColor,
+        )?;
+        stdout.flush()
+    }
+}
+
+/// Tags typed as words, separated by spaces or commas, with or without `#`.
+fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value.split([' ', ',']).map(|t| t.trim().trim_start_matches('#')) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// One list row: when, what, how long, and the task and tags.
+fn row_text(entry: &LogEntry) -> String {
+    let mut line = format!(
+        "{}  {:<14} {:>7}",
+        entry.completed_at.format("%Y-%m-%d %a %H:%M"),
+        entry.name,
+        log::format_duration_human(entry.duration_secs),
+    );
+    if let Some(task) = &entry.task {
+        line.push_str(&format!("  {task}"));
+    }
+    for tag in &entry.tags {
+        line.push_str(&format!("  #{tag}"));
+    }
+    line
+}
+
+/// Every field the block has, one per line.
+fn detail_lines(entry: &LogEntry) -> Vec<String> {
+    let mut lines = vec![format!("{:<10}{}", "finished", entry.completed_at.format("%a %Y-%m-%d %H:%M:%S"))];
+    let mut length = log::format_duration_human(entry.duration_secs);
+    if entry.extended_secs > 0 {
+        length.push_str(&format!(" ({} in flow)", log::format_duration_human(entry.extended_secs)));
+    }
+    lines.push(format!("{:<10}{} {length}", "length", entry.name));
+    if let Some(task) = &entry.task {
+        let estimate = entry.estimate.map(|e| format!(" (estimate {e})")).unwrap_or_default();
+        lines.push(format!("{:<10}{task}{estimate}", "task"));
+    }
+    if !entry.tags.is_empty() {
+        let tags: Vec<String> = entry.tags.iter().map(|t| format!("#{t}")).collect();
+        lines.push(format!("{:<10}{}", "tags", tags.join(" ")));
+    }
+    if let Some(project) = &entry.project {
+        lines.push(format!("{:<10}{project}", "project"));
+    }
+    if let Some(repo) = &entry.repo {
+        let branch = entry.branch.as_deref().map(|b| format!("@{b}")).unwrap_or_default();
+        lines.push(format!("{:<10}{repo}{branch}", "repo"));
+    }
+    if let Some(cwd) = &entry.cwd {
+        lines.push(format!("{:<10}{cwd}", "directory"));
+    }
+    if entry.skipped_secs > 0 {
+        lines.push(format!("{:<10}{} of break", "skipped", log::format_duration_human(entry.skipped_secs)));
+    }
+    if entry.repaid_secs > 0 {
+        lines.push(format!("{:<10}{} of break debt", "repaid", log::format_duration_human(entry.repaid_secs)));
+    }
+    lines
+}
+
+/// Browse the blocks within `range` until quit with `q`, esc or Ctrl+C.
+pub async fn run(range: DateRange) {
+    let within = |entries: Vec<LogEntry>| entries.into_iter().filter(|e| range.contains(e.completed_at.date_naive())).collect();
+    let mut browser = Browser::new(within(log::read_entries()));
+    let input = Input::start();
+    let _screen = match Screen::enter(true) {
+        Ok(screen) => screen,
+        Err(e) => {
+            eprintln!("Failed to setup terminal: {e}");
+            return;
+        }
+    };
+    let _ = browser.draw();
+    while let Some(event) = input.next().await {
+        let key = match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            Event::Resize(..) => {
+                let _ = browser.draw();
+                continue;
+            }
+            _ => continue,
+        };
+        // Rows the list gets at most, for page up and down
+        let page = terminal::size().map_or(10, |(_, rows)| rows.saturating_sub(4) as usize / 2);
+        match browser.key(key, page) {
+            Step::Stay => {}
+            Step::Quit => break,
+            Step::Write(change) => match log::replace_entry(&change.0, change.1) {
+                Ok(Some(entries)) => browser.reload(within(entries)),
+                Ok(None) => {
+                    browser.reload(within(log::read_entries()));
+                    browser.message = Some("That block changed in the log meanwhile; reloaded.".to_string());
+                }
+                Err(e) => browser.message = Some(format!("Couldn't write the log: {e}")),
+            },
+        }
+        let _ = browser.draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn entry(hour: u32, task: Option<&str>, tags: &[&str]) -> LogEntry {
+        LogEntry {
+            name: "pomodoro".to_string(),
+            duration_secs: 1500,
+            completed_at: Local.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap(),
+            task: task.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn press(browser: &mut Browser, keys: &str) -> Step {
+        let mut step = Step::Stay;
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                '\x08' => KeyCode::Backspace,
+                c => KeyCode::Char(c),
+            };
+            step = browser.key(KeyEvent::new(code, KeyModifiers::NONE), 5);
+        }
+        step
+    }
+
+    #[test]
+    fn newest_first_and_filtered() {
+        let mut browser = Browser::new(vec![entry(9, Some("spec"), &[]), entry(10, None, &["admin"]), entry(11, Some("review"), &[])]);
+        assert_eq!(browser.visible(), [2, 1, 0]);
+        press(&mut browser, "/ADMIN\n");
+        assert_eq!(browser.visible(), [1]);
+        press(&mut browser, "\x1b");
+        assert_eq!(browser.visible(), [2, 1, 0]);
+        assert_eq!(press(&mut browser, "\x1b"), Step::Quit);
+    }
+
+    #[test]
+    fn selection_stays_in_range_and_on_screen() {
+        let mut browser = Browser::new((0..10).map(|h| entry(h, None, &[])).collect());
+        press(&mut browser, "jjjjjjj");
+        browser.scroll(3);
+        assert_eq!((browser.selected, browser.top), (7, 5));
+        press(&mut browser, "G");
+        assert_eq!(browser.selected, 9);
+        press(&mut browser, "j");
+        assert_eq!(browser.selected, 9);
+        press(&mut browser, "g");
+        browser.scroll(3);
+        assert_eq!((browser.selected, browser.top), (0, 0));
+    }
+
+    #[test]
+    fn edits_and_deletes_the_selected_block() {
+        let mut browser = Browser::new(vec![entry(9, Some("spec"), &[]), entry(10, Some("review"), &["work"])]);
+        let old = browser.entries[1].clone();
+        let mut new = old.clone();
+        new.task = Some("review PR".to_string());
+        assert_eq!(press(&mut browser, "e PR\n"), Step::Write(Box::new((old.clone(), Some(new)))));
+
+        let mut new = old.clone();
+        new.tags = vec!["work".to_string(), "code".to_string()];
+        assert_eq!(press(&mut browser, "t #code,work\n"), Step::Write(Box::new((old.clone(), Some(new)))));
+
+        // Unchanged edits and cancelled deletes write nothing
+        assert_eq!(press(&mut browser, "e\n"), Step::Stay);
+        assert_eq!(press(&mut browser, "dn"), Step::Stay);
+        assert_eq!(press(&mut browser, "dy"), Step::Write(Box::new((old, None))));
+    }
+
+    #[test]
+    fn reload_keeps_selection_in_range() {
+        let mut browser = Browser::new(vec![entry(9, None, &[]), entry(10, None, &[])]);
+        press(&mut browser, "j");
+        browser.reload(vec![entry(9, None, &[])]);
+        assert_eq!(browser.selected, 0);
+        browser.reload(Vec::new());
+        assert_eq!((browser.selected, browser.current()), (0, None));
+    }
+
+    #[test]
+    fn details_list_only_set_fields() {
+        let mut block = entry(9, Some("spec"), &["docs"]);
+        block.estimate = Some(3);
+        block.repo = Some("pomitik".to_string());
+        block.branch = Some("main".to_string());
+        assert_eq!(
+            detail_lines(&block),
+            [
+                "finished  Mon 2026-03-02 09:00:00",
+                "length    pomodoro 25m",
+                "task      spec (estimate 3)",
+                "tags      #docs",
+                "repo      pomitik@main",
+            ]
+        );
+        assert_eq!(row_text(&block), "2026-03-02 Mon 09:00  pomodoro           25m  spec  #docs");
+    }
+}
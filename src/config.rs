@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SessionConfig {
@@ -11,32 +11,599 @@ pub struct SessionConfig {
     pub rounds: u32,
 }
 
+impl SessionConfig {
+    /// Total work time the session plans for, at its configured round count.
+    pub fn planned_work_secs(&self, config: &Config) -> u64 {
+        let work = config.resolve_preset(&self.work).unwrap_or(&self.work);
+        crate::duration::Duration::parse(work).map_or(0, |d| d.total_secs * u64::from(self.rounds))
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Config {
-    #[serde(default)]
+    /// Preset lengths, with per-weekday values already resolved for today.
+    #[serde(default, deserialize_with = "deserialize_presets")]
     pub presets: HashMap<String, String>,
     #[serde(default)]
     pub sessions: HashMap<String, SessionConfig>,
+    /// Presets (or durations) run in order as one session, e.g.
+    /// `routine = ["warmup", "pomodoro", "break", "pomodoro"]`.
+    #[serde(default)]
+    pub sequences: HashMap<String, Vec<String>>,
+    /// Wait for a key press after each phase instead of flowing onward.
+    #[serde(default)]
+    pub require_ack: bool,
+    /// How much the flow key adds near the end of a work block ("off" disables it).
+    #[serde(default)]
+    pub flow_extend: Option<String>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Flash the time for ~2s when a timer reaches zero (default: on).
+    #[serde(default)]
+    pub completion_animation: Option<bool>,
+    /// Keep the screen from blanking or locking during work phases.
+    #[serde(default)]
+    pub inhibit_sleep: bool,
+    /// Shell command run when a break starts; "lock" locks the screen.
+    #[serde(default)]
+    pub break_command: Option<String>,
+    #[serde(default)]
+    pub blocker: BlockerConfig,
+    #[serde(default)]
+    pub plan: PlanConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub discord: DiscordConfig,
+    #[serde(default)]
+    pub footer: FooterConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    /// Weekly time budgets per tag, e.g. `"client-a" = "10h/week"`.
+    #[serde(default)]
+    pub budgets: HashMap<String, String>,
+    /// Project names by directory, e.g. `"~/work/acme" = "acme"`. Work started
+    /// inside a listed directory is credited to its project.
+    #[serde(default)]
+    pub projects: HashMap<String, String>,
+    /// Record the directory each timer was started in on its work blocks.
+    #[serde(default)]
+    pub record_cwd: bool,
+    /// Record the git repository and branch each timer was started in.
+    #[serde(default)]
+    pub record_git: bool,
+    /// Wall-clock times: "24h" (default), "12h" or a strftime string.
+    #[serde(default)]
+    pub time_format: Option<String>,
+    /// Sound pack for phase ends and the one-minute warning ("classic", "soft" or a user pack).
+    #[serde(default)]
+    pub sound_pack: Option<String>,
+    /// Tick softly through work phases from the start (`m` toggles it either way).
+    #[serde(default)]
+    pub tick_sound: bool,
+    /// Lengthen a session's long break by the break time skipped today.
+    #[serde(default)]
+    pub repay_break_debt: bool,
+    /// What a skipped work phase logs: the time actually worked (default),
+    /// nothing, or a prompt asking which.
+    #[serde(default)]
+    pub skip_logging: SkipLogging,
+    /// Timer redraws per second, for a smooth progress bar (capped at 30).
+    #[serde(default)]
+    pub frame_rate: Option<u32>,
+    /// Show tenths of a second on timers under a minute (also `--tenths`).
+    #[serde(default)]
+    pub tenths: bool,
+    /// Timers longer than this ask first, to catch typos like `25h` (default
+    /// 24h, "off" disables).
+    #[serde(default)]
+    pub max_duration: Option<String>,
+    /// Screen reader mode (also `--a11y`): plain status lines instead of the
+    /// full-screen timer.
+    #[serde(default)]
+    pub a11y: bool,
+    /// Record how each phase went in a local usage file, for `tik insights`.
+    #[serde(default)]
+    pub insights: bool,
+    /// Change the work length of a session to the one suggested from the
+    /// session log, instead of only showing the suggestion.
+    #[serde(default)]
+    pub adaptive: bool,
+    /// Ask for a retrospective note when a session finishes: "inline" or
+    /// "editor" (default off).
+    #[serde(default)]
+    pub journal: Journal,
+    /// Progress ring drawn with terminal graphics in place of the bar
+    /// (requires the `graphics` build feature): "auto", "kitty", "sixel" or "off".
+    #[serde(default)]
+    pub graphics: Graphics,
+}
+
+/// Which terminal graphics protocol draws the progress ring.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Graphics {
+    /// Whichever the terminal is known to support, if any.
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    Off,
+}
+
+/// Where to write a note when a session finishes.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Journal {
+    #[default]
+    Off,
+    /// A text box on the timer screen.
+    Inline,
+    /// `$VISUAL` or `$EDITOR`.
+    Editor,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipLogging {
+    #[default]
+    Partial,
+    None,
+    Prompt,
+}
+
+/// Discord Rich Presence during work phases (requires the `discord` build feature).
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct DiscordConfig {
+    /// Application ID from the Discord developer portal. Off when unset.
+    pub client_id: Option<String>,
+}
+
+/// MQTT broker to publish timer state to (requires the `mqtt` build feature).
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct MqttConfig {
+    /// Broker host name. The integration is off when unset.
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// Topic prefix: state goes to `<topic>/state`, commands come from `<topic>/command`.
+    pub topic: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
 }
 
+/// Inbound webhooks accepted by `tik daemon`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct WebhookConfig {
+    /// Address to listen on, e.g. "127.0.0.1:7878". Webhooks are off when unset.
+    pub listen: Option<String>,
+    /// Shared secret sent as `Authorization: Bearer <token>`.
+    pub token: Option<String>,
+}
+
+/// Distraction blocking during work phases.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct BlockerConfig {
+    /// Domains pointed at 0.0.0.0 in the hosts file while working.
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Hosts file to edit (default: the system hosts file).
+    pub hosts_file: Option<String>,
+    /// Run when a work phase starts, e.g. to enable an external blocker.
+    pub start_command: Option<String>,
+    /// Run when a work phase ends.
+    pub stop_command: Option<String>,
+}
+
+impl BlockerConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.domains.is_empty() || self.start_command.is_some() || self.stop_command.is_some()
+    }
+}
+
+/// A custom line at the bottom of the timer screen.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct FooterConfig {
+    /// Line to show, e.g. "{battery} | {task} | {command}". Off when unset.
+    pub template: Option<String>,
+    /// Shell command whose first output line fills `{command}`.
+    pub command: Option<String>,
+    /// How often the command and battery are re-read (default: 30s).
+    pub refresh: Option<String>,
+}
+
+/// Notification settings.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct NotifyConfig {
+    pub quiet_hours: Option<QuietHours>,
+    /// Where notifications go; each one gets every notification (default:
+    /// desktop and sound).
+    pub channels: Option<Vec<Channel>>,
+    /// URL the `webhook` channel POSTs a JSON body to.
+    pub webhook_url: Option<String>,
+    /// What the notification says when a work phase (or a standalone timer)
+    /// ends.
+    pub work: Option<NotifyTemplate>,
+    /// What it says when a break ends.
+    #[serde(rename = "break")]
+    pub break_template: Option<NotifyTemplate>,
+}
+
+/// Notification text with placeholders: `{name}`, `{duration}`, `{round}`
+/// ("2/4"), `{task}` and `{next}` (the next phase, "break 5m"). Unset parts
+/// keep the built-in text.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct NotifyTemplate {
+    pub summary: Option<String>,
+    pub body: Option<String>,
+}
+
+/// A way of delivering notifications, see `crate::notify`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// The system's notification center.
+    Desktop,
+    /// An OSC 9 notification and the bell, shown by the terminal itself.
+    Terminal,
+    /// A POST to `webhook_url`.
+    Webhook,
+    /// The sound pack's end-of-phase cue.
+    Sound,
+}
+
+/// Hours when notifications make no sound, or aren't sent at all with
+/// `suppress = true`. May wrap past midnight ("22:00" to "08:00").
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QuietHours {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub suppress: bool,
+}
+
+impl QuietHours {
+    /// Start and end as times of day.
+    pub fn range(&self) -> Result<(chrono::NaiveTime, chrono::NaiveTime), String> {
+        let parse = |value: &str| {
+            chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M")
+                .map_err(|_| format!("Invalid quiet_hours time '{value}': expected HH:MM, e.g. \"22:00\""))
+        };
+        Ok((parse(&self.from)?, parse(&self.to)?))
+    }
+
+    /// Whether `time` falls in the quiet hours. Invalid times never do.
+    pub fn contains(&self, time: chrono::NaiveTime) -> bool {
+        match self.range() {
+            Ok((from, to)) if from <= to => from <= time && time < to,
+            Ok((from, to)) => time >= from || time < to,
+            Err(_) => false,
+        }
+    }
+
+    pub fn is_now(&self) -> bool {
+        self.contains(chrono::Local::now().time())
+    }
+}
+
+impl NotifyConfig {
+    /// The quiet hours if it's currently within them.
+    pub fn quiet_now(&self) -> Option<&QuietHours> {
+        self.quiet_hours.as_ref().filter(|quiet| quiet.is_now())
+    }
+
+    /// The template for the end of a phase of this kind.
+    pub fn template(&self, context: crate::timer::TimerContext) -> Option<&NotifyTemplate> {
+        match context {
+            crate::timer::TimerContext::Break => self.break_template.as_ref(),
+            _ => self.work.as_ref(),
+        }
+    }
+
+    /// The selected channels, in order.
+    pub fn channels(&self) -> &[Channel] {
+        self.channels.as_deref().unwrap_or(&[Channel::Desktop, Channel::Sound])
+    }
+}
+
+/// Daily goal and working hours for `tik plan status`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct PlanConfig {
+    /// Focus time to log per day, e.g. "4h".
+    pub daily_goal: Option<String>,
+    /// When the working day ends, as "HH:MM".
+    pub end_of_day: Option<String>,
+    /// Session whose work and break lengths the plan uses (default: pomodoro).
+    pub session: Option<String>,
+}
+
+/// Accent and optional background colors per phase kind. Values are color
+/// names (`red`, `dark_cyan`) or `#rrggbb`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct ThemeConfig {
+    pub work: Option<String>,
+    #[serde(rename = "break")]
+    pub break_accent: Option<String>,
+    pub work_background: Option<String>,
+    pub break_background: Option<String>,
+    /// Time left when the bar turns yellow: "20%" (default) or a duration.
+    pub warning: Option<String>,
+    /// Time left when the bar turns red: "60s" (default) or a percentage.
+    pub critical: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Resolve colors for a timer context. Standalone timers use the work theme;
+    /// unknown color names and invalid thresholds fall back to the defaults.
+    pub fn colors_for(&self, context: crate::timer::TimerContext) -> crate::render::PhaseColors {
+        use crate::render::{Threshold, parse_color};
+        use crossterm::style::Color;
+
+        let (accent, background, default_accent) = match context {
+            crate::timer::TimerContext::Break => (&self.break_accent, &self.break_background, Color::Cyan),
+            _ => (&self.work, &self.work_background, Color::Green),
+        };
+        let (warning, critical) = self.thresholds().unwrap_or((Threshold::WARNING, Threshold::CRITICAL));
+        crate::render::PhaseColors {
+            accent: accent.as_deref().and_then(parse_color).unwrap_or(default_accent),
+            background: background.as_deref().and_then(parse_color),
+            warning,
+            critical,
+        }
+    }
+
+    /// The yellow and red thresholds, or what's wrong with them.
+    pub fn thresholds(&self) -> Result<(crate::render::Threshold, crate::render::Threshold), String> {
+        use crate::render::Threshold;
+        let parse = |value: &Option<String>, key: &str, default| match value {
+            Some(value) => Threshold::parse(value).map_err(|e| format!("Invalid theme.{key} {e}")),
+            None => Ok(default),
+        };
+        Ok((parse(&self.warning, "warning", Threshold::WARNING)?, parse(&self.critical, "critical", Threshold::CRITICAL)?))
+    }
+}
+
+/// Lay `overlay` over `base`: tables merge recursively, other values replace.
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Prefix of environment variables that override config keys.
+const ENV_PREFIX: &str = "TIK_";
+
+/// Apply `TIK_*` variables over the file config: `TIK_PRESETS_POMODORO=30m`
+/// sets `presets.pomodoro`. Keys that hold a string take the value as
+/// written; others read it as TOML where it parses (`true`, `10`, `["a"]`).
+/// Tables a variable creates start from their defaults, so one session
+/// field can be set alone. If the result isn't a valid config, the
+/// overrides are reported and dropped together.
+fn apply_env_overrides(config: &mut toml::Value, vars: impl Iterator<Item = (String, String)>) {
+    let mut schema = toml::Value::try_from(Config {
+        presets: Config::defaults(),
+        sessions: Config::default_sessions(),
+        ..Config::default()
+    })
+    .unwrap_or(toml::Value::Table(Default::default()));
+    merge_toml(&mut schema, config.clone());
+    let mut updated = config.clone();
+    let mut names = Vec::new();
+    for (name, raw) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let path = env_key_path(key, &schema);
+        let current = path.iter().try_fold(&schema, |value, key| value.get(key));
+        let value = match current {
+            Some(toml::Value::String(_)) => toml::Value::String(raw),
+            _ => format!("v = {raw}")
+                .parse::<toml::Table>()
+                .ok()
+                .and_then(|mut t| t.remove("v"))
+                .unwrap_or(toml::Value::String(raw)),
+        };
+        if let Err(e) = set_toml_path(&mut updated, &schema, &path, value) {
+            eprintln!("Ignoring {name}: {e}");
+            continue;
+        }
+        names.push(name);
+    }
+    if names.is_empty() {
+        return;
+    }
+    match updated.clone().try_into::<Config>() {
+        Ok(_) => *config = updated,
+        Err(e) => eprintln!("Ignoring {}: {}", names.join(", "), e.to_string().split_whitespace().collect::<Vec<_>>().join(" ")),
+    }
+}
+
+/// The config key path for a variable name without its prefix. `__`
+/// separates levels explicitly (`NOTIFY__QUIET_HOURS__FROM`); otherwise
+/// the longest run of words naming a known key is taken at each level, with
+/// `_` or `-` between them, and any words left over form a new key.
+fn env_key_path(name: &str, schema: &toml::Value) -> Vec<String> {
+    let name = name.to_lowercase();
+    if name.contains("__") {
+        return name.split("__").map(str::to_string).collect();
+    }
+    let words: Vec<&str> = name.split('_').collect();
+    let mut rest = &words[..];
+    let mut table = schema.as_table();
+    let mut path = Vec::new();
+    while !rest.is_empty() {
+        let found = table.and_then(|table| {
+            (1..=rest.len()).rev().find_map(|n| {
+                [rest[..n].join("_"), rest[..n].join("-")].into_iter().find(|key| table.contains_key(key)).map(|key| (n, key))
+            })
+        });
+        let Some((n, key)) = found else {
+            path.push(rest.join("_"));
+            break;
+        };
+        table = table.and_then(|table| table.get(&key)).and_then(toml::Value::as_table);
+        path.push(key);
+        rest = &rest[n..];
+    }
+    path
+}
+
+/// Set the value at `path`, creating missing tables from `schema` (or empty).
+fn set_toml_path(config: &mut toml::Value, schema: &toml::Value, path: &[String], value: toml::Value) -> Result<(), String> {
+    let Some((last, parents)) = path.split_last() else {
+        return Err("empty key".to_string());
+    };
+    let mut table = config.as_table_mut().ok_or("the config is not a table")?;
+    let mut schema = Some(schema);
+    for key in parents {
+        schema = schema.and_then(|s| s.get(key));
+        let default = schema.filter(|s| s.is_table()).cloned().unwrap_or(toml::Value::Table(Default::default()));
+        table = table
+            .entry(key.clone())
+            .or_insert(default)
+            .as_table_mut()
+            .ok_or_else(|| format!("'{key}' is not a table"))?;
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+/// This machine's short host name ("laptop" for "laptop.local").
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its length, and gethostname
+    // NUL-terminates within it on success
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let name = std::ffi::CStr::from_bytes_until_nul(&buf).ok()?.to_str().ok()?;
+    name.split('.').next().filter(|n| !n.is_empty()).map(str::to_string)
+}
+
+#[cfg(windows)]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok().filter(|n| !n.is_empty())
+}
+
+/// A preset's length in the config file: fixed, or by weekday like
+/// `pomodoro = { default = "25m", fri = "20m" }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PresetValue {
+    Fixed(String),
+    ByDay(HashMap<String, String>),
+}
+
+impl PresetValue {
+    /// The length on `day`: that day's entry ("fri" or "friday"), else
+    /// `default`. A preset with neither doesn't exist that day.
+    fn for_day(self, day: chrono::Weekday) -> Option<String> {
+        match self {
+            PresetValue::Fixed(value) => Some(value),
+            PresetValue::ByDay(mut days) => {
+                let today = days.iter().find(|(key, _)| key.parse::<chrono::Weekday>() == Ok(day)).map(|(k, _)| k.clone());
+                today.and_then(|key| days.remove(&key)).or_else(|| days.remove("default"))
+            }
+        }
+    }
+}
+
+fn deserialize_presets<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, String>, D::Error> {
+    use chrono::Datelike;
+    let today = chrono::Local::now().weekday();
+    let presets = HashMap::<String, PresetValue>::deserialize(deserializer)?;
+    Ok(presets.into_iter().filter_map(|(name, value)| Some((name, value.for_day(today)?))).collect())
+}
+
+const DEFAULT_FLOW_EXTEND: &str = "5m";
+const DEFAULT_FRAME_RATE: u32 = 4;
+const DEFAULT_MAX_DURATION: &str = "24h";
+/// Beyond this the bar can't visibly move faster; it only costs CPU.
+const MAX_FRAME_RATE: u32 = 30;
+const CLOCK_24H: &str = "%H:%M:%S";
+const CLOCK_12H: &str = "%-I:%M:%S %p";
+
 impl Config {
     pub fn load() -> Self {
+        let mut config = Self::read_user_config().unwrap_or_default();
         let mut presets = Self::defaults();
+        presets.extend(std::mem::take(&mut config.presets));
+        config.presets = presets;
         let mut sessions = Self::default_sessions();
+        sessions.extend(std::mem::take(&mut config.sessions));
+        config.sessions = sessions;
+        config
+    }
+
+    /// The config file, with `config.<hostname>.toml` next to it laid over
+    /// the top: tables merge key by key, anything else is replaced.
+    fn read_user_config() -> Option<Config> {
         let path = Self::config_path();
-        if path.exists() {
-            if let Ok(contents) = std::fs::read_to_string(&path) {
-                if let Ok(user_config) = toml::from_str::<Config>(&contents) {
-                    for (k, v) in user_config.presets {
-                        presets.insert(k, v);
-                    }
-                    for (k, v) in user_config.sessions {
-                        sessions.insert(k, v);
-                    }
-                }
+        let mut config: toml::Value = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents.parse().ok()?,
+            Err(_) => toml::Value::Table(Default::default()),
+        };
+        let overlay = Self::host_config_path().and_then(|path| std::fs::read_to_string(path).ok());
+        if let Some(overlay) = overlay {
+            match overlay.parse() {
+                Ok(overlay) => merge_toml(&mut config, overlay),
+                Err(e) => eprintln!("Ignoring the host config: {e}"),
             }
         }
-        Config { presets, sessions }
+        apply_env_overrides(&mut config, std::env::vars());
+        config.try_into().ok()
+    }
+
+    /// Everything that stops the config files from loading as written, for
+    /// `tik doctor`. `load` falls back to defaults on these silently.
+    pub fn problems() -> Vec<String> {
+        let one_line = |e: &dyn std::fmt::Display| e.to_string().split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut problems = Vec::new();
+        let mut merged = toml::Value::Table(Default::default());
+        let mut files = vec![Self::config_path()];
+        files.extend(Self::host_config_path());
+        for path in files {
+            let Ok(contents) = std::fs::read_to_string(&path) else { continue };
+            match contents.parse() {
+                Ok(value) => merge_toml(&mut merged, value),
+                Err(e) => problems.push(format!("{}: {}", path.display(), one_line(&e))),
+            }
+        }
+        if problems.is_empty()
+            && let Err(e) = merged.try_into::<Config>()
+        {
+            problems.push(one_line(&e));
+        }
+        let config = Self::load();
+        if let Err(e) = config.theme.thresholds() {
+            problems.push(e);
+        }
+        if let Some(Err(e)) = config.notify.quiet_hours.as_ref().map(QuietHours::range) {
+            problems.push(e);
+        }
+        if config.notify.channels().contains(&Channel::Webhook) && config.notify.webhook_url.is_none() {
+            problems.push("The webhook notification channel needs notify.webhook_url".to_string());
+        }
+        problems
+    }
+
+    /// `config.<hostname>.toml` beside the config file, for settings that
+    /// differ per machine in a shared config directory.
+    pub fn host_config_path() -> Option<PathBuf> {
+        let host = hostname()?;
+        Some(Self::config_path().with_file_name(format!("config.{host}.toml")))
     }
 
     pub fn config_path() -> PathBuf {
@@ -74,6 +641,84 @@ impl Config {
         self.sessions.get(name)
     }
 
+    pub fn resolve_sequence(&self, name: &str) -> Option<&[String]> {
+        self.sequences.get(name).map(Vec::as_slice)
+    }
+
+    /// Project for work started in `dir`: the mapping with the longest
+    /// matching directory wins. `~/` expands to the home directory.
+    pub fn project_for(&self, dir: &Path) -> Option<String> {
+        self.projects
+            .iter()
+            .filter_map(|(prefix, project)| {
+                let prefix = match prefix.strip_prefix("~/") {
+                    Some(rest) => dirs::home_dir()?.join(rest),
+                    None => PathBuf::from(prefix),
+                };
+                dir.starts_with(&prefix).then(|| (prefix.components().count(), project))
+            })
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, project)| project.clone())
+    }
+
+    pub fn completion_animation(&self) -> bool {
+        self.completion_animation.unwrap_or(true)
+    }
+
+    /// strftime pattern for wall-clock times. An invalid custom pattern is
+    /// reported and replaced by the 24-hour default.
+    pub fn clock_format(&self) -> String {
+        match self.time_format.as_deref() {
+            None | Some("24h") => CLOCK_24H.to_string(),
+            Some("12h") => CLOCK_12H.to_string(),
+            Some(custom) => {
+                let invalid = chrono::format::StrftimeItems::new(custom)
+                    .any(|item| item == chrono::format::Item::Error);
+                if invalid {
+                    crate::messages::warn(format!("Invalid time_format '{custom}', using 24h"));
+                    CLOCK_24H.to_string()
+                } else {
+                    custom.to_string()
+                }
+            }
+        }
+    }
+
+    /// Flow extension in seconds; 0 when disabled or unparseable.
+    pub fn flow_extend_secs(&self) -> u64 {
+        let value = self.flow_extend.as_deref().unwrap_or(DEFAULT_FLOW_EXTEND);
+        crate::duration::Duration::parse(value).map_or(0, |d| d.total_secs)
+    }
+
+    /// Longest timer started without asking, from `max_duration` ("off": no limit).
+    pub fn max_duration_secs(&self) -> Option<u64> {
+        let value = self.max_duration.as_deref().unwrap_or(DEFAULT_MAX_DURATION);
+        crate::duration::Duration::parse(value).ok().map(|d| d.total_secs)
+    }
+
+    /// Time between timer redraws, from `frame_rate` kept within 1-30 per second.
+    pub fn frame_interval(&self) -> std::time::Duration {
+        let rate = self.frame_rate.unwrap_or(DEFAULT_FRAME_RATE).clamp(1, MAX_FRAME_RATE);
+        std::time::Duration::from_millis(1000 / u64::from(rate))
+    }
+
+    /// Budgets in seconds per week, sorted by tag. Invalid entries are reported and skipped.
+    pub fn weekly_budgets(&self) -> Vec<(String, u64)> {
+        let mut budgets: Vec<_> = self
+            .budgets
+            .iter()
+            .filter_map(|(tag, value)| match parse_weekly_budget(value) {
+                Ok(secs) => Some((tag.clone(), secs)),
+                Err(e) => {
+                    eprintln!("Ignoring budget for '{tag}': {e}");
+                    None
+                }
+            })
+            .collect();
+        budgets.sort();
+        budgets
+    }
+
     pub fn show_config(&self) {
         let defaults = Self::defaults();
         let default_rounds: u32 = 4;
@@ -91,38 +736,277 @@ impl Config {
         let is_default = current_rounds == default_rounds;
         let suffix = if is_default { "  (default)" } else { "" };
         println!("{:<12}{}{}", "rounds", current_rounds, suffix);
+
+        let suffix = if self.require_ack { "" } else { "  (default)" };
+        println!("{:<12}{}{}", "require-ack", self.require_ack, suffix);
     }
 
-    pub fn set_value(key: &str, value: &str) -> Result<(), String> {
+    pub fn set_value(key: &str, value: &str) -> Result<(), ConfigError> {
         if key == "rounds" {
-            let rounds: u32 = value.parse().map_err(|_| {
-                format!("Invalid rounds value: '{value}'. Must be a positive integer.")
-            })?;
+            let rounds: u32 = value.parse().map_err(|_| ConfigError::InvalidRounds(value.to_string()))?;
             if rounds == 0 {
-                return Err("Rounds must be greater than zero.".to_string());
+                return Err(ConfigError::ZeroRounds);
             }
-            Self::update_config_file(|config_str| Self::set_toml_rounds(config_str, rounds))?;
+            Self::update_config_file(|config_str| Self::set_toml_rounds(config_str, rounds)).map_err(ConfigError::Write)?;
             println!("Updated rounds to {rounds}");
             return Ok(());
         }
 
-        let preset_name = config_key_to_preset(key).ok_or_else(|| {
-            format!("Unknown config key: '{key}'. Valid keys: work, break, long-break, rounds")
-        })?;
+        if key == "require-ack" {
+            let enabled: bool = value.parse().map_err(|_| ConfigError::InvalidBool { key: key.to_string(), value: value.to_string() })?;
+            Self::update_config_file(|config_str| {
+                Self::set_toml_top_level(config_str, "require_ack", toml::Value::Boolean(enabled))
+            })
+            .map_err(ConfigError::Write)?;
+            println!("Updated require-ack to {enabled}");
+            return Ok(());
+        }
+
+        let preset_name = config_key_to_preset(key).ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
 
         crate::duration::Duration::parse(value)
-            .map_err(|e| format!("Invalid duration '{value}': {e}"))?;
+            .map_err(|error| ConfigError::InvalidDuration { value: value.to_string(), error })?;
 
         Self::update_config_file(|config_str| {
             Self::set_toml_preset(config_str, preset_name, value)
-        })?;
+        })
+        .map_err(ConfigError::Write)?;
         println!("Updated {key} to {value}");
         Ok(())
     }
 
+    /// A value for `tik config get`: one of the keys `set` takes, or a dotted
+    /// path into the loaded config such as `theme.work` or `notify.channels`.
+    pub fn get_value(&self, key: &str) -> Result<String, String> {
+        match key {
+            "rounds" => return Ok(self.sessions.get("pomodoro").map_or(4, |s| s.rounds).to_string()),
+            "require-ack" => return Ok(self.require_ack.to_string()),
+            _ => {}
+        }
+        if let Some(preset) = config_key_to_preset(key) {
+            return self.presets.get(preset).cloned().ok_or_else(|| format!("No {key} preset set"));
+        }
+        let config = toml::Value::try_from(self).map_err(|e| format!("Failed to read the config: {e}"))?;
+        let value = key
+            .split('.')
+            .try_fold(&config, |value, part| value.get(part))
+            .ok_or_else(|| format!("'{key}' isn't set or isn't a config key (keys: work, break, long-break, rounds, require-ack, or a path like theme.work)"))?;
+        Ok(match value {
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Table(table) => toml::to_string_pretty(table).unwrap_or_default().trim_end().to_string(),
+            other => other.to_string(),
+        })
+    }
+
+    /// `tik preset list`: every preset with its length and where it comes
+    /// from, built in or set in the config.
+    pub fn print_presets(&self) {
+        let user = Self::read_user_config().map(|config| config.presets).unwrap_or_default();
+        for line in self.preset_lines(&user) {
+            println!("{line}");
+        }
+    }
+
+    fn preset_lines(&self, user: &HashMap<String, String>) -> Vec<String> {
+        let defaults = Self::defaults();
+        let mut names: Vec<&String> = self.presets.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| {
+                let value = &self.presets[name];
+                let source = match (user.contains_key(name), defaults.get(name)) {
+                    (true, Some(builtin)) if builtin != value => format!("user (built-in: {builtin})"),
+                    (true, _) => "user".to_string(),
+                    (false, _) => "built-in".to_string(),
+                };
+                format!("{name:<12}{value:<8}{source}")
+            })
+            .collect()
+    }
+
+    /// `tik preset add`: set a preset in the config file, replacing any with
+    /// the same name.
+    pub fn add_preset(name: &str, value: &str) -> Result<(), String> {
+        check_name(name, "preset")?;
+        crate::duration::Duration::parse(value).map_err(|e| format!("Invalid duration '{value}': {e}"))?;
+        let old = Self::load().presets.remove(name);
+        Self::update_config_file(|config_str| Self::set_toml_preset(config_str, name, value))?;
+        match old {
+            Some(old) => println!("Updated preset '{name}': {old} -> {value}"),
+            None => println!("Added preset '{name}' ({value})"),
+        }
+        Ok(())
+    }
+
+    /// `tik preset remove`: take a preset out of the config file. A built-in
+    /// one goes back to its built-in length.
+    pub fn remove_preset(name: &str) -> Result<(), String> {
+        let path = Self::config_path();
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        // A config that doesn't parse sets nothing, so say that rather than "no preset"
+        Self::parse_for_update(&existing)?;
+        let defaults = Self::defaults();
+        let Some(updated) = Self::remove_toml_entry(&existing, "presets", name) else {
+            return Err(if defaults.contains_key(name) {
+                format!("'{name}' is built in and can't be removed; `tik preset add {name} <duration>` changes its length")
+            } else if Self::load().presets.contains_key(name) {
+                format!("'{name}' isn't set in {}; it comes from the host config or an environment variable", path.display())
+            } else {
+                format!("No preset named '{name}' (`tik preset list` shows them)")
+            });
+        };
+        Self::update_config_file(|_| Ok(updated))?;
+        match defaults.get(name) {
+            Some(builtin) => println!("Removed '{name}' from the config; it's back to the built-in {builtin}"),
+            None => println!("Removed preset '{name}'"),
+        }
+        let config = Self::load();
+        let mut users: Vec<&String> = config
+            .sessions
+            .iter()
+            .filter(|(_, s)| [&s.work, &s.break_preset, &s.long_break].iter().any(|preset| *preset == name))
+            .map(|(session, _)| session)
+            .collect();
+        users.sort();
+        if !defaults.contains_key(name) {
+            for session in users {
+                eprintln!("Warning: session '{session}' still uses '{name}'");
+            }
+        }
+        Ok(())
+    }
+
+    /// `tik session list`: every session with its phases resolved to
+    /// lengths, and where it comes from.
+    pub fn print_sessions(&self) {
+        let user = Self::read_user_config().map(|config| config.sessions).unwrap_or_default();
+        for line in self.session_lines(&user) {
+            println!("{line}");
+        }
+    }
+
+    fn session_lines(&self, user: &HashMap<String, SessionConfig>) -> Vec<String> {
+        let defaults = Self::default_sessions();
+        let mut names: Vec<&String> = self.sessions.keys().collect();
+        names.sort();
+        let header = format!("{:<12}{:<8}{:<8}{:<12}{:<8}Source", "Session", "Work", "Break", "Long break", "Rounds");
+        std::iter::once(header)
+            .chain(names.into_iter().map(|name| {
+                let session = &self.sessions[name];
+                let source = match (user.contains_key(name), defaults.contains_key(name)) {
+                    (true, true) => "user (overrides built-in)",
+                    (true, false) => "user",
+                    (false, _) => "built-in",
+                };
+                format!(
+                    "{name:<12}{:<8}{:<8}{:<12}{:<8}{source}",
+                    self.phase_length(&session.work),
+                    self.phase_length(&session.break_preset),
+                    self.phase_length(&session.long_break),
+                    session.rounds,
+                )
+            }))
+            .collect()
+    }
+
+    /// A session phase's length as a duration spec, looking presets up.
+    fn phase_length(&self, phase: &str) -> String {
+        let spec = self.resolve_preset(phase).unwrap_or(phase);
+        crate::duration::Duration::parse(spec).map_or_else(|_| format!("{phase}?"), |d| d.to_spec())
+    }
+
+    /// `tik session add`: set a session in the config file. Phases left out
+    /// keep the session's current ones, or the pomodoro session's for a new
+    /// one; each may be a preset or a duration.
+    pub fn add_session(
+        name: &str,
+        work: Option<&str>,
+        break_preset: Option<&str>,
+        long_break: Option<&str>,
+        rounds: Option<u32>,
+    ) -> Result<(), String> {
+        check_name(name, "session")?;
+        let config = Self::load();
+        let old = config.sessions.get(name).cloned();
+        let base = old.clone().unwrap_or_else(|| Self::default_sessions().remove("pomodoro").unwrap());
+        let session = SessionConfig {
+            work: work.map_or(base.work, str::to_string),
+            break_preset: break_preset.map_or(base.break_preset, str::to_string),
+            long_break: long_break.map_or(base.long_break, str::to_string),
+            rounds: rounds.unwrap_or(base.rounds),
+        };
+        if session.rounds == 0 {
+            return Err("Rounds must be greater than zero.".to_string());
+        }
+        crate::session::SessionPlan::new(&session, &config)?;
+        Self::update_config_file(|config_str| Self::set_toml_session(config_str, name, &session))?;
+        let phases = format!(
+            "{} rounds: {} work, {} break, {} long break",
+            session.rounds,
+            config.phase_length(&session.work),
+            config.phase_length(&session.break_preset),
+            config.phase_length(&session.long_break),
+        );
+        if old.is_some() {
+            println!("Updated session '{name}': {phases}");
+            return Ok(());
+        }
+        println!("Added session '{name}': {phases}");
+        if let Some(kind) = [("preset", config.presets.contains_key(name)), ("sequence", config.sequences.contains_key(name))]
+            .into_iter()
+            .find_map(|(kind, taken)| taken.then_some(kind))
+        {
+            eprintln!("Warning: `tik {name}` now starts the session instead of the {kind} '{name}'");
+        }
+        Ok(())
+    }
+
+    /// `tik session remove`: take a session out of the config file. The
+    /// built-in pomodoro session goes back to its defaults.
+    pub fn remove_session(name: &str) -> Result<(), String> {
+        let path = Self::config_path();
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        // A config that doesn't parse sets nothing, so say that rather than "no session"
+        Self::parse_for_update(&existing)?;
+        let builtin = Self::default_sessions().contains_key(name);
+        let Some(updated) = Self::remove_toml_entry(&existing, "sessions", name) else {
+            return Err(if builtin {
+                format!("'{name}' is built in and can't be removed; `tik session add {name}` changes its phases")
+            } else if Self::load().sessions.contains_key(name) {
+                format!("'{name}' isn't set in {}; it comes from the host config", path.display())
+            } else {
+                format!("No session named '{name}' (`tik session list` shows them)")
+            });
+        };
+        Self::update_config_file(|_| Ok(updated))?;
+        if builtin {
+            println!("Removed '{name}' from the config; it's back to the built-in phases");
+        } else {
+            println!("Removed session '{name}'");
+        }
+        Ok(())
+    }
+
+    /// Open the config file in `$EDITOR`, creating it first if needed, and
+    /// report anything that stops the result from loading.
+    pub fn edit() -> Result<(), String> {
+        let path = Self::config_path();
+        if !path.exists() {
+            Self::update_config_file(|_| Ok(String::new()))?;
+        }
+        crate::hooks::edit_file(&path)?;
+        for problem in Self::problems() {
+            eprintln!("Config problem: {problem}");
+        }
+        Ok(())
+    }
+
+    /// Write the config file as `updater` changes it. An updater that fails
+    /// leaves the file as it was.
     fn update_config_file<F>(updater: F) -> Result<(), String>
     where
-        F: FnOnce(&str) -> String,
+        F: FnOnce(&str) -> Result<String, String>,
     {
         let path = Self::config_path();
         if let Some(parent) = path.parent() {
@@ -130,15 +1014,22 @@ impl Config {
                 .map_err(|e| format!("Failed to create config dir: {e}"))?;
         }
         let existing = std::fs::read_to_string(&path).unwrap_or_default();
-        let updated = updater(&existing);
+        let updated = updater(&existing)?;
         std::fs::write(&path, updated).map_err(|e| format!("Failed to write config: {e}"))?;
         Ok(())
     }
 
-    fn set_toml_preset(config_str: &str, key: &str, value: &str) -> String {
-        let mut config: toml::Value = config_str
-            .parse()
-            .unwrap_or(toml::Value::Table(Default::default()));
+    /// The config file's contents for a setter to change. One that doesn't
+    /// parse is an error rather than an empty table, which would be written
+    /// over everything in it.
+    fn parse_for_update(config_str: &str) -> Result<toml::Value, String> {
+        config_str.parse().map_err(|e| {
+            format!("{} doesn't parse, so it was left unchanged; fix it first (`tik config edit`):\n{e}", Self::config_path().display())
+        })
+    }
+
+    fn set_toml_preset(config_str: &str, key: &str, value: &str) -> Result<String, String> {
+        let mut config = Self::parse_for_update(config_str)?;
         let table = config.as_table_mut().unwrap();
         let presets = table
             .entry("presets")
@@ -147,13 +1038,42 @@ impl Config {
             .as_table_mut()
             .unwrap()
             .insert(key.to_string(), toml::Value::String(value.to_string()));
-        toml::to_string_pretty(&config).unwrap_or_default()
+        Ok(toml::to_string_pretty(&config).unwrap_or_default())
     }
 
-    fn set_toml_rounds(config_str: &str, rounds: u32) -> String {
-        let mut config: toml::Value = config_str
-            .parse()
-            .unwrap_or(toml::Value::Table(Default::default()));
+    fn set_toml_session(config_str: &str, name: &str, session: &SessionConfig) -> Result<String, String> {
+        let mut config = Self::parse_for_update(config_str)?;
+        let table = config.as_table_mut().unwrap();
+        let sessions = table
+            .entry("sessions")
+            .or_insert(toml::Value::Table(Default::default()));
+        let session = toml::Value::try_from(session).expect("a session is a plain table");
+        sessions.as_table_mut().unwrap().insert(name.to_string(), session);
+        Ok(toml::to_string_pretty(&config).unwrap_or_default())
+    }
+
+    /// The config without `name` in table `table_name` (`presets`,
+    /// `sessions`), or None when it doesn't set one. An emptied table is
+    /// dropped too.
+    fn remove_toml_entry(config_str: &str, table_name: &str, name: &str) -> Option<String> {
+        let mut config: toml::Value = config_str.parse().ok()?;
+        let table = config.as_table_mut()?;
+        let entries = table.get_mut(table_name)?.as_table_mut()?;
+        entries.remove(name)?;
+        if entries.is_empty() {
+            table.remove(table_name);
+        }
+        Some(toml::to_string_pretty(&config).unwrap_or_default())
+    }
+
+    fn set_toml_top_level(config_str: &str, key: &str, value: toml::Value) -> Result<String, String> {
+        let mut config = Self::parse_for_update(config_str)?;
+        config.as_table_mut().unwrap().insert(key.to_string(), value);
+        Ok(toml::to_string_pretty(&config).unwrap_or_default())
+    }
+
+    fn set_toml_rounds(config_str: &str, rounds: u32) -> Result<String, String> {
+        let mut config = Self::parse_for_update(config_str)?;
         let table = config.as_table_mut().unwrap();
         let sessions = table
             .entry("sessions")
@@ -175,7 +1095,65 @@ impl Config {
             .or_insert(toml::Value::String("long-break".to_string()));
         pomodoro_table.insert("rounds".to_string(), toml::Value::Integer(rounds as i64));
 
-        toml::to_string_pretty(&config).unwrap_or_default()
+        Ok(toml::to_string_pretty(&config).unwrap_or_default())
+    }
+}
+
+/// A name for `tik preset add` or `tik session add` that `tik <name>` can
+/// start. `kind` is "preset" or "session".
+fn check_name(name: &str, kind: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '+') {
+        return Err(format!("'{name}' can't be a {kind} name: use no spaces or '+'"));
+    }
+    if crate::duration::Duration::parse(name).is_ok() {
+        return Err(format!("'{name}' reads as a duration, so `tik {name}` couldn't start the {kind}"));
+    }
+    Ok(())
+}
+
+/// Parse "10h/week" (or just "10h") into seconds per week.
+pub fn parse_weekly_budget(value: &str) -> Result<u64, String> {
+    let amount = value.trim();
+    let amount = amount.strip_suffix("/week").unwrap_or(amount);
+    if amount.contains('/') {
+        return Err(format!("'{value}': only weekly budgets (\"10h/week\") are supported"));
+    }
+    crate::duration::Duration::parse(amount.trim()).map(|d| d.total_secs).map_err(|e| format!("'{value}': {e}"))
+}
+
+/// Why `tik config set` refused a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidRounds(String),
+    ZeroRounds,
+    InvalidBool { key: String, value: String },
+    InvalidDuration { value: String, error: crate::duration::DurationError },
+    /// The config file doesn't parse, or couldn't be written.
+    Write(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => {
+                write!(f, "Unknown config key: '{key}'. Valid keys: work, break, long-break, rounds, require-ack")
+            }
+            ConfigError::InvalidRounds(value) => write!(f, "Invalid rounds value: '{value}'. Must be a positive integer."),
+            ConfigError::ZeroRounds => write!(f, "Rounds must be greater than zero."),
+            ConfigError::InvalidBool { key, value } => write!(f, "Invalid {key} value: '{value}'. Must be true or false."),
+            ConfigError::InvalidDuration { value, error } => write!(f, "Invalid duration '{value}': {error}"),
+            ConfigError::Write(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::InvalidDuration { error, .. } => Some(error),
+            _ => None,
+        }
     }
 }
 
@@ -192,6 +1170,15 @@ pub fn config_key_to_preset(key: &str) -> Option<&'static str> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn clock_format_presets_and_custom() {
+        let with = |value: Option<&str>| Config { time_format: value.map(str::to_string), ..Default::default() };
+        assert_eq!(with(None).clock_format(), "%H:%M:%S");
+        assert_eq!(with(Some("12h")).clock_format(), "%-I:%M:%S %p");
+        assert_eq!(with(Some("%H.%M")).clock_format(), "%H.%M");
+        assert_eq!(with(Some("%Q")).clock_format(), "%H:%M:%S");
+    }
+
     #[test]
     fn defaults_include_pomodoro() {
         let defaults = Config::defaults();
@@ -222,6 +1209,95 @@ rest = "10m"
         assert_eq!(config.presets.get("rest").unwrap(), "10m");
     }
 
+    #[test]
+    fn host_overlay_merges_tables() {
+        let mut base: toml::Value = "sound_pack = \"soft\"\n[presets]\npomodoro = \"25m\"\nbreak = \"5m\"\n".parse().unwrap();
+        let overlay: toml::Value = "sound_pack = \"classic\"\n[presets]\nbreak = \"10m\"\n".parse().unwrap();
+        merge_toml(&mut base, overlay);
+        let config: Config = base.try_into().unwrap();
+        assert_eq!(config.sound_pack.as_deref(), Some("classic"));
+        assert_eq!(config.presets.get("pomodoro").unwrap(), "25m");
+        assert_eq!(config.presets.get("break").unwrap(), "10m");
+    }
+
+    #[test]
+    fn env_overrides_find_nested_keys() {
+        let vars = [
+            ("TIK_PRESETS_POMODORO", "30m"),
+            ("TIK_PRESETS_LONG_BREAK", "20m"),
+            ("TIK_REQUIRE_ACK", "true"),
+            ("TIK_FRAME_RATE", "10"),
+            ("TIK_SESSIONS_POMODORO_ROUNDS", "6"),
+            ("TIK_THEME_WORK_BACKGROUND", "black"),
+            ("TIK_NOTIFY__QUIET_HOURS__FROM", "22:00"),
+            ("TIK_NOTIFY__QUIET_HOURS__TO", "07:00"),
+            ("HOME", "/home/me"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let mut value = toml::Value::Table(Default::default());
+        apply_env_overrides(&mut value, vars.into_iter());
+        let config: Config = value.try_into().unwrap();
+        assert_eq!(config.presets.get("pomodoro").unwrap(), "30m");
+        assert_eq!(config.presets.get("long-break").unwrap(), "20m");
+        assert!(config.require_ack);
+        assert_eq!(config.frame_rate, Some(10));
+        assert_eq!(config.sessions.get("pomodoro").unwrap().rounds, 6);
+        assert_eq!(config.theme.work_background.as_deref(), Some("black"));
+        assert_eq!(config.notify.quiet_hours.unwrap().to, "07:00");
+
+        // An invalid value drops the overrides, not the file config
+        let mut value: toml::Value = "tenths = true".parse().unwrap();
+        let vars = [("TIK_REQUIRE_ACK", "true"), ("TIK_TENTHS", "maybe")].map(|(k, v)| (k.to_string(), v.to_string()));
+        apply_env_overrides(&mut value, vars.into_iter());
+        let config: Config = value.try_into().unwrap();
+        assert!(config.tenths && !config.require_ack);
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight() {
+        let at = |h, m| chrono::NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let quiet = |from: &str, to: &str| QuietHours { from: from.to_string(), to: to.to_string(), suppress: false };
+        let night = quiet("22:00", "08:00");
+        assert!(night.contains(at(23, 30)) && night.contains(at(2, 0)) && night.contains(at(22, 0)));
+        assert!(!night.contains(at(8, 0)) && !night.contains(at(14, 0)));
+        let lunch = quiet("12:00", "13:00");
+        assert!(lunch.contains(at(12, 30)) && !lunch.contains(at(13, 30)));
+        let broken = quiet("10pm", "08:00");
+        assert!(broken.range().unwrap_err().contains("'10pm'"));
+        assert!(!broken.contains(at(23, 0)));
+    }
+
+    #[test]
+    fn notify_channels_default_to_desktop_and_sound() {
+        assert_eq!(NotifyConfig::default().channels(), [Channel::Desktop, Channel::Sound]);
+        let config: Config = toml::from_str("[notify]\nchannels = [\"terminal\", \"webhook\"]").unwrap();
+        assert_eq!(config.notify.channels(), [Channel::Terminal, Channel::Webhook]);
+        assert!(toml::from_str::<Config>("[notify]\nchannels = [\"pager\"]").is_err());
+    }
+
+    #[test]
+    fn weekday_presets_pick_the_day_or_default() {
+        use chrono::Weekday;
+        let value = || {
+            let config: HashMap<String, PresetValue> =
+                toml::from_str(r#"pomodoro = { default = "25m", fri = "20m", Saturday = "15m" }"#).unwrap();
+            config.into_values().next().unwrap()
+        };
+        assert_eq!(value().for_day(Weekday::Fri).as_deref(), Some("20m"));
+        assert_eq!(value().for_day(Weekday::Sat).as_deref(), Some("15m"));
+        assert_eq!(value().for_day(Weekday::Mon).as_deref(), Some("25m"));
+        let fridays_only: PresetValue = toml::from_str::<HashMap<String, PresetValue>>(r#"p = { fri = "20m" }"#)
+            .unwrap()
+            .remove("p")
+            .unwrap();
+        assert_eq!(fridays_only.for_day(Weekday::Tue), None);
+
+        // Loading resolves them, next to plain values
+        let config: Config = toml::from_str("[presets]\nfocus = \"50m\"\npomodoro = { default = \"25m\" }\n").unwrap();
+        assert_eq!(config.presets.get("focus").unwrap(), "50m");
+        assert_eq!(config.presets.get("pomodoro").unwrap(), "25m");
+    }
+
     #[test]
     fn resolve_preset_found() {
         let mut config = Config::default();
@@ -229,6 +1305,18 @@ rest = "10m"
         assert_eq!(config.resolve_preset("pomodoro"), Some("25m"));
     }
 
+    #[test]
+    fn project_for_longest_matching_directory() {
+        let mut config = Config::default();
+        config.projects.insert("/work".to_string(), "work".to_string());
+        config.projects.insert("/work/acme".to_string(), "acme".to_string());
+        assert_eq!(config.project_for(Path::new("/work/acme/api")), Some("acme".to_string()));
+        assert_eq!(config.project_for(Path::new("/work/other")), Some("work".to_string()));
+        // Whole directory names only
+        assert_eq!(config.project_for(Path::new("/work/acme-old")), Some("work".to_string()));
+        assert_eq!(config.project_for(Path::new("/home")), None);
+    }
+
     #[test]
     fn resolve_preset_not_found() {
         let config = Config::default();
@@ -269,6 +1357,22 @@ deep = { work = "focus", break = "rest", long_break = "rest", rounds = 3 }
         assert!(config.resolve_session("nonexistent").is_none());
     }
 
+    #[test]
+    fn get_value_by_key_or_path() {
+        let config: Config = toml::from_str(
+            "require_ack = true\n[presets]\npomodoro = \"30m\"\n[theme]\nwork = \"blue\"\n[notify]\nchannels = [\"terminal\"]",
+        )
+        .unwrap();
+        assert_eq!(config.get_value("work").unwrap(), "30m");
+        assert_eq!(config.get_value("require-ack").unwrap(), "true");
+        assert_eq!(config.get_value("rounds").unwrap(), "4");
+        assert_eq!(config.get_value("presets.pomodoro").unwrap(), "30m");
+        assert_eq!(config.get_value("theme.work").unwrap(), "blue");
+        assert_eq!(config.get_value("theme").unwrap(), "work = \"blue\"");
+        assert_eq!(config.get_value("notify.channels").unwrap(), "[\"terminal\"]");
+        assert!(config.get_value("theme.nope").unwrap_err().starts_with("'theme.nope' isn't set"));
+    }
+
     #[test]
     fn config_key_to_preset_mapping() {
         assert_eq!(super::config_key_to_preset("work"), Some("pomodoro"));
@@ -280,7 +1384,7 @@ deep = { work = "focus", break = "rest", long_break = "rest", rounds = 3 }
 
     #[test]
     fn set_toml_preset_empty_config() {
-        let result = Config::set_toml_preset("", "pomodoro", "30m");
+        let result = Config::set_toml_preset("", "pomodoro", "30m").unwrap();
         assert!(result.contains("pomodoro"));
         assert!(result.contains("30m"));
     }
@@ -288,13 +1392,254 @@ deep = { work = "focus", break = "rest", long_break = "rest", rounds = 3 }
     #[test]
     fn set_toml_preset_existing_config() {
         let existing = "[presets]\npomodoro = \"25m\"\n";
-        let result = Config::set_toml_preset(existing, "pomodoro", "30m");
+        let result = Config::set_toml_preset(existing, "pomodoro", "30m").unwrap();
         assert!(result.contains("30m"));
     }
 
+    #[test]
+    fn set_toml_preset_refuses_a_config_that_doesnt_parse() {
+        let broken = "require_ack = true\n[presets\npomodoro = \"25m\"\n";
+        assert!(Config::set_toml_preset(broken, "x", "10m").unwrap_err().contains("left unchanged"));
+    }
+
+    #[test]
+    fn parse_toml_skip_logging() {
+        let config: Config = toml::from_str("skip_logging = \"prompt\"").unwrap();
+        assert_eq!(config.skip_logging, SkipLogging::Prompt);
+        assert_eq!(Config::default().skip_logging, SkipLogging::Partial);
+        assert!(toml::from_str::<Config>("skip_logging = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn parse_toml_journal() {
+        let config: Config = toml::from_str("journal = \"editor\"").unwrap();
+        assert_eq!(config.journal, Journal::Editor);
+        assert_eq!(Config::default().journal, Journal::Off);
+        let config: Config = toml::from_str("graphics = \"sixel\"").unwrap();
+        assert_eq!(config.graphics, Graphics::Sixel);
+    }
+
+    #[test]
+    fn parse_toml_require_ack() {
+        let config: Config = toml::from_str("require_ack = true").unwrap();
+        assert!(config.require_ack);
+        let config: Config = toml::from_str("").unwrap();
+        assert!(!config.require_ack);
+    }
+
+    #[test]
+    fn flow_extend_defaults_to_five_minutes() {
+        assert_eq!(Config::default().flow_extend_secs(), 300);
+    }
+
+    #[test]
+    fn frame_interval_defaults_and_caps() {
+        assert_eq!(Config::default().frame_interval().as_millis(), 250);
+        let config = |frame_rate| Config { frame_rate: Some(frame_rate), ..Config::default() };
+        assert_eq!(config(10).frame_interval().as_millis(), 100);
+        assert_eq!(config(240).frame_interval().as_millis(), 33);
+        assert_eq!(config(0).frame_interval().as_millis(), 1000);
+    }
+
+    #[test]
+    fn max_duration_defaults_and_off() {
+        assert_eq!(Config::default().max_duration_secs(), Some(86400));
+        let config: Config = toml::from_str("max_duration = \"off\"").unwrap();
+        assert_eq!(config.max_duration_secs(), None);
+        let config: Config = toml::from_str("max_duration = \"8h\"").unwrap();
+        assert_eq!(config.max_duration_secs(), Some(28800));
+    }
+
+    #[test]
+    fn flow_extend_off_disables() {
+        let config: Config = toml::from_str("flow_extend = \"off\"").unwrap();
+        assert_eq!(config.flow_extend_secs(), 0);
+        let config: Config = toml::from_str("flow_extend = \"10m\"").unwrap();
+        assert_eq!(config.flow_extend_secs(), 600);
+    }
+
+    #[test]
+    fn theme_defaults_distinguish_phases() {
+        use crate::timer::TimerContext;
+        let theme = ThemeConfig::default();
+        assert_ne!(theme.colors_for(TimerContext::Work), theme.colors_for(TimerContext::Break));
+        assert_eq!(theme.colors_for(TimerContext::Standalone), theme.colors_for(TimerContext::Work));
+        assert!(theme.colors_for(TimerContext::Work).background.is_none());
+    }
+
+    #[test]
+    fn parse_toml_theme() {
+        use crate::timer::TimerContext;
+        use crossterm::style::Color;
+        let toml_str = r##"
+[theme]
+work = "red"
+break = "#00ff00"
+break_background = "dark_blue"
+"##;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.theme.colors_for(TimerContext::Work).accent, Color::Red);
+        let colors = config.theme.colors_for(TimerContext::Break);
+        assert_eq!(colors.accent, Color::Rgb { r: 0, g: 255, b: 0 });
+        assert_eq!(colors.background, Some(Color::DarkBlue));
+    }
+
+    #[test]
+    fn theme_thresholds_validated() {
+        use crate::render::Threshold;
+        let theme = |warning: &str, critical: &str| ThemeConfig {
+            warning: Some(warning.to_string()),
+            critical: Some(critical.to_string()),
+            ..ThemeConfig::default()
+        };
+        assert_eq!(ThemeConfig::default().thresholds(), Ok((Threshold::WARNING, Threshold::CRITICAL)));
+        assert_eq!(theme("5m", "10%").thresholds(), Ok((Threshold::Secs(300), Threshold::Percent(10))));
+        let invalid = theme("5m", "150%");
+        assert!(invalid.thresholds().unwrap_err().contains("theme.critical"));
+        assert_eq!(invalid.colors_for(crate::timer::TimerContext::Work).critical, Threshold::CRITICAL);
+    }
+
+    #[test]
+    fn weekly_budget_parsing() {
+        assert_eq!(parse_weekly_budget("10h/week"), Ok(36000));
+        assert_eq!(parse_weekly_budget("90m"), Ok(5400));
+        assert!(parse_weekly_budget("2h/day").is_err());
+        assert!(parse_weekly_budget("lots/week").is_err());
+    }
+
+    #[test]
+    fn set_value_errors_say_what_was_wrong() {
+        use crate::duration::DurationError;
+        // Each is refused before the config file is touched
+        assert_eq!(Config::set_value("colour", "red"), Err(ConfigError::UnknownKey("colour".to_string())));
+        assert_eq!(Config::set_value("rounds", "0"), Err(ConfigError::ZeroRounds));
+        assert_eq!(Config::set_value("rounds", "four"), Err(ConfigError::InvalidRounds("four".to_string())));
+        let error = Config::set_value("work", "25").unwrap_err();
+        assert_eq!(
+            error,
+            ConfigError::InvalidDuration { value: "25".to_string(), error: DurationError::MissingUnit("25".to_string()) }
+        );
+        assert_eq!(error.to_string(), "Invalid duration '25': '25' needs a unit (h, m or s)");
+    }
+
+    #[test]
+    fn parse_toml_budgets() {
+        let config: Config = toml::from_str("[budgets]\n\"client-a\" = \"10h/week\"\n").unwrap();
+        assert_eq!(config.weekly_budgets(), vec![("client-a".to_string(), 36000)]);
+    }
+
+    #[test]
+    fn parse_toml_blocker() {
+        let toml_str = r#"
+[blocker]
+domains = ["twitter.com"]
+start_command = "blocker on"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.blocker.is_enabled());
+        assert_eq!(config.blocker.domains, vec!["twitter.com"]);
+        assert!(!Config::default().blocker.is_enabled());
+    }
+
+    #[test]
+    fn remove_toml_entry_only_when_set() {
+        let existing = "require_ack = true\n[presets]\nfocus = \"50m\"\n";
+        let updated = Config::remove_toml_entry(existing, "presets", "focus").unwrap();
+        let parsed: Config = toml::from_str(&updated).unwrap();
+        assert!(parsed.presets.is_empty());
+        assert!(parsed.require_ack);
+        assert!(!updated.contains("[presets]"));
+        assert_eq!(Config::remove_toml_entry(existing, "presets", "pomodoro"), None);
+        assert_eq!(Config::remove_toml_entry("", "presets", "focus"), None);
+
+        let both = "[presets]\nfocus = \"50m\"\nrest = \"10m\"\n";
+        let parsed: Config = toml::from_str(&Config::remove_toml_entry(both, "presets", "focus").unwrap()).unwrap();
+        assert_eq!(parsed.presets.get("rest").unwrap(), "10m");
+    }
+
+    #[test]
+    fn preset_lines_mark_where_presets_come_from() {
+        let mut config = Config { presets: Config::defaults(), ..Default::default() };
+        config.presets.insert("focus".to_string(), "50m".to_string());
+        config.presets.insert("pomodoro".to_string(), "30m".to_string());
+        let user = HashMap::from([("focus".to_string(), "50m".to_string()), ("pomodoro".to_string(), "30m".to_string())]);
+        assert_eq!(
+            config.preset_lines(&user),
+            [
+                "break       5m      built-in",
+                "focus       50m     user",
+                "long-break  15m     built-in",
+                "pomodoro    30m     user (built-in: 25m)",
+            ]
+        );
+    }
+
+    #[test]
+    fn session_lines_resolve_presets_to_lengths() {
+        let mut config = Config { presets: Config::defaults(), sessions: Config::default_sessions(), ..Default::default() };
+        let deep = SessionConfig {
+            work: "50m".to_string(),
+            break_preset: "break".to_string(),
+            long_break: "nap".to_string(),
+            rounds: 3,
+        };
+        config.sessions.insert("deep".to_string(), deep.clone());
+        let user = HashMap::from([("deep".to_string(), deep)]);
+        assert_eq!(
+            config.session_lines(&user),
+            [
+                "Session     Work    Break   Long break  Rounds  Source",
+                "deep        50m     5m      nap?        3       user",
+                "pomodoro    25m     5m      15m         4       built-in",
+            ]
+        );
+    }
+
+    #[test]
+    fn set_toml_session_round_trips() {
+        let session = SessionConfig {
+            work: "50m".to_string(),
+            break_preset: "10m".to_string(),
+            long_break: "long-break".to_string(),
+            rounds: 3,
+        };
+        let existing = "[presets]\nfocus = \"50m\"\n";
+        let updated = Config::set_toml_session(existing, "deep", &session).unwrap();
+        let parsed: Config = toml::from_str(&updated).unwrap();
+        let deep = parsed.sessions.get("deep").unwrap();
+        assert_eq!((deep.work.as_str(), deep.break_preset.as_str(), deep.rounds), ("50m", "10m", 3));
+        assert_eq!(parsed.presets.get("focus").unwrap(), "50m");
+
+        let removed: Config = toml::from_str(&Config::remove_toml_entry(&updated, "sessions", "deep").unwrap()).unwrap();
+        assert!(removed.sessions.is_empty());
+
+        assert!(Config::set_toml_session("[sessions\n", "deep", &session).unwrap_err().contains("left unchanged"));
+    }
+
+    #[test]
+    fn set_toml_top_level_keeps_tables() {
+        let existing = "[presets]\npomodoro = \"25m\"\n";
+        let result = Config::set_toml_top_level(existing, "require_ack", toml::Value::Boolean(true)).unwrap();
+        let parsed: Config = toml::from_str(&result).unwrap();
+        assert!(parsed.require_ack);
+        assert_eq!(parsed.presets.get("pomodoro").unwrap(), "25m");
+    }
+
+    #[test]
+    fn set_toml_top_level_refuses_a_config_that_doesnt_parse() {
+        let broken = "[presets\npomodoro = \"25m\"\n";
+        let error = Config::set_toml_top_level(broken, "require_ack", toml::Value::Boolean(true)).unwrap_err();
+        assert!(error.contains("left unchanged"));
+    }
+
     #[test]
     fn set_toml_rounds_empty_config() {
-        let result = Config::set_toml_rounds("", 6);
+        let result = Config::set_toml_rounds("", 6).unwrap();
         assert!(result.contains("rounds = 6"));
     }
+
+    #[test]
+    fn set_toml_rounds_refuses_a_config_that_doesnt_parse() {
+        assert!(Config::set_toml_rounds("[sessions.pomodoro\n", 6).unwrap_err().contains("left unchanged"));
+    }
 }
@@ -0,0 +1,160 @@
+//! `tik cook "pasta=11m" "sauce=20m"`: labeled countdowns running side by
+//! side, each notifying on its own when it runs out. Nothing is logged; these
+//! aren't focus time.
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+use crate::config::Config;
+use crate::duration::Duration;
+use crate::events::{EventBus, TikEvent};
+use crate::input::Input;
+use crate::render::{AlarmRow, Screen};
+use crate::timer::{ActiveClock, TimerContext};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alarm {
+    pub label: String,
+    pub secs: u64,
+}
+
+/// Parse `label=duration`, e.g. `pasta=11m`.
+pub fn parse_alarm(spec: &str) -> Result<Alarm, String> {
+    let (label, duration) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid alarm '{spec}': expected label=duration, e.g. pasta=11m"))?;
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(format!("Invalid alarm '{spec}': the label is empty"));
+    }
+    let duration = Duration::parse(duration.trim()).map_err(|e| format!("Invalid duration for '{label}': {e}"))?;
+    Ok(Alarm { label: label.to_string(), secs: duration.total_secs })
+}
+
+/// Display order: running alarms soonest first, then finished ones in the
+/// order they finished. Ties keep the command-line order.
+fn display_order(alarms: &[Alarm], elapsed_secs: u64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..alarms.len()).collect();
+    order.sort_by_key(|&i| (alarms[i].secs <= elapsed_secs, alarms[i].secs));
+    order
+}
+
+/// Run the alarms until the last one goes off, or until stopped with `x` or
+/// Ctrl+C. Space pauses them all.
+pub async fn run(config: &Config, alarms: Vec<Alarm>, silent: bool, headless: bool, no_input: bool) {
+    let events = EventBus::start(config, silent, false);
+    let input = (!headless && !no_input).then(Input::start);
+    let screen = if headless {
+        None
+    } else {
+        match Screen::enter(input.is_some()) {
+            Ok(screen) => Some(screen),
+            Err(e) => {
+                eprintln!("Failed to setup terminal: {e}");
+                return;
+            }
+        }
+    };
+    let clock_format = config.clock_format();
+    if headless {
+        for alarm in &alarms {
+            println!("{} started ({})", alarm.label, Duration::from_secs(alarm.secs).format_hms());
+        }
+    }
+
+    let shutdown = crate::timer::shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut clock = ActiveClock::new();
+    let mut paused = false;
+    let mut fired = vec![false; alarms.len()];
+    let mut last_drawn = None;
+    let next_key = || async {
+        match &input {
+            Some(input) => input.next().await,
+            None => std::future::pending().await,
+        }
+    };
+    loop {
+        let elapsed_secs = clock.active_elapsed(paused).as_secs();
+        for (alarm, fired) in alarms.iter().zip(fired.iter_mut()) {
+            if *fired || alarm.secs > elapsed_secs {
+                continue;
+            }
+            *fired = true;
+            if headless {
+                println!("[{}] {} done", chrono::Local::now().format(&clock_format), alarm.label);
+            }
+            events.emit(TikEvent::Completed {
+                name: alarm.label.clone(),
+                context: TimerContext::Standalone,
+                duration_secs: alarm.secs,
+                skipped: false,
+                next: None,
+                entries: Vec::new(),
+            });
+        }
+        if fired.iter().all(|&f| f) {
+            break;
+        }
+
+        if let Some(screen) = &screen
+            && last_drawn != Some((elapsed_secs, paused))
+        {
+            last_drawn = Some((elapsed_secs, paused));
+            let rows: Vec<AlarmRow> = display_order(&alarms, elapsed_secs)
+                .into_iter()
+                .map(|i| AlarmRow {
+                    label: &alarms[i].label,
+                    remaining_secs: alarms[i].secs.saturating_sub(elapsed_secs),
+                })
+                .collect();
+            let _ = screen.renderer().draw_alarms(&rows, paused, input.is_some());
+        }
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            event = next_key() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char(' ') => paused = !paused,
+                    KeyCode::Char('x') | KeyCode::Char('q') => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    _ => {}
+                },
+                Some(Event::Resize(..)) => last_drawn = None,
+                Some(_) => {}
+                None => break,
+            },
+            _ = tokio::time::sleep(std::time::Duration::from_millis(250)) => {}
+        }
+    }
+
+    drop(screen);
+    let done = fired.iter().filter(|&&f| f).count();
+    if done < alarms.len() {
+        println!("Stopped with {done} of {} alarms done.", alarms.len());
+    } else {
+        println!("All {} alarms done.", alarms.len());
+    }
+    events.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_alarm_label_and_duration() {
+        assert_eq!(parse_alarm("pasta=11m"), Ok(Alarm { label: "pasta".to_string(), secs: 660 }));
+        assert_eq!(parse_alarm(" tea = 3m30s").unwrap().secs, 210);
+        assert!(parse_alarm("pasta").is_err());
+        assert!(parse_alarm("=5m").is_err());
+        assert!(parse_alarm("pasta=soon").is_err());
+    }
+
+    #[test]
+    fn soonest_first_then_finished() {
+        let alarm = |label: &str, secs| Alarm { label: label.to_string(), secs };
+        let alarms = [alarm("sauce", 1200), alarm("pasta", 660), alarm("eggs", 420), alarm("tea", 180)];
+        assert_eq!(display_order(&alarms, 0), [3, 2, 1, 0]);
+        assert_eq!(display_order(&alarms, 500), [1, 0, 3, 2]);
+    }
+}
@@ -0,0 +1,298 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::ipc::{Request, Response};
+
+/// Run the background daemon: listen for IPC requests (and webhooks, if
+/// configured) and run timers headless.
+pub async fn run() -> Result<(), String> {
+    let config = crate::config::Config::load();
+    let listener = crate::ipc::Listener::bind().map_err(|e| format!("Failed to start daemon: {e}"))?;
+    println!("tik daemon listening on {}", listener.describe());
+
+    let webhook_listener = match &config.webhook.listen {
+        Some(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .map_err(|e| format!("Failed to listen for webhooks on {addr}: {e}"))?;
+            println!("tik daemon accepting webhooks on http://{addr}");
+            if config.webhook.token.is_none() {
+                eprintln!("Warning: webhook.token is not set; anyone who can reach {addr} can control tik");
+            }
+            Some(listener)
+        }
+        None => None,
+    };
+    notify_ready();
+    crate::spawn_integrations(&config);
+
+    let current: Arc<Mutex<Option<JoinHandle<()>>>> = Arc::default();
+    let handler = |request| match current.lock() {
+        Ok(mut current) => handle(request, &mut current),
+        Err(_) => Response::Error { message: "Daemon state unavailable".to_string() },
+    };
+    let webhooks = async {
+        match webhook_listener {
+            Some(listener) => crate::webhook::serve(listener, config.webhook.token.clone(), handler).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = crate::ipc::serve(listener, handler) => result.map_err(|e| format!("Daemon error: {e}")),
+        result = webhooks => result.map_err(|e| format!("Webhook error: {e}")),
+        _ = crate::timer::shutdown_signal() => {
+            crate::state::clear();
+            Ok(())
+        }
+    }
+}
+
+/// Let `tik pause`/`skip`/`stop` reach a foreground timer when no daemon is
+/// running. If the socket is taken (daemon or another instance), do nothing.
+pub fn serve_instance() {
+    let Ok(listener) = crate::ipc::Listener::bind() else {
+        return;
+    };
+    tokio::spawn(async move {
+        let _ = crate::ipc::serve(listener, |request| match request {
+            Request::Start { .. } => Response::Error {
+                message: "tik is running in the foreground; start `tik daemon` to use `tik start`.".to_string(),
+            },
+            Request::Stop => reply(crate::timer::remote_stop().map(|_| "Timer stopped.".to_string())),
+            other => handle_control(other),
+        })
+        .await;
+    });
+}
+
+/// Requests that act on whichever timer is running in this process.
+fn handle_control(request: Request) -> Response {
+    match request {
+        Request::Pause => reply(
+            crate::timer::remote_toggle_pause()
+                .map(|paused| if paused { "Paused." } else { "Resumed." }.to_string()),
+        ),
+        Request::Skip => reply(crate::timer::remote_skip().map(|_| "Skipped.".to_string())),
+        Request::Status => Response::Status { state: crate::state::read() },
+        Request::Start { .. } | Request::Stop => Response::Error { message: "Unsupported request.".to_string() },
+    }
+}
+
+/// What a daemon job leaves for crash recovery: its resume checkpoint and its
+/// autosaved block. `tik stop` aborts the job, which drops the timer before it
+/// can clean these up itself, and a timer stopped on purpose isn't one to
+/// resume or to log as interrupted.
+struct Forget {
+    checkpoint: PathBuf,
+    pending: PathBuf,
+}
+
+impl Forget {
+    fn current() -> Self {
+        Forget { checkpoint: crate::resume::checkpoint_path(), pending: crate::log::pending_path(std::process::id()) }
+    }
+}
+
+impl Drop for Forget {
+    fn drop(&mut self) {
+        crate::resume::clear_at(&self.checkpoint);
+        let _ = std::fs::remove_file(&self.pending);
+    }
+}
+
+/// Run a job, forgetting it once it's done or aborted. The guard is dropped
+/// after the job's timer, so no checkpoint gets written after it.
+fn spawn_job(forget: Forget, job: impl Future<Output = ()> + Send + 'static) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let _forget = forget;
+        job.await;
+    })
+}
+
+fn reply(result: Result<String, String>) -> Response {
+    match result {
+        Ok(message) => Response::Ok { message },
+        Err(message) => Response::Error { message },
+    }
+}
+
+pub fn handle(request: Request, current: &mut Option<JoinHandle<()>>) -> Response {
+    if current.as_ref().is_some_and(|job| job.is_finished()) {
+        *current = None;
+    }
+    match request {
+        Request::Pause | Request::Skip | Request::Status => handle_control(request),
+        Request::Start { target } => {
+            if current.is_some() {
+                return Response::Error { message: "A timer is already running.".to_string() };
+            }
+            let config = crate::config::Config::load();
+            let resolved = match crate::resolve_target(&config, &target) {
+                Ok(t) => t,
+                Err(e) => return Response::Error { message: e },
+            };
+            *current = Some(spawn_job(Forget::current(), async move {
+                let options = crate::session::SessionOptions {
+                    silent: false,
+                    title: None,
+                    ack: false,
+                    headless: true,
+                    no_input: true,
+                    tenths: false,
+                    a11y: false,
+                    notify_at: &[],
+                    labels: &crate::log::WorkLabels::default(),
+                    log: true,
+                };
+                crate::run_target(&config, resolved, options).await;
+            }));
+            Response::Ok { message: format!("Started {target}.") }
+        }
+        Request::Stop => match current.take() {
+            Some(job) => {
+                job.abort();
+                crate::state::clear();
+                Response::Ok { message: "Timer stopped.".to_string() }
+            }
+            None => Response::Error { message: "No timer running.".to_string() },
+        },
+    }
+}
+
+/// Tell systemd we're ready (Type=notify). A no-op when not run by systemd.
+#[cfg(unix)]
+fn notify_ready() {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(addr) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let Ok(socket) = UnixDatagram::unbound() else {
+        return;
+    };
+    let addr = addr.to_string_lossy();
+    // A leading '@' names a socket in the abstract namespace
+    #[cfg(target_os = "linux")]
+    if let Some(name) = addr.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        if let Ok(abstract_addr) = std::os::unix::net::SocketAddr::from_abstract_name(name) {
+            let _ = socket.send_to_addr(b"READY=1", &abstract_addr);
+        }
+        return;
+    }
+    let _ = socket.send_to(b"READY=1", addr.as_ref());
+}
+
+#[cfg(not(unix))]
+fn notify_ready() {}
+
+/// systemd user units for socket-activated `tik daemon`.
+fn unit_files(exe: &Path) -> [(&'static str, String); 2] {
+    let socket = "\
+[Unit]
+Description=tik timer daemon socket
+
+[Socket]
+ListenStream=%t/pomitik.sock
+
+[Install]
+WantedBy=sockets.target
+"
+    .to_string();
+    let service = format!(
+        "\
+[Unit]
+Description=tik timer daemon
+Requires=tik.socket
+After=tik.socket
+
+[Service]
+Type=notify
+ExecStart={} daemon
+Restart=on-failure
+
+[Install]
+WantedBy=default.target
+",
+        exe.display()
+    );
+    [("tik.socket", socket), ("tik.service", service)]
+}
+
+/// Write the systemd user units for this binary to ~/.config/systemd/user.
+pub fn install_unit() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate tik binary: {e}"))?;
+    let dir = dirs::config_dir()
+        .ok_or("Could not determine config directory")?
+        .join("systemd")
+        .join("user");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    for (name, contents) in unit_files(&exe) {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+    println!("Enable with: systemctl --user daemon-reload && systemctl --user enable --now tik.socket");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stopped_job_leaves_nothing_to_recover() {
+        let dir = std::env::temp_dir().join(format!("pomitik-daemon-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let forget = Forget { checkpoint: dir.join("resume.json"), pending: dir.join("pending.json") };
+        let now = chrono::Local::now();
+        let checkpoint = crate::resume::Checkpoint {
+            version: crate::resume::SCHEMA_VERSION,
+            pid: std::process::id(),
+            name: "25m".to_string(),
+            title: None,
+            context: crate::timer::TimerContext::Standalone,
+            started_at: now,
+            total_secs: 1500,
+            remaining_secs: 900,
+            paused: false,
+            paused_secs: 0,
+            updated_at: now,
+            labels: Default::default(),
+        };
+        std::fs::write(&forget.checkpoint, serde_json::to_string(&checkpoint).unwrap()).unwrap();
+        std::fs::write(&forget.pending, "{}").unwrap();
+
+        // Stopped the way `Request::Stop` does it
+        let job = spawn_job(forget, std::future::pending());
+        job.abort();
+        assert!(job.await.unwrap_err().is_cancelled());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn service_unit_runs_this_binary() {
+        let [(socket_name, socket), (service_name, service)] = unit_files(Path::new("/usr/bin/tik"));
+        assert_eq!(socket_name, "tik.socket");
+        assert!(socket.contains("ListenStream=%t/pomitik.sock"));
+        assert_eq!(service_name, "tik.service");
+        assert!(service.contains("ExecStart=/usr/bin/tik daemon"));
+        assert!(service.contains("Type=notify"));
+    }
+
+    #[tokio::test]
+    async fn stop_without_timer_is_an_error() {
+        let mut current = None;
+        assert!(matches!(handle(Request::Stop, &mut current), Response::Error { .. }));
+    }
+
+    #[test]
+    fn pause_without_timer_is_an_error() {
+        assert_eq!(
+            handle_control(Request::Pause),
+            Response::Error { message: "No timer running.".to_string() }
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Day expressions for the log's `--since`, `--until` and `--last`: "today",
+//! "yesterday", a weekday, a date, and spans like "30d" that end today.
+
+use chrono::{Datelike, Months, NaiveDate};
+
+/// A day: "today", "yesterday", a weekday ("mon", "friday") for the most
+/// recent one, today included, or YYYY-MM-DD.
+pub fn parse_day(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let day = input.trim().to_lowercase();
+    match day.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - chrono::Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(weekday) = day.parse::<chrono::Weekday>() {
+        let back = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        return Ok(today - chrono::Duration::days(i64::from(back)));
+    }
+    NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{input}': expected today, yesterday, a weekday or YYYY-MM-DD"))
+}
+
+/// The first day of a span ending today: "7d" is today and the six days
+/// before it, and "2w", "3m" and "1y" count weeks, months and years.
+pub fn parse_span(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let span = input.trim();
+    let invalid = || format!("Invalid span '{input}': expected a number of days, weeks, months or years, like 30d, 2w, 3m or 1y");
+    let unit = span.chars().last().ok_or_else(invalid)?;
+    let count: u32 = span[..span.len() - unit.len_utf8()].parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?;
+    let start = match unit {
+        'd' => today.checked_sub_days(chrono::Days::new(u64::from(count))),
+        'w' => today.checked_sub_days(chrono::Days::new(u64::from(count) * 7)),
+        'm' => today.checked_sub_months(Months::new(count)),
+        'y' => count.checked_mul(12).and_then(|months| today.checked_sub_months(Months::new(months))),
+        _ => return Err(invalid()),
+    };
+    start.and_then(|day| day.succ_opt()).ok_or_else(|| format!("'{input}' goes back further than dates can"))
+}
+
+/// The days a log command looks at; either end may be open.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DateRange {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    /// From `--since` and `--until` days, or a `--last` span.
+    pub fn parse(since: Option<&str>, until: Option<&str>, last: Option<&str>, today: NaiveDate) -> Result<Self, String> {
+        let range = DateRange {
+            since: match last {
+                Some(span) => Some(parse_span(span, today)?),
+                None => since.map(|day| parse_day(day, today)).transpose()?,
+            },
+            until: until.map(|day| parse_day(day, today)).transpose()?,
+        };
+        if let (Some(since), Some(until)) = (range.since, range.until)
+            && since > until
+        {
+            return Err(format!("The range starts on {since}, after it ends on {until}"));
+        }
+        Ok(range)
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.since.is_none_or(|since| day >= since) && self.until.is_none_or(|until| day <= until)
+    }
+
+    /// The range cut to start no earlier than `day`.
+    pub fn starting(self, day: NaiveDate) -> Self {
+        DateRange { since: Some(self.since.map_or(day, |since| since.max(day))), ..self }
+    }
+
+    /// "2024-05-01 to 2024-05-31", "Since 2024-05-01" or "Until 2024-05-31".
+    pub fn title(&self) -> String {
+        match (self.since, self.until) {
+            (Some(since), Some(until)) if since == until => since.format("%Y-%m-%d %a").to_string(),
+            (Some(since), Some(until)) => format!("{since} to {until}"),
+            (Some(since), None) => format!("Since {since}"),
+            (None, Some(until)) => format!("Until {until}"),
+            (None, None) => "All time".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_day_accepts_names_weekdays_and_dates() {
+        // A Tuesday
+        let today = date(2026, 3, 3);
+        assert_eq!(parse_day("yesterday", today), Ok(date(2026, 3, 2)));
+        assert_eq!(parse_day("Today", today), Ok(today));
+        assert_eq!(parse_day("tue", today), Ok(today));
+        assert_eq!(parse_day("friday", today), Ok(date(2026, 2, 27)));
+        assert_eq!(parse_day("2026-01-31", today), Ok(date(2026, 1, 31)));
+        assert!(parse_day("last tuesday", today).unwrap_err().contains("YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn spans_end_today() {
+        let today = date(2026, 3, 31);
+        assert_eq!(parse_span("1d", today), Ok(today));
+        assert_eq!(parse_span("30d", today), Ok(date(2026, 3, 2)));
+        assert_eq!(parse_span("2w", today), Ok(date(2026, 3, 18)));
+        assert_eq!(parse_span("1m", today), Ok(date(2026, 3, 1)));
+        assert_eq!(parse_span("1y", today), Ok(date(2025, 4, 1)));
+        for bad in ["", "d", "0d", "30", "30x", "-3d", "99999999999d"] {
+            assert!(parse_span(bad, today).is_err(), "{bad}");
+        }
+        assert!(parse_span("4000000000y", today).unwrap_err().contains("further"));
+    }
+
+    #[test]
+    fn ranges_check_their_ends() {
+        let today = date(2026, 3, 3);
+        let range = DateRange::parse(Some("2026-03-01"), Some("yesterday"), None, today).unwrap();
+        assert!(range.contains(date(2026, 3, 1)) && range.contains(date(2026, 3, 2)));
+        assert!(!range.contains(today) && !range.contains(date(2026, 2, 28)));
+        assert_eq!(range.title(), "2026-03-01 to 2026-03-02");
+        assert_eq!(range.starting(date(2026, 3, 2)).since, Some(date(2026, 3, 2)));
+        assert_eq!(range.starting(date(2026, 1, 1)), range);
+
+        let last = DateRange::parse(None, None, Some("7d"), today).unwrap();
+        assert_eq!(last, DateRange { since: Some(date(2026, 2, 25)), until: None });
+        assert!(DateRange::parse(None, None, None, today).unwrap().is_all());
+        assert!(DateRange::parse(Some("today"), Some("yesterday"), None, today).unwrap_err().contains("after it ends"));
+    }
+}
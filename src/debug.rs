@@ -0,0 +1,51 @@
+//! A plain-text log of things that went wrong in the background, such as a
+//! notification that couldn't be delivered. Printing them would tear the
+//! full-screen timer, so they're written here instead.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Past this size the file starts over, so it never needs cleaning up.
+const MAX_LEN: u64 = 1024 * 1024;
+
+pub fn debug_path() -> PathBuf {
+    crate::log::log_path().with_file_name("debug.log")
+}
+
+/// Append a timestamped line. Failing to write it is ignored: there's
+/// nowhere better to report it.
+pub fn record(message: &str) {
+    let _ = record_to(&debug_path(), message, chrono::Local::now());
+}
+
+fn record_to(path: &Path, message: &str, now: chrono::DateTime<chrono::Local>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let full = std::fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_LEN);
+    let mut file = std::fs::OpenOptions::new().create(true).append(!full).write(true).truncate(full).open(path)?;
+    writeln!(file, "{} {message}", now.format("%Y-%m-%dT%H:%M:%S%:z"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_appended_until_the_file_is_full() {
+        let dir = std::env::temp_dir().join(format!("pomitik-debug-{}", std::process::id()));
+        let path = dir.join("debug.log");
+        let now = chrono::Local::now();
+        record_to(&path, "first", now).unwrap();
+        record_to(&path, "second", now).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert!(contents.lines().next().unwrap().ends_with(" first"));
+
+        std::fs::write(&path, vec![b'x'; MAX_LEN as usize + 1]).unwrap();
+        record_to(&path, "fresh", now).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().trim_end().ends_with(" fresh"));
+        assert!(std::fs::metadata(&path).unwrap().len() < 100);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use discord_rich_presence::activity::{Activity, Timestamps};
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+use std::time::Duration;
+
+use crate::config::DiscordConfig;
+use crate::state::RuntimeState;
+use crate::timer::TimerContext;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+
+/// What we show on Discord; `None` clears the activity.
+#[derive(Debug, Clone, PartialEq)]
+struct Presence {
+    details: String,
+    state: String,
+    /// Unix time the countdown ends; Discord renders it as "mm:ss left".
+    end: Option<i64>,
+}
+
+/// Show focus blocks as Discord Rich Presence. The client is blocking, so it
+/// runs on its own thread and polls the state file.
+pub fn spawn(config: DiscordConfig) {
+    let Some(client_id) = config.client_id else {
+        return;
+    };
+    std::thread::spawn(move || run(&client_id));
+}
+
+fn run(client_id: &str) {
+    let mut client = DiscordIpcClient::new(client_id);
+    let mut connected = false;
+    let mut shown: Option<Option<Presence>> = None;
+    loop {
+        if !connected {
+            // Discord may not be running yet; keep trying quietly
+            if client.connect().is_err() {
+                std::thread::sleep(RECONNECT_DELAY);
+                continue;
+            }
+            connected = true;
+            shown = None;
+        }
+
+        let presence = presence_for(crate::state::read().as_ref());
+        if shown.as_ref() != Some(&presence) {
+            let result = match &presence {
+                Some(p) => {
+                    let mut activity = Activity::new().details(&p.details).state(&p.state);
+                    if let Some(end) = p.end {
+                        activity = activity.timestamps(Timestamps::new().end(end));
+                    }
+                    client.set_activity(activity)
+                }
+                None => client.clear_activity(),
+            };
+            if result.is_err() {
+                connected = false;
+                continue;
+            }
+            shown = Some(presence);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Work and standalone timers show as "Focusing"; breaks and idle clear it.
+fn presence_for(state: Option<&RuntimeState>) -> Option<Presence> {
+    let state = state?;
+    if state.phase == TimerContext::Break {
+        return None;
+    }
+    let round = state.round.map(|(current, total)| format!("round {current}/{total}"));
+    if state.paused {
+        let remaining = crate::duration::Duration::from_secs(state.remaining_secs).format_hms();
+        let state_line = match round {
+            Some(round) => format!("{remaining} left ({round})"),
+            None => format!("{remaining} left"),
+        };
+        return Some(Presence { details: "Focusing (paused)".to_string(), state: state_line, end: None });
+    }
+    // Derived from the last state write so it stays stable between polls
+    let end = state.updated_at.timestamp() + state.remaining_secs as i64;
+    Some(Presence {
+        details: "Focusing".to_string(),
+        state: round.unwrap_or_else(|| state.name.clone()),
+        end: Some(end),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(phase: TimerContext, paused: bool) -> RuntimeState {
+        RuntimeState {
+            version: crate::state::SCHEMA_VERSION,
+            pid: 1,
+            name: "pomodoro".to_string(),
+            phase,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 1122,
+            paused,
+            updated_at: chrono::Local::now(),
+        }
+    }
+
+    #[test]
+    fn work_shows_countdown_and_round() {
+        let state = sample(TimerContext::Work, false);
+        let presence = presence_for(Some(&state)).unwrap();
+        assert_eq!(presence.details, "Focusing");
+        assert_eq!(presence.state, "round 2/4");
+        assert_eq!(presence.end, Some(state.updated_at.timestamp() + 1122));
+    }
+
+    #[test]
+    fn paused_work_shows_remaining_text() {
+        let presence = presence_for(Some(&sample(TimerContext::Work, true))).unwrap();
+        assert_eq!(presence.state, "18:42 left (round 2/4)");
+        assert_eq!(presence.end, None);
+    }
+
+    #[test]
+    fn breaks_and_idle_clear_presence() {
+        assert_eq!(presence_for(Some(&sample(TimerContext::Break, false))), None);
+        assert_eq!(presence_for(None), None);
+    }
+}
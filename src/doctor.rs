@@ -0,0 +1,211 @@
+//! `tik doctor`: checks the things tik relies on but can't control (a
+//! notification server, a sound player, the terminal, writable data dirs),
+//! plus blocks logged twice, and says how to fix whatever is wrong.
+
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, PartialEq)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Check { name, status: Status::Pass, detail: detail.into(), hint: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check { name, status: Status::Warn, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check { name, status: Status::Fail, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+/// Run every check and print the results. Returns false if any failed.
+pub fn run() -> bool {
+    let checks = [
+        check_config(),
+        check_notifications(),
+        check_sound(),
+        check_terminal(),
+        check_writable("data dir", crate::log::log_path().parent()),
+        check_log(),
+        #[cfg(unix)]
+        check_writable("socket dir", crate::ipc::socket_path().parent()),
+    ];
+    for check in &checks {
+        println!("{}", format_check(check));
+    }
+    !checks.iter().any(|c| c.status == Status::Fail)
+}
+
+fn format_check(check: &Check) -> String {
+    let mark = match check.status {
+        Status::Pass => "ok  ",
+        Status::Warn => "warn",
+        Status::Fail => "FAIL",
+    };
+    let mut line = format!("[{mark}] {:<14}{}", check.name, check.detail);
+    if let Some(hint) = &check.hint {
+        line.push_str(&format!("\n       {:<14}{hint}", ""));
+    }
+    line
+}
+
+fn check_config() -> Check {
+    let path = Config::config_path();
+    let problems = Config::problems();
+    if !problems.is_empty() {
+        return Check::fail(
+            "config",
+            problems.join("; "),
+            "fix the file, or run `tik help config` for the keys; tik uses the defaults until then",
+        );
+    }
+    if path.exists() {
+        Check::pass("config", path.display().to_string())
+    } else {
+        Check::pass("config", format!("no file at {}, using the defaults", path.display()))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn check_notifications() -> Check {
+    match notify_rust::get_server_information() {
+        Ok(info) => Check::pass("notifications", format!("{} {}", info.name, info.version)),
+        Err(e) => Check::fail(
+            "notifications",
+            format!("no notification server: {e}"),
+            "start one (dunst, mako, or your desktop's own), and check DBUS_SESSION_BUS_ADDRESS is set",
+        ),
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn check_notifications() -> Check {
+    Check::warn(
+        "notifications",
+        "can't be checked on this platform",
+        "if none appear, allow notifications for your terminal in the system settings",
+    )
+}
+
+fn check_sound() -> Check {
+    match crate::sound::available_player() {
+        Some(player) => Check::pass("sound", player),
+        None => Check::warn(
+            "sound",
+            format!("none of {} found on PATH", crate::sound::PLAYERS.join(", ")),
+            "install one to hear cues; timers and notifications work without",
+        ),
+    }
+}
+
+fn check_terminal() -> Check {
+    let term = std::env::var("TERM").ok();
+    let colors = crossterm::style::available_color_count();
+    terminal_check(std::io::stdout().is_terminal(), term.as_deref(), colors)
+}
+
+fn terminal_check(tty: bool, term: Option<&str>, colors: u16) -> Check {
+    if !tty {
+        return Check::warn(
+            "terminal",
+            "stdout is not a terminal",
+            "run tik in a terminal, or use --headless for a line per event",
+        );
+    }
+    let term_name = term.map_or_else(|| "TERM unset".to_string(), |t| format!("TERM={t}"));
+    if crate::render::is_dumb(term) {
+        return Check::warn(
+            "terminal",
+            format!("{term_name}: no alternate screen, so timers print plain lines"),
+            "set TERM to your terminal's type, e.g. xterm-256color, for the full-screen timer",
+        );
+    }
+    if colors < 256 {
+        return Check::warn(
+            "terminal",
+            format!("{term_name}, {colors} colors"),
+            "colors will be approximate; set COLORTERM=truecolor if your terminal supports it",
+        );
+    }
+    Check::pass("terminal", format!("{term_name}, {colors} colors, alternate screen"))
+}
+
+fn check_log() -> Check {
+    let entries = crate::log::read_entries();
+    match crate::log::duplicate_indices(&entries).len() {
+        0 => Check::pass("log", format!("{} blocks", entries.len())),
+        n => Check::warn(
+            "log",
+            format!("{n} of {} blocks were logged twice", entries.len()),
+            "run `tik log dedupe` to remove the repeats",
+        ),
+    }
+}
+
+fn check_writable(name: &'static str, dir: Option<&Path>) -> Check {
+    let Some(dir) = dir else {
+        return Check::fail(name, "no directory to use", "set HOME, or XDG_DATA_HOME");
+    };
+    let probe = dir.join(".tik-doctor");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&probe, b""))
+        .and_then(|()| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::pass(name, dir.display().to_string()),
+        Err(e) => Check::fail(
+            name,
+            format!("{} is not writable: {e}", dir.display()),
+            format!("check the permissions on {}", dir.display()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumb_terminal_fails() {
+        assert_eq!(terminal_check(false, Some("xterm-256color"), 256).status, Status::Warn);
+        assert_eq!(terminal_check(true, Some("dumb"), 8).status, Status::Warn);
+        assert_eq!(terminal_check(true, Some("xterm"), 8).status, Status::Warn);
+        assert_eq!(terminal_check(true, Some("xterm-256color"), 256).status, Status::Pass);
+    }
+
+    #[test]
+    fn hint_on_its_own_line() {
+        let check = Check::fail("sound", "none found", "install paplay");
+        assert_eq!(format_check(&check), "[FAIL] sound         none found\n                     install paplay");
+        assert_eq!(format_check(&Check::pass("sound", "paplay")), "[ok  ] sound         paplay");
+    }
+
+    #[test]
+    fn unwritable_dir_fails() {
+        let dir = std::env::temp_dir().join(format!("tik-doctor-{}", std::process::id()));
+        assert_eq!(check_writable("data dir", Some(&dir)).status, Status::Pass);
+        assert!(!dir.join(".tik-doctor").exists());
+        std::fs::remove_dir(&dir).unwrap();
+        let file = std::env::temp_dir().join(format!("tik-doctor-file-{}", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        assert_eq!(check_writable("data dir", Some(&file.join("sub"))).status, Status::Fail);
+        std::fs::remove_file(&file).unwrap();
+    }
+}
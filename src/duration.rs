@@ -1,31 +1,125 @@
-use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
 
-#[derive(Debug, PartialEq)]
+/// A whole number of seconds. Subtraction saturates at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Duration {
     pub total_secs: u64,
 }
 
 impl Duration {
-    pub fn parse(input: &str) -> Result<Self, String> {
-        let re = Regex::new(r"^(?:(\d+)h)?(?:(\d+)m)?(?:(\d+)s)?$").unwrap();
-        let caps = re.captures(input).ok_or_else(|| {
-            format!("Invalid duration format: '{input}'")
-        })?;
+    /// The longest duration `parse` accepts. Far past any useful timer, and
+    /// well inside what `Instant` and chrono can add without overflowing.
+    pub const MAX: Duration = Duration::from_secs(9999 * 3600);
 
-        let hours: u64 = caps.get(1).map_or(0, |m| m.as_str().parse().unwrap());
-        let minutes: u64 = caps.get(2).map_or(0, |m| m.as_str().parse().unwrap());
-        let seconds: u64 = caps.get(3).map_or(0, |m| m.as_str().parse().unwrap());
-
-        let total_secs = hours * 3600 + minutes * 60 + seconds;
+    pub const fn from_secs(total_secs: u64) -> Self {
+        Duration { total_secs }
+    }
 
-        if total_secs == 0 {
-            return Err("Duration must be greater than zero".to_string());
+    /// Parse "25m", "1h30m", "90s", or decimal hours and minutes like "1.5h".
+    /// Zero is rejected: it's never a useful timer.
+    pub fn parse(input: &str) -> Result<Self, DurationError> {
+        let duration = Self::parse_spec(input)?;
+        if duration.total_secs == 0 {
+            return Err(DurationError::Zero);
         }
+        Ok(duration)
+    }
 
+    /// Components are read one at a time, hours then minutes then seconds,
+    /// so an error can name the one that's wrong.
+    fn parse_spec(input: &str) -> Result<Self, DurationError> {
+        if input.is_empty() {
+            return Err(DurationError::Empty);
+        }
+        let mut rest = input;
+        let mut total_secs: u64 = 0;
+        // Units still allowed: each appears at most once, in this order
+        let mut units: &[(char, u64)] = &[('h', 3600), ('m', 60), ('s', 1)];
+        while !rest.is_empty() {
+            let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let (number, after) = rest.split_at(number_len);
+            let Some(unit) = after.chars().next() else {
+                return Err(DurationError::MissingUnit(number.to_string()));
+            };
+            let component = &rest[..number_len + unit.len_utf8()];
+            if number.is_empty() {
+                return Err(DurationError::MissingNumber(unit));
+            }
+            let Some(position) = units.iter().position(|(u, _)| *u == unit) else {
+                return Err(if "hms".contains(unit) {
+                    DurationError::OutOfOrder(component.to_string())
+                } else {
+                    DurationError::BadUnit { component: component.to_string(), unit }
+                });
+            };
+            let secs = component_secs(component, number, units[position].1)?;
+            total_secs = total_secs
+                .checked_add(secs)
+                .filter(|&secs| secs <= Duration::MAX.total_secs)
+                .ok_or_else(|| DurationError::Overflow(input.to_string()))?;
+            units = &units[position + 1..];
+            rest = &after[unit.len_utf8()..];
+        }
         Ok(Duration { total_secs })
     }
 
+    /// Time from `now` until the clock next reads minute MM, for ":MM", or
+    /// the next full hour, for "endofhour". None when `input` is neither.
+    /// Landing exactly on the boundary counts to the following one.
+    pub fn until_clock(input: &str, now: chrono::NaiveTime) -> Option<Result<Self, String>> {
+        use chrono::Timelike;
+        let minute = match input.trim() {
+            "endofhour" => 0,
+            spec => {
+                let digits = spec.strip_prefix(':')?;
+                match digits.parse::<u32>() {
+                    Ok(minute) if minute < 60 && digits.len() == 2 => minute,
+                    _ => return Some(Err(format!("Invalid clock minute: '{spec}' (use :00 to :59)"))),
+                }
+            }
+        };
+        let now_secs = u64::from(now.minute() * 60 + now.second());
+        let target = u64::from(minute * 60);
+        let secs = (target + 3600 - now_secs - 1) % 3600 + 1;
+        Some(Ok(Duration::from_secs(secs)))
+    }
+
+    /// The likely intended spec when a one-unit duration was typed with the
+    /// next unit up: "25h" for "25m", "90m" for "90s".
+    pub fn smaller_unit(input: &str) -> Option<String> {
+        let input = input.trim();
+        let (number, unit) = input.split_at(input.len().checked_sub(1)?);
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let smaller = match unit {
+            "h" => "m",
+            "m" => "s",
+            _ => return None,
+        };
+        Some(format!("{number}{smaller}"))
+    }
+
+    /// The shortest form `parse` reads back: "25m", "1h30m", "1m30s", "0s".
+    pub fn to_spec(self) -> String {
+        let h = self.total_secs / 3600;
+        let m = (self.total_secs % 3600) / 60;
+        let s = self.total_secs % 60;
+        let mut spec = String::new();
+        if h > 0 {
+            spec.push_str(&format!("{h}h"));
+        }
+        if m > 0 {
+            spec.push_str(&format!("{m}m"));
+        }
+        if s > 0 || spec.is_empty() {
+            spec.push_str(&format!("{s}s"));
+        }
+        spec
+    }
+
     pub fn format_hms(&self) -> String {
         let h = self.total_secs / 3600;
         let m = (self.total_secs % 3600) / 60;
@@ -37,6 +131,119 @@ impl Duration {
             format!("{m}:{s:02}")
         }
     }
+
+    /// Whole minutes ("25m", "1h05m"), or seconds under a minute ("45s").
+    pub fn format_compact(&self) -> String {
+        let h = self.total_secs / 3600;
+        let m = (self.total_secs % 3600) / 60;
+        if h > 0 {
+            format!("{h}h{m:02}m")
+        } else if m > 0 {
+            format!("{m}m")
+        } else {
+            format!("{}s", self.total_secs)
+        }
+    }
+
+    /// Spelled out for screen readers: "1 hour 5 minutes", "30 seconds".
+    pub fn format_words(&self) -> String {
+        let parts = [(self.total_secs / 3600, "hour"), ((self.total_secs % 3600) / 60, "minute"), (self.total_secs % 60, "second")];
+        let words: Vec<String> = parts
+            .iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, unit)| if *n == 1 { format!("1 {unit}") } else { format!("{n} {unit}s") })
+            .collect();
+        if words.is_empty() { "0 seconds".to_string() } else { words.join(" ") }
+    }
+}
+
+/// Seconds in one component such as "30m" or "1.5h", whose amount is
+/// `number`. Decimal hours and minutes ("1.5", "0.25") round to the nearest
+/// second; amounts past `Duration::MAX` are refused rather than wrapped or
+/// saturated.
+fn component_secs(component: &str, number: &str, unit_secs: u64) -> Result<u64, DurationError> {
+    let too_long = || DurationError::Overflow(component.to_string());
+    let Some((whole, fraction)) = number.split_once('.') else {
+        // All digits, so the only way to fail is too many of them
+        let amount = number.parse::<u64>().map_err(|_| too_long())?;
+        return amount.checked_mul(unit_secs).filter(|&secs| secs <= Duration::MAX.total_secs).ok_or_else(too_long);
+    };
+    if unit_secs == 1 {
+        return Err(DurationError::Fraction(component.to_string()));
+    }
+    let not_a_number = || DurationError::NotANumber(component.to_string());
+    if whole.is_empty() || fraction.is_empty() || fraction.contains('.') {
+        return Err(not_a_number());
+    }
+    let secs = (number.parse::<f64>().map_err(|_| not_a_number())? * unit_secs as f64).round();
+    if secs > Duration::MAX.total_secs as f64 {
+        return Err(too_long());
+    }
+    Ok(secs as u64)
+}
+
+/// Why `Duration::parse` refused its input. Each names the part that's
+/// wrong but not the whole input; callers say which duration it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DurationError {
+    Empty,
+    Zero,
+    /// A component, or the whole duration, is past `Duration::MAX`.
+    Overflow(String),
+    /// A letter other than h, m or s, as in "30x".
+    BadUnit { component: String, unit: char },
+    /// A unit repeated or after a smaller one, as in "30m1h".
+    OutOfOrder(String),
+    /// A trailing number with no unit, as in "1h30".
+    MissingUnit(String),
+    /// A unit with no number, as in "1hm".
+    MissingNumber(char),
+    /// Fractional seconds, as in "1.5s".
+    Fraction(String),
+    /// More than one decimal point, or one with no digits on a side.
+    NotANumber(String),
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationError::Empty => write!(f, "it's empty (try \"25m\" or \"1h30m\")"),
+            DurationError::Zero => write!(f, "it must be greater than zero"),
+            DurationError::Overflow(spec) => write!(f, "'{spec}' is longer than the {} limit", Duration::MAX.to_spec()),
+            DurationError::BadUnit { component, unit } => write!(f, "'{unit}' in '{component}' isn't a unit (use h, m or s)"),
+            DurationError::OutOfOrder(component) => {
+                write!(f, "'{component}' is out of order or repeated (write hours, then minutes, then seconds)")
+            }
+            DurationError::MissingUnit(number) => write!(f, "'{number}' needs a unit (h, m or s)"),
+            DurationError::MissingNumber(unit) => write!(f, "'{unit}' needs a number before it"),
+            DurationError::Fraction(component) => write!(f, "'{component}' has a fraction; seconds must be whole"),
+            DurationError::NotANumber(component) => write!(f, "'{component}' isn't a number"),
+        }
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// How a countdown amount is shown. Negative amounts (overtime) get a
+/// leading minus in either style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeStyle {
+    /// "25:00", "1:05:00"
+    #[default]
+    Clock,
+    /// "25m", "1h05m", "45s": for status bars
+    Compact,
+}
+
+impl TimeStyle {
+    pub fn format(self, secs: i64) -> String {
+        let sign = if secs < 0 { "-" } else { "" };
+        let d = Duration::from_secs(secs.unsigned_abs());
+        match self {
+            TimeStyle::Clock => format!("{sign}{}", d.format_hms()),
+            TimeStyle::Compact => format!("{sign}{}", d.format_compact()),
+        }
+    }
 }
 
 impl fmt::Display for Duration {
@@ -45,6 +252,93 @@ impl fmt::Display for Duration {
     }
 }
 
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, rhs: Duration) -> Duration {
+        Duration { total_secs: self.total_secs.saturating_add(rhs.total_secs) }
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, rhs: Duration) -> Duration {
+        Duration { total_secs: self.total_secs.saturating_sub(rhs.total_secs) }
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+/// Drops sub-second precision.
+impl From<std::time::Duration> for Duration {
+    fn from(d: std::time::Duration) -> Self {
+        Duration { total_secs: d.as_secs() }
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(d: Duration) -> Self {
+        std::time::Duration::from_secs(d.total_secs)
+    }
+}
+
+/// Fails for negative deltas; drops sub-second precision.
+impl TryFrom<chrono::Duration> for Duration {
+    type Error = String;
+
+    fn try_from(d: chrono::Duration) -> Result<Self, String> {
+        u64::try_from(d.num_seconds())
+            .map(Duration::from_secs)
+            .map_err(|_| format!("Negative duration: {d}"))
+    }
+}
+
+/// Saturates at chrono's largest duration.
+impl From<Duration> for chrono::Duration {
+    fn from(d: Duration) -> Self {
+        i64::try_from(d.total_secs).ok().and_then(chrono::Duration::try_seconds).unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+/// Serialized as a duration string ("1h30m").
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_spec())
+    }
+}
+
+/// Accepts a duration string ("25m") or a number of seconds.
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Secs(u64),
+            Spec(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Secs(total_secs) if total_secs > Duration::MAX.total_secs => {
+                Err(serde::de::Error::custom(format!("{total_secs} seconds is longer than the {} limit", Duration::MAX.to_spec())))
+            }
+            Repr::Secs(total_secs) => Ok(Duration { total_secs }),
+            Repr::Spec(spec) => {
+                Duration::parse_spec(&spec).map_err(|e| serde::de::Error::custom(format!("invalid duration '{spec}': {e}")))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -99,8 +393,166 @@ mod tests {
         assert_eq!(Duration { total_secs: 45 }.format_hms(), "0:45");
     }
 
+    #[test]
+    fn arithmetic_saturates() {
+        let mut d = Duration::from_secs(1500) + Duration::from_secs(300);
+        assert_eq!(d.total_secs, 1800);
+        d -= Duration::from_secs(2000);
+        assert_eq!(d, Duration::default());
+        d += Duration::from_secs(60);
+        assert_eq!(Duration::from_secs(90) - d, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn std_and_chrono_conversions() {
+        assert_eq!(Duration::from(std::time::Duration::from_millis(1999)).total_secs, 1);
+        assert_eq!(std::time::Duration::from(Duration::from_secs(5)).as_secs(), 5);
+        assert_eq!(Duration::try_from(chrono::Duration::minutes(25)), Ok(Duration::from_secs(1500)));
+        assert!(Duration::try_from(chrono::Duration::seconds(-1)).is_err());
+        assert_eq!(chrono::Duration::from(Duration::from_secs(90)).num_seconds(), 90);
+    }
+
+    #[test]
+    fn to_spec_roundtrips() {
+        for (secs, spec) in [(1500, "25m"), (5400, "1h30m"), (90, "1m30s"), (3605, "1h5s")] {
+            assert_eq!(Duration::from_secs(secs).to_spec(), spec);
+            assert_eq!(Duration::parse(spec).unwrap().total_secs, secs);
+        }
+        assert_eq!(Duration::default().to_spec(), "0s");
+    }
+
+    #[test]
+    fn serde_as_string_or_seconds() {
+        let d = Duration::from_secs(5400);
+        assert_eq!(serde_json::to_string(&d).unwrap(), r#""1h30m""#);
+        assert_eq!(serde_json::from_str::<Duration>(r#""1h30m""#).unwrap(), d);
+        assert_eq!(serde_json::from_str::<Duration>("5400").unwrap(), d);
+        assert_eq!(serde_json::from_str::<Duration>(r#""0s""#).unwrap(), Duration::default());
+        assert!(serde_json::from_str::<Duration>(r#""soon""#).is_err());
+    }
+
+    #[test]
+    fn parse_decimal_hours_and_minutes() {
+        assert_eq!(Duration::parse("1.5h").unwrap().total_secs, 5400);
+        assert_eq!(Duration::parse("0.5m").unwrap().total_secs, 30);
+        assert_eq!(Duration::parse("1.5h30m").unwrap().total_secs, 7200);
+        assert_eq!(Duration::parse("0.01m").unwrap().total_secs, 1);
+        assert!(Duration::parse("0.0h").is_err());
+        assert!(Duration::parse("1.5s").is_err());
+        assert!(Duration::parse(".5h").is_err());
+    }
+
+    #[test]
+    fn until_clock_boundaries() {
+        let at = |h, m, s| chrono::NaiveTime::from_hms_opt(h, m, s).unwrap();
+        let secs = |input, now| Duration::until_clock(input, now).unwrap().unwrap().total_secs;
+        assert_eq!(secs("endofhour", at(14, 35, 0)), 1500);
+        assert_eq!(secs(":00", at(14, 59, 30)), 30);
+        assert_eq!(secs(":30", at(14, 35, 0)), 3300);
+        assert_eq!(secs(":30", at(14, 10, 15)), 1185);
+        // On the boundary: the next one, not a zero-length timer
+        assert_eq!(secs(":00", at(15, 0, 0)), 3600);
+        assert!(Duration::until_clock(":75", at(14, 0, 0)).unwrap().is_err());
+        assert!(Duration::until_clock("25m", at(14, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn smaller_unit_for_typos() {
+        assert_eq!(Duration::smaller_unit("25h").as_deref(), Some("25m"));
+        assert_eq!(Duration::smaller_unit("90m").as_deref(), Some("90s"));
+        assert_eq!(Duration::smaller_unit("90s"), None);
+        assert_eq!(Duration::smaller_unit("1h30m"), None);
+        assert_eq!(Duration::smaller_unit("h"), None);
+    }
+
+    #[test]
+    fn format_words_spells_out_units() {
+        assert_eq!(Duration::from_secs(1500).format_words(), "25 minutes");
+        assert_eq!(Duration::from_secs(3900).format_words(), "1 hour 5 minutes");
+        assert_eq!(Duration::from_secs(61).format_words(), "1 minute 1 second");
+        assert_eq!(Duration::from_secs(0).format_words(), "0 seconds");
+    }
+
+    #[test]
+    fn format_compact_styles() {
+        assert_eq!(Duration::from_secs(1500).format_compact(), "25m");
+        assert_eq!(Duration::from_secs(3900).format_compact(), "1h05m");
+        assert_eq!(Duration::from_secs(45).format_compact(), "45s");
+    }
+
+    #[test]
+    fn negative_amounts_get_a_minus() {
+        assert_eq!(TimeStyle::Clock.format(-83), "-1:23");
+        assert_eq!(TimeStyle::Clock.format(83), "1:23");
+        assert_eq!(TimeStyle::Compact.format(-3900), "-1h05m");
+    }
+
     #[test]
     fn format_human_readable() {
         assert_eq!(Duration { total_secs: 1500 }.to_string(), "25:00");
     }
+
+    #[test]
+    fn huge_amounts_are_refused_not_wrapped() {
+        assert_eq!(Duration::parse("9999h"), Ok(Duration::MAX));
+        for spec in ["999999999h", "10000h", "9999h1s", "599941m", "99999999999999999999999s", "1e300h", "9999999999999999.5h"] {
+            let error = Duration::parse(spec).unwrap_err();
+            assert!(matches!(error, DurationError::Overflow(_) | DurationError::BadUnit { .. }), "{spec}: {error}");
+        }
+        assert!(serde_json::from_str::<Duration>("18446744073709551615").is_err());
+        assert_eq!(chrono::Duration::from(Duration::from_secs(u64::MAX)), chrono::Duration::MAX);
+    }
+
+    #[test]
+    fn errors_name_the_bad_component() {
+        let error = |spec| Duration::parse(spec).unwrap_err();
+        assert_eq!(error(""), DurationError::Empty);
+        assert_eq!(error("0h0m"), DurationError::Zero);
+        assert_eq!(error("1h30x"), DurationError::BadUnit { component: "30x".to_string(), unit: 'x' });
+        assert_eq!(error("1h30x").to_string(), "'x' in '30x' isn't a unit (use h, m or s)");
+        assert_eq!(error("30m1h"), DurationError::OutOfOrder("1h".to_string()));
+        assert_eq!(error("5m5m"), DurationError::OutOfOrder("5m".to_string()));
+        assert_eq!(error("1h30"), DurationError::MissingUnit("30".to_string()));
+        assert_eq!(error("1hm"), DurationError::MissingNumber('m'));
+        assert_eq!(error("1.5s"), DurationError::Fraction("1.5s".to_string()));
+        assert_eq!(error("1.2.3h"), DurationError::NotANumber("1.2.3h".to_string()));
+        assert_eq!(error("2.h"), DurationError::NotANumber("2.h".to_string()));
+        assert_eq!(error("10000h"), DurationError::Overflow("10000h".to_string()));
+        assert_eq!(error("9999h1s"), DurationError::Overflow("9999h1s".to_string()));
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn spec_roundtrips(secs in 0..=Duration::MAX.total_secs) {
+                let d = Duration::from_secs(secs);
+                prop_assert_eq!(Duration::parse_spec(&d.to_spec()), Ok(d));
+            }
+
+            #[test]
+            fn components_add_up(h in 0u64..=9998, m in 0u64..120, s in 0u64..120) {
+                let spec = format!("{h}h{m}m{s}s");
+                prop_assert_eq!(Duration::parse_spec(&spec).map(|d| d.total_secs), Ok(h * 3600 + m * 60 + s));
+            }
+
+            #[test]
+            fn never_panics_or_passes_the_limit(input in "[0-9hms.]{0,24}|\\PC{0,12}") {
+                if let Ok(d) = Duration::parse(&input) {
+                    prop_assert!(d <= Duration::MAX);
+                    prop_assert!(d.total_secs > 0);
+                }
+            }
+
+            #[test]
+            fn arithmetic_never_overflows(a: u64, b: u64) {
+                let (a, b) = (Duration::from_secs(a), Duration::from_secs(b));
+                prop_assert_eq!((a + b).total_secs, a.total_secs.saturating_add(b.total_secs));
+                prop_assert_eq!((a - b).total_secs, a.total_secs.saturating_sub(b.total_secs));
+                let _ = (chrono::Duration::from(a), a.format_hms(), a.format_words(), a.to_spec());
+            }
+        }
+    }
 }
@@ -0,0 +1,362 @@
+//! Typed events from a run (one timer, or one whole session), broadcast to
+//! the subsystems that react to them: the notifiers (desktop, sound...), the
+//! log, hooks and the state file. The timer and session loops only say what
+//! happened; each subscriber decides what to do about it.
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::log::{LogEntry, SessionRecord, WorkLabels};
+use crate::sound::SoundPack;
+use crate::state::RuntimeState;
+use crate::timer::TimerContext;
+
+/// Large enough that subscribers doing file or D-Bus work never fall behind a
+/// once-a-second tick.
+const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TikEvent {
+    /// A countdown started, with the labels a work block is logged with.
+    PhaseStarted { name: String, context: TimerContext, total_secs: u64, round: Option<(u32, u32)>, labels: WorkLabels },
+    /// The countdown moved on a second, or its length or round count changed.
+    Tick { total_secs: u64, remaining_secs: u64, round: Option<(u32, u32)> },
+    Paused { paused: bool },
+    /// A `--notify-at` mark was reached.
+    Milestone { name: String, remaining_secs: u64 },
+    /// The countdown stopped, however it ended. A closed bus implies it too.
+    TimerEnded,
+    /// A phase ran out or was skipped, with the entries to log for it and, in
+    /// a session, the phase that comes next (name and length).
+    Completed { name: String, context: TimerContext, duration_secs: u64, skipped: bool, next: Option<(String, u64)>, entries: Vec<LogEntry> },
+    SessionEnded(SessionRecord),
+}
+
+/// The event channel for one run, with its subscribers. Close it at the end
+/// of the run so queued events are handled before the process moves on.
+pub struct EventBus {
+    tx: broadcast::Sender<TikEvent>,
+    subscribers: Vec<JoinHandle<()>>,
+    sounds: Option<SoundPack>,
+}
+
+impl EventBus {
+    /// A bus with the standard subscribers. `log` is off when another running
+    /// timer already counts this time.
+    pub fn start(config: &crate::config::Config, silent: bool, log: bool) -> Self {
+        let sounds = SoundPack::from_config(config, silent);
+        let mut bus = EventBus { tx: broadcast::channel(CAPACITY).0, subscribers: Vec::new(), sounds: sounds.clone() };
+        let notify = config.notify.clone();
+        let notifiers = crate::notify::Notifiers::from_config(&notify, sounds.as_ref());
+        let mut round = None;
+        bus.subscribe(move |event| {
+            match event {
+                TikEvent::PhaseStarted { round: started, .. } => round = *started,
+                TikEvent::Tick { round: now, .. } => round = *now,
+                _ => {}
+            }
+            let Some(notice) = crate::notify::Notice::from_event(event, round) else {
+                return;
+            };
+            // Checked per event: a long session can run into the quiet hours
+            let quiet = notify.quiet_now();
+            if quiet.is_some_and(|quiet| quiet.suppress) {
+                return;
+            }
+            notifiers.send(&notice, silent || quiet.is_some());
+        });
+        if log {
+            bus.subscribe(write_log);
+            let mut running = None;
+            bus.subscribe(move |event| autosave(event, &mut running));
+        }
+        if let Some(command) = config.break_command.clone() {
+            bus.subscribe(move |event| {
+                if let TikEvent::PhaseStarted { context: TimerContext::Break, .. } = event {
+                    crate::hooks::spawn(&command);
+                }
+            });
+        }
+        if config.insights {
+            let mut recorder = crate::insights::Recorder::default();
+            bus.subscribe(move |event| crate::insights::record(event, &mut recorder));
+        }
+        let mut current = None;
+        bus.subscribe(move |event| update_state_file(event, &mut current));
+        bus
+    }
+
+    /// Run `handle` for every event on a blocking thread, since notifications,
+    /// sounds and file writes all block. Events missed by a lagging subscriber
+    /// are skipped rather than stalling the timer.
+    fn subscribe(&mut self, mut handle: impl FnMut(&TikEvent) + Send + 'static) {
+        let mut rx = self.tx.subscribe();
+        self.subscribers.push(tokio::task::spawn_blocking(move || {
+            loop {
+                match rx.blocking_recv() {
+                    Ok(event) => handle(&event),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            handle(&TikEvent::TimerEnded);
+        }));
+    }
+
+    /// The configured sound pack, also used by timers for the warning and ticks.
+    pub fn sounds(&self) -> Option<&SoundPack> {
+        self.sounds.as_ref()
+    }
+
+    pub fn emit(&self, event: TikEvent) {
+        // No subscribers is fine: nothing wants to know
+        let _ = self.tx.send(event);
+    }
+
+    /// Stop accepting events and wait for the subscribers to catch up.
+    pub async fn close(self) {
+        drop(self.tx);
+        for subscriber in self.subscribers {
+            let _ = subscriber.await;
+        }
+    }
+}
+
+fn write_log(event: &TikEvent) {
+    match event {
+        TikEvent::Completed { entries, .. } => {
+            for entry in entries {
+                if let Err(e) = crate::log::append_entry(entry) {
+                    crate::messages::warn(format!("Failed to write log: {e}"));
+                }
+            }
+        }
+        TikEvent::SessionEnded(record) => {
+            if let Err(e) = crate::log::append_session(record) {
+                crate::messages::warn(format!("Failed to write session log: {e}"));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// How often the running work block is saved while it counts down.
+const AUTOSAVE_INTERVAL: chrono::Duration = chrono::Duration::seconds(30);
+
+/// The work block being counted down, for autosaving.
+#[derive(Debug, Clone, PartialEq)]
+struct Running {
+    name: String,
+    labels: WorkLabels,
+    elapsed_secs: u64,
+    saved_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Autosave {
+    Nothing,
+    Write(Box<LogEntry>),
+    Clear,
+}
+
+/// Save the running work block now and then, so a crash still credits most
+/// of it; `recover_pending` logs what's left behind. Dropped once the block
+/// is logged or the timer stops.
+fn autosave(event: &TikEvent, running: &mut Option<Running>) {
+    match apply_to_autosave(event, running, chrono::Local::now()) {
+        Autosave::Nothing => {}
+        Autosave::Write(entry) => {
+            let _ = crate::log::write_pending(&crate::log::PendingEntry { pid: std::process::id(), entry: *entry });
+        }
+        Autosave::Clear => crate::log::clear_pending(),
+    }
+}
+
+fn apply_to_autosave(event: &TikEvent, running: &mut Option<Running>, now: chrono::DateTime<chrono::Local>) -> Autosave {
+    match event {
+        TikEvent::PhaseStarted { name, context, labels, .. } => {
+            *running = (*context != TimerContext::Break).then(|| Running {
+                name: name.clone(),
+                labels: labels.clone(),
+                elapsed_secs: 0,
+                saved_at: None,
+            });
+            Autosave::Nothing
+        }
+        TikEvent::Tick { total_secs, remaining_secs, .. } => {
+            let Some(block) = running else {
+                return Autosave::Nothing;
+            };
+            block.elapsed_secs = total_secs.saturating_sub(*remaining_secs);
+            // The first save waits a whole interval too: a crash in the
+            // first seconds isn't worth crediting
+            let since = block.saved_at.map_or(block.elapsed_secs as i64, |at| (now - at).num_seconds());
+            if since < AUTOSAVE_INTERVAL.num_seconds() {
+                return Autosave::Nothing;
+            }
+            block.saved_at = Some(now);
+            Autosave::Write(Box::new(LogEntry {
+                completed_at: now,
+                ..LogEntry::work(&block.name, block.elapsed_secs, &block.labels)
+            }))
+        }
+        TikEvent::Completed { .. } | TikEvent::TimerEnded => match running.take() {
+            Some(block) if block.saved_at.is_some() => Autosave::Clear,
+            _ => Autosave::Nothing,
+        },
+        TikEvent::Paused { .. } | TikEvent::Milestone { .. } | TikEvent::SessionEnded(_) => Autosave::Nothing,
+    }
+}
+
+/// Keep the state file in step with the timer.
+fn update_state_file(event: &TikEvent, current: &mut Option<RuntimeState>) {
+    if !apply_to_state(event, current, chrono::Local::now()) {
+        return;
+    }
+    let _ = match current {
+        Some(state) => crate::state::write(state),
+        None => {
+            crate::state::clear();
+            Ok(())
+        }
+    };
+}
+
+/// Fold an event into the running timer's state. Returns whether the file
+/// needs rewriting (or removing, once `current` is None). Readers extrapolate
+/// the countdown from `updated_at`, so ticks only count when the length or
+/// round count changed.
+fn apply_to_state(event: &TikEvent, current: &mut Option<RuntimeState>, now: chrono::DateTime<chrono::Local>) -> bool {
+    match event {
+        TikEvent::PhaseStarted { name, context, total_secs, round, .. } => {
+            *current = Some(RuntimeState {
+                version: crate::state::SCHEMA_VERSION,
+                pid: std::process::id(),
+                name: name.clone(),
+                phase: *context,
+                round: *round,
+                total_secs: *total_secs,
+                remaining_secs: *total_secs,
+                paused: false,
+                updated_at: now,
+            });
+            true
+        }
+        TikEvent::Tick { total_secs, remaining_secs, round } => {
+            let Some(state) = current else {
+                return false;
+            };
+            let changed = state.total_secs != *total_secs || state.round != *round;
+            state.total_secs = *total_secs;
+            state.remaining_secs = *remaining_secs;
+            state.round = *round;
+            if changed {
+                state.updated_at = now;
+            }
+            changed
+        }
+        TikEvent::Paused { paused } => {
+            let Some(state) = current else {
+                return false;
+            };
+            state.paused = *paused;
+            state.updated_at = now;
+            true
+        }
+        TikEvent::TimerEnded => current.take().is_some(),
+        TikEvent::Milestone { .. } | TikEvent::Completed { .. } | TikEvent::SessionEnded(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started() -> TikEvent {
+        TikEvent::PhaseStarted {
+            name: "pomodoro".to_string(),
+            context: TimerContext::Work,
+            total_secs: 1500,
+            round: Some((1, 4)),
+            labels: WorkLabels { tags: vec!["deep".to_string()], task: Some("report".to_string()), ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn state_rewritten_on_start_pause_and_changes_only() {
+        let now = chrono::Local::now();
+        let mut current = None;
+        assert!(apply_to_state(&started(), &mut current, now));
+        let tick = |total_secs, remaining_secs, round| TikEvent::Tick { total_secs, remaining_secs, round: Some(round) };
+        assert!(!apply_to_state(&tick(1500, 1200, (1, 4)), &mut current, now));
+        assert!(apply_to_state(&TikEvent::Paused { paused: true }, &mut current, now));
+        let state = current.clone().unwrap();
+        assert_eq!((state.remaining_secs, state.paused), (1200, true));
+
+        // Flow extension and an added round
+        assert!(apply_to_state(&tick(1800, 300, (1, 5)), &mut current, now));
+        let state = current.clone().unwrap();
+        assert_eq!((state.total_secs, state.remaining_secs, state.round), (1800, 300, Some((1, 5))));
+
+        assert!(apply_to_state(&TikEvent::TimerEnded, &mut current, now));
+        assert_eq!(current, None);
+        assert!(!apply_to_state(&TikEvent::TimerEnded, &mut current, now));
+    }
+
+    #[test]
+    fn events_before_a_phase_leave_no_state() {
+        let now = chrono::Local::now();
+        let mut current = None;
+        assert!(!apply_to_state(&TikEvent::Tick { total_secs: 60, remaining_secs: 30, round: None }, &mut current, now));
+        assert!(!apply_to_state(&TikEvent::Paused { paused: true }, &mut current, now));
+        assert_eq!(current, None);
+    }
+
+    #[test]
+    fn work_block_autosaved_every_interval_until_logged() {
+        let start = chrono::Local::now();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+        let tick = |remaining_secs| TikEvent::Tick { total_secs: 1500, remaining_secs, round: Some((1, 4)) };
+        let mut running = None;
+        assert_eq!(apply_to_autosave(&started(), &mut running, start), Autosave::Nothing);
+        assert_eq!(apply_to_autosave(&tick(1490), &mut running, at(10)), Autosave::Nothing);
+        let Autosave::Write(entry) = apply_to_autosave(&tick(1470), &mut running, at(30)) else {
+            panic!("expected a save after 30s");
+        };
+        assert_eq!((entry.name.as_str(), entry.duration_secs), ("pomodoro", 30));
+        // Recovered, it's logged like the finished block would have been
+        assert_eq!((entry.task.as_deref(), entry.tags.as_slice()), (Some("report"), ["deep".to_string()].as_slice()));
+        assert_eq!(apply_to_autosave(&tick(1460), &mut running, at(40)), Autosave::Nothing);
+        assert!(matches!(apply_to_autosave(&tick(1440), &mut running, at(60)), Autosave::Write(_)));
+        assert_eq!(apply_to_autosave(&TikEvent::TimerEnded, &mut running, at(61)), Autosave::Clear);
+        assert_eq!(apply_to_autosave(&TikEvent::TimerEnded, &mut running, at(62)), Autosave::Nothing);
+    }
+
+    #[test]
+    fn breaks_not_autosaved() {
+        let start = chrono::Local::now();
+        let mut running = None;
+        let started = TikEvent::PhaseStarted {
+            name: "break".to_string(),
+            context: TimerContext::Break,
+            total_secs: 300,
+            round: None,
+            labels: WorkLabels::default(),
+        };
+        apply_to_autosave(&started, &mut running, start);
+        let tick = TikEvent::Tick { total_secs: 300, remaining_secs: 200, round: None };
+        assert_eq!(apply_to_autosave(&tick, &mut running, start + chrono::Duration::seconds(100)), Autosave::Nothing);
+    }
+
+    #[tokio::test]
+    async fn close_delivers_queued_events() {
+        let mut bus = EventBus { tx: broadcast::channel(CAPACITY).0, subscribers: Vec::new(), sounds: None };
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = std::sync::Arc::clone(&seen);
+        bus.subscribe(move |event| log.lock().unwrap().push(event.clone()));
+        bus.emit(started());
+        bus.emit(TikEvent::Paused { paused: true });
+        bus.close().await;
+        assert_eq!(*seen.lock().unwrap(), [started(), TikEvent::Paused { paused: true }, TikEvent::TimerEnded]);
+    }
+}
@@ -0,0 +1,129 @@
+//! The custom footer line from `[footer]` in the config. Placeholders that
+//! need a process or file read ({command}, {battery}) are refreshed in the
+//! background so drawing a frame never waits on them.
+
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::config::FooterConfig;
+
+const DEFAULT_REFRESH: &str = "30s";
+
+/// Values filled in by the background refresh.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Cached {
+    command: Option<String>,
+    battery: Option<String>,
+}
+
+/// A configured footer for one run. Dropping it stops the refresh.
+pub struct Footer {
+    template: String,
+    cached: Arc<Mutex<Cached>>,
+    refresh: JoinHandle<()>,
+}
+
+impl Footer {
+    /// Start refreshing the footer's values, or None when no template is set.
+    /// Must be called inside the tokio runtime.
+    pub fn start(config: &FooterConfig) -> Option<Self> {
+        let template = config.template.clone().filter(|t| !t.trim().is_empty())?;
+        let every = crate::duration::Duration::parse(config.refresh.as_deref().unwrap_or(DEFAULT_REFRESH))
+            .map_or(30, |d| d.total_secs)
+            .max(1);
+        let command = config.command.clone().filter(|_| template.contains("{command}"));
+        let battery = template.contains("{battery}");
+        let cached = Arc::new(Mutex::new(Cached::default()));
+        let refresh = {
+            let cached = Arc::clone(&cached);
+            tokio::spawn(async move {
+                if command.is_none() && !battery {
+                    return;
+                }
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(every));
+                loop {
+                    interval.tick().await;
+                    let command = command.clone();
+                    let read = tokio::task::spawn_blocking(move || Cached {
+                        command: command.as_deref().and_then(crate::hooks::first_line),
+                        battery: if battery { read_battery() } else { None },
+                    });
+                    if let Ok(values) = read.await
+                        && let Ok(mut cached) = cached.lock()
+                    {
+                        *cached = values;
+                    }
+                }
+            })
+        };
+        Some(Footer { template, cached, refresh })
+    }
+
+    /// The footer as it should read now, or None when every placeholder is empty.
+    pub fn line(&self, task: Option<&str>, clock: &str) -> Option<String> {
+        let cached = self.cached.lock().map(|c| c.clone()).unwrap_or_default();
+        let line = render(&self.template, task, clock, &cached);
+        (!line.is_empty()).then_some(line)
+    }
+}
+
+impl Drop for Footer {
+    fn drop(&mut self) {
+        self.refresh.abort();
+    }
+}
+
+/// Fill in the template. Placeholders with nothing to show become empty.
+fn render(template: &str, task: Option<&str>, clock: &str, cached: &Cached) -> String {
+    let battery = cached.battery.as_deref().map(|b| format!("{b}%")).unwrap_or_default();
+    template
+        .replace("{task}", task.unwrap_or(""))
+        .replace("{clock}", clock)
+        .replace("{battery}", &battery)
+        .replace("{command}", cached.command.as_deref().unwrap_or(""))
+        .trim()
+        .to_string()
+}
+
+/// Charge of the first battery, in percent. Only Linux exposes it as a file.
+fn read_battery() -> Option<String> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut batteries: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("BAT")))
+        .collect();
+    batteries.sort();
+    let capacity = std::fs::read_to_string(batteries.first()?.join("capacity")).ok()?;
+    let capacity = capacity.trim();
+    capacity.parse::<u8>().ok().map(|_| capacity.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_filled() {
+        let cached = Cached { command: Some("main".to_string()), battery: Some("84".to_string()) };
+        assert_eq!(
+            render("{battery} | {task} | {command} | {clock}", Some("write report"), "14:05", &cached),
+            "84% | write report | main | 14:05"
+        );
+    }
+
+    #[test]
+    fn missing_values_left_empty() {
+        assert_eq!(render(" {task} {battery}", None, "14:05", &Cached::default()), "");
+        assert_eq!(render("on battery {battery}", None, "", &Cached::default()), "on battery");
+    }
+
+    #[tokio::test]
+    async fn no_template_no_footer() {
+        assert!(Footer::start(&FooterConfig::default()).is_none());
+        let config = FooterConfig { template: Some("{task}".to_string()), ..FooterConfig::default() };
+        let footer = Footer::start(&config).unwrap();
+        assert_eq!(footer.line(Some("review"), "09:00").as_deref(), Some("review"));
+        assert_eq!(footer.line(None, "09:00"), None);
+    }
+}
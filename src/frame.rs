@@ -0,0 +1,190 @@
+//! Parts of the timer frame worth keeping between draws: the progress bar
+//! strings and the formatted times. The timer redraws several times a second
+//! but these change far less often, so they are rebuilt on change instead of
+//! allocated per frame. Kept free of crate dependencies so the render
+//! benchmarks can include it directly.
+
+use std::fmt::Write;
+
+/// Left-aligned partial blocks, one to seven eighths of a cell.
+const PARTIAL_BLOCKS: [char; 7] = ['\u{258F}', '\u{258E}', '\u{258D}', '\u{258C}', '\u{258B}', '\u{258A}', '\u{2589}'];
+
+/// Braille cells filling one dot at a time, up the left column then the
+/// right: eight steps per character, like the partial blocks.
+const BRAILLE_STEPS: [char; 9] =
+    ['\u{2800}', '\u{2840}', '\u{2844}', '\u{2846}', '\u{2847}', '\u{28C7}', '\u{28E7}', '\u{28F7}', '\u{28FF}'];
+
+/// Glyphs for a few-character progress indicator in a status bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MiniBar {
+    Blocks,
+    Braille,
+}
+
+/// `width` characters of progress (0.0 to 1.0), each cell moving in eighths
+/// so even a one-character bar shows how far along the timer is. Always
+/// exactly `width` wide, so a status bar segment never shifts.
+pub fn mini_bar(progress: f64, width: usize, style: MiniBar) -> String {
+    let steps = (progress.clamp(0.0, 1.0) * width as f64 * 8.0) as usize;
+    (0..width)
+        .map(|cell| {
+            let filled = steps.saturating_sub(cell * 8).min(8);
+            match style {
+                MiniBar::Braille => BRAILLE_STEPS[filled],
+                MiniBar::Blocks => match filled {
+                    0 => '\u{2591}',
+                    8 => '\u{2588}',
+                    n => PARTIAL_BLOCKS[n - 1],
+                },
+            }
+        })
+        .collect()
+}
+
+/// Progress bar halves, rebuilt only when the filled width changes. The fill
+/// moves in eighths of a cell, so long timers creep rather than jump.
+pub struct BarCache {
+    width: u16,
+    filled: Option<u32>,
+    filled_str: String,
+    empty_str: String,
+}
+
+impl BarCache {
+    pub fn new(width: u16) -> Self {
+        BarCache { width, filled: None, filled_str: String::new(), empty_str: String::new() }
+    }
+
+    /// Filled width for `progress` (0.0 to 1.0), in eighths of a cell. The
+    /// bar only looks different when this changes.
+    pub fn steps(&self, progress: f64) -> u32 {
+        (progress.clamp(0.0, 1.0) * f64::from(self.width) * 8.0) as u32
+    }
+
+    /// Filled and empty halves of the bar for `progress` (0.0 to 1.0).
+    pub fn get(&mut self, progress: f64) -> (&str, &str) {
+        let filled = self.steps(progress);
+        if self.filled != Some(filled) {
+            self.filled = Some(filled);
+            let (full, eighths) = ((filled / 8) as usize, (filled % 8) as usize);
+            self.filled_str.clear();
+            self.filled_str.extend(std::iter::repeat_n('\u{2588}', full));
+            if eighths > 0 {
+                self.filled_str.push(PARTIAL_BLOCKS[eighths - 1]);
+            }
+            let used = full + usize::from(eighths > 0);
+            self.empty_str.clear();
+            self.empty_str.extend(std::iter::repeat_n('\u{2591}', self.width as usize - used));
+        }
+        (&self.filled_str, &self.empty_str)
+    }
+}
+
+/// A formatted time, reformatted only when the second (or tenth) changes.
+#[derive(Default)]
+pub struct TimeText {
+    key: Option<(i64, Option<u64>)>,
+    text: String,
+}
+
+impl TimeText {
+    pub fn get(&mut self, secs: i64) -> &str {
+        self.format(secs, None)
+    }
+
+    /// `mm:ss.t` for a time given in tenths of a second.
+    pub fn get_tenths(&mut self, tenths: u64) -> &str {
+        self.format((tenths / 10) as i64, Some(tenths % 10))
+    }
+
+    fn format(&mut self, secs: i64, tenth: Option<u64>) -> &str {
+        if self.key != Some((secs, tenth)) {
+            self.key = Some((secs, tenth));
+            self.text.clear();
+            write_time(&mut self.text, secs);
+            if let Some(tenth) = tenth {
+                let _ = write!(self.text, ".{tenth}");
+            }
+        }
+        &self.text
+    }
+}
+
+/// Fraction of the countdown done at `elapsed_millis`, for a bar that moves
+/// between the seconds shown; a zero-length timer counts as done.
+pub fn progress_at(elapsed_millis: u64, total_secs: u64) -> f64 {
+    if total_secs > 0 {
+        (elapsed_millis as f64 / (total_secs as f64 * 1000.0)).min(1.0)
+    } else {
+        1.0
+    }
+}
+
+/// `mm:ss`, or `h:mm:ss` from an hour up; negative (overtime) as `-mm:ss`.
+fn write_time(out: &mut String, secs: i64) {
+    if secs < 0 {
+        out.push('-');
+    }
+    let secs = secs.unsigned_abs();
+    let h = secs / 3600;
+    let m = (secs % 3600) / 60;
+    let s = secs % 60;
+    let _ = if h > 0 {
+        write!(out, "{h}:{m:02}:{s:02}")
+    } else {
+        write!(out, "{m:02}:{s:02}")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_cache_tracks_progress() {
+        let mut bar = BarCache::new(10);
+        assert_eq!(bar.get(0.0), ("", "\u{2591}".repeat(10).as_str()));
+        let (filled, empty) = bar.get(0.5);
+        assert_eq!((filled.chars().count(), empty.chars().count()), (5, 5));
+        assert_eq!(bar.get(2.0).0.chars().count(), 10);
+    }
+
+    #[test]
+    fn bar_cache_fills_in_eighths() {
+        let mut bar = BarCache::new(10);
+        // 3/80 of the way: three eighths of the first cell
+        let (filled, empty) = bar.get(3.0 / 80.0);
+        assert_eq!(filled, "\u{258D}");
+        assert_eq!(empty.chars().count(), 9);
+        let (filled, empty) = bar.get(0.25);
+        assert_eq!((filled, empty.chars().count()), ("\u{2588}\u{2588}\u{258C}", 7));
+        assert_eq!(bar.steps(0.5), 40);
+    }
+
+    #[test]
+    fn mini_bar_keeps_its_width() {
+        assert_eq!(mini_bar(0.0, 3, MiniBar::Braille), "\u{2800}\u{2800}\u{2800}");
+        assert_eq!(mini_bar(0.5, 3, MiniBar::Braille), "\u{28FF}\u{2847}\u{2800}");
+        assert_eq!(mini_bar(1.0, 3, MiniBar::Braille), "\u{28FF}".repeat(3));
+        assert_eq!(mini_bar(0.5, 1, MiniBar::Blocks), "\u{258C}");
+        assert_eq!(mini_bar(0.25, 2, MiniBar::Blocks), "\u{258C}\u{2591}");
+    }
+
+    #[test]
+    fn progress_at_uses_milliseconds() {
+        assert_eq!(progress_at(1500, 10), 0.15);
+        assert_eq!(progress_at(20_000, 10), 1.0);
+        assert_eq!(progress_at(0, 0), 1.0);
+    }
+
+    #[test]
+    fn time_text_reformats_on_change() {
+        let mut text = TimeText::default();
+        assert_eq!(text.get(65), "01:05");
+        assert_eq!(text.get(3725), "1:02:05");
+        assert_eq!(text.get(59), "00:59");
+        assert_eq!(text.get(-83), "-01:23");
+        assert_eq!(text.get_tenths(94), "00:09.4");
+        assert_eq!(text.get(9), "00:09");
+    }
+}
@@ -0,0 +1,54 @@
+//! The git repository and branch a timer was started in, recorded on work
+//! blocks with `record_git = true`. Uses the `git` binary; outside a
+//! repository, or without git installed, nothing is recorded.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitContext {
+    /// Name of the repository's top-level directory.
+    pub repo: String,
+    /// None on a detached HEAD.
+    pub branch: Option<String>,
+}
+
+pub fn detect(dir: &Path) -> Option<GitContext> {
+    let toplevel = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    let repo = repo_name(&toplevel)?;
+    // symbolic-ref also works on a branch with no commits yet
+    let branch = run_git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]);
+    Some(GitContext { repo, branch })
+}
+
+fn repo_name(toplevel: &str) -> Option<String> {
+    Path::new(toplevel).file_name()?.to_str().map(str::to_string)
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_name_is_the_top_level_directory() {
+        assert_eq!(repo_name("/home/me/src/pomitik"), Some("pomitik".to_string()));
+        assert_eq!(repo_name("/"), None);
+    }
+}
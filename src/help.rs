@@ -0,0 +1,227 @@
+use clap::CommandFactory;
+use std::io::{self, Write};
+
+/// Extended help topics shown by `tik help <topic>` and appended to the man page.
+pub const TOPICS: &[(&str, &str, &str)] = &[
+    ("keys", "Keyboard controls in the timer UI", KEYS),
+    ("config", "Config file location and format", CONFIG),
+    ("durations", "Duration formats and presets", DURATIONS),
+];
+
+const KEYS: &str = "\
+Timer focus:
+  space        pause / resume
+  s            skip to next phase (disabled on last round)
+  a / d        add / remove a round (sessions only)
+  f            flow: extend a work block in its final minutes
+  n            switch task mid-block (enter confirms, esc cancels)
+  m            ticking sound on / off during work
+  x            stop early
+  tab          switch focus to the todo sidebar
+  ctrl+c       quit
+
+From another terminal (or a hotkey): tik pause, tik skip, tik stop.
+After a crash or a closed terminal: tik resume.
+
+Round header (before each session phase):
+  1-9          set the upcoming phase to 5-45 minutes
+  0            restore the configured length
+  enter        start now
+
+Todo sidebar focus:
+  up / down    select task
+  enter        toggle done / undone
+  shift+up/dn  reorder tasks
+  tab          switch focus back to the timer";
+
+const CONFIG: &str = "\
+The config file lives at ~/.config/pomitik/config.toml
+(macOS: ~/Library/Application Support/pomitik/config.toml).
+config.<hostname>.toml beside it overrides it on that machine only, and
+TIK_* environment variables override both: TIK_PRESETS_POMODORO=30m,
+TIK_NOTIFY__QUIET_HOURS__FROM=22:00 (`__` separates levels explicitly).
+Run `tik config show` to see the current values, `tik config get theme.work`
+for any one of them and `tik config edit` to change the file.
+
+  require_ack = false      # wait for a key press after each phase
+  flow_extend = \"5m\"       # added by the flow key (\"off\" disables)
+  completion_animation = true  # flash the time for ~2s at zero
+  inhibit_sleep = false    # keep the screen awake during work phases
+  break_command = \"lock\"   # run at break start (\"lock\" or any shell command)
+  record_git = false       # store the git repo and branch on work blocks
+  time_format = \"24h\"     # \"12h\" or a strftime string for wall-clock times
+  sound_pack = \"soft\"     # \"classic\", \"soft\" or ~/.config/pomitik/sounds/<name>/
+  tick_sound = false       # tick through work phases (toggle with m)
+  repay_break_debt = false # add breaks skipped today to the long break
+  skip_logging = \"partial\" # skipped work: \"partial\", \"none\" or \"prompt\"
+  frame_rate = 4           # redraws per second for the progress bar (max 30)
+  tenths = false           # mm:ss.t on timers under a minute (or --tenths)
+  max_duration = \"24h\"     # ask before longer timers, to catch typos (\"off\")
+  a11y = false             # screen reader status lines, no full screen (or --a11y)
+  insights = false         # record usage locally for `tik insights`
+  adaptive = false         # use the work length suggested from the session log
+  journal = \"off\"          # note at session end: \"inline\" or \"editor\"
+  graphics = \"auto\"        # progress ring: \"kitty\", \"sixel\" or \"off\" (feature `graphics`)
+
+  [presets]
+  pomodoro = \"25m\"
+  break = \"5m\"
+  long-break = \"15m\"
+  # by weekday: pomodoro = { default = \"25m\", fri = \"20m\" }
+
+  [sessions.pomodoro]
+  work = \"pomodoro\"
+  break = \"break\"
+  long_break = \"long-break\"
+  rounds = 4
+
+  [sequences]              # presets run in order as one session
+  routine = [\"warmup\", \"pomodoro\", \"break\", \"pomodoro\"]
+
+  [theme]                  # color names (red, dark_cyan) or #rrggbb
+  work = \"green\"
+  break = \"cyan\"
+  work_background = \"black\" # optional
+  break_background = \"dark_blue\"
+  warning = \"20%\"        # yellow with this much left (percentage or duration)
+  critical = \"60s\"       # red from here
+
+  [notify]
+  channels = [\"desktop\", \"sound\"] # also \"terminal\" (OSC 9 + bell), \"webhook\"
+  webhook_url = \"https://...\" # the webhook channel POSTs JSON here
+
+  [notify.work]            # also [notify.break]; standalone timers use work
+  summary = \"Round {round} done\" # {name} {duration} {round} {task} {next}
+  body = \"Up next: {next}\"
+
+  [notify.quiet_hours]     # no sounds in this window (may wrap midnight)
+  from = \"22:00\"
+  to = \"08:00\"
+  suppress = false         # true: no notifications at all
+
+  [footer]                 # a line of your own under the timer
+  template = \"{battery} | {command} | {task}\"  # also {clock}
+  command = \"git branch --show-current\"  # first output line
+  refresh = \"30s\"        # how often the command and battery are re-read
+
+  [budgets]                # weekly budgets for --tag, shown in `tik log`
+  \"client-a\" = \"10h/week\"
+
+  [blocker]                # block sites during work phases
+  domains = [\"twitter.com\"] # added to the hosts file while working
+  start_command = \"...\"    # optional: run at work start / end
+  stop_command = \"...\"
+
+  [plan]                   # tik plan status
+  daily_goal = \"4h\"
+  end_of_day = \"18:00\"
+
+  [projects]               # credit work started in a directory
+  \"~/work/acme\" = \"acme\"  # (record_cwd = true also stores the path)
+
+  [webhook]                # tik daemon: POST /start {\"preset\": \"pomodoro\"}
+  listen = \"127.0.0.1:7878\"
+  token = \"change-me\"      # sent as Authorization: Bearer <token>
+
+Presets map a name to a duration. Sessions reference presets (or raw
+durations) for their work, break and long-break phases.";
+
+const DURATIONS: &str = "\
+A duration is any combination of hours, minutes and seconds, in that order:
+
+  90s          90 seconds
+  25m          25 minutes
+  1h30m        1 hour 30 minutes
+  1h30m15s     1 hour 30 minutes 15 seconds
+  1.5h         1 hour 30 minutes (hours and minutes may have decimals)
+
+:MM (e.g. :00, :30) runs until the clock next shows that minute, and
+`endofhour` until the next full hour.
+
+Add durations and presets with +: `25m+5m`, `pomodoro+10m`.
+
+Instead of a duration you can pass a preset, session or sequence name.
+Names are resolved in this order: session, sequence, then preset or raw
+duration. The built-in `pomodoro` session therefore wins over the
+`pomodoro` preset.";
+
+pub fn topic(name: &str) -> Option<&'static str> {
+    TOPICS.iter().find(|(n, _, _)| *n == name).map(|(_, _, text)| *text)
+}
+
+/// Print help for `tik help [topic]`. Unknown topics fall back to subcommand help.
+pub fn print_help<C: CommandFactory>(name: Option<&str>) -> Result<(), String> {
+    let mut cmd = C::command();
+    cmd.build();
+    let Some(name) = name else {
+        let _ = cmd.print_long_help();
+        println!();
+        print_topic_list();
+        return Ok(());
+    };
+
+    if let Some(text) = topic(name) {
+        println!("{text}");
+        return Ok(());
+    }
+    if let Some(sub) = cmd.find_subcommand_mut(name) {
+        let _ = sub.print_long_help();
+        return Ok(());
+    }
+    Err(format!("Unknown help topic: '{name}'. Run `tik help` to list topics."))
+}
+
+fn print_topic_list() {
+    println!("Help topics:");
+    for (name, summary, _) in TOPICS {
+        println!("  {name:<12}{summary}");
+    }
+}
+
+/// Render the man page (roff) for `C`, followed by one section per help topic.
+pub fn write_man_page<C: CommandFactory>(out: &mut dyn Write) -> io::Result<()> {
+    let man = clap_mangen::Man::new(C::command());
+    man.render(out)?;
+    for (name, _, text) in TOPICS {
+        writeln!(out, ".SH {}", name.to_uppercase())?;
+        writeln!(out, ".nf")?;
+        for line in text.lines() {
+            writeln!(out, "{}", roff_escape(line))?;
+        }
+        writeln!(out, ".fi")?;
+    }
+    Ok(())
+}
+
+fn roff_escape(line: &str) -> String {
+    let escaped = line.replace('\\', "\\e").replace('-', "\\-");
+    // A leading dot or apostrophe would be parsed as a roff request
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_found() {
+        assert!(topic("keys").unwrap().contains("pause"));
+        assert!(topic("config").unwrap().contains("[presets]"));
+        assert!(topic("durations").unwrap().contains("1h30m"));
+    }
+
+    #[test]
+    fn topic_not_found() {
+        assert!(topic("nonexistent").is_none());
+    }
+
+    #[test]
+    fn roff_escape_hyphens_and_leading_dot() {
+        assert_eq!(roff_escape("long-break"), "long\\-break");
+        assert_eq!(roff_escape(".config"), "\\&.config");
+    }
+}
@@ -0,0 +1,121 @@
+use std::process::{Command, Stdio};
+
+/// Config value for `break_command` that locks the screen with the platform's own command.
+pub const LOCK: &str = "lock";
+
+#[cfg(target_os = "macos")]
+const LOCK_COMMAND: &str = "pmset displaysleepnow";
+#[cfg(windows)]
+const LOCK_COMMAND: &str = "rundll32.exe user32.dll,LockWorkStation";
+#[cfg(not(any(target_os = "macos", windows)))]
+const LOCK_COMMAND: &str = "loginctl lock-session";
+
+/// Expand the `lock` shorthand; anything else is run as written.
+pub fn resolve_command(command: &str) -> &str {
+    if command.trim() == LOCK { LOCK_COMMAND } else { command }
+}
+
+/// Run a user-configured shell command without waiting for it. Failures are
+/// reported (see `crate::messages`) but never stop the timer. A thread waits
+/// on the child, so a long session doesn't collect a zombie per hook.
+pub fn spawn(command: &str) {
+    let command = resolve_command(command);
+    let result = shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match result {
+        Ok(mut child) => {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+        Err(e) => crate::messages::warn(format!("Failed to run '{command}': {e}")),
+    }
+}
+
+/// Run a command and wait for the first line it prints, trimmed. None when it
+/// fails to start, exits unsuccessfully or prints nothing.
+pub fn first_line(command: &str) -> Option<String> {
+    let output = shell(command).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Open `path` in `$VISUAL` or `$EDITOR` and wait for it to close. Errors
+/// when the editor can't be started or exits unsuccessfully.
+pub fn edit_file(path: &std::path::Path) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    match shell(&format!("{editor} \"{}\"", path.display())).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("'{editor}' exited with {status}")),
+        Err(e) => Err(format!("Failed to run '{editor}': {e}")),
+    }
+}
+
+/// Let the user write a note in `$VISUAL` or `$EDITOR` and return what they
+/// saved, trimmed. None when it's empty or the editor failed.
+pub fn edit_note() -> Option<String> {
+    let path = std::env::temp_dir().join(format!("tik-note-{}.md", std::process::id()));
+    std::fs::write(&path, "").ok()?;
+    let edited = edit_file(&path);
+    let note = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    if let Err(e) = edited {
+        eprintln!("{e}");
+        return None;
+    }
+    let note = note.ok()?;
+    let note = note.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_shorthand_expands() {
+        assert_eq!(resolve_command("lock"), LOCK_COMMAND);
+        assert_eq!(resolve_command(" lock "), LOCK_COMMAND);
+    }
+
+    #[test]
+    fn other_commands_pass_through() {
+        assert_eq!(resolve_command("xset dpms force off"), "xset dpms force off");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn first_line_of_output() {
+        assert_eq!(first_line("printf ' one \\ntwo\\n'").as_deref(), Some("one"));
+        assert_eq!(first_line("true"), None);
+        assert_eq!(first_line("echo hi; false"), None);
+    }
+}
@@ -0,0 +1,124 @@
+/// Keeps the screen from blanking or locking while a work timer is running.
+///
+/// Linux holds a `systemd-inhibit` idle lock, macOS runs `caffeinate`, and
+/// Windows sets the thread execution state. Failures are silent: the timer
+/// still works, the screen may just lock.
+pub struct SleepInhibitor {
+    enabled: bool,
+    guard: Option<platform::Guard>,
+}
+
+impl SleepInhibitor {
+    pub fn new(enabled: bool) -> Self {
+        SleepInhibitor { enabled, guard: None }
+    }
+
+    /// Acquire or release the inhibition. Cheap to call every tick.
+    pub fn set_active(&mut self, active: bool) {
+        if !self.enabled {
+            return;
+        }
+        if active && self.guard.is_none() {
+            self.guard = platform::Guard::acquire();
+        } else if !active {
+            self.guard = None;
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use std::process::{Child, Command, Stdio};
+
+    pub struct Guard {
+        child: Child,
+    }
+
+    impl Guard {
+        pub fn acquire() -> Option<Self> {
+            Self::command()
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()
+                .map(|child| Guard { child })
+        }
+
+        #[cfg(target_os = "macos")]
+        fn command() -> Command {
+            let mut cmd = Command::new("caffeinate");
+            cmd.args(["-d", "-i"]);
+            cmd
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        fn command() -> Command {
+            let mut cmd = Command::new("systemd-inhibit");
+            cmd.args([
+                "--what=idle",
+                "--who=tik",
+                "--why=Work timer running",
+                "--mode=block",
+                "sleep",
+                "infinity",
+            ]);
+            cmd
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::sync::mpsc;
+    use std::thread::JoinHandle;
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+    };
+
+    /// Execution state is per-thread, so a dedicated thread holds it until released.
+    pub struct Guard {
+        release_tx: mpsc::Sender<()>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Guard {
+        pub fn acquire() -> Option<Self> {
+            let (release_tx, release_rx) = mpsc::channel();
+            let thread = std::thread::spawn(move || {
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED) };
+                let _ = release_rx.recv();
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+            });
+            Some(Guard { release_tx, thread: Some(thread) })
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            let _ = self.release_tx.send(());
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_inhibitor_never_acquires() {
+        let mut inhibitor = SleepInhibitor::new(false);
+        inhibitor.set_active(true);
+        assert!(inhibitor.guard.is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use crossterm::event::{self, Event};
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// The one keyboard reader for a run. crossterm's event reads block, so a
+/// thread forwards terminal events into a channel; the timer, the round
+/// header and prompts take turns receiving from it. Keys nobody is waiting
+/// for stay queued for whoever asks next instead of being read and dropped by
+/// a consumer that has already finished.
+///
+/// Dropping it cancels the thread and waits for it to exit, so no reader
+/// outlives the run.
+pub struct Input {
+    events: Mutex<mpsc::UnboundedReceiver<Event>>,
+    cancel: CancellationToken,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// How often the reader checks for cancellation between events.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+impl Input {
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        let thread = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                while !cancel.is_cancelled() {
+                    // Stop once nobody can receive, or the terminal is gone:
+                    // a failing poll fails straight away, so retrying it spins
+                    match event::poll(POLL_INTERVAL) {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(_) => break,
+                    }
+                    let Ok(event) = event::read() else {
+                        break;
+                    };
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+        Input { events: Mutex::new(rx), cancel, thread: Some(thread) }
+    }
+
+    /// The next terminal event, or None once the reader has stopped. Safe to
+    /// cancel: an event is only taken off the queue when this returns it.
+    pub async fn next(&self) -> Option<Event> {
+        self.events.lock().await.recv().await
+    }
+}
+
+impl Drop for Input {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Opt-in local usage metrics (`insights = true`): one line per phase in
+//! `usage.json` with how it went (pauses, flow extension, skipped or quit),
+//! and `tik insights`, which turns them into suggested config tweaks.
+//! Nothing leaves the machine.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::duration::Duration;
+use crate::events::TikEvent;
+use crate::timer::TimerContext;
+
+/// Phases of one name needed before it gets a suggestion.
+const MIN_RUNS: usize = 5;
+/// Share of phases skipped, quit or extended that's worth a suggestion.
+const NOTABLE_SHARE: f64 = 0.5;
+/// Average pauses per work phase that's worth a suggestion.
+const NOTABLE_PAUSES: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Completed,
+    Skipped,
+    /// Stopped without finishing (`q`, Ctrl-C).
+    Quit,
+}
+
+/// How one phase went.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseUsage {
+    pub started_at: DateTime<Local>,
+    pub name: String,
+    pub context: TimerContext,
+    pub planned_secs: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub extended_secs: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pauses: u32,
+    pub outcome: Outcome,
+}
+
+fn is_zero<T: Default + PartialEq>(n: &T) -> bool {
+    *n == T::default()
+}
+
+pub fn usage_path() -> PathBuf {
+    crate::log::log_path().with_file_name("usage.json")
+}
+
+/// Follows a run's events and records each phase once it's settled.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    /// The phase counting down.
+    current: Option<PhaseUsage>,
+    /// A phase whose countdown stopped, waiting to hear whether it completed.
+    ended: Option<PhaseUsage>,
+}
+
+impl Recorder {
+    /// Fold in an event; returns a phase that's ready to record.
+    pub fn apply(&mut self, event: &TikEvent, now: DateTime<Local>) -> Option<PhaseUsage> {
+        match event {
+            TikEvent::PhaseStarted { name, context, total_secs, .. } => {
+                self.current = Some(PhaseUsage {
+                    started_at: now,
+                    name: name.clone(),
+                    context: *context,
+                    planned_secs: *total_secs,
+                    extended_secs: 0,
+                    pauses: 0,
+                    outcome: Outcome::Quit,
+                });
+                self.ended.take()
+            }
+            TikEvent::Tick { total_secs, .. } => {
+                if let Some(phase) = &mut self.current {
+                    phase.extended_secs = total_secs.saturating_sub(phase.planned_secs);
+                }
+                None
+            }
+            TikEvent::Paused { paused: true } => {
+                if let Some(phase) = &mut self.current {
+                    phase.pauses += 1;
+                }
+                None
+            }
+            // The timer's own end comes before the session says how it went;
+            // a second one (the bus closing) means nobody will
+            TikEvent::TimerEnded => match self.current.take() {
+                Some(phase) => self.ended.replace(phase),
+                None => self.ended.take(),
+            },
+            TikEvent::Completed { skipped, .. } => {
+                let mut phase = self.ended.take()?;
+                phase.outcome = if *skipped { Outcome::Skipped } else { Outcome::Completed };
+                Some(phase)
+            }
+            TikEvent::Paused { paused: false } | TikEvent::Milestone { .. } | TikEvent::SessionEnded(_) => None,
+        }
+    }
+}
+
+/// Event bus subscriber for `insights = true`.
+pub fn record(event: &TikEvent, recorder: &mut Recorder) {
+    if let Some(phase) = recorder.apply(event, Local::now())
+        && let Err(e) = crate::log::LogStore::at(usage_path()).append(&phase)
+    {
+        crate::messages::warn(format!("Failed to write usage: {e}"));
+    }
+}
+
+/// Counts for one phase name.
+#[derive(Debug, Default, PartialEq)]
+struct NameStats {
+    context: Option<TimerContext>,
+    runs: usize,
+    skipped: usize,
+    quit: usize,
+    extended: usize,
+    extended_secs: u64,
+    pauses: u32,
+    planned_secs: u64,
+}
+
+fn share(part: usize, whole: usize) -> f64 {
+    if whole == 0 { 0.0 } else { part as f64 / whole as f64 }
+}
+
+fn percent(part: usize, whole: usize) -> u32 {
+    (share(part, whole) * 100.0).round() as u32
+}
+
+/// Suggested config tweaks, most actionable first.
+fn suggestions(usage: &[PhaseUsage], presets: &HashMap<String, String>) -> Vec<String> {
+    let mut by_name: HashMap<&str, NameStats> = HashMap::new();
+    for phase in usage {
+        let stats = by_name.entry(&phase.name).or_default();
+        stats.context = Some(phase.context);
+        stats.runs += 1;
+        stats.pauses += phase.pauses;
+        stats.planned_secs = phase.planned_secs;
+        match phase.outcome {
+            Outcome::Skipped => stats.skipped += 1,
+            Outcome::Quit => stats.quit += 1,
+            Outcome::Completed => {}
+        }
+        if phase.extended_secs > 0 {
+            stats.extended += 1;
+            stats.extended_secs += phase.extended_secs;
+        }
+    }
+    let mut names: Vec<_> = by_name.into_iter().filter(|(_, s)| s.runs >= MIN_RUNS).collect();
+    names.sort_by(|a, b| b.1.runs.cmp(&a.1.runs).then(a.0.cmp(b.0)));
+
+    let mut tips = Vec::new();
+    for (name, stats) in &names {
+        let length = Duration::from_secs(stats.planned_secs).to_spec();
+        if stats.context == Some(TimerContext::Break) {
+            if share(stats.skipped, stats.runs) >= NOTABLE_SHARE {
+                tips.push(format!(
+                    "You skip {}% of '{name}' breaks ({length}) — shorten them?",
+                    percent(stats.skipped, stats.runs)
+                ));
+            }
+            continue;
+        }
+        let unfinished = stats.skipped + stats.quit;
+        if share(unfinished, stats.runs) >= NOTABLE_SHARE {
+            tips.push(format!(
+                "You finish only {}% of '{name}' blocks ({length}) — try a shorter length?",
+                100 - percent(unfinished, stats.runs)
+            ));
+        } else if share(stats.extended, stats.runs) >= NOTABLE_SHARE {
+            let longer = Duration::from_secs(stats.planned_secs + stats.extended_secs / stats.extended as u64);
+            tips.push(format!(
+                "You extend {}% of '{name}' blocks — make it {}?",
+                percent(stats.extended, stats.runs),
+                longer.to_spec()
+            ));
+        }
+        let pauses = stats.pauses as f64 / stats.runs as f64;
+        if pauses >= NOTABLE_PAUSES {
+            tips.push(format!("You pause '{name}' {pauses:.1} times per block — shorter blocks may fit better"));
+        }
+    }
+
+    // Plain durations typed often enough to deserve a name
+    for (name, stats) in &names {
+        if stats.context == Some(TimerContext::Standalone)
+            && Duration::parse(name).is_ok()
+            && !presets.values().any(|v| Duration::parse(v).is_ok_and(|d| d.total_secs == stats.planned_secs))
+        {
+            tips.push(format!("You've run {name} {} times — add it as a preset, e.g. focus = \"{name}\"", stats.runs));
+        }
+    }
+    tips
+}
+
+/// `tik insights`: a short summary of the usage file and what to change.
+pub fn print(config: &crate::config::Config) {
+    let usage: Vec<PhaseUsage> = crate::log::LogStore::at(usage_path()).read();
+    if usage.is_empty() {
+        if config.insights {
+            println!("No usage recorded yet. Run a few timers and come back.");
+        } else {
+            println!("Usage isn't being recorded. Set `insights = true` in the config to start.");
+        }
+        return;
+    }
+    let count = |outcome| usage.iter().filter(|p| p.outcome == outcome).count();
+    let pauses: u32 = usage.iter().map(|p| p.pauses).sum();
+    println!(
+        "{} phases since {}: {} completed, {} skipped, {} quit, {pauses} pauses",
+        usage.len(),
+        usage[0].started_at.format("%Y-%m-%d"),
+        count(Outcome::Completed),
+        count(Outcome::Skipped),
+        count(Outcome::Quit),
+    );
+    let tips = suggestions(&usage, &config.presets);
+    if tips.is_empty() {
+        println!("\nNothing to suggest yet.");
+        return;
+    }
+    println!();
+    for tip in tips {
+        println!("- {tip}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phase(name: &str, context: TimerContext, outcome: Outcome) -> PhaseUsage {
+        PhaseUsage {
+            started_at: Local::now(),
+            name: name.to_string(),
+            context,
+            planned_secs: Duration::parse(name).map_or(300, |d| d.total_secs),
+            extended_secs: 0,
+            pauses: 0,
+            outcome,
+        }
+    }
+
+    #[test]
+    fn recorder_waits_for_the_outcome() {
+        let now = Local::now();
+        let mut recorder = Recorder::default();
+        let started = TikEvent::PhaseStarted {
+            name: "pomodoro".into(),
+            context: TimerContext::Work,
+            total_secs: 1500,
+            round: None,
+            labels: Default::default(),
+        };
+        assert_eq!(recorder.apply(&started, now), None);
+        recorder.apply(&TikEvent::Paused { paused: true }, now);
+        recorder.apply(&TikEvent::Paused { paused: false }, now);
+        recorder.apply(&TikEvent::Tick { total_secs: 1800, remaining_secs: 10, round: None }, now);
+        assert_eq!(recorder.apply(&TikEvent::TimerEnded, now), None);
+        let completed = TikEvent::Completed {
+            name: "pomodoro".into(),
+            context: TimerContext::Work,
+            duration_secs: 1800,
+            skipped: true,
+            next: None,
+            entries: Vec::new(),
+        };
+        let phase = recorder.apply(&completed, now).unwrap();
+        assert_eq!((phase.pauses, phase.extended_secs, phase.outcome), (1, 300, Outcome::Skipped));
+        assert_eq!(recorder.apply(&TikEvent::TimerEnded, now), None);
+    }
+
+    #[test]
+    fn recorder_counts_a_quit_when_the_bus_closes() {
+        let now = Local::now();
+        let mut recorder = Recorder::default();
+        let started = TikEvent::PhaseStarted {
+            name: "10m".into(),
+            context: TimerContext::Standalone,
+            total_secs: 600,
+            round: None,
+            labels: Default::default(),
+        };
+        recorder.apply(&started, now);
+        assert_eq!(recorder.apply(&TikEvent::TimerEnded, now), None);
+        let phase = recorder.apply(&TikEvent::TimerEnded, now).unwrap();
+        assert_eq!((phase.name.as_str(), phase.outcome), ("10m", Outcome::Quit));
+    }
+
+    #[test]
+    fn skipped_breaks_and_unfinished_blocks_suggested() {
+        let mut usage = Vec::new();
+        for i in 0..5 {
+            let outcome = if i < 4 { Outcome::Skipped } else { Outcome::Completed };
+            usage.push(phase("long-break", TimerContext::Break, outcome));
+            let outcome = if i < 3 { Outcome::Quit } else { Outcome::Completed };
+            usage.push(phase("pomodoro", TimerContext::Work, outcome));
+            usage.push(phase("break", TimerContext::Break, Outcome::Completed));
+        }
+        let tips = suggestions(&usage, &HashMap::new());
+        assert_eq!(tips.len(), 2);
+        assert!(tips.iter().any(|t| t.starts_with("You skip 80% of 'long-break' breaks")));
+        assert!(tips.iter().any(|t| t.starts_with("You finish only 40% of 'pomodoro' blocks")));
+    }
+
+    #[test]
+    fn extensions_pauses_and_repeated_durations_suggested() {
+        let mut usage = Vec::new();
+        for _ in 0..5 {
+            usage.push(PhaseUsage { extended_secs: 600, pauses: 3, ..phase("pomodoro", TimerContext::Work, Outcome::Completed) });
+            usage.push(phase("45m", TimerContext::Standalone, Outcome::Completed));
+        }
+        usage.iter_mut().filter(|p| p.name == "pomodoro").for_each(|p| p.planned_secs = 1500);
+        let tips = suggestions(&usage, &HashMap::new());
+        assert!(tips.contains(&"You extend 100% of 'pomodoro' blocks — make it 35m?".to_string()));
+        assert!(tips.iter().any(|t| t.starts_with("You pause 'pomodoro' 3.0 times")));
+        assert!(tips.iter().any(|t| t.starts_with("You've run 45m 5 times")));
+
+        let presets = HashMap::from([("deep".to_string(), "45m".to_string())]);
+        assert!(!suggestions(&usage, &presets).iter().any(|t| t.starts_with("You've run")));
+    }
+
+    #[test]
+    fn too_few_runs_no_suggestions() {
+        let usage = vec![phase("break", TimerContext::Break, Outcome::Skipped); MIN_RUNS - 1];
+        assert!(suggestions(&usage, &HashMap::new()).is_empty());
+    }
+}
@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::state::RuntimeState;
+
+/// A request from a `tik` client to the daemon. Each connection carries one
+/// JSON request line and gets one JSON response line back, over a Unix socket
+/// or a Windows named pipe.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+pub enum Request {
+    /// Start a duration, preset or session by name.
+    Start { target: String },
+    /// Stop the running timer.
+    Stop,
+    /// Toggle pause on the running timer.
+    Pause,
+    /// Skip to the next session phase.
+    Skip,
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "result", rename_all = "lowercase")]
+pub enum Response {
+    Ok { message: String },
+    Error { message: String },
+    Status { state: Option<RuntimeState> },
+}
+
+/// Give up on a client that doesn't send its request line promptly.
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+pub use platform::{Listener, endpoint};
+#[cfg(unix)]
+pub use platform::socket_path;
+
+/// Accept connections forever, answering each request with `handler`.
+pub async fn serve<F>(mut listener: Listener, mut handler: F) -> io::Result<()>
+where
+    F: FnMut(Request) -> Response,
+{
+    loop {
+        let stream = listener.accept().await?;
+        match tokio::time::timeout(REQUEST_TIMEOUT, handle_connection(stream, &mut handler)).await {
+            Ok(Err(e)) => eprintln!("IPC connection error: {e}"),
+            Err(_) => eprintln!("IPC client timed out"),
+            Ok(Ok(())) => {}
+        }
+    }
+}
+
+async fn handle_connection<S, F>(stream: S, handler: &mut F) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: FnMut(Request) -> Response,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => handler(request),
+        Err(e) => Response::Error { message: format!("Invalid request: {e}") },
+    };
+    write_line(&mut writer, &response).await
+}
+
+/// Send one request to the daemon and wait for its response.
+pub async fn request(request: &Request) -> Result<Response, String> {
+    let stream = platform::connect()
+        .await
+        .map_err(|e| format!("No running tik found at {}: {e}", endpoint()))?;
+    let (reader, mut writer) = tokio::io::split(stream);
+    write_line(&mut writer, request).await.map_err(|e| format!("Failed to send request: {e}"))?;
+
+    let mut line = String::new();
+    BufReader::new(reader)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("Failed to read response: {e}"))?;
+    serde_json::from_str(&line).map_err(|e| format!("Invalid response from daemon: {e}"))
+}
+
+async fn write_line<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let mut json = serde_json::to_string(value)?;
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await?;
+    writer.flush().await
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::io;
+    use std::os::fd::FromRawFd;
+    use std::path::PathBuf;
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// First file descriptor passed by systemd socket activation.
+    const SD_LISTEN_FDS_START: i32 = 3;
+
+    pub fn socket_path() -> PathBuf {
+        match dirs::runtime_dir() {
+            Some(dir) => dir.join("pomitik.sock"),
+            None => dirs::data_local_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("pomitik")
+                .join("tik.sock"),
+        }
+    }
+
+    pub fn endpoint() -> String {
+        socket_path().display().to_string()
+    }
+
+    pub struct Listener {
+        inner: UnixListener,
+        /// Socket file we created ourselves and remove on drop (None when socket-activated).
+        owned_path: Option<PathBuf>,
+    }
+
+    impl Listener {
+        /// Use the socket systemd passed us if there is one, otherwise bind our own.
+        pub fn bind() -> io::Result<Self> {
+            if let Some(listener) = activated_listener()? {
+                return Ok(Listener { inner: listener, owned_path: None });
+            }
+            let path = socket_path();
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("a daemon is already listening on {}", path.display()),
+                ));
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Left behind by a daemon that didn't shut down cleanly
+            let _ = std::fs::remove_file(&path);
+            let inner = UnixListener::bind(&path)?;
+            Ok(Listener { inner, owned_path: Some(path) })
+        }
+
+        pub fn describe(&self) -> String {
+            match &self.owned_path {
+                Some(path) => path.display().to_string(),
+                None => "socket passed by systemd".to_string(),
+            }
+        }
+
+        pub async fn accept(&mut self) -> io::Result<UnixStream> {
+            self.inner.accept().await.map(|(stream, _)| stream)
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            if let Some(path) = &self.owned_path {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn activated_listener() -> io::Result<Option<UnixListener>> {
+        let listen_pid = std::env::var("LISTEN_PID").ok();
+        let listen_fds = std::env::var("LISTEN_FDS").ok();
+        if !is_activated(listen_pid.as_deref(), listen_fds.as_deref(), std::process::id()) {
+            return Ok(None);
+        }
+        // SAFETY: systemd guarantees fd 3 is an open listening socket for this process
+        let std_listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+        std_listener.set_nonblocking(true)?;
+        UnixListener::from_std(std_listener).map(Some)
+    }
+
+    /// Socket activation applies when LISTEN_PID names us and at least one fd was passed.
+    pub(super) fn is_activated(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> bool {
+        let pid_matches = listen_pid.and_then(|p| p.parse::<u32>().ok()) == Some(pid);
+        let fds = listen_fds.and_then(|n| n.parse::<u32>().ok()).unwrap_or(0);
+        pid_matches && fds >= 1
+    }
+
+    pub async fn connect() -> io::Result<UnixStream> {
+        UnixStream::connect(socket_path()).await
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::io;
+    use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions};
+    use windows_sys::Win32::Foundation::ERROR_PIPE_BUSY;
+
+    const PIPE_NAME: &str = r"\\.\pipe\pomitik";
+    /// How long a client waits for a free pipe instance before giving up.
+    const CONNECT_ATTEMPTS: u32 = 20;
+
+    pub fn endpoint() -> String {
+        PIPE_NAME.to_string()
+    }
+
+    /// Named pipes serve one client per instance, so a fresh instance is
+    /// created as soon as the current one is connected.
+    pub struct Listener {
+        server: NamedPipeServer,
+    }
+
+    impl Listener {
+        pub fn bind() -> io::Result<Self> {
+            // Fails if another daemon already owns the pipe name
+            let server = ServerOptions::new().first_pipe_instance(true).create(PIPE_NAME)?;
+            Ok(Listener { server })
+        }
+
+        pub fn describe(&self) -> String {
+            endpoint()
+        }
+
+        pub async fn accept(&mut self) -> io::Result<NamedPipeServer> {
+            self.server.connect().await?;
+            let next = ServerOptions::new().create(PIPE_NAME)?;
+            Ok(std::mem::replace(&mut self.server, next))
+        }
+    }
+
+    pub async fn connect() -> io::Result<NamedPipeClient> {
+        let mut attempts = 0;
+        loop {
+            match ClientOptions::new().open(PIPE_NAME) {
+                Ok(client) => return Ok(client),
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) && attempts < CONNECT_ATTEMPTS => {
+                    attempts += 1;
+                }
+                Err(e) => return Err(e),
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "IPC is not supported on this platform yet")
+    }
+
+    pub fn endpoint() -> String {
+        "(unsupported)".to_string()
+    }
+
+    pub struct Listener;
+
+    impl Listener {
+        pub fn bind() -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub fn describe(&self) -> String {
+            endpoint()
+        }
+
+        pub async fn accept(&mut self) -> io::Result<tokio::io::DuplexStream> {
+            Err(unsupported())
+        }
+    }
+
+    pub async fn connect() -> io::Result<tokio::io::DuplexStream> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_wire_format() {
+        let json = serde_json::to_string(&Request::Start { target: "pomodoro".to_string() }).unwrap();
+        assert_eq!(json, r#"{"cmd":"start","target":"pomodoro"}"#);
+        assert_eq!(serde_json::from_str::<Request>(r#"{"cmd":"stop"}"#).unwrap(), Request::Stop);
+    }
+
+    #[test]
+    fn response_wire_format() {
+        let json = serde_json::to_string(&Response::Status { state: None }).unwrap();
+        assert_eq!(json, r#"{"result":"status","state":null}"#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_activation_requires_matching_pid() {
+        assert!(platform::is_activated(Some("42"), Some("1"), 42));
+        assert!(!platform::is_activated(Some("41"), Some("1"), 42));
+        assert!(!platform::is_activated(Some("42"), Some("0"), 42));
+        assert!(!platform::is_activated(None, None, 42));
+    }
+
+    #[tokio::test]
+    async fn connection_answers_one_request() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = tokio::spawn(async move {
+            let mut handler = |request| match request {
+                Request::Status => Response::Status { state: None },
+                _ => Response::Error { message: "unexpected".to_string() },
+            };
+            handle_connection(server, &mut handler).await
+        });
+
+        let (reader, mut writer) = tokio::io::split(client);
+        write_line(&mut writer, &Request::Status).await.unwrap();
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await.unwrap();
+        server.await.unwrap().unwrap();
+        assert_eq!(serde_json::from_str::<Response>(&line).unwrap(), Response::Status { state: None });
+    }
+}
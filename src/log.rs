@@ -4,11 +4,192 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+use crate::dates::DateRange;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone)]
 pub struct LogEntry {
     pub name: String,
     pub duration_secs: u64,
     pub completed_at: DateTime<Local>,
+    /// Part of `duration_secs` added with the flow key.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub extended_secs: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Estimated pomodoros for `task`, as given when this block was started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Project from the `[projects]` directory mapping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Directory the timer was started in, with `record_cwd = true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Git repository and branch at the start, with `record_git = true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// On a skipped break: the planned break time that was skipped.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub skipped_secs: u64,
+    /// On a long break: the part added to pay back skipped breaks.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub repaid_secs: u64,
+}
+
+/// Labels from the command line that are recorded on work blocks.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkLabels {
+    pub tags: Vec<String>,
+    pub task: Option<String>,
+    pub estimate: Option<u32>,
+    /// Tasks handed out to work rounds in turn, from `--tasks`.
+    pub tasks: Vec<String>,
+    pub project: Option<String>,
+    pub cwd: Option<String>,
+    pub git: Option<crate::git::GitContext>,
+}
+
+impl WorkLabels {
+    /// Labels for a 1-based work round, with the round's task from `tasks`.
+    pub fn for_round(&self, round: u32) -> WorkLabels {
+        let mut labels = self.clone();
+        if !self.tasks.is_empty() {
+            let index = (round.max(1) - 1) as usize % self.tasks.len();
+            labels.task = Some(self.tasks[index].clone());
+        }
+        labels
+    }
+}
+
+impl LogEntry {
+    /// A work block completed now, carrying the given labels.
+    pub fn work(name: &str, duration_secs: u64, labels: &WorkLabels) -> Self {
+        LogEntry {
+            name: name.to_string(),
+            duration_secs,
+            completed_at: Local::now(),
+            tags: labels.tags.clone(),
+            task: labels.task.clone(),
+            estimate: labels.estimate,
+            project: labels.project.clone(),
+            cwd: labels.cwd.clone(),
+            repo: labels.git.as_ref().map(|g| g.repo.clone()),
+            branch: labels.git.as_ref().and_then(|g| g.branch.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// Split a block at mid-block task switches, given as (active seconds into
+    /// the block, new task), so each task is credited with its own time. Flow
+    /// extension time belongs to the last part; the estimate to the first.
+    pub fn split_at_task_switches(self, switches: &[(u64, String)]) -> Vec<LogEntry> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut task = self.task.clone();
+        let mut estimate = self.estimate;
+        for (at, next_task) in switches {
+            let at = (*at).clamp(start, self.duration_secs);
+            if at > start {
+                parts.push(LogEntry {
+                    name: self.name.clone(),
+                    duration_secs: at - start,
+                    completed_at: self.completed_at - chrono::Duration::seconds((self.duration_secs - at) as i64),
+                    extended_secs: 0,
+                    tags: self.tags.clone(),
+                    task: task.take(),
+                    estimate: estimate.take(),
+                    project: self.project.clone(),
+                    cwd: self.cwd.clone(),
+                    repo: self.repo.clone(),
+                    branch: self.branch.clone(),
+                    skipped_secs: 0,
+                    repaid_secs: 0,
+                });
+            }
+            start = at;
+            task = Some(next_task.clone());
+            estimate = None;
+        }
+        let duration_secs = self.duration_secs - start;
+        if duration_secs > 0 || parts.is_empty() {
+            parts.push(LogEntry {
+                duration_secs,
+                extended_secs: self.extended_secs.min(duration_secs),
+                task,
+                estimate,
+                ..self
+            });
+        }
+        parts
+    }
+}
+
+/// A whole session, written when it ends.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionRecord {
+    pub name: String,
+    pub rounds_planned: u32,
+    /// Work phases that ran to the end.
+    pub rounds_completed: u32,
+    /// Work phases skipped before the end; not counted as completed.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rounds_skipped: u32,
+    /// Work time logged across completed and skipped rounds.
+    pub focus_secs: u64,
+    /// Planned length of each work round; absent for sequences.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub work_secs: u64,
+    /// Retrospective note written when the session finished (`journal`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    pub end: SessionEnd,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEnd {
+    Completed,
+    /// Stopped with the stop key or `tik stop`.
+    StoppedEarly,
+    /// Quit with q or Ctrl+C.
+    Cancelled,
+}
+
+impl SessionRecord {
+    /// One-line summary printed when the session ends, e.g.
+    /// "Session stopped early after 2 of 4 rounds (50m focus, 1 skipped)."
+    pub fn summary(&self) -> String {
+        let how = match self.end {
+            SessionEnd::Completed => "complete!",
+            SessionEnd::StoppedEarly => "stopped early after",
+            SessionEnd::Cancelled => "cancelled after",
+        };
+        let mut details = format!("{} focus", format_duration_human(self.focus_secs));
+        if self.rounds_skipped > 0 {
+            details.push_str(&format!(", {} skipped", self.rounds_skipped));
+        }
+        if self.end == SessionEnd::Completed && self.rounds_skipped == 0 {
+            return format!("Session {how} {} rounds finished ({details}).", self.rounds_completed);
+        }
+        let finished = if self.end == SessionEnd::Completed { " finished" } else { "" };
+        format!(
+            "Session {how} {} of {} round{}{finished} ({details}).",
+            self.rounds_completed,
+            self.rounds_planned,
+            if self.rounds_planned == 1 { "" } else { "s" },
+        )
+    }
+}
+
+fn is_zero<T: Default + PartialEq>(n: &T) -> bool {
+    *n == T::default()
 }
 
 pub fn log_path() -> PathBuf {
@@ -18,38 +199,266 @@ pub fn log_path() -> PathBuf {
         .join("log.json")
 }
 
-pub fn append_entry(entry: &LogEntry) -> std::io::Result<()> {
-    let path = log_path();
+pub fn sessions_path() -> PathBuf {
+    log_path().with_file_name("sessions.json")
+}
+
+/// The running work block as autosaved so far by process `pid`, kept until
+/// it's logged. One file per process, so two running timers keep their own.
+pub fn pending_path(pid: u32) -> PathBuf {
+    log_path().with_file_name(format!("pending-{pid}.json"))
+}
+
+/// Autosave files, including the single `pending.json` older versions kept.
+fn is_pending_file(name: &str) -> bool {
+    name.starts_with("pending") && name.ends_with(".json")
+}
+
+/// A provisional entry for a block still running in process `pid`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PendingEntry {
+    pub pid: u32,
+    pub entry: LogEntry,
+}
+
+/// Save the running block's progress, replacing the last save.
+pub fn write_pending(pending: &PendingEntry) -> std::io::Result<()> {
+    let path = pending_path(pending.pid);
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)?;
-    let mut json = serde_json::to_string(entry)?;
-    json.push('\n');
-    file.write_all(json.as_bytes())?;
-    Ok(())
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string(pending)?)?;
+    std::fs::rename(&tmp, &path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
 }
 
-pub fn read_entries() -> Vec<LogEntry> {
-    let path = log_path();
-    if !path.exists() {
-        return Vec::new();
+/// Drop this process's autosave; other running timers keep theirs.
+pub fn clear_pending() {
+    let _ = std::fs::remove_file(pending_path(std::process::id()));
+}
+
+/// Log the blocks crashed runs left behind, credited up to their last save.
+/// A block whose process is still running is left alone.
+pub fn recover_pending() {
+    let Some(Ok(files)) = log_path().parent().map(std::fs::read_dir) else {
+        return;
+    };
+    for file in files.flatten() {
+        if is_pending_file(&file.file_name().to_string_lossy()) {
+            recover_pending_file(&file.path());
+        }
     }
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
+}
+
+fn recover_pending_file(path: &std::path::Path) {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
     };
-    contents
-        .lines()
-        .filter(|l| !l.trim().is_empty())
-        .filter_map(|l| serde_json::from_str(l).ok())
-        .collect()
+    let pending = match serde_json::from_str::<PendingEntry>(&contents) {
+        Ok(pending) if crate::state::process_alive(pending.pid) => return,
+        Ok(pending) => Some(pending.entry),
+        Err(_) => None,
+    };
+    if let Some(entry) = pending.filter(|e| e.duration_secs > 0)
+        && let Err(e) = append_entry(&entry)
+    {
+        eprintln!("Failed to log the interrupted block: {e}");
+        return;
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+pub fn append_entry(entry: &LogEntry) -> std::io::Result<()> {
+    LogStore::entries().append(entry)
+}
+
+pub fn append_session(record: &SessionRecord) -> std::io::Result<()> {
+    LogStore::sessions().append(record)
+}
+
+/// One NDJSON file of records: the blocks, the sessions or the usage log.
+/// Records are appended one line at a time; anything that changes existing
+/// lines goes through `rewrite`.
+pub struct LogStore {
+    path: PathBuf,
+}
+
+impl LogStore {
+    pub fn at(path: PathBuf) -> Self {
+        LogStore { path }
+    }
+
+    pub fn entries() -> Self {
+        LogStore::at(log_path())
+    }
+
+    pub fn sessions() -> Self {
+        LogStore::at(sessions_path())
+    }
+
+    pub fn append(&self, value: &impl Serialize) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut json = serde_json::to_string(value)?;
+        json.push('\n');
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Every record that parses, in file order. A missing or unreadable file
+    /// reads as empty.
+    pub fn read<T: serde::de::DeserializeOwned>(&self) -> Vec<T> {
+        self.read_split().0
+    }
+
+    /// Parsed records, and the lines that didn't parse.
+    fn read_split<T: serde::de::DeserializeOwned>(&self) -> (Vec<T>, Vec<String>) {
+        let contents = std::fs::read_to_string(&self.path).unwrap_or_default();
+        let mut records = Vec::new();
+        let mut unparsed = Vec::new();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(_) => unparsed.push(line.to_string()),
+            }
+        }
+        (records, unparsed)
+    }
+
+    /// Read the records fresh, let `edit` change them, and write the result
+    /// back. The new file is written next to the old one, synced, then renamed
+    /// over it, so a crash part way leaves the old file whole. Lines that
+    /// don't parse are kept as they are, after the rest. Returns the records
+    /// as written.
+    pub fn rewrite<T: Serialize + serde::de::DeserializeOwned>(
+        &self,
+        edit: impl FnOnce(&mut Vec<T>),
+    ) -> std::io::Result<Vec<T>> {
+        let (mut records, unparsed) = self.read_split();
+        edit(&mut records);
+        let mut contents = String::new();
+        for record in &records {
+            contents.push_str(&serde_json::to_string(record)?);
+            contents.push('\n');
+        }
+        for line in &unparsed {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension(format!("json.{}.tmp", std::process::id()));
+        let written = std::fs::File::create(&tmp).and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        });
+        written.and_then(|()| std::fs::rename(&tmp, &self.path)).inspect_err(|_| {
+            let _ = std::fs::remove_file(&tmp);
+        })?;
+        Ok(records)
+    }
+}
+
+/// Replace the first block equal to `old` with `new`, or remove it when
+/// `new` is None. Returns the log as written, or None if `old` is gone.
+pub fn replace_entry(old: &LogEntry, new: Option<LogEntry>) -> std::io::Result<Option<Vec<LogEntry>>> {
+    let mut found = false;
+    let entries = LogStore::entries().rewrite(|entries: &mut Vec<LogEntry>| {
+        let Some(index) = entries.iter().position(|e| e == old) else {
+            return;
+        };
+        found = true;
+        match new {
+            Some(new) => entries[index] = new,
+            None => {
+                entries.remove(index);
+            }
+        }
+    })?;
+    Ok(found.then_some(entries))
+}
+
+/// Whether `b` repeats `a`: the same block finished within a second of it,
+/// as when one block was logged by two code paths.
+fn is_duplicate(a: &LogEntry, b: &LogEntry) -> bool {
+    a.name == b.name
+        && a.duration_secs == b.duration_secs
+        && (a.completed_at - b.completed_at).num_milliseconds().abs() <= 1000
+}
+
+/// Positions of blocks that repeat an earlier one, in file order.
+pub fn duplicate_indices(entries: &[LogEntry]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|&i| entries[i].completed_at);
+    let mut duplicates = Vec::new();
+    for (n, &i) in order.iter().enumerate() {
+        let earlier = order[..n]
+            .iter()
+            .rev()
+            .take_while(|&&j| (entries[i].completed_at - entries[j].completed_at).num_milliseconds() <= 1000)
+            .filter(|j| !duplicates.contains(*j));
+        if earlier.into_iter().any(|&j| is_duplicate(&entries[j], &entries[i])) {
+            duplicates.push(i);
+        }
+    }
+    duplicates.sort();
+    duplicates
 }
 
-fn format_duration_human(secs: u64) -> String {
+/// `tik log dedupe`: list blocks logged twice and, unless `dry_run`, remove
+/// the repeats.
+pub fn dedupe(dry_run: bool, range: &DateRange) -> std::io::Result<()> {
+    let mut removed = Vec::new();
+    let store = LogStore::entries();
+    let duplicates = |entries: &[LogEntry]| -> Vec<usize> {
+        duplicate_indices(entries).into_iter().filter(|&i| range.contains(entries[i].completed_at.date_naive())).collect()
+    };
+    if dry_run {
+        let entries: Vec<LogEntry> = store.read();
+        removed = duplicates(&entries).into_iter().map(|i| entries[i].clone()).collect();
+    } else {
+        store.rewrite(|entries: &mut Vec<LogEntry>| {
+            for i in duplicates(entries).into_iter().rev() {
+                removed.push(entries.remove(i));
+            }
+            removed.reverse();
+        })?;
+    }
+    if removed.is_empty() {
+        println!("No duplicate blocks in the log.");
+        return Ok(());
+    }
+    let count = removed.len();
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!("{verb} {count} duplicate block{}:", if count == 1 { "" } else { "s" });
+    for entry in &removed {
+        println!(
+            "  {}  {:<14} {:>7}",
+            entry.completed_at.format("%Y-%m-%d %H:%M:%S"),
+            entry.name,
+            format_duration_human(entry.duration_secs)
+        );
+    }
+    Ok(())
+}
+
+pub fn read_entries() -> Vec<LogEntry> {
+    LogStore::entries().read()
+}
+
+pub fn read_sessions() -> Vec<SessionRecord> {
+    LogStore::sessions().read()
+}
+
+pub fn format_duration_human(secs: u64) -> String {
     let h = secs / 3600;
     let m = (secs % 3600) / 60;
     if h > 0 {
@@ -59,7 +468,229 @@ fn format_duration_human(secs: u64) -> String {
     }
 }
 
-pub fn print_summary() {
+fn week_start(now: DateTime<Local>) -> chrono::NaiveDate {
+    let days_since_monday = now.weekday().num_days_from_monday();
+    now.date_naive() - chrono::Duration::days(days_since_monday as i64)
+}
+
+/// A span of the log that `tik log` can show on its own and compare with the
+/// one before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Today,
+    Week,
+}
+
+impl Period {
+    /// First day of the current period and of the one before it.
+    pub fn starts(self, now: DateTime<Local>) -> (chrono::NaiveDate, chrono::NaiveDate) {
+        match self {
+            Period::Today => (now.date_naive(), now.date_naive() - chrono::Duration::days(1)),
+            Period::Week => (week_start(now), week_start(now) - chrono::Duration::days(7)),
+        }
+    }
+
+    fn titles(self) -> (&'static str, &'static str) {
+        match self {
+            Period::Today => ("Today", "Yesterday"),
+            Period::Week => ("This week", "Last week"),
+        }
+    }
+}
+
+/// What blocks are totalled by in `tik log` (`--group-by`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GroupBy {
+    #[default]
+    Preset,
+    Tag,
+    Task,
+    Project,
+    Repo,
+    Weekday,
+    Hour,
+}
+
+impl GroupBy {
+    /// The keys a block counts under: a block with two tags counts under
+    /// both, one without a task under no task.
+    fn keys(self, entry: &LogEntry) -> Vec<String> {
+        match self {
+            GroupBy::Preset => vec![entry.name.clone()],
+            GroupBy::Tag => entry.tags.clone(),
+            GroupBy::Task => entry.task.iter().cloned().collect(),
+            GroupBy::Project => entry.project.iter().cloned().collect(),
+            GroupBy::Repo => repo_branch(entry).into_iter().collect(),
+            GroupBy::Weekday => vec![entry.completed_at.format("%a").to_string()],
+            GroupBy::Hour => vec![entry.completed_at.format("%H:00").to_string()],
+        }
+    }
+
+    /// Sort position for groupings that read best in calendar order.
+    fn calendar_order(self, key: &str) -> Option<u32> {
+        match self {
+            GroupBy::Weekday => key.parse::<chrono::Weekday>().ok().map(|d| d.num_days_from_monday()),
+            GroupBy::Hour => key.get(..2).and_then(|h| h.parse().ok()),
+            _ => None,
+        }
+    }
+}
+
+/// One row of a grouped total: key, blocks and seconds.
+type GroupRow = (String, usize, u64);
+
+/// Blocks and time per key. Weekdays and hours come in calendar order, the
+/// rest largest first. Blocks without a key are left out.
+fn group_totals<'a>(entries: impl IntoIterator<Item = &'a LogEntry>, by: GroupBy) -> Vec<GroupRow> {
+    let mut totals: HashMap<String, (usize, u64)> = HashMap::new();
+    for e in entries {
+        for key in by.keys(e) {
+            let total = totals.entry(key).or_default();
+            total.0 += 1;
+            total.1 += e.duration_secs;
+        }
+    }
+    let mut rows: Vec<GroupRow> = totals.into_iter().map(|(key, (count, secs))| (key, count, secs)).collect();
+    rows.sort_by(|a, b| {
+        let order = by.calendar_order(&a.0).cmp(&by.calendar_order(&b.0));
+        order.then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0))
+    });
+    rows
+}
+
+/// One line of a period comparison: (label, seconds now, seconds before).
+type CompareRow = (String, u64, u64);
+
+/// Totals in the current and previous period: overall, then per preset name,
+/// tag and project. Rows are sorted by current time, largest first.
+fn compare_periods(entries: &[LogEntry], period: Period, now: DateTime<Local>) -> (CompareRow, [Vec<CompareRow>; 3]) {
+    let (current_start, previous_start) = period.starts(now);
+    let mut total = (0, 0);
+    let groups = [GroupBy::Preset, GroupBy::Tag, GroupBy::Project];
+    let mut by_group: [HashMap<String, (u64, u64)>; 3] = Default::default();
+    for e in entries {
+        let day = e.completed_at.date_naive();
+        if day < previous_start || day > now.date_naive() {
+            continue;
+        }
+        let add = |totals: &mut (u64, u64)| {
+            if day >= current_start {
+                totals.0 += e.duration_secs;
+            } else {
+                totals.1 += e.duration_secs;
+            }
+        };
+        add(&mut total);
+        for (by, totals) in groups.iter().zip(&mut by_group) {
+            for key in by.keys(e) {
+                add(totals.entry(key).or_default());
+            }
+        }
+    }
+    let rows = |totals: HashMap<String, (u64, u64)>| {
+        let mut rows: Vec<CompareRow> = totals.into_iter().map(|(k, (now, before))| (k, now, before)).collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        rows
+    };
+    (("total".to_string(), total.0, total.1), by_group.map(rows))
+}
+
+/// Seconds logged this week per key (project, repo, ...), largest first.
+/// Entries without a key are left out.
+fn week_totals_by(entries: &[LogEntry], now: DateTime<Local>, by: GroupBy) -> Vec<(String, u64)> {
+    let week_start = week_start(now);
+    let week = entries.iter().filter(|e| e.completed_at.date_naive() >= week_start);
+    group_totals(week, by).into_iter().map(|(key, _, secs)| (key, secs)).collect()
+}
+
+/// "repo (branch)", or just the repo on a detached HEAD.
+fn repo_branch(entry: &LogEntry) -> Option<String> {
+    let repo = entry.repo.as_ref()?;
+    Some(match &entry.branch {
+        Some(branch) => format!("{repo} ({branch})"),
+        None => repo.clone(),
+    })
+}
+
+/// Break time skipped today and not yet paid back with longer long breaks.
+pub fn break_debt(entries: &[LogEntry], now: DateTime<Local>) -> u64 {
+    let today = now.date_naive();
+    let (skipped, repaid) = entries
+        .iter()
+        .filter(|e| e.completed_at.date_naive() == today)
+        .fold((0, 0), |(skipped, repaid), e| (skipped + e.skipped_secs, repaid + e.repaid_secs));
+    skipped.saturating_sub(repaid)
+}
+
+/// "skipped 15m of breaks today", or None without any debt.
+pub fn break_debt_line(debt_secs: u64) -> Option<String> {
+    (debt_secs >= 60).then(|| format!("skipped {} of breaks today", format_duration_human(debt_secs)))
+}
+
+/// Seconds logged per tag since the start of the current week.
+fn week_tag_totals(entries: &[LogEntry], now: DateTime<Local>) -> HashMap<&str, u64> {
+    let week_start = week_start(now);
+    let mut totals = HashMap::new();
+    for e in entries.iter().filter(|e| e.completed_at.date_naive() >= week_start) {
+        for tag in &e.tags {
+            *totals.entry(tag.as_str()).or_insert(0) += e.duration_secs;
+        }
+    }
+    totals
+}
+
+/// Per task: (task, pomodoros logged, latest estimate), for tasks worked on this week.
+fn task_stats(entries: &[LogEntry], now: DateTime<Local>) -> Vec<(String, usize, Option<u32>)> {
+    let week_start = week_start(now);
+    let mut stats: HashMap<&str, (usize, Option<u32>, bool)> = HashMap::new();
+    for e in entries {
+        let Some(task) = e.task.as_deref() else {
+            continue;
+        };
+        let stat = stats.entry(task).or_insert((0, None, false));
+        stat.0 += 1;
+        // Entries are in chronological order, so the last estimate wins
+        if e.estimate.is_some() {
+            stat.1 = e.estimate;
+        }
+        stat.2 |= e.completed_at.date_naive() >= week_start;
+    }
+    let mut rows: Vec<_> = stats
+        .into_iter()
+        .filter(|(_, (_, _, this_week))| *this_week)
+        .map(|(task, (actual, estimate, _))| (task.to_string(), actual, estimate))
+        .collect();
+    rows.sort();
+    rows
+}
+
+/// Warnings for tags whose weekly budget would be exceeded by `planned_secs` more.
+pub fn budget_warnings(
+    entries: &[LogEntry],
+    budgets: &[(String, u64)],
+    tags: &[String],
+    planned_secs: u64,
+    now: DateTime<Local>,
+) -> Vec<String> {
+    let totals = week_tag_totals(entries, now);
+    budgets
+        .iter()
+        .filter(|(tag, _)| tags.contains(tag))
+        .filter_map(|(tag, limit)| {
+            let used = totals.get(tag.as_str()).copied().unwrap_or(0);
+            (used + planned_secs > *limit).then(|| {
+                format!(
+                    "'{tag}' is at {} of its {}/week budget; this block would bring it to {}",
+                    format_duration_human(used),
+                    format_duration_human(*limit),
+                    format_duration_human(used + planned_secs),
+                )
+            })
+        })
+        .collect()
+}
+
+pub fn print_summary(budgets: &[(String, u64)], scoring: &crate::stats::Scoring, group_by: GroupBy) {
     let entries = read_entries();
     if entries.is_empty() {
         println!("No sessions logged yet.");
@@ -68,8 +699,7 @@ pub fn print_summary() {
 
     let now = Local::now();
     let today = now.date_naive();
-    let days_since_monday = now.weekday().num_days_from_monday();
-    let week_start = today - chrono::Duration::days(days_since_monday as i64);
+    let week_start = week_start(now);
 
     let today_entries: Vec<&LogEntry> = entries
         .iter()
@@ -81,17 +711,190 @@ pub fn print_summary() {
         .filter(|e| e.completed_at.date_naive() >= week_start)
         .collect();
 
-    print_section("Today", &today_entries);
+    let sessions = read_sessions();
+    let today_sessions: Vec<&SessionRecord> = sessions
+        .iter()
+        .filter(|s| s.ended_at.date_naive() == today)
+        .collect();
+    let week_sessions: Vec<&SessionRecord> = sessions
+        .iter()
+        .filter(|s| s.ended_at.date_naive() >= week_start)
+        .collect();
+
+    print_section("Today", &today_entries, &today_sessions, group_by);
+    if let Some(score) = crate::stats::focus_score(&entries, &sessions, scoring, today) {
+        println!("  {}", score.line());
+    }
     println!();
-    print_section("This week", &week_entries);
+    print_section("This week", &week_entries, &week_sessions, group_by);
+
+    if let Some(line) = break_debt_line(break_debt(&entries, now)) {
+        println!();
+        println!("You've {line}.");
+    }
+
+    let groups = [
+        ("Projects", week_totals_by(&entries, now, GroupBy::Project)),
+        ("Repos", week_totals_by(&entries, now, GroupBy::Repo)),
+    ];
+    for (title, totals) in groups {
+        if totals.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{title} (this week):");
+        for (key, secs) in totals {
+            println!("  {key:<14} {:>7}", format_duration_human(secs));
+        }
+    }
+
+    if !budgets.is_empty() {
+        println!();
+        print_budgets(budgets, &week_tag_totals(&entries, now));
+    }
+
+    let tasks = task_stats(&entries, now);
+    if !tasks.is_empty() {
+        println!();
+        print_tasks(&tasks);
+    }
+}
+
+/// Blocks and sessions that ended within `range`.
+fn read_within(range: &DateRange) -> (Vec<LogEntry>, Vec<SessionRecord>) {
+    let mut entries = read_entries();
+    let mut sessions = read_sessions();
+    entries.retain(|e| range.contains(e.completed_at.date_naive()));
+    sessions.retain(|s| range.contains(s.ended_at.date_naive()));
+    (entries, sessions)
+}
+
+/// `tik log --since/--until/--last` without a period: the blocks and
+/// sessions in the range.
+pub fn print_range(range: &DateRange, group_by: GroupBy) {
+    let (entries, sessions) = read_within(range);
+    let entries: Vec<&LogEntry> = entries.iter().collect();
+    let sessions: Vec<&SessionRecord> = sessions.iter().collect();
+    print_section(&range.title(), &entries, &sessions, group_by);
 }
 
-fn print_section(title: &str, entries: &[&LogEntry]) {
+/// Print one period's blocks and sessions within `range`, optionally
+/// followed by a comparison with the period before it.
+pub fn print_period(period: Period, range: &DateRange, compare: bool, scoring: &crate::stats::Scoring, group_by: GroupBy) {
+    let (entries, sessions) = read_within(range);
+    let now = Local::now();
+    let (start, _) = period.starts(now);
+    let period_entries: Vec<&LogEntry> = entries.iter().filter(|e| e.completed_at.date_naive() >= start).collect();
+    let period_sessions: Vec<&SessionRecord> = sessions.iter().filter(|s| s.ended_at.date_naive() >= start).collect();
+    print_section(period.titles().0, &period_entries, &period_sessions, group_by);
+    if let Some(line) = score_line(&entries, &sessions, scoring, start, now.date_naive()) {
+        println!("  {line}");
+    }
+    if compare {
+        println!();
+        print_comparison(&entries, period, now);
+    }
+}
+
+/// Today's focus score, or one score per day for a longer period.
+fn score_line(
+    entries: &[LogEntry],
+    sessions: &[SessionRecord],
+    scoring: &crate::stats::Scoring,
+    start: chrono::NaiveDate,
+    today: chrono::NaiveDate,
+) -> Option<String> {
+    if start == today {
+        return crate::stats::focus_score(entries, sessions, scoring, today).map(|score| score.line());
+    }
+    let days: Vec<String> = start
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .filter_map(|day| {
+            let score = crate::stats::focus_score(entries, sessions, scoring, day)?;
+            Some(format!("{} {}", day.format("%a"), score.score))
+        })
+        .collect();
+    (!days.is_empty()).then(|| format!("Focus score by day: {}", days.join(", ")))
+}
+
+fn print_comparison(entries: &[LogEntry], period: Period, now: DateTime<Local>) {
+    let (current, previous) = period.titles();
+    let (total, [by_name, by_tag, by_project]) = compare_periods(entries, period, now);
+    println!("{current} vs {}:", previous.to_lowercase());
+    println!("  {:<14} {:>10} {:>10} {:>9}", "", current, previous, "change");
+    println!("  {}", compare_line(&total));
+    for (heading, rows) in [("By preset:", by_name), ("By tag:", by_tag), ("By project:", by_project)] {
+        if rows.is_empty() {
+            continue;
+        }
+        println!("  {heading}");
+        for row in &rows {
+            println!("  {}", compare_line(row));
+        }
+    }
+}
+
+fn compare_line((label, now, before): &CompareRow) -> String {
+    let delta = if now >= before {
+        format!("+{}", format_duration_human(now - before))
+    } else {
+        format!("-{}", format_duration_human(before - now))
+    };
+    format!(
+        "{label:<14} {:>10} {:>10} {delta:>9} {:>6}",
+        format_duration_human(*now),
+        format_duration_human(*before),
+        percent_change(*now, *before),
+    )
+}
+
+/// "+25%", "-100%", or "new" when there was nothing to compare against.
+fn percent_change(now: u64, before: u64) -> String {
+    if before == 0 {
+        return if now == 0 { "0%".to_string() } else { "new".to_string() };
+    }
+    let percent = (now as f64 - before as f64) * 100.0 / before as f64;
+    format!("{:+}%", percent.round() as i64)
+}
+
+fn print_tasks(tasks: &[(String, usize, Option<u32>)]) {
+    println!("Tasks (pomodoros done / estimated):");
+    for (task, actual, estimate) in tasks {
+        match estimate {
+            Some(estimate) => {
+                let over = if *actual > *estimate as usize { "  over estimate" } else { "" };
+                println!("  {task:<14} {actual:>3} / {estimate}{over}");
+            }
+            None => println!("  {task:<14} {actual:>3} / -"),
+        }
+    }
+}
+
+fn print_budgets(budgets: &[(String, u64)], totals: &HashMap<&str, u64>) {
+    println!("Budgets (this week):");
+    for (tag, limit) in budgets {
+        let used = totals.get(tag.as_str()).copied().unwrap_or(0);
+        let percent = if *limit == 0 { 100 } else { used * 100 / limit };
+        let over = if used > *limit {
+            format!("  over by {}", format_duration_human(used - limit))
+        } else {
+            String::new()
+        };
+        println!(
+            "  {tag:<14} {:>7} / {:<7} {percent:>3}%{over}",
+            format_duration_human(used),
+            format_duration_human(*limit)
+        );
+    }
+}
+
+fn print_section(title: &str, entries: &[&LogEntry], sessions: &[&SessionRecord], group_by: GroupBy) {
     let total_secs: u64 = entries.iter().map(|e| e.duration_secs).sum();
     let count = entries.len();
 
     println!(
-        "{title} ({count} session{}, {}):",
+        "{title} ({count} block{}, {}):",
         if count == 1 { "" } else { "s" },
         format_duration_human(total_secs)
     );
@@ -101,23 +904,200 @@ fn print_section(title: &str, entries: &[&LogEntry]) {
         return;
     }
 
-    let mut by_name: HashMap<&str, (usize, u64)> = HashMap::new();
-    for e in entries {
-        let entry = by_name.entry(e.name.as_str()).or_insert((0, 0));
-        entry.0 += 1;
-        entry.1 += e.duration_secs;
-    }
-
-    let mut names: Vec<_> = by_name.into_iter().collect();
-    names.sort_by(|a, b| b.1 .1.cmp(&a.1 .1));
-
-    for (name, (count, secs)) in names {
+    for (name, count, secs) in group_totals(entries.iter().copied(), group_by) {
         if count > 1 {
             println!("  {name:<14} x{count:<4} {}", format_duration_human(secs));
         } else {
             println!("  {name:<14}       {}", format_duration_human(secs));
         }
     }
+
+    for line in session_lines(sessions) {
+        println!("  {line}");
+    }
+    for session in sessions {
+        if let Some(note) = &session.note {
+            let note = note.replace('\n', "\n    ");
+            println!("  note {} {}: {note}", session.ended_at.format("%a %H:%M"), session.name);
+        }
+    }
+}
+
+/// "3 full pomodoro sessions (+1 partial)", one line per session name.
+fn session_lines(sessions: &[&SessionRecord]) -> Vec<String> {
+    let mut by_name: HashMap<&str, (usize, usize)> = HashMap::new();
+    for s in sessions {
+        let counts = by_name.entry(s.name.as_str()).or_insert((0, 0));
+        if s.end == SessionEnd::Completed {
+            counts.0 += 1;
+        } else if s.rounds_completed > 0 {
+            counts.1 += 1;
+        }
+    }
+    let mut names: Vec<_> = by_name.into_iter().filter(|(_, (full, partial))| full + partial > 0).collect();
+    names.sort_by_key(|(name, (full, _))| (std::cmp::Reverse(*full), *name));
+    names
+        .into_iter()
+        .map(|(name, (full, partial))| {
+            let mut line = format!("{full} full {name} session{}", if full == 1 { "" } else { "s" });
+            if partial > 0 {
+                line.push_str(&format!(" (+{partial} partial)"));
+            }
+            line
+        })
+        .collect()
+}
+
+/// Whether any of the entry's text fields contain `query` (lowercase).
+pub fn entry_matches(entry: &LogEntry, query: &str) -> bool {
+    let found = |text: &str| text.to_lowercase().contains(query);
+    found(&entry.name)
+        || entry.tags.iter().any(|t| found(t))
+        || [&entry.task, &entry.project, &entry.repo, &entry.branch].into_iter().flatten().any(|t| found(t))
+}
+
+/// Lines for `tik log search`: blocks and session notes containing `query`
+/// (case-insensitive) within `range`, oldest first under a heading per day.
+fn search_lines(entries: &[LogEntry], sessions: &[SessionRecord], query: &str, range: &DateRange) -> Vec<String> {
+    let query = query.to_lowercase();
+    let in_range = |at: &DateTime<Local>| range.contains(at.date_naive());
+    let mut matches: Vec<(DateTime<Local>, String)> = Vec::new();
+    for entry in entries.iter().filter(|e| in_range(&e.completed_at) && entry_matches(e, &query)) {
+        let mut line = format!("{:<14} {:>7}", entry.name, format_duration_human(entry.duration_secs));
+        if let Some(task) = &entry.task {
+            line.push_str(&format!("  {task}"));
+        }
+        for tag in &entry.tags {
+            line.push_str(&format!("  #{tag}"));
+        }
+        if let Some(project) = &entry.project {
+            line.push_str(&format!("  [{project}]"));
+        }
+        if let (Some(repo), branch) = (&entry.repo, &entry.branch) {
+            line.push_str(&format!("  {repo}{}", branch.as_deref().map(|b| format!("@{b}")).unwrap_or_default()));
+        }
+        matches.push((entry.completed_at, line));
+    }
+    for session in sessions.iter().filter(|s| in_range(&s.ended_at)) {
+        let note = session.note.as_deref().unwrap_or("");
+        if session.name.to_lowercase().contains(&query) || note.to_lowercase().contains(&query) {
+            let mut line = format!("{:<14} session ({} of {} rounds)", session.name, session.rounds_completed, session.rounds_planned);
+            if !note.is_empty() {
+                // Under the name, past the "  HH:MM  " prefix
+                line.push_str(&format!("\n{:9}note: {}", "", note.replace('\n', &format!("\n{:15}", ""))));
+            }
+            matches.push((session.ended_at, line));
+        }
+    }
+    matches.sort_by_key(|(at, _)| *at);
+
+    let mut lines = Vec::new();
+    let mut day = None;
+    for (at, line) in &matches {
+        if day != Some(at.date_naive()) {
+            day = Some(at.date_naive());
+            lines.push(at.format("%Y-%m-%d %a").to_string());
+        }
+        lines.push(format!("  {}  {line}", at.format("%H:%M")));
+    }
+    lines
+}
+
+pub fn print_search(query: &str, range: &DateRange) {
+    let lines = search_lines(&read_entries(), &read_sessions(), query, range);
+    if lines.is_empty() {
+        println!("Nothing in the log matches '{query}'.");
+        return;
+    }
+    for line in lines {
+        println!("{line}");
+    }
+}
+
+/// File formats for `tik log --export`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One block as exported: its start worked out from the end and length,
+/// both as ISO 8601 timestamps.
+#[derive(Debug, Serialize, PartialEq)]
+struct ExportRow {
+    name: String,
+    duration_secs: u64,
+    started_at: String,
+    completed_at: String,
+    task: Option<String>,
+    tags: Vec<String>,
+    project: Option<String>,
+}
+
+impl ExportRow {
+    const HEADER: &str = "name,duration_secs,started_at,completed_at,task,tags,project";
+
+    fn new(entry: &LogEntry) -> Self {
+        let started_at = entry.completed_at - chrono::Duration::seconds(entry.duration_secs as i64);
+        let iso = |at: DateTime<Local>| at.to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        ExportRow {
+            name: entry.name.clone(),
+            duration_secs: entry.duration_secs,
+            started_at: iso(started_at),
+            completed_at: iso(entry.completed_at),
+            task: entry.task.clone(),
+            tags: entry.tags.clone(),
+            project: entry.project.clone(),
+        }
+    }
+
+    /// A CSV line; tags are joined with ";".
+    fn csv(&self) -> String {
+        let fields = [
+            self.name.clone(),
+            self.duration_secs.to_string(),
+            self.started_at.clone(),
+            self.completed_at.clone(),
+            self.task.clone().unwrap_or_default(),
+            self.tags.join(";"),
+            self.project.clone().unwrap_or_default(),
+        ];
+        fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Quote a field that holds a comma, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn export_text(entries: &[&LogEntry], format: ExportFormat) -> Result<String, String> {
+    let rows: Vec<ExportRow> = entries.iter().map(|entry| ExportRow::new(entry)).collect();
+    match format {
+        ExportFormat::Csv => {
+            let lines: Vec<String> = std::iter::once(ExportRow::HEADER.to_string()).chain(rows.iter().map(ExportRow::csv)).collect();
+            Ok(lines.join("\n") + "\n")
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(&rows).map(|json| json + "\n").map_err(|e| format!("Failed to serialize the log: {e}")),
+    }
+}
+
+/// Write the blocks logged within `range` to `output`, or to stdout.
+pub fn export(format: ExportFormat, range: &DateRange, output: Option<&std::path::Path>) -> Result<(), String> {
+    let entries = read_entries();
+    let entries: Vec<&LogEntry> = entries.iter().filter(|e| range.contains(e.completed_at.date_naive())).collect();
+    let text = export_text(&entries, format)?;
+    let Some(path) = output else {
+        print!("{text}");
+        return Ok(());
+    };
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    println!("Exported {} blocks to {}", entries.len(), path.display());
+    Ok(())
 }
 
 #[cfg(test)]
@@ -130,10 +1110,26 @@ mod tests {
             name: "pomodoro".to_string(),
             duration_secs: 1500,
             completed_at: Local::now(),
+            ..Default::default()
         };
         let json = serde_json::to_string(&entry).unwrap();
         assert!(json.contains("pomodoro"));
         assert!(json.contains("1500"));
+        assert!(!json.contains("extended_secs"));
+    }
+
+    #[test]
+    fn extended_secs_roundtrip() {
+        let entry = LogEntry {
+            name: "pomodoro".to_string(),
+            duration_secs: 1800,
+            completed_at: Local::now(),
+            extended_secs: 300,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        let parsed: LogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.extended_secs, 300);
     }
 
     #[test]
@@ -150,6 +1146,7 @@ mod tests {
             name: "break".to_string(),
             duration_secs: 300,
             completed_at: Local::now(),
+            ..Default::default()
         };
         let json = serde_json::to_string(&entry).unwrap();
         let parsed: LogEntry = serde_json::from_str(&json).unwrap();
@@ -157,6 +1154,56 @@ mod tests {
         assert_eq!(parsed.duration_secs, entry.duration_secs);
     }
 
+    #[test]
+    fn each_process_autosaves_to_its_own_file() {
+        assert_ne!(pending_path(41), pending_path(42));
+        let name = pending_path(42).file_name().unwrap().to_string_lossy().into_owned();
+        assert!(is_pending_file(&name) && is_pending_file("pending.json"));
+        // Half-written saves and the logs themselves aren't recovered
+        assert!(!is_pending_file(&format!("{name}.42.tmp")) && !is_pending_file("log.json"));
+    }
+
+    #[test]
+    fn rewrite_replaces_records_and_keeps_unparsed_lines() {
+        let dir = std::env::temp_dir().join(format!("tik-logstore-{}", std::process::id()));
+        let store = LogStore::at(dir.join("log.json"));
+        let entry = |name: &str| LogEntry { name: name.to_string(), completed_at: Local::now(), ..Default::default() };
+        for name in ["spec", "review"] {
+            store.append(&entry(name)).unwrap();
+        }
+        std::fs::write(&store.path, std::fs::read_to_string(&store.path).unwrap() + "{broken\n").unwrap();
+
+        let written = store.rewrite(|entries: &mut Vec<LogEntry>| entries.retain(|e| e.name != "spec")).unwrap();
+        assert_eq!(written.len(), 1);
+        let names: Vec<String> = store.read::<LogEntry>().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["review"]);
+        assert!(std::fs::read_to_string(&store.path).unwrap().ends_with("}\n{broken\n"));
+        // Only the log itself is left, no temporary file
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicates_within_a_second() {
+        let at = Local::now();
+        let entry = |name: &str, secs: u64, millis: i64| LogEntry {
+            name: name.to_string(),
+            duration_secs: secs,
+            completed_at: at + chrono::Duration::milliseconds(millis),
+            ..Default::default()
+        };
+        let entries = [
+            entry("pomodoro", 1500, 0),
+            entry("pomodoro", 1500, 400),
+            entry("break", 300, 500),
+            entry("pomodoro", 1500, 900),
+            entry("pomodoro", 1200, 600),
+            entry("pomodoro", 1500, 2500),
+        ];
+        assert_eq!(duplicate_indices(&entries), [1, 3]);
+        assert!(duplicate_indices(&entries[..1]).is_empty());
+    }
+
     #[test]
     fn log_path_ends_with_expected() {
         let path = log_path();
@@ -178,6 +1225,285 @@ mod tests {
         assert_eq!(format_duration_human(3600), "1h 0m");
     }
 
+    fn tagged(tag: &str, secs: u64, completed_at: DateTime<Local>) -> LogEntry {
+        LogEntry {
+            name: "pomodoro".to_string(),
+            duration_secs: secs,
+            completed_at,
+            tags: vec![tag.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn export_has_iso_times_and_quoted_fields() {
+        use chrono::TimeZone;
+        let completed_at = Local.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let entry = LogEntry {
+            task: Some("write \"spec\", part 2".to_string()),
+            tags: vec!["docs".to_string(), "q1".to_string()],
+            ..tagged("docs", 1500, completed_at)
+        };
+        let csv = export_text(&[&entry], ExportFormat::Csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(ExportRow::HEADER));
+        let started = (completed_at - chrono::Duration::minutes(25)).to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("pomodoro,1500,{started},{},\"write \"\"spec\"\", part 2\",docs;q1,", completed_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, false))
+        );
+        assert!(started.starts_with("2026-03-02T09:35:00"));
+
+        let json: serde_json::Value = serde_json::from_str(&export_text(&[&entry], ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["started_at"], started.as_str());
+        assert_eq!(json[0]["tags"][1], "q1");
+        assert_eq!(json[0]["project"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn tags_omitted_when_empty() {
+        let entry = LogEntry { name: "x".to_string(), ..Default::default() };
+        assert!(!serde_json::to_string(&entry).unwrap().contains("tags"));
+    }
+
+    #[test]
+    fn week_tag_totals_ignore_previous_weeks() {
+        let now = Local::now();
+        let entries = vec![
+            tagged("client-a", 3600, now),
+            tagged("client-a", 1800, now),
+            tagged("client-a", 7200, now - chrono::Duration::days(8)),
+        ];
+        assert_eq!(week_tag_totals(&entries, now).get("client-a"), Some(&5400));
+    }
+
+    #[test]
+    fn project_totals_for_this_week() {
+        let now = Local::now();
+        let in_project = |project: &str, secs, at| LogEntry { project: Some(project.to_string()), ..tagged("x", secs, at) };
+        let entries = vec![
+            in_project("acme", 1500, now),
+            in_project("acme", 1500, now),
+            in_project("side", 1800, now),
+            in_project("side", 9000, now - chrono::Duration::days(8)),
+            tagged("x", 600, now),
+        ];
+        assert_eq!(
+            week_totals_by(&entries, now, GroupBy::Project),
+            vec![("acme".to_string(), 3000), ("side".to_string(), 1800)]
+        );
+    }
+
+    #[test]
+    fn break_debt_counts_today_minus_repaid() {
+        let now = Local::now();
+        let skipped = |secs, at| LogEntry { skipped_secs: secs, ..tagged("x", 0, at) };
+        let mut entries = vec![skipped(300, now), skipped(600, now), skipped(900, now - chrono::Duration::days(1))];
+        assert_eq!(break_debt(&entries, now), 900);
+        assert_eq!(break_debt_line(900).as_deref(), Some("skipped 15m of breaks today"));
+        entries.push(LogEntry { repaid_secs: 1200, ..tagged("x", 2100, now) });
+        assert_eq!(break_debt(&entries, now), 0);
+        assert_eq!(break_debt_line(0), None);
+    }
+
+    #[test]
+    fn repo_branch_labels() {
+        let mut entry = LogEntry { repo: Some("pomitik".to_string()), branch: Some("main".to_string()), ..Default::default() };
+        assert_eq!(repo_branch(&entry).as_deref(), Some("pomitik (main)"));
+        entry.branch = None;
+        assert_eq!(repo_branch(&entry).as_deref(), Some("pomitik"));
+        entry.repo = None;
+        assert_eq!(repo_branch(&entry), None);
+    }
+
+    #[test]
+    fn compare_weeks_per_preset_and_tag() {
+        use chrono::TimeZone;
+        // A Wednesday: this week started on Monday the 2nd
+        let now = Local.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+        let entries = vec![
+            tagged("client-a", 3600, now - chrono::Duration::days(1)),
+            tagged("client-a", 1800, now - chrono::Duration::days(7)),
+            tagged("side", 1800, now - chrono::Duration::days(8)),
+            tagged("client-a", 7200, now - chrono::Duration::days(20)),
+        ];
+        let (total, [by_name, by_tag, by_project]) = compare_periods(&entries, Period::Week, now);
+        assert_eq!(total, ("total".to_string(), 3600, 3600));
+        assert_eq!(by_name, vec![("pomodoro".to_string(), 3600, 3600)]);
+        assert_eq!(by_tag, vec![("client-a".to_string(), 3600, 1800), ("side".to_string(), 0, 1800)]);
+        assert!(by_project.is_empty());
+    }
+
+    #[test]
+    fn percent_change_and_deltas() {
+        assert_eq!(percent_change(3600, 2880), "+25%");
+        assert_eq!(percent_change(0, 1800), "-100%");
+        assert_eq!(percent_change(1800, 0), "new");
+        assert_eq!(percent_change(0, 0), "0%");
+        assert!(compare_line(&("side".to_string(), 600, 1800)).contains("-20m"));
+    }
+
+    #[test]
+    fn budget_warning_when_block_would_exceed() {
+        let now = Local::now();
+        let entries = vec![tagged("client-a", 9 * 3600 + 50 * 60, now)];
+        let budgets = vec![("client-a".to_string(), 10 * 3600)];
+        let tags = vec!["client-a".to_string()];
+        let warnings = budget_warnings(&entries, &budgets, &tags, 1500, now);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("9h 50m of its 10h 0m/week budget"));
+        assert!(budget_warnings(&entries, &budgets, &tags, 300, now).is_empty());
+        assert!(budget_warnings(&entries, &budgets, &[], 1500, now).is_empty());
+    }
+
+    #[test]
+    fn task_stats_use_latest_estimate() {
+        let now = Local::now();
+        let labels = |estimate| WorkLabels { task: Some("spec".to_string()), estimate, ..Default::default() };
+        let mut entries = vec![
+            LogEntry::work("pomodoro", 1500, &labels(Some(3))),
+            LogEntry::work("pomodoro", 1500, &labels(None)),
+            LogEntry::work("pomodoro", 1500, &labels(Some(4))),
+        ];
+        let mut old = LogEntry::work("pomodoro", 1500, &WorkLabels { task: Some("old".to_string()), ..Default::default() });
+        old.completed_at = now - chrono::Duration::days(30);
+        entries.insert(0, old);
+        assert_eq!(task_stats(&entries, now), vec![("spec".to_string(), 3, Some(4))]);
+    }
+
+    fn session(end: SessionEnd, rounds_completed: u32) -> SessionRecord {
+        SessionRecord {
+            name: "pomodoro".to_string(),
+            rounds_planned: 4,
+            rounds_completed,
+            rounds_skipped: 0,
+            focus_secs: u64::from(rounds_completed) * 1500,
+            work_secs: 1500,
+            note: None,
+            started_at: Local::now() - chrono::Duration::hours(2),
+            ended_at: Local::now(),
+            end,
+        }
+    }
+
+    #[test]
+    fn session_summary_reports_rounds_and_focus() {
+        assert_eq!(
+            session(SessionEnd::StoppedEarly, 2).summary(),
+            "Session stopped early after 2 of 4 rounds (50m focus)."
+        );
+        assert_eq!(
+            session(SessionEnd::Cancelled, 0).summary(),
+            "Session cancelled after 0 of 4 rounds (0m focus)."
+        );
+        assert_eq!(
+            session(SessionEnd::Completed, 4).summary(),
+            "Session complete! 4 rounds finished (1h 40m focus)."
+        );
+        let skipped = SessionRecord { rounds_skipped: 1, focus_secs: 3900, ..session(SessionEnd::Completed, 3) };
+        assert_eq!(skipped.summary(), "Session complete! 3 of 4 rounds finished (1h 5m focus, 1 skipped).");
+        assert!(!serde_json::to_string(&session(SessionEnd::Completed, 4)).unwrap().contains("rounds_skipped"));
+    }
+
+    #[test]
+    fn session_lines_count_full_and_partial() {
+        let records = [
+            session(SessionEnd::Completed, 4),
+            session(SessionEnd::Completed, 4),
+            session(SessionEnd::StoppedEarly, 2),
+            session(SessionEnd::Cancelled, 0),
+        ];
+        let refs: Vec<&SessionRecord> = records.iter().collect();
+        assert_eq!(session_lines(&refs), vec!["2 full pomodoro sessions (+1 partial)"]);
+    }
+
+    #[test]
+    fn session_record_roundtrip() {
+        let record = session(SessionEnd::StoppedEarly, 1);
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""end":"stopped_early""#));
+        assert_eq!(serde_json::from_str::<SessionRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn tasks_rotate_across_rounds() {
+        let labels = WorkLabels { tasks: vec!["spec".to_string(), "review".to_string()], ..Default::default() };
+        let tasks: Vec<_> = (1..=3).map(|round| labels.for_round(round).task).collect();
+        assert_eq!(tasks, [Some("spec".to_string()), Some("review".to_string()), Some("spec".to_string())]);
+
+        let single = WorkLabels { task: Some("email".to_string()), ..Default::default() };
+        assert_eq!(single.for_round(2).task.as_deref(), Some("email"));
+    }
+
+    #[test]
+    fn split_at_task_switches_credits_each_task() {
+        let labels = WorkLabels { task: Some("spec".to_string()), estimate: Some(2), ..Default::default() };
+        let entry = LogEntry { extended_secs: 300, ..LogEntry::work("pomodoro", 1800, &labels) };
+        let end = entry.completed_at;
+        let parts = entry.split_at_task_switches(&[(600, "review".to_string()), (1200, "email".to_string())]);
+
+        let summary: Vec<_> = parts
+            .iter()
+            .map(|p| (p.task.as_deref().unwrap(), p.duration_secs, p.extended_secs, p.estimate))
+            .collect();
+        assert_eq!(summary, [("spec", 600, 0, Some(2)), ("review", 600, 0, None), ("email", 600, 300, None)]);
+        assert_eq!(parts[0].completed_at, end - chrono::Duration::seconds(1200));
+        assert_eq!(parts[2].completed_at, end);
+    }
+
+    #[test]
+    fn split_without_switches_keeps_entry() {
+        let entry = LogEntry::work("pomodoro", 1500, &WorkLabels::default());
+        assert_eq!(entry.clone().split_at_task_switches(&[]), vec![entry]);
+    }
+
+    #[test]
+    fn grouped_by_tag_weekday_and_hour() {
+        use chrono::TimeZone;
+        let at = |day: u32, hour: u32| Local.with_ymd_and_hms(2026, 3, day, hour, 30, 0).unwrap();
+        let entries = vec![
+            LogEntry { tags: vec!["a".to_string(), "b".to_string()], ..tagged("a", 1500, at(4, 14)) },
+            tagged("b", 600, at(2, 9)),
+            tagged("a", 300, at(3, 9)),
+            LogEntry { tags: Vec::new(), ..tagged("", 900, at(8, 22)) },
+        ];
+        assert_eq!(group_totals(&entries, GroupBy::Tag), [("b".to_string(), 2, 2100), ("a".to_string(), 2, 1800)]);
+        let weekdays: Vec<_> = group_totals(&entries, GroupBy::Weekday).into_iter().map(|(k, n, _)| (k, n)).collect();
+        assert_eq!(weekdays, [("Mon".to_string(), 1), ("Tue".to_string(), 1), ("Wed".to_string(), 1), ("Sun".to_string(), 1)]);
+        let hours: Vec<_> = group_totals(&entries, GroupBy::Hour).into_iter().map(|(k, _, secs)| (k, secs)).collect();
+        assert_eq!(hours, [("09:00".to_string(), 900), ("14:00".to_string(), 1500), ("22:00".to_string(), 900)]);
+        assert!(group_totals(&entries, GroupBy::Task).is_empty());
+    }
+
+    #[test]
+    fn search_matches_fields_and_notes_by_day() {
+        use chrono::TimeZone;
+        let at = |day: u32, hour: u32| Local.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        let entries = vec![
+            LogEntry { task: Some("Write Report".to_string()), ..tagged("client-a", 1500, at(2, 10)) },
+            tagged("report-prep", 900, at(3, 9)),
+            tagged("other", 1500, at(3, 11)),
+        ];
+        let sessions = vec![SessionRecord {
+            note: Some("report went\nwell".to_string()),
+            ended_at: at(3, 12),
+            ..session(SessionEnd::Completed, 4)
+        }];
+        assert_eq!(
+            search_lines(&entries, &sessions, "REPORT", &DateRange::default()),
+            [
+                "2026-03-02 Mon",
+                "  10:00  pomodoro           25m  Write Report  #client-a",
+                "2026-03-03 Tue",
+                "  09:00  pomodoro           15m  #report-prep",
+                "  12:00  pomodoro       session (4 of 4 rounds)\n         note: report went\n               well",
+            ]
+        );
+        let day = Some(chrono::NaiveDate::from_ymd_opt(2026, 3, 3).unwrap());
+        assert_eq!(search_lines(&entries, &sessions, "client", &DateRange { since: day, until: None }), Vec::<String>::new());
+        assert_eq!(search_lines(&entries, &sessions, "pomodoro", &DateRange { since: None, until: day }).len(), 6);
+    }
+
     #[test]
     fn format_duration_human_zero() {
         assert_eq!(format_duration_human(0), "0m");
@@ -1,20 +1,64 @@
+mod adaptive;
+mod attach;
+mod blocker;
+mod browse;
 mod config;
+mod cook;
+mod daemon;
+mod dates;
+mod debug;
+#[cfg(feature = "discord")]
+mod discord;
+mod doctor;
 mod duration;
+mod events;
+mod footer;
+mod frame;
+mod git;
+mod help;
+mod hooks;
+mod inhibit;
+mod input;
+mod insights;
+mod ipc;
 mod log;
+mod messages;
+#[cfg(feature = "mqtt")]
+mod mqtt;
 mod notify;
+mod overlay;
+mod plan;
 mod render;
+mod resume;
+#[cfg(feature = "graphics")]
+mod ring;
+#[cfg(feature = "msgpack-rpc")]
+mod rpc;
 mod session;
+mod sound;
+mod state;
+mod stats;
+mod surface;
+mod talk;
+mod text;
 mod timer;
 mod todo;
+mod watch;
+mod webhook;
+mod wrapped;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::io::IsTerminal;
 
 #[derive(Parser)]
-#[command(name = "tik", about = "A command-line countdown timer", version)]
+#[command(name = "tik", about = "A command-line countdown timer", version, disable_help_subcommand = true)]
 struct Cli {
     /// Duration (e.g., 25m, 1h30m, 90s) or preset name (e.g., pomodoro, break)
     duration: Option<String>,
 
+    /// Name a one-off timer in the log and on screen, e.g. `tik 17m emails`
+    name: Option<String>,
+
     /// Suppress notification sound
     #[arg(long)]
     silent: bool,
@@ -23,37 +67,364 @@ struct Cli {
     #[arg(long)]
     title: Option<String>,
 
+    /// Wait for a key press when a timer completes
+    #[arg(long)]
+    ack: bool,
+
+    /// Tag the logged work (repeatable), e.g. --tag client-a
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Task this work is for; `tik log` compares pomodoros done with --estimate
+    #[arg(long)]
+    task: Option<String>,
+
+    /// Estimated number of pomodoros for --task
+    #[arg(long, requires = "task")]
+    estimate: Option<u32>,
+
+    /// Comma-separated tasks assigned to a session's work rounds in turn
+    #[arg(long, value_name = "TASKS", value_delimiter = ',', conflicts_with = "task")]
+    tasks: Vec<String>,
+
+    /// Run without a terminal UI: print start/end lines only (for scripts and services)
+    #[arg(long)]
+    headless: bool,
+
+    /// Also notify when this much time is left, e.g. --notify-at 30m,10m,5m
+    #[arg(long, value_name = "DURATIONS", value_delimiter = ',', value_parser = parse_notify_at)]
+    notify_at: Vec<u64>,
+
+    /// Show tenths of a second on timers under a minute (intervals, talks)
+    #[arg(long)]
+    tenths: bool,
+
+    /// Screen reader mode: no full-screen UI, just status lines like "20 minutes remaining"
+    #[arg(long)]
+    a11y: bool,
+
+    /// Show the timer but never read the keyboard or enable raw mode (cron, CI, supervisors)
+    #[arg(long)]
+    no_input: bool,
+
+    /// Serve msgpack-rpc on stdin/stdout for editor plugins (requires the `msgpack-rpc` build feature)
+    #[arg(long, conflicts_with = "duration")]
+    msgpack_rpc: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum LogPeriod {
+    Today,
+    Week,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LogGroup {
+    Preset,
+    Tag,
+    Task,
+    Project,
+    Repo,
+    Weekday,
+    Hour,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AttachFormat {
+    Json,
+    Line,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BarStyle {
+    Braille,
+    Blocks,
+}
+
+/// `--since`, `--until` and `--last`, for the commands that read the log.
+#[derive(Args)]
+struct DateArgs {
+    /// Only from this day on: today, yesterday, a weekday or YYYY-MM-DD
+    #[arg(long, value_name = "DAY")]
+    since: Option<String>,
+    /// Only up to this day: today, yesterday, a weekday or YYYY-MM-DD
+    #[arg(long, value_name = "DAY")]
+    until: Option<String>,
+    /// Only the last days, weeks, months or years, e.g. 30d, 2w, 3m, 1y
+    #[arg(long, value_name = "SPAN", conflicts_with = "since")]
+    last: Option<String>,
+}
+
+impl DateArgs {
+    /// The days given; exits on a bad one.
+    fn range(&self) -> dates::DateRange {
+        let today = chrono::Local::now().date_naive();
+        dates::DateRange::parse(self.since.as_deref(), self.until.as_deref(), self.last.as_deref(), today).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Show session log summary
-    Log,
+    Log {
+        /// Show only today or this week
+        #[arg(value_enum)]
+        period: Option<LogPeriod>,
+        /// Compare with the previous day or week: totals per preset and tag with changes
+        #[arg(long, requires = "period")]
+        compare: bool,
+        /// Total blocks by something other than their preset
+        #[arg(long, value_enum, value_name = "KEY")]
+        group_by: Option<LogGroup>,
+        /// Print totals and the focus score per day of the period as JSON
+        #[arg(long, conflicts_with_all = ["compare", "group_by"])]
+        json: bool,
+        /// Export the blocks (of the period and days given, or all of them) as csv or json
+        #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["compare", "group_by", "json"])]
+        export: Option<ExportFormat>,
+        /// File to export to (default: stdout)
+        #[arg(long, value_name = "PATH", requires = "export")]
+        output: Option<std::path::PathBuf>,
+        #[command(flatten)]
+        dates: DateArgs,
+        #[command(subcommand)]
+        action: Option<LogAction>,
+    },
+    /// Stream the running timer for editor statuslines, a line per change
+    Attach {
+        /// JSON objects, or `tik status --compact` text
+        #[arg(long, value_enum, default_value = "json")]
+        format: AttachFormat,
+        /// Print the current line and exit
+        #[arg(long)]
+        once: bool,
+    },
+    /// Show the running timer, if any
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Short output for status bars, e.g. "work 24m"
+        #[arg(long, conflicts_with = "json")]
+        compact: bool,
+        /// Add a 4-character progress bar to --compact, e.g. "work ⣿⣿⡆⠀ 10m"
+        #[arg(long, value_enum, value_name = "STYLE", requires = "compact")]
+        bar: Option<BarStyle>,
+    },
+    /// Run the background daemon that `tik start` and `tik stop` talk to
+    Daemon {
+        /// Write systemd user units (tik.socket, tik.service) instead of running
+        #[arg(long)]
+        install_unit: bool,
+    },
+    /// Check notifications, sound, the terminal, config and data dirs
+    Doctor,
+    /// Suggest config tweaks from recorded usage (needs `insights = true`)
+    Insights,
+    /// Completion rate, average session, focus per weekday and longest streak
+    Stats {
+        /// This week (the default without --since, --until or --last)
+        #[arg(long, conflicts_with_all = ["month", "all"])]
+        week: bool,
+        /// This month
+        #[arg(long, conflicts_with = "all")]
+        month: bool,
+        /// The whole log
+        #[arg(long)]
+        all: bool,
+        #[command(flatten)]
+        dates: DateArgs,
+    },
+    /// A year in review: pomodoros, longest streak, busiest day, top tasks
+    Wrapped {
+        /// Year to review (default: this year)
+        #[arg(long)]
+        year: Option<i32>,
+        /// Print markdown to share instead of colored text
+        #[arg(long)]
+        markdown: bool,
+        #[command(flatten)]
+        dates: DateArgs,
+    },
+    /// Start a duration, preset or session in the background daemon
+    Start {
+        /// Duration, preset or session name
+        target: String,
+    },
+    /// Stop the running timer (daemon or another terminal)
+    Stop,
+    /// Pick up a timer cut off by a crash, a closed terminal or a reboot
+    Resume,
+    /// Write the remaining time to a file (and/or serve a page) for streaming overlays
+    Overlay {
+        /// Text file to keep updated, e.g. for an OBS text source
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+        /// Also serve a browser-source page at this address (e.g. 127.0.0.1:7879)
+        #[arg(long)]
+        http: Option<String>,
+        /// Text template: {remaining}, {name}, {phase}, {round}, {paused}
+        #[arg(long, default_value = overlay::DEFAULT_FORMAT)]
+        format: String,
+    },
+    /// Run labeled countdowns side by side, e.g. tik cook "pasta=11m" "sauce=20m"
+    Cook {
+        /// Alarms as label=duration
+        #[arg(required = true, value_name = "LABEL=DURATION")]
+        alarms: Vec<String>,
+    },
+    /// Time a talk in sections, e.g. tik talk "intro 5m, demo 10m, Q&A 5m"
+    Talk {
+        /// Comma-separated sections, each a name and a duration
+        sections: String,
+    },
+    /// Count down a time another program writes to a file or FIFO
+    WatchFile {
+        /// File whose last line is the time left: 90, 4m30s, 4:30, an RFC 3339 deadline, or left/total
+        path: std::path::PathBuf,
+    },
+    /// Pause or resume the running timer (daemon or another terminal)
+    Pause,
+    /// Skip to the next phase of the running session
+    Skip,
+    /// Plan the rest of the day against the [plan] goal and end of day
+    Plan {
+        #[command(subcommand)]
+        action: PlanAction,
+    },
     /// View or change configuration
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// List, add or remove presets
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
+    },
+    /// List, add or remove sessions
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
     /// Manage todo tasks
     Todo {
         #[command(subcommand)]
         action: Option<TodoAction>,
     },
+    /// Show help for a topic (keys, config, durations) or subcommand
+    Help {
+        /// Topic or subcommand name
+        topic: Option<String>,
+    },
+    /// Print the man page (roff) to stdout
+    #[command(hide = true)]
+    Man,
+}
+
+#[derive(Subcommand)]
+enum LogAction {
+    /// Remove blocks logged twice (same name and length, within a second)
+    Dedupe {
+        /// Only list the duplicates
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Scroll through the log newest first, filter it, and edit or delete blocks
+    Browse,
+    /// Find blocks and session notes by name, task, tag, project or note text
+    Search {
+        /// Text to look for (case-insensitive)
+        query: String,
+        #[command(flatten)]
+        dates: DateArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum PlanAction {
+    /// How much of today's goal is left and how many pomodoros fit before end of day
+    Status,
 }
 
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Show current configuration
     Show,
-    /// Set a configuration value (keys: work, break, long-break, rounds)
+    /// Print one value: a key `set` takes, or a path like theme.work
+    Get {
+        /// Config key or dotted path
+        key: String,
+    },
+    /// Set a configuration value (keys: work, break, long-break, rounds, require-ack)
     Set {
         /// Config key to set
         key: String,
-        /// New value (duration like "25m" or number for rounds)
+        /// New value (duration like "25m", number for rounds, true/false for require-ack)
         value: String,
     },
+    /// Print the config file's path
+    Path,
+    /// Open the config file in $VISUAL or $EDITOR
+    Edit,
+}
+
+#[derive(Subcommand)]
+enum PresetAction {
+    /// List the presets, built-in and user-defined
+    List,
+    /// Add a preset, or change the length of one
+    Add {
+        /// Preset name, e.g. focus
+        name: String,
+        /// Its length, e.g. 50m
+        duration: String,
+    },
+    /// Remove a preset from the config
+    Remove {
+        /// Preset name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionAction {
+    /// List the sessions with their phase lengths
+    List,
+    /// Add a session, or change the phases of one
+    Add {
+        /// Session name, e.g. deep
+        name: String,
+        /// Work phase: a preset or duration (default: pomodoro)
+        #[arg(long)]
+        work: Option<String>,
+        /// Short break: a preset or duration (default: break)
+        #[arg(long = "break")]
+        break_preset: Option<String>,
+        /// Long break after the last round: a preset or duration (default: long-break)
+        #[arg(long)]
+        long_break: Option<String>,
+        /// Work rounds (default: 4)
+        #[arg(long)]
+        rounds: Option<u32>,
+    },
+    /// Remove a session from the config
+    Remove {
+        /// Session name
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -105,23 +476,304 @@ enum TodoAction {
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    // A block autosaved by a run that crashed is logged before anything reads the log
+    log::recover_pending();
+
+    if cli.msgpack_rpc {
+        #[cfg(feature = "msgpack-rpc")]
+        let result = rpc::run().await;
+        #[cfg(not(feature = "msgpack-rpc"))]
+        let result: Result<(), String> = Err("This tik was built without the `msgpack-rpc` feature".to_string());
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
 
     // Handle subcommands
     if let Some(command) = cli.command {
         match command {
-            Commands::Log => {
-                log::print_summary();
+            Commands::Log { action: Some(LogAction::Browse), dates, .. } => {
+                let range = dates.range();
+                if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() || render::dumb_terminal() {
+                    eprintln!("tik log browse needs a terminal; use tik log or tik log search instead");
+                    std::process::exit(1);
+                }
+                browse::run(range).await;
+            }
+            Commands::Log { action: Some(LogAction::Dedupe { dry_run }), dates, .. } => {
+                if let Err(e) = log::dedupe(dry_run, &dates.range()) {
+                    eprintln!("Failed to rewrite the log: {e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Log { action: Some(LogAction::Search { query, dates: search_dates }), dates, .. } => {
+                // `tik log --since X search` and `tik log search --since X` both work
+                let range = match (dates.range(), search_dates.range()) {
+                    (range, search_range) if range.is_all() => search_range,
+                    (range, search_range) if search_range.is_all() => range,
+                    _ => {
+                        eprintln!("Give the days either before or after `search`, not both");
+                        std::process::exit(1);
+                    }
+                };
+                log::print_search(&query, &range);
+            }
+            Commands::Log { period, export: Some(format), output, dates, action: None, .. } => {
+                let format = match format {
+                    ExportFormat::Csv => log::ExportFormat::Csv,
+                    ExportFormat::Json => log::ExportFormat::Json,
+                };
+                let mut range = dates.range();
+                if let Some(period) = period {
+                    range = range.starting(log_period(period).starts(chrono::Local::now()).0);
+                }
+                if let Err(e) = log::export(format, &range, output.as_deref()) {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Log { period, json: true, dates, action: None, .. } => {
+                let range = stats::json_range(period.map(log_period), dates.range(), chrono::Local::now());
+                stats::print_json(&config::Config::load(), &range);
+            }
+            Commands::Log { period, compare, group_by, dates, action: None, .. } => {
+                let group_by = group_by.map_or(log::GroupBy::Preset, |group| match group {
+                    LogGroup::Preset => log::GroupBy::Preset,
+                    LogGroup::Tag => log::GroupBy::Tag,
+                    LogGroup::Task => log::GroupBy::Task,
+                    LogGroup::Project => log::GroupBy::Project,
+                    LogGroup::Repo => log::GroupBy::Repo,
+                    LogGroup::Weekday => log::GroupBy::Weekday,
+                    LogGroup::Hour => log::GroupBy::Hour,
+                });
+                let range = dates.range();
+                let config = config::Config::load();
+                let scoring = stats::Scoring::new(&config);
+                match period {
+                    Some(period) => log::print_period(log_period(period), &range, compare, &scoring, group_by),
+                    None if !range.is_all() => log::print_range(&range, group_by),
+                    None => log::print_summary(&config.weekly_budgets(), &scoring, group_by),
+                }
+            }
+            Commands::Attach { format, once } => {
+                let format = match format {
+                    AttachFormat::Json => attach::Format::Json,
+                    AttachFormat::Line => attach::Format::Line,
+                };
+                attach::run(format, once).await;
+            }
+            Commands::Status { json, compact, bar } => {
+                let style = if compact { duration::TimeStyle::Compact } else { duration::TimeStyle::Clock };
+                let bar = bar.map(|bar| match bar {
+                    BarStyle::Braille => frame::MiniBar::Braille,
+                    BarStyle::Blocks => frame::MiniBar::Blocks,
+                });
+                state::print_status(json, style, bar);
+            }
+            Commands::Daemon { install_unit } => {
+                let result = if install_unit { daemon::install_unit() } else { daemon::run().await };
+                if let Err(e) = result {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Doctor => {
+                if !doctor::run() {
+                    std::process::exit(1);
+                }
+            }
+            Commands::Insights => insights::print(&config::Config::load()),
+            Commands::Stats { week, month, all, dates } => {
+                let dates = dates.range();
+                // Given days on their own are looked at in full, not cut to this week
+                let range = if all || (!week && !month && !dates.is_all()) {
+                    stats::Range::All
+                } else if month {
+                    stats::Range::Month
+                } else {
+                    stats::Range::Week
+                };
+                stats::print(&config::Config::load(), range, &dates);
+            }
+            Commands::Wrapped { year, markdown, dates } => wrapped::print(&config::Config::load(), year, markdown, &dates.range()),
+            Commands::Start { target } => {
+                send_request(ipc::Request::Start { target }).await;
+            }
+            Commands::Stop => {
+                send_request(ipc::Request::Stop).await;
+            }
+            Commands::Resume => {
+                let checkpoint = match resume::take() {
+                    Ok(checkpoint) => checkpoint,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    }
+                };
+                let config = config::Config::load();
+                println!("{}", checkpoint.summary(&config.clock_format()));
+                let no_input = cli.no_input || !std::io::stdin().is_terminal();
+                let a11y = cli.a11y || config.a11y || render::dumb_terminal();
+                let options = session::SessionOptions {
+                    silent: cli.silent,
+                    title: cli.title.as_deref().or(checkpoint.title.as_deref()),
+                    ack: cli.ack || config.require_ack,
+                    headless: cli.headless || a11y || !std::io::stdout().is_terminal(),
+                    no_input,
+                    tenths: cli.tenths || config.tenths,
+                    a11y,
+                    notify_at: &cli.notify_at,
+                    labels: &checkpoint.labels,
+                    // A break carries on as a plain timer, which would log as work
+                    log: checkpoint.context != timer::TimerContext::Break && check_overlap(no_input),
+                };
+                let target = Target::Timer { name: checkpoint.name, dur: duration::Duration::from_secs(checkpoint.remaining_secs) };
+                daemon::serve_instance();
+                spawn_integrations(&config);
+                run_target(&config, target, options).await;
+            }
+            Commands::Overlay { file, http, format } => {
+                if let Err(e) = overlay::run(overlay::OverlayOptions { file, http, format }).await {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Cook { alarms } => {
+                let alarms = match alarms.iter().map(|spec| cook::parse_alarm(spec)).collect::<Result<Vec<_>, _>>() {
+                    Ok(alarms) => alarms,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    }
+                };
+                let config = config::Config::load();
+                let a11y = cli.a11y || config.a11y || render::dumb_terminal();
+                let headless = cli.headless || a11y || !std::io::stdout().is_terminal();
+                let no_input = cli.no_input || !std::io::stdin().is_terminal();
+                cook::run(&config, alarms, cli.silent, headless, no_input).await;
+            }
+            Commands::WatchFile { path } => {
+                let config = config::Config::load();
+                let a11y = cli.a11y || config.a11y || render::dumb_terminal();
+                let headless = cli.headless || a11y || !std::io::stdout().is_terminal();
+                let no_input = cli.no_input || !std::io::stdin().is_terminal();
+                watch::run(&config, path, cli.silent, headless, no_input).await;
+            }
+            Commands::Talk { sections } => {
+                let talk = match talk::Talk::parse(&sections) {
+                    Ok(talk) => talk,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    }
+                };
+                let config = config::Config::load();
+                let no_input = cli.no_input || !std::io::stdin().is_terminal();
+                let a11y = cli.a11y || config.a11y || render::dumb_terminal();
+                let options = session::SessionOptions {
+                    silent: cli.silent,
+                    title: cli.title.as_deref(),
+                    ack: cli.ack || config.require_ack,
+                    headless: cli.headless || a11y || !std::io::stdout().is_terminal(),
+                    no_input,
+                    tenths: cli.tenths || config.tenths,
+                    a11y,
+                    notify_at: &cli.notify_at,
+                    labels: &log::WorkLabels::default(),
+                    log: false,
+                };
+                talk::run(&config, &talk, options).await;
+            }
+            Commands::Pause => {
+                send_request(ipc::Request::Pause).await;
+            }
+            Commands::Skip => {
+                send_request(ipc::Request::Skip).await;
+            }
+            Commands::Plan { action: PlanAction::Status } => {
+                if let Err(e) = plan::print_status(&config::Config::load()) {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Help { topic } => {
+                if let Err(e) = help::print_help::<Cli>(topic.as_deref()) {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Man => {
+                if let Err(e) = help::write_man_page::<Cli>(&mut std::io::stdout()) {
+                    eprintln!("Failed to write man page: {e}");
+                    std::process::exit(1);
+                }
             }
             Commands::Config { action } => {
                 let cfg = config::Config::load();
                 match action {
                     ConfigAction::Show => cfg.show_config(),
+                    ConfigAction::Get { key } => match cfg.get_value(&key) {
+                        Ok(value) => println!("{value}"),
+                        Err(e) => {
+                            eprintln!("{e}");
+                            std::process::exit(1);
+                        }
+                    },
                     ConfigAction::Set { key, value } => {
                         if let Err(e) = config::Config::set_value(&key, &value) {
                             eprintln!("{e}");
                             std::process::exit(1);
                         }
                     }
+                    ConfigAction::Path => println!("{}", config::Config::config_path().display()),
+                    ConfigAction::Edit => {
+                        if let Err(e) = config::Config::edit() {
+                            eprintln!("{e}");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+            Commands::Preset { action } => {
+                let result = match action {
+                    PresetAction::List => {
+                        config::Config::load().print_presets();
+                        Ok(())
+                    }
+                    PresetAction::Add { name, .. } if <Cli as clap::CommandFactory>::command().find_subcommand(&name).is_some() => {
+                        Err(format!("'{name}' is a tik command, so `tik {name}` couldn't start the preset"))
+                    }
+                    PresetAction::Add { name, duration } => config::Config::add_preset(&name, &duration),
+                    PresetAction::Remove { name } => config::Config::remove_preset(&name),
+                };
+                if let Err(e) = result {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Session { action } => {
+                let result = match action {
+                    SessionAction::List => {
+                        config::Config::load().print_sessions();
+                        Ok(())
+                    }
+                    SessionAction::Add { name, .. } if <Cli as clap::CommandFactory>::command().find_subcommand(&name).is_some() => {
+                        Err(format!("'{name}' is a tik command, so `tik {name}` couldn't start the session"))
+                    }
+                    SessionAction::Add { name, work, break_preset, long_break, rounds } => config::Config::add_session(
+                        &name,
+                        work.as_deref(),
+                        break_preset.as_deref(),
+                        long_break.as_deref(),
+                        rounds,
+                    ),
+                    SessionAction::Remove { name } => config::Config::remove_session(&name),
+                };
+                if let Err(e) = result {
+                    eprintln!("{e}");
+                    std::process::exit(1);
                 }
             }
             Commands::Todo { action } => {
@@ -215,46 +867,342 @@ async fn main() {
         Some(d) => d,
         None => {
             eprintln!("Usage: tik <duration|preset>");
-            eprintln!("       tik <log|config|todo>");
+            eprintln!("       tik <log|status|start|stop|pause|skip|daemon|config|todo|doctor|help>");
             eprintln!("Examples: tik 25m, tik pomodoro, tik todo add \"Task\"");
             std::process::exit(1);
         }
     };
 
-    // Resolution order: session → preset → duration
     let config = config::Config::load();
+    if let Err(e) = config.theme.thresholds() {
+        eprintln!("{e}; using the default colors");
+    }
+    if let Some(Err(e)) = config.notify.quiet_hours.as_ref().map(config::QuietHours::range) {
+        eprintln!("{e}; quiet hours are off");
+    }
+    let target = match resolve_target(&config, &input).and_then(|t| t.named(cli.name.as_deref())) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let cwd = std::env::current_dir().ok();
+    let labels = log::WorkLabels {
+        tags: cli.tags,
+        task: cli.task,
+        estimate: cli.estimate,
+        tasks: cli.tasks.into_iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect(),
+        project: cwd.as_deref().and_then(|dir| config.project_for(dir)),
+        cwd: cwd.as_deref().filter(|_| config.record_cwd).map(|dir| dir.display().to_string()),
+        git: cwd.as_deref().filter(|_| config.record_git).and_then(git::detect),
+    };
+    // Raw mode and the alternate screen only make sense on a terminal: piped
+    // output gets plain lines, and without a terminal stdin there are no keys
+    // --a11y swaps the screen for the same plain lines, spelled out, and so
+    // does a terminal that can't draw it (TERM=dumb, e.g. an Emacs shell buffer)
+    let no_input = cli.no_input || !std::io::stdin().is_terminal();
+    let target = check_max_duration(&config, target, &input, no_input);
+    let a11y = cli.a11y || config.a11y || render::dumb_terminal();
+    let options = session::SessionOptions {
+        silent: cli.silent,
+        title: cli.title.as_deref().or(cli.name.as_deref()),
+        ack: cli.ack || config.require_ack,
+        headless: cli.headless || a11y || !std::io::stdout().is_terminal(),
+        no_input,
+        tenths: cli.tenths || config.tenths,
+        a11y,
+        notify_at: &cli.notify_at,
+        labels: &labels,
+        log: check_overlap(no_input),
+    };
+    daemon::serve_instance();
+    spawn_integrations(&config);
+    run_target(&config, target, options).await;
+}
+
+fn log_period(period: LogPeriod) -> log::Period {
+    match period {
+        LogPeriod::Today => log::Period::Today,
+        LogPeriod::Week => log::Period::Week,
+    }
+}
 
+/// One `--notify-at` mark, in seconds left.
+fn parse_notify_at(value: &str) -> Result<u64, String> {
+    duration::Duration::parse(value.trim()).map(|d| d.total_secs).map_err(|e| format!("Invalid duration '{value}': {e}"))
+}
+
+/// Send a request to the daemon and print its answer; exits non-zero on failure.
+async fn send_request(request: ipc::Request) {
+    match ipc::request(&request).await {
+        Ok(ipc::Response::Ok { message }) => println!("{message}"),
+        Ok(ipc::Response::Status { state }) => match state {
+            Some(state) => println!("{}", state::status_line(&state, chrono::Local::now(), duration::TimeStyle::Clock, None)),
+            None => println!("No timer running."),
+        },
+        Ok(ipc::Response::Error { message }) | Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Start the optional integrations that mirror the running timer elsewhere.
+pub fn spawn_integrations(config: &config::Config) {
+    #[cfg(feature = "mqtt")]
+    if config.mqtt.host.is_some() {
+        tokio::spawn(mqtt::run(config.mqtt.clone()));
+    }
+    #[cfg(not(feature = "mqtt"))]
+    if config.mqtt.host.is_some() {
+        eprintln!("Note: [mqtt] is configured but this tik was built without the `mqtt` feature");
+    }
+
+    #[cfg(feature = "discord")]
+    discord::spawn(config.discord.clone());
+    #[cfg(not(feature = "discord"))]
+    if config.discord.client_id.is_some() {
+        eprintln!("Note: [discord] is configured but this tik was built without the `discord` feature");
+    }
+}
+
+/// What a duration/preset argument resolved to.
+pub enum Target {
+    Session { name: String, session: config::SessionConfig },
+    Sequence { name: String, steps: Vec<String> },
+    Timer { name: String, dur: duration::Duration },
+}
+
+impl Target {
+    /// Give a timer the name from `tik 17m emails`, logged in place of "17m".
+    /// Sessions keep their own name for their phases.
+    fn named(self, name: Option<&str>) -> Result<Target, String> {
+        let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) else {
+            return Ok(self);
+        };
+        match self {
+            Target::Timer { dur, .. } => Ok(Target::Timer { name: name.to_string(), dur }),
+            Target::Session { name: session, .. } | Target::Sequence { name: session, .. } => {
+                Err(format!("'{session}' is a session; a name only applies to a single timer (try --title)"))
+            }
+        }
+    }
+}
+
+/// Resolution order: session → preset → duration
+pub fn resolve_target(config: &config::Config, input: &str) -> Result<Target, String> {
     // 1. Check if it's a session
-    if let Some(session_config) = config.resolve_session(&input) {
-        let session_config = session_config.clone();
-        session::run_session(&session_config, &config, cli.silent, cli.title.as_deref()).await;
+    if let Some(session_config) = config.resolve_session(input) {
+        return Ok(Target::Session { name: input.to_string(), session: session_config.clone() });
+    }
+    if let Some(steps) = config.resolve_sequence(input) {
+        return Ok(Target::Sequence { name: input.to_string(), steps: steps.to_vec() });
+    }
+
+    // 2. Sums like "pomodoro+10m": every term a duration or preset
+    if input.contains('+') {
+        let mut total = duration::Duration::default();
+        for term in input.split('+').map(str::trim) {
+            total += resolve_term(config, term)?;
+        }
+        return Ok(Target::Timer { name: input.to_string(), dur: total });
+    }
+
+    // 3. Until a clock minute: ":00", ":30", "endofhour"
+    if let Some(dur) = duration::Duration::until_clock(input, chrono::Local::now().time()) {
+        return dur.map(|dur| Target::Timer { name: input.to_string(), dur });
+    }
+
+    // 4. Try parsing as duration, then as preset
+    let parse_error = match duration::Duration::parse(input) {
+        Ok(dur) => return Ok(Target::Timer { name: input.to_string(), dur }),
+        Err(e) => e,
+    };
+    match config.resolve_preset(input) {
+        Some(preset_duration) => match duration::Duration::parse(preset_duration) {
+            Ok(dur) => Ok(Target::Timer { name: input.to_string(), dur }),
+            Err(e) => Err(format!("Invalid preset duration for '{input}': {e}")),
+        },
+        None if !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit()) => {
+            Err(format!("'{input}' needs a unit: did you mean {input}s or {input}m?"))
+        }
+        // Looks like a duration, so say what's wrong with it
+        None if input.starts_with(|c: char| c.is_ascii_digit()) => Err(format!("Invalid duration '{input}': {parse_error}")),
+        None => Err(format!(
+            "Unknown duration or preset: '{input}'\n\
+             Valid formats: 25m, 1h30m, 90s\n\
+             Built-in presets: pomodoro, break, long-break"
+        )),
+    }
+}
+
+/// One term of a sum: a duration or a preset, but not a session.
+fn resolve_term(config: &config::Config, term: &str) -> Result<duration::Duration, String> {
+    if term.is_empty() {
+        return Err("Empty term in a duration sum, e.g. 'pomodoro+10m'".to_string());
+    }
+    if config.resolve_session(term).is_some() && config.resolve_preset(term).is_none() {
+        return Err(format!("'{term}' is a session; only durations and presets can be added"));
+    }
+    let spec = config.resolve_preset(term).unwrap_or(term);
+    duration::Duration::parse(spec).map_err(|e| format!("Invalid term '{term}' in a duration sum: {e}"))
+}
+
+/// Warn when another process is already running a work timer, and ask whether
+/// to go ahead. Returns whether this timer should write to the log: merging
+/// runs it unlogged so the same focus time isn't counted twice. Without a
+/// keyboard the warning is printed and the timer starts as usual.
+fn check_overlap(no_input: bool) -> bool {
+    let Some(other) = state::running_work() else {
+        return true;
+    };
+    eprintln!("Warning: {}.", state::overlap_warning(&other, chrono::Local::now()));
+    if no_input {
+        return true;
+    }
+    eprint!("Continue anyway? [y]es, [m]erge logs (don't log this timer), [N]o: ");
+    let mut answer = String::new();
+    let _ = std::io::stdin().read_line(&mut answer);
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "m" | "merge" => false,
+        _ => std::process::exit(1),
+    }
+}
+
+/// Ask before starting a timer over `max_duration`, offering the next unit
+/// down when that looks like the typo ("25h" for "25m"). Without a keyboard
+/// the timer is refused instead.
+fn check_max_duration(config: &config::Config, target: Target, input: &str, no_input: bool) -> Target {
+    let Target::Timer { name, dur } = target else {
+        return target;
+    };
+    let Some(max) = config.max_duration_secs().filter(|&max| dur.total_secs > max) else {
+        return Target::Timer { name, dur };
+    };
+    let limit = duration::Duration::from_secs(max).to_spec();
+    let suggestion = duration::Duration::smaller_unit(input);
+    eprintln!("Warning: {input} is longer than {limit} (max_duration in the config).");
+    if no_input {
+        std::process::exit(1);
+    }
+    match &suggestion {
+        Some(spec) => eprint!("Did you mean {spec}? [y]es, [k]eep {input}, [N]o: "),
+        None => eprint!("Start it anyway? [k]eep {input}, [N]o: "),
+    }
+    let mut answer = String::new();
+    let _ = std::io::stdin().read_line(&mut answer);
+    match (answer.trim().to_lowercase().as_str(), suggestion) {
+        ("y" | "yes", Some(spec)) => match duration::Duration::parse(&spec) {
+            // A name given after the duration stays; otherwise the fixed spec is the name
+            Ok(fixed) => Target::Timer { name: if name == input { spec } else { name }, dur: fixed },
+            Err(_) => std::process::exit(1),
+        },
+        ("k" | "keep", _) => Target::Timer { name, dur },
+        _ => std::process::exit(1),
+    }
+}
+
+/// Warn before starting tagged work that would exceed a weekly budget.
+async fn check_budgets(config: &config::Config, target: &Target, options: &session::SessionOptions<'_>) {
+    if options.labels.tags.is_empty() {
+        return;
+    }
+    let budgets = config.weekly_budgets();
+    if budgets.is_empty() {
         return;
     }
+    let planned_secs = match target {
+        Target::Timer { dur, .. } => dur.total_secs,
+        Target::Session { session, .. } => session.planned_work_secs(config),
+        Target::Sequence { steps, .. } => session::SessionPlan::sequence(steps, config).map_or(0, |p| p.planned_work_secs()),
+    };
+    let warnings = log::budget_warnings(&log::read_entries(), &budgets, &options.labels.tags, planned_secs, chrono::Local::now());
+    for warning in &warnings {
+        eprintln!("Budget warning: {warning}");
+    }
+    if !warnings.is_empty() && !options.headless {
+        // Give the warning a moment on screen before the timer UI takes over
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    }
+}
+
+pub async fn run_target(config: &config::Config, target: Target, options: session::SessionOptions<'_>) {
+    check_budgets(config, &target, &options).await;
+    let events = events::EventBus::start(config, options.silent, options.log);
+    match target {
+        Target::Session { name, session } => run_plan(&name, session::SessionPlan::new(&session, config), config, options, &events).await,
+        Target::Sequence { name, steps } => run_plan(&name, session::SessionPlan::sequence(&steps, config), config, options, &events).await,
+        Target::Timer { name, dur } => run_timer(config, &name, dur, options, &events).await,
+    }
+    events.close().await;
+}
 
-    // 2. Try parsing as duration, then as preset
-    let (name, dur) = match duration::Duration::parse(&input) {
-        Ok(d) => (input.clone(), d),
-        Err(_) => {
-            // Try as preset
-            match config.resolve_preset(&input) {
-                Some(preset_duration) => match duration::Duration::parse(preset_duration) {
-                    Ok(d) => (input.clone(), d),
-                    Err(e) => {
-                        eprintln!("Invalid preset duration for '{input}': {e}");
-                        std::process::exit(1);
-                    }
-                },
-                None => {
-                    eprintln!("Unknown duration or preset: '{input}'");
-                    eprintln!("Valid formats: 25m, 1h30m, 90s");
-                    eprintln!("Built-in presets: pomodoro, break, long-break");
-                    std::process::exit(1);
-                }
+async fn run_plan(
+    name: &str,
+    plan: Result<session::SessionPlan, String>,
+    config: &config::Config,
+    options: session::SessionOptions<'_>,
+    events: &events::EventBus,
+) {
+    match plan {
+        Ok(mut plan) => {
+            let mut tips = adapt_work_length(&mut plan, config);
+            let scoring = stats::Scoring::new(config);
+            if let Some(nudge) = stats::skipped_breaks_nudge(&log::read_entries(), &scoring, chrono::Local::now()) {
+                println!("Tip: {nudge}.");
+                tips = true;
             }
+            if tips && !options.headless {
+                // Leave the tips on screen a moment before the timer UI takes over
+                tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+            }
+            session::run_session(name, plan, config, options, events).await
         }
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+/// Show, or with `adaptive = true` apply, a work length the session log
+/// suggests for this plan. Returns whether anything was printed.
+fn adapt_work_length(plan: &mut session::SessionPlan, config: &config::Config) -> bool {
+    let Some(work_secs) = plan.work_secs() else {
+        return false;
     };
+    let Some(suggestion) = adaptive::suggest(&log::read_sessions(), work_secs) else {
+        return false;
+    };
+    let length = duration::Duration::from_secs(suggestion.work_secs).to_spec();
+    if config.adaptive {
+        plan.set_work_secs(suggestion.work_secs);
+        println!("Work rounds set to {length}: {} (adaptive)", suggestion.reason());
+    } else {
+        println!("Tip: {}; try {length} work rounds (or set adaptive = true)", suggestion.reason());
+    }
+    true
+}
 
-    let display = dur.format_hms();
+async fn run_timer(
+    config: &config::Config,
+    name: &str,
+    dur: duration::Duration,
+    options: session::SessionOptions<'_>,
+    events: &events::EventBus,
+) {
+    let input = (!options.headless && !options.no_input).then(input::Input::start);
+    let screen = if options.headless {
+        None
+    } else {
+        match render::Screen::enter(input.is_some()) {
+            Ok(screen) => Some(screen.with_graphics(config.graphics)),
+            Err(e) => {
+                eprintln!("Failed to setup terminal: {e}");
+                return;
+            }
+        }
+    };
+    let footer = screen.as_ref().and_then(|_| footer::Footer::start(&config.footer));
     let todos = {
         let list = todo::TodoList::load();
         if list.items.is_empty() {
@@ -263,28 +1211,63 @@ async fn main() {
             Some(std::sync::Arc::new(std::sync::Mutex::new(list)))
         }
     };
-    let outcome = timer::run(dur.total_secs, &name, timer::TimerContext::Standalone, cli.title.as_deref(), None, todos.clone()).await;
+    let ack_prompt = (options.ack && !options.headless && !options.no_input).then_some(timer::ACK_PROMPT);
+    let sounds = events.sounds();
+    let ticker = sound::Ticker::new(config, sounds, options.silent);
+    let result = timer::run(timer::TimerParams {
+        name,
+        total_secs: dur.total_secs,
+        context: timer::TimerContext::Standalone,
+        title: options.title,
+        round_info: None,
+        todos: todos.clone(),
+        ack_prompt,
+        flow_extend_secs: config.flow_extend_secs(),
+        colors: config.theme.colors_for(timer::TimerContext::Standalone),
+        animate_completion: config.completion_animation(),
+        inhibit_sleep: config.inhibit_sleep,
+        screen: screen.as_ref(),
+        input: input.as_ref(),
+        blocker: Some(&config.blocker),
+        clock_format: &config.clock_format(),
+        frame_interval: config.frame_interval(),
+        tenths: options.tenths,
+        announce: options.a11y,
+        notify_at: options.notify_at,
+        sounds,
+        ticker: ticker.as_ref(),
+        plan: None,
+        talk: None,
+        break_debt: None,
+        footer: footer.as_ref(),
+        events,
+        labels: &options.labels.for_round(1),
+    }).await;
+    drop(screen);
 
     // Save todos if they were modified during timer
-    if let Some(ref todos) = todos {
-        if let Ok(list) = todos.lock() {
-            if let Err(e) = list.save() {
-                eprintln!("Failed to save todos: {e}");
-            }
-        }
+    if let Some(ref todos) = todos
+        && let Ok(list) = todos.lock()
+        && let Err(e) = list.save()
+    {
+        eprintln!("Failed to save todos: {e}");
     }
 
-    if outcome == timer::TimerOutcome::Completed {
-        notify::send_completion(&name, &display, cli.silent);
-
+    if result.outcome.is_completed() {
+        let total_secs = dur.total_secs + result.extended_secs;
+        let display = duration::Duration::from_secs(total_secs).format_hms();
         let entry = log::LogEntry {
-            name,
-            duration_secs: dur.total_secs,
-            completed_at: chrono::Local::now(),
+            extended_secs: result.extended_secs,
+            ..log::LogEntry::work(name, total_secs, &options.labels.for_round(1))
         };
-        if let Err(e) = log::append_entry(&entry) {
-            eprintln!("Failed to write log: {e}");
-        }
+        events.emit(events::TikEvent::Completed {
+            name: name.to_string(),
+            context: timer::TimerContext::Standalone,
+            duration_secs: total_secs,
+            skipped: false,
+            next: None,
+            entries: entry.split_at_task_switches(&result.task_switches),
+        });
 
         println!("Timer complete: {display}");
     } else {
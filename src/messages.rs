@@ -0,0 +1,84 @@
+//! Warnings from background work (a log write that failed, a hook that
+//! wouldn't start) that can't go to stderr while the full-screen timer is up:
+//! printed there, they'd scribble over the frame. While a `Screen` is active
+//! they're held, the newest shown on the timer's top row for a few seconds,
+//! and all of them printed once the terminal is restored.
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long a warning stays on the timer screen.
+const SHOWN_FOR: Duration = Duration::from_secs(8);
+
+#[derive(Default)]
+struct Queue {
+    /// Screens currently up; warnings are held while any is.
+    screens: usize,
+    held: Vec<String>,
+    /// The newest warning and when it came in.
+    latest: Option<(String, Instant)>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue { screens: 0, held: Vec::new(), latest: None });
+
+fn queue() -> std::sync::MutexGuard<'static, Queue> {
+    QUEUE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Report a problem: on stderr, or held until the screen is gone.
+pub fn warn(message: impl Into<String>) {
+    let message = message.into();
+    let mut queue = queue();
+    if queue.screens == 0 {
+        drop(queue);
+        eprintln!("{message}");
+        return;
+    }
+    queue.latest = Some((message.clone(), Instant::now()));
+    queue.held.push(message);
+}
+
+/// A screen went up: hold warnings from now on.
+pub fn hold() {
+    queue().screens += 1;
+}
+
+/// A screen came down. Once the last one is gone, the held warnings are
+/// returned to be printed.
+pub fn release() -> Vec<String> {
+    let mut queue = queue();
+    queue.screens = queue.screens.saturating_sub(1);
+    if queue.screens > 0 {
+        return Vec::new();
+    }
+    queue.latest = None;
+    std::mem::take(&mut queue.held)
+}
+
+/// The warning to show on the screen now, if a recent one came in.
+pub fn current() -> Option<String> {
+    let queue = queue();
+    let (message, at) = queue.latest.as_ref()?;
+    (at.elapsed() < SHOWN_FOR).then(|| message.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One test, since the queue is shared by the whole process
+    #[test]
+    fn held_while_a_screen_is_up() {
+        hold();
+        hold();
+        warn("Failed to write log: disk full");
+        assert_eq!(current().as_deref(), Some("Failed to write log: disk full"));
+        warn("MQTT: connection refused");
+        assert_eq!(current().as_deref(), Some("MQTT: connection refused"));
+        // The outer screen is still up
+        assert_eq!(release(), Vec::<String>::new());
+        assert_eq!(release(), ["Failed to write log: disk full", "MQTT: connection refused"]);
+        assert_eq!(current(), None);
+        assert_eq!(release(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,138 @@
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::config::MqttConfig;
+use crate::ipc::Request;
+use crate::state::RuntimeState;
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_TOPIC: &str = "tik";
+const PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Mirror the running timer to `<topic>/state` (retained JSON) and accept
+/// commands on `<topic>/command`: `pause`, `skip`, `stop` or `start <target>`.
+pub async fn run(config: MqttConfig) {
+    let Some(host) = config.host.clone() else {
+        return;
+    };
+    let topic = config.topic.clone().unwrap_or_else(|| DEFAULT_TOPIC.to_string());
+    let state_topic = format!("{topic}/state");
+    let command_topic = format!("{topic}/command");
+
+    let mut options = MqttOptions::new(format!("tik-{}", std::process::id()), host, config.port.unwrap_or(DEFAULT_PORT));
+    options.set_keep_alive(Duration::from_secs(30));
+    // Brokers publish this if we disappear without a clean disconnect
+    options.set_last_will(LastWill::new(&state_topic, state_payload(None, chrono::Local::now()), QoS::AtLeastOnce, true));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, 10);
+    let mut ticker = tokio::time::interval(PUBLISH_INTERVAL);
+    let mut last_payload = String::new();
+    let mut reported_error = false;
+    loop {
+        tokio::select! {
+            event = eventloop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    reported_error = false;
+                    last_payload.clear();
+                    let _ = client.subscribe(&command_topic, QoS::AtLeastOnce).await;
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if let Some(request) = parse_command(&String::from_utf8_lossy(&publish.payload)) {
+                        let _ = crate::ipc::request(&request).await;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    // Report once per outage; the event loop reconnects on the next poll
+                    if !reported_error {
+                        crate::messages::warn(format!("MQTT: {e}"));
+                        reported_error = true;
+                    }
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = ticker.tick() => {
+                let payload = state_payload(crate::state::read().as_ref(), chrono::Local::now());
+                if payload != last_payload {
+                    let _ = client.try_publish(&state_topic, QoS::AtLeastOnce, true, payload.as_bytes());
+                    last_payload = payload;
+                }
+            }
+        }
+    }
+}
+
+/// State message for Home Assistant and friends; phase is "idle" when nothing runs.
+fn state_payload(state: Option<&RuntimeState>, now: chrono::DateTime<chrono::Local>) -> String {
+    let value = match state {
+        None => json!({ "phase": "idle" }),
+        Some(state) => json!({
+            "phase": state.phase.label(),
+            "name": state.name,
+            "remaining_secs": state.remaining_at(now),
+            "total_secs": state.total_secs,
+            "paused": state.paused,
+            "round": state.round.map(|(current, _)| current),
+            "total_rounds": state.round.map(|(_, total)| total),
+        }),
+    };
+    value.to_string()
+}
+
+fn parse_command(payload: &str) -> Option<Request> {
+    let payload = payload.trim();
+    let (command, argument) = payload.split_once(char::is_whitespace).unwrap_or((payload, ""));
+    match command.to_ascii_lowercase().as_str() {
+        "pause" => Some(Request::Pause),
+        "skip" => Some(Request::Skip),
+        "stop" => Some(Request::Stop),
+        "start" if !argument.trim().is_empty() => Some(Request::Start { target: argument.trim().to_string() }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerContext;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(parse_command("pause"), Some(Request::Pause));
+        assert_eq!(parse_command(" STOP\n"), Some(Request::Stop));
+        assert_eq!(parse_command("start pomodoro"), Some(Request::Start { target: "pomodoro".to_string() }));
+        assert_eq!(parse_command("start"), None);
+        assert_eq!(parse_command("dance"), None);
+    }
+
+    #[test]
+    fn idle_payload_when_nothing_runs() {
+        assert_eq!(state_payload(None, chrono::Local::now()), r#"{"phase":"idle"}"#);
+    }
+
+    #[test]
+    fn running_payload_includes_round() {
+        let now = chrono::Local::now();
+        let state = RuntimeState {
+            version: crate::state::SCHEMA_VERSION,
+            pid: 1,
+            name: "pomodoro".to_string(),
+            phase: TimerContext::Work,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 600,
+            paused: false,
+            updated_at: now,
+        };
+        let payload: serde_json::Value = serde_json::from_str(&state_payload(Some(&state), now)).unwrap();
+        assert_eq!(payload["phase"], "work");
+        assert_eq!(payload["remaining_secs"], 600);
+        assert_eq!(payload["round"], 2);
+        assert_eq!(payload["total_rounds"], 4);
+    }
+}
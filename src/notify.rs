@@ -1,16 +1,510 @@
-pub fn send_completion(name: &str, duration_display: &str, silent: bool) {
-    let mut notification = notify_rust::Notification::new();
-    notification
-        .summary(&format!("{name} complete"))
-        .body(&format!("{duration_display} timer finished"))
-        .appname("pomitik");
+//! Notifications, one `Notifier` per channel: desktop, terminal, webhook and
+//! sound. `[notify] channels` picks which run, and every one picked gets each
+//! notice, so adding a channel means adding a notifier rather than growing
+//! one function.
+//!
+//! Delivery is retried once. A channel that still fails hands the notice to
+//! the terminal instead, and the failure goes to the debug log rather than
+//! stderr, where it would scribble over the full-screen timer.
 
-    #[cfg(target_os = "macos")]
-    if !silent {
-        notification.sound_name("Glass");
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use crate::config::{Channel, NotifyConfig, NotifyTemplate};
+use crate::duration::Duration;
+use crate::events::TikEvent;
+use crate::sound::{Cue, SoundPack};
+use crate::timer::TimerContext;
+
+/// Something worth telling the user about.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Notice<'a> {
+    /// A phase ran out (skipped phases stay quiet).
+    Completed {
+        name: &'a str,
+        context: TimerContext,
+        duration_secs: u64,
+        round: Option<(u32, u32)>,
+        task: Option<&'a str>,
+        /// The phase after this one in a session: name and length.
+        next: Option<(&'a str, u64)>,
+    },
+    /// A `--notify-at` mark was reached.
+    Milestone { name: &'a str, remaining_secs: u64 },
+}
+
+/// What a notification says.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    pub summary: String,
+    pub body: Option<String>,
+}
+
+impl Text {
+    /// Summary and body on one line.
+    fn line(&self) -> String {
+        match &self.body {
+            Some(body) => format!("{}: {body}", self.summary),
+            None => self.summary.clone(),
+        }
+    }
+}
+
+impl<'a> Notice<'a> {
+    /// The notice for an event, if it calls for one. `round` is the round of
+    /// the phase that was running, which the event doesn't carry.
+    pub fn from_event(event: &'a TikEvent, round: Option<(u32, u32)>) -> Option<Self> {
+        match event {
+            TikEvent::Completed { name, context, duration_secs, skipped: false, next, entries } => Some(Notice::Completed {
+                name,
+                context: *context,
+                duration_secs: *duration_secs,
+                round,
+                task: entries.iter().find_map(|entry| entry.task.as_deref()),
+                next: next.as_ref().map(|(name, secs)| (name.as_str(), *secs)),
+            }),
+            TikEvent::Milestone { name, remaining_secs } => Some(Notice::Milestone { name, remaining_secs: *remaining_secs }),
+            _ => None,
+        }
+    }
+
+    /// The built-in text, or the template's where it has one.
+    pub fn text(&self, template: Option<&NotifyTemplate>) -> Text {
+        let fill = |template: &Option<String>| template.as_deref().map(|template| self.fill(template));
+        let template = template.filter(|_| matches!(self, Notice::Completed { .. }));
+        let (summary, body) = match self {
+            Notice::Completed { name, duration_secs, .. } => {
+                (format!("{name} complete"), Some(format!("{} timer finished", Duration::from_secs(*duration_secs).format_hms())))
+            }
+            Notice::Milestone { name, remaining_secs } => {
+                (format!("{name}: {} left", Duration::from_secs(*remaining_secs).format_compact()), None)
+            }
+        };
+        Text {
+            summary: template.and_then(|t| fill(&t.summary)).unwrap_or(summary),
+            body: match template.map(|t| fill(&t.body)) {
+                Some(Some(body)) => Some(body).filter(|body| !body.is_empty()),
+                _ => body,
+            },
+        }
+    }
+
+    /// Fill in a template. Placeholders with nothing to show become empty.
+    fn fill(&self, template: &str) -> String {
+        let Notice::Completed { name, duration_secs, round, task, next, .. } = self else {
+            return template.to_string();
+        };
+        let round = round.map(|(round, total)| format!("{round}/{total}")).unwrap_or_default();
+        let next = next.map(|(name, secs)| format!("{name} {}", Duration::from_secs(secs).format_compact())).unwrap_or_default();
+        template
+            .replace("{name}", name)
+            .replace("{duration}", &Duration::from_secs(*duration_secs).format_compact())
+            .replace("{round}", &round)
+            .replace("{task}", task.unwrap_or(""))
+            .replace("{next}", &next)
+            .trim()
+            .to_string()
+    }
+
+    /// Heads-ups are always silent: the completion is the one that should be heard.
+    fn audible(&self) -> bool {
+        matches!(self, Notice::Completed { .. })
+    }
+}
+
+/// How long the notification server gets to take a notification. A busy
+/// D-Bus can otherwise hold the call, and every notice after it, for good.
+const SHOW_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The pause before the one retry.
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// One way of delivering notices.
+pub trait Notifier: Send {
+    /// The channel's config name, for error messages.
+    fn name(&self) -> &'static str;
+
+    /// Deliver a notice, which says `text`. `silent` with `--silent` or in
+    /// the quiet hours.
+    fn notify(&self, notice: &Notice, text: &Text, silent: bool) -> Result<(), String>;
+}
+
+/// The notifiers for a run, with the one that stands in when a channel fails.
+pub struct Notifiers {
+    channels: Vec<Box<dyn Notifier>>,
+    config: NotifyConfig,
+    /// The terminal, unless it's one of the channels already.
+    fallback: Option<Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    /// The notifiers for the configured channels. `sounds` is the active
+    /// sound pack; while the sound channel plays it, desktop notifications
+    /// don't add the system sound on top.
+    pub fn from_config(config: &NotifyConfig, sounds: Option<&SoundPack>) -> Self {
+        let fallback: Option<Box<dyn Notifier>> = (!config.channels().contains(&Channel::Terminal)).then(|| Box::new(TerminalNotifier) as _);
+        Notifiers { channels: channels_from_config(config, sounds), config: config.clone(), fallback }
+    }
+
+    /// Hand a notice to every channel. A failing channel doesn't stop the
+    /// rest; the fallback delivers the notice once if any of them failed.
+    pub fn send(&self, notice: &Notice, silent: bool) {
+        self.send_with(notice, silent, RETRY_DELAY, &mut |failure| crate::debug::record(&failure));
+    }
+
+    /// `send`, pausing `delay` before a retry and handing failures to `report`.
+    fn send_with(&self, notice: &Notice, silent: bool, delay: std::time::Duration, report: &mut dyn FnMut(String)) {
+        let template = match notice {
+            Notice::Completed { context, .. } => self.config.template(*context),
+            Notice::Milestone { .. } => None,
+        };
+        let text = notice.text(template);
+        let mut failed = false;
+        for notifier in &self.channels {
+            if let Err(e) = deliver(notifier.as_ref(), notice, &text, silent, delay) {
+                report(format!("{} notification failed: {e}", notifier.name()));
+                failed = true;
+            }
+        }
+        if failed
+            && let Some(fallback) = &self.fallback
+            && let Err(e) = deliver(fallback.as_ref(), notice, &text, silent, delay)
+        {
+            report(format!("{} fallback notification failed: {e}", fallback.name()));
+        }
+    }
+}
+
+/// Try a notifier, and once more after `delay` if it fails.
+fn deliver(notifier: &dyn Notifier, notice: &Notice, text: &Text, silent: bool, delay: std::time::Duration) -> Result<(), String> {
+    notifier.notify(notice, text, silent).or_else(|_| {
+        std::thread::sleep(delay);
+        notifier.notify(notice, text, silent)
+    })
+}
+
+/// Run `f` on its own thread and give up on it after `timeout`. A call that
+/// never returns keeps its thread, but not the caller.
+fn with_timeout<T: Send + 'static>(timeout: std::time::Duration, f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(timeout).unwrap_or_else(|_| Err(format!("no answer after {timeout:?}")))
+}
+
+fn channels_from_config(config: &NotifyConfig, sounds: Option<&SoundPack>) -> Vec<Box<dyn Notifier>> {
+    let channels = config.channels();
+    let pack_plays = sounds.is_some() && channels.contains(&Channel::Sound);
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    for channel in channels {
+        match channel {
+            Channel::Desktop => notifiers.push(Box::new(DesktopNotifier { silent: pack_plays })),
+            Channel::Terminal => notifiers.push(Box::new(TerminalNotifier)),
+            // Reported by `tik doctor` when the URL is missing
+            Channel::Webhook => {
+                if let Some(url) = &config.webhook_url {
+                    notifiers.push(Box::new(WebhookNotifier { url: url.clone() }));
+                }
+            }
+            Channel::Sound => {
+                if let Some(pack) = sounds {
+                    notifiers.push(Box::new(SoundNotifier { pack: pack.clone() }));
+                }
+            }
+        }
+    }
+    notifiers
+}
+
+/// The system notification center, via notify-rust.
+pub struct DesktopNotifier {
+    /// Never play the system sound, because a sound pack plays instead.
+    silent: bool,
+}
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn notify(&self, notice: &Notice, text: &Text, silent: bool) -> Result<(), String> {
+        let mut notification = notify_rust::Notification::new();
+        notification.summary(&text.summary).appname("pomitik");
+        if let Some(body) = &text.body {
+            notification.body(body);
+        }
+
+        #[cfg(target_os = "macos")]
+        if notice.audible() && !silent && !self.silent {
+            notification.sound_name("Glass");
+        }
+        #[cfg(not(target_os = "macos"))]
+        let _ = (notice, silent, self.silent);
+
+        with_timeout(SHOW_TIMEOUT, move || notification.show().map(drop).map_err(|e| e.to_string()))
+    }
+}
+
+/// The terminal's own notification (OSC 9, shown by iTerm2, WezTerm, kitty,
+/// foot and Windows Terminal) plus the bell. Only when stdout is a terminal.
+pub struct TerminalNotifier;
+
+impl Notifier for TerminalNotifier {
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn notify(&self, notice: &Notice, text: &Text, silent: bool) -> Result<(), String> {
+        let stdout = std::io::stdout();
+        if !stdout.is_terminal() {
+            return Ok(());
+        }
+        // One write, so it can't land in the middle of an escape sequence
+        // the renderer is writing
+        let mut stdout = stdout.lock();
+        stdout.write_all(terminal_sequence(notice, text, silent).as_bytes()).and_then(|()| stdout.flush()).map_err(|e| e.to_string())
+    }
+}
+
+fn terminal_sequence(notice: &Notice, text: &Text, silent: bool) -> String {
+    // A control character in a task name would end the sequence early
+    let text: String = text.line().chars().filter(|c| !c.is_control()).collect();
+    let bell = if notice.audible() && !silent { "\x07" } else { "" };
+    format!("\x1b]9;{text}\x1b\\{bell}")
+}
+
+/// A JSON POST to a URL (ntfy, Slack-style hooks, Home Assistant), sent
+/// with `curl` so https works without a TLS stack in tik.
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn notify(&self, notice: &Notice, text: &Text, _silent: bool) -> Result<(), String> {
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--max-time", "10", "--header", "Content-Type: application/json"])
+            .args(["--data-binary", "@-", &self.url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("couldn't run curl: {e}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(webhook_payload(notice, text).to_string().as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if error.is_empty() { format!("curl exited with {}", output.status) } else { error })
+    }
+}
+
+fn webhook_payload(notice: &Notice, text: &Text) -> serde_json::Value {
+    let mut payload = match notice {
+        Notice::Completed { name, context, duration_secs, round, task, next } => serde_json::json!({
+            "event": "completed",
+            "name": name,
+            "phase": context,
+            "duration_secs": duration_secs,
+            "round": round,
+            "task": task,
+            "next": next.map(|(name, secs)| serde_json::json!({ "name": name, "duration_secs": secs })),
+        }),
+        Notice::Milestone { name, remaining_secs } => {
+            serde_json::json!({ "event": "milestone", "name": name, "remaining_secs": remaining_secs })
+        }
+    };
+    payload["summary"] = text.summary.clone().into();
+    if let Some(body) = &text.body {
+        payload["body"] = body.clone().into();
+    }
+    payload
+}
+
+/// The sound pack's cue for the end of a work or break phase.
+pub struct SoundNotifier {
+    pack: SoundPack,
+}
+
+impl Notifier for SoundNotifier {
+    fn name(&self) -> &'static str {
+        "sound"
+    }
+
+    fn notify(&self, notice: &Notice, _text: &Text, silent: bool) -> Result<(), String> {
+        let Notice::Completed { context, .. } = notice else {
+            return Ok(());
+        };
+        if !silent {
+            self.pack.play(if *context == TimerContext::Break { Cue::BreakEnd } else { Cue::WorkEnd });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed() -> Notice<'static> {
+        Notice::Completed { name: "pomodoro", context: TimerContext::Work, duration_secs: 1500, round: None, task: None, next: None }
+    }
+
+    fn text(notice: &Notice) -> Text {
+        notice.text(None)
+    }
+
+    #[test]
+    fn notices_come_from_completions_and_milestones() {
+        let event = |skipped| TikEvent::Completed {
+            name: "pomodoro".to_string(),
+            context: TimerContext::Work,
+            duration_secs: 1500,
+            skipped,
+            next: Some(("break".to_string(), 300)),
+            entries: vec![crate::log::LogEntry { task: Some("spec".to_string()), ..Default::default() }],
+        };
+        let (event, skipped) = (event(false), event(true));
+        let notice = Notice::from_event(&event, Some((2, 4))).unwrap();
+        let Notice::Completed { round, task, next, .. } = notice else { panic!("expected a completion") };
+        assert_eq!((round, task, next), (Some((2, 4)), Some("spec"), Some(("break", 300))));
+        assert_eq!(Notice::from_event(&skipped, None), None);
+        assert_eq!(Notice::from_event(&TikEvent::TimerEnded, None), None);
+
+        let milestone = TikEvent::Milestone { name: "deep work".to_string(), remaining_secs: 600 };
+        let notice = Notice::from_event(&milestone, None).unwrap();
+        assert_eq!(text(&notice), Text { summary: "deep work: 10m left".to_string(), body: None });
+        assert_eq!(text(&completed()), Text { summary: "pomodoro complete".to_string(), body: Some("25:00 timer finished".to_string()) });
+    }
+
+    #[test]
+    fn templates_fill_placeholders() {
+        let notice = Notice::Completed {
+            name: "pomodoro",
+            context: TimerContext::Work,
+            duration_secs: 1500,
+            round: Some((2, 4)),
+            task: Some("spec"),
+            next: Some(("break", 300)),
+        };
+        let template = |summary: Option<&str>, body: Option<&str>| NotifyTemplate { summary: summary.map(str::to_string), body: body.map(str::to_string) };
+        let text = notice.text(Some(&template(Some("{name} {round} done: {task}"), Some("Up next: {next} ({duration} worked)"))));
+        assert_eq!(text.summary, "pomodoro 2/4 done: spec");
+        assert_eq!(text.body.as_deref(), Some("Up next: break 5m (25m worked)"));
+        // Unset parts keep the built-in text, and a body left empty is dropped
+        let text = completed().text(Some(&template(None, Some("{next}"))));
+        assert_eq!((text.summary.as_str(), text.body), ("pomodoro complete", None));
+        let milestone = Notice::Milestone { name: "pomodoro", remaining_secs: 60 };
+        assert_eq!(milestone.text(Some(&template(Some("{name} done"), None))).summary, "pomodoro: 1m left");
+    }
+
+    #[test]
+    fn channels_pick_notifiers() {
+        let names = |config: &NotifyConfig| channels_from_config(config, None).iter().map(|n| n.name()).collect::<Vec<_>>();
+        // No sound pack, so nothing to play
+        assert_eq!(names(&NotifyConfig::default()), ["desktop"]);
+        let config = NotifyConfig {
+            channels: Some(vec![Channel::Terminal, Channel::Webhook, Channel::Desktop]),
+            webhook_url: Some("http://localhost:8080/tik".to_string()),
+            ..NotifyConfig::default()
+        };
+        assert_eq!(names(&config), ["terminal", "webhook", "desktop"]);
+        let config = NotifyConfig { webhook_url: None, ..config };
+        assert_eq!(names(&config), ["terminal", "desktop"]);
+    }
+
+    /// Fails its first `failures` calls.
+    struct Flaky {
+        failures: usize,
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Notifier for Flaky {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn notify(&self, _notice: &Notice, _text: &Text, _silent: bool) -> Result<(), String> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if call < self.failures { Err("busy".to_string()) } else { Ok(()) }
+        }
+    }
+
+    fn flaky(failures: usize) -> (Box<dyn Notifier>, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        let calls = std::sync::Arc::default();
+        (Box::new(Flaky { failures, calls: std::sync::Arc::clone(&calls) }), calls)
+    }
+
+    #[test]
+    fn delivery_retried_once() {
+        let calls = |failures| {
+            let (notifier, calls) = flaky(failures);
+            let result = deliver(notifier.as_ref(), &completed(), &text(&completed()), false, std::time::Duration::ZERO);
+            (result.is_ok(), calls.load(std::sync::atomic::Ordering::SeqCst))
+        };
+        assert_eq!(calls(0), (true, 1));
+        assert_eq!(calls(1), (true, 2));
+        assert_eq!(calls(5), (false, 2));
+    }
+
+    #[test]
+    fn fallback_only_after_a_failure() {
+        let send = |channels: Vec<Box<dyn Notifier>>, fallback: Box<dyn Notifier>| {
+            let mut failures = Vec::new();
+            let notifiers = Notifiers { channels, config: NotifyConfig::default(), fallback: Some(fallback) };
+            notifiers.send_with(&completed(), true, std::time::Duration::ZERO, &mut |failure| failures.push(failure));
+            failures
+        };
+        let (working, _) = flaky(0);
+        let (fallback, fallback_calls) = flaky(0);
+        assert!(send(vec![working], fallback).is_empty());
+        assert_eq!(fallback_calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        let (working, _) = flaky(0);
+        let (broken, _) = flaky(usize::MAX);
+        let (fallback, fallback_calls) = flaky(0);
+        assert_eq!(send(vec![broken, working], fallback), ["flaky notification failed: busy"]);
+        assert_eq!(fallback_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn hung_calls_time_out() {
+        let timeout = std::time::Duration::from_millis(20);
+        assert_eq!(with_timeout(timeout, || Ok(1)), Ok(1));
+        let hung = with_timeout(timeout, || {
+            std::thread::sleep(std::time::Duration::from_secs(5));
+            Ok(())
+        });
+        assert_eq!(hung, Err("no answer after 20ms".to_string()));
+    }
+
+    #[test]
+    fn terminal_sequence_is_osc_9_with_a_bell() {
+        let sequence = |notice: &Notice, silent| terminal_sequence(notice, &text(notice), silent);
+        assert_eq!(sequence(&completed(), false), "\x1b]9;pomodoro complete: 25:00 timer finished\x1b\\\x07");
+        assert_eq!(sequence(&completed(), true), "\x1b]9;pomodoro complete: 25:00 timer finished\x1b\\");
+        let milestone = Notice::Milestone { name: "a\x07b", remaining_secs: 60 };
+        assert_eq!(sequence(&milestone, false), "\x1b]9;ab: 1m left\x1b\\");
     }
 
-    if let Err(e) = notification.show() {
-        eprintln!("Failed to send notification: {e}");
+    #[test]
+    fn webhook_payload_describes_the_notice() {
+        let payload = webhook_payload(&completed(), &text(&completed()));
+        assert_eq!(payload["event"], "completed");
+        assert_eq!(payload["phase"], "work");
+        assert_eq!(payload["duration_secs"], 1500);
+        assert_eq!(payload["summary"], "pomodoro complete");
+        let milestone = Notice::Milestone { name: "pomodoro", remaining_secs: 300 };
+        let payload = webhook_payload(&milestone, &text(&milestone));
+        assert_eq!((payload["event"].as_str(), payload["remaining_secs"].as_u64()), (Some("milestone"), Some(300)));
+        assert!(payload.get("body").is_none());
     }
 }
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+use crate::state::RuntimeState;
+
+pub const DEFAULT_FORMAT: &str = "{remaining}";
+const REFRESH: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Where `tik overlay` sends the formatted time.
+pub struct OverlayOptions {
+    pub file: Option<PathBuf>,
+    pub http: Option<String>,
+    pub format: String,
+}
+
+/// Keep the output file (and/or HTTP page) in sync with the running timer
+/// until Ctrl+C. The text is empty while no timer runs.
+pub async fn run(options: OverlayOptions) -> Result<(), String> {
+    if options.file.is_none() && options.http.is_none() {
+        return Err("Nothing to do: pass --file <path> and/or --http <addr>".to_string());
+    }
+    let text = Arc::new(Mutex::new(String::new()));
+
+    if let Some(addr) = &options.http {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| format!("Failed to listen on {addr}: {e}"))?;
+        println!("Overlay page at http://{addr}/ (plain text at /text)");
+        tokio::spawn(serve(listener, Arc::clone(&text)));
+    }
+    if let Some(path) = &options.file {
+        println!("Writing overlay text to {}", path.display());
+    }
+
+    let shutdown = crate::timer::shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut last = None;
+    loop {
+        let current = render_text(&options.format, crate::state::read().as_ref(), Local::now());
+        if last.as_ref() != Some(&current) {
+            if let Some(path) = &options.file
+                && let Err(e) = write_file(path, &current)
+            {
+                eprintln!("Failed to write {}: {e}", path.display());
+            }
+            if let Ok(mut text) = text.lock() {
+                text.clone_from(&current);
+            }
+            last = Some(current);
+        }
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(REFRESH) => {}
+        }
+    }
+
+    if let Some(path) = &options.file {
+        let _ = write_file(path, "");
+    }
+    Ok(())
+}
+
+/// Fill in `{remaining}`, `{name}`, `{phase}`, `{round}` and `{paused}`.
+fn render_text(format: &str, state: Option<&RuntimeState>, now: DateTime<Local>) -> String {
+    let Some(state) = state else {
+        return String::new();
+    };
+    let remaining = crate::duration::Duration::from_secs(state.remaining_at(now)).format_hms();
+    let round = state.round.map(|(current, total)| format!("{current}/{total}")).unwrap_or_default();
+    format
+        .replace("{remaining}", &remaining)
+        .replace("{name}", &state.name)
+        .replace("{phase}", state.phase.label())
+        .replace("{round}", &round)
+        .replace("{paused}", if state.paused { "paused" } else { "" })
+        .trim()
+        .to_string()
+}
+
+/// Replace the file in one step so OBS never reads a half-written value.
+fn write_file(path: &Path, text: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("tik.tmp");
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, path)
+}
+
+async fn serve(listener: TcpListener, text: Arc<Mutex<String>>) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let text = Arc::clone(&text);
+        tokio::spawn(async move {
+            let Ok(request) = crate::webhook::read_request(&mut stream).await else {
+                return;
+            };
+            let current = text.lock().map(|t| t.clone()).unwrap_or_default();
+            let _ = match request.path.as_str() {
+                "/" => crate::webhook::write_response(&mut stream, 200, "text/html; charset=utf-8", PAGE).await,
+                "/text" => crate::webhook::write_response(&mut stream, 200, "text/plain; charset=utf-8", &current).await,
+                _ => crate::webhook::write_response(&mut stream, 404, "text/plain", "Not found").await,
+            };
+        });
+    }
+}
+
+/// Transparent page for an OBS browser source; polls /text once a second.
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { margin: 0; background: transparent; }
+  #time { font: bold 96px system-ui, sans-serif; color: white; text-shadow: 0 0 8px black; }
+</style>
+</head>
+<body>
+<div id="time"></div>
+<script>
+  async function refresh() {
+    try {
+      const res = await fetch("/text", { cache: "no-store" });
+      document.getElementById("time").textContent = await res.text();
+    } catch (e) {}
+  }
+  refresh();
+  setInterval(refresh, 1000);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::TimerContext;
+
+    fn sample() -> RuntimeState {
+        RuntimeState {
+            version: crate::state::SCHEMA_VERSION,
+            pid: 1,
+            name: "pomodoro".to_string(),
+            phase: TimerContext::Work,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 1122,
+            paused: false,
+            updated_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn default_format_is_remaining_time() {
+        let state = sample();
+        assert_eq!(render_text(DEFAULT_FORMAT, Some(&state), state.updated_at), "18:42");
+    }
+
+    #[test]
+    fn format_placeholders() {
+        let state = sample();
+        let text = render_text("{phase} {round}: {remaining} {paused}", Some(&state), state.updated_at);
+        assert_eq!(text, "work 2/4: 18:42");
+    }
+
+    #[test]
+    fn idle_renders_empty() {
+        assert_eq!(render_text(DEFAULT_FORMAT, None, Local::now()), "");
+    }
+}
@@ -0,0 +1,223 @@
+//! `tik plan status`: how much of today's goal is left and how many more
+//! pomodoros fit before the end of the working day.
+
+use chrono::{DateTime, Local, NaiveTime};
+
+use crate::config::{Config, SessionConfig};
+use crate::duration::Duration;
+use crate::log::LogEntry;
+
+/// Phase lengths the projection steps through, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lengths {
+    work: u64,
+    short_break: u64,
+    long_break: u64,
+    rounds: u32,
+}
+
+impl Lengths {
+    fn resolve(session: &SessionConfig, config: &Config) -> Result<Self, String> {
+        let secs = |name: &str| {
+            let spec = config.resolve_preset(name).unwrap_or(name);
+            Duration::parse(spec).map(|d| d.total_secs).map_err(|e| format!("Invalid duration '{name}': {e}"))
+        };
+        Ok(Lengths {
+            work: secs(&session.work)?,
+            short_break: secs(&session.break_preset)?,
+            long_break: secs(&session.long_break)?,
+            rounds: session.rounds.max(1),
+        })
+    }
+}
+
+/// Start and end of each pomodoro from `start` on, with breaks between them,
+/// stopping at `limit` pomodoros or the first one that would end after `until`.
+fn project(start: DateTime<Local>, lengths: Lengths, until: Option<DateTime<Local>>, limit: usize) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let mut slots = Vec::new();
+    let mut at = start;
+    for n in 1..=limit {
+        let end = at + chrono::Duration::seconds(lengths.work as i64);
+        if until.is_some_and(|until| end > until) {
+            break;
+        }
+        slots.push((at, end));
+        let pause = if n.is_multiple_of(lengths.rounds as usize) { lengths.long_break } else { lengths.short_break };
+        at = end + chrono::Duration::seconds(pause as i64);
+    }
+    slots
+}
+
+/// Work logged today, leaving out the session's break phases.
+fn done_today(entries: &[LogEntry], session: &SessionConfig, now: DateTime<Local>) -> u64 {
+    entries
+        .iter()
+        .filter(|e| e.completed_at.date_naive() == now.date_naive())
+        .filter(|e| e.name != session.break_preset && e.name != session.long_break)
+        .map(|e| e.duration_secs)
+        .sum()
+}
+
+/// End of today's working day at `time`, or None once it has passed.
+fn end_of_day(time: &str, now: DateTime<Local>) -> Result<Option<DateTime<Local>>, String> {
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid end_of_day '{time}': expected HH:MM, e.g. \"18:00\""))?;
+    let end = now.date_naive().and_time(time).and_local_timezone(Local).earliest();
+    Ok(end.filter(|end| *end > now))
+}
+
+/// Longest list of projected pomodoros worth printing.
+const MAX_SLOTS: usize = 16;
+
+fn status_lines(
+    entries: &[LogEntry],
+    session: &SessionConfig,
+    lengths: Lengths,
+    goal: Option<u64>,
+    until: Option<Option<DateTime<Local>>>,
+    now: DateTime<Local>,
+    clock_format: &str,
+) -> Vec<String> {
+    let human = |secs: u64| Duration::from_secs(secs).format_compact();
+    let mut lines = Vec::new();
+    let needed = goal.map(|goal| {
+        let done = done_today(entries, session, now);
+        let left = goal.saturating_sub(done);
+        let needed = left.div_ceil(lengths.work) as usize;
+        if left == 0 {
+            lines.push(format!("Today: {} of {} goal done. Goal reached.", human(done), human(goal)));
+        } else {
+            lines.push(format!(
+                "Today: {} of {} goal done, {} to go ({needed} x {}).",
+                human(done),
+                human(goal),
+                human(left),
+                human(lengths.work),
+            ));
+        }
+        needed
+    });
+    if needed == Some(0) {
+        return lines;
+    }
+
+    let clock = |t: DateTime<Local>| t.format(clock_format).to_string();
+    let limit = needed.unwrap_or(MAX_SLOTS).min(MAX_SLOTS);
+    let slots = match until {
+        Some(None) => {
+            lines.push("The working day is over.".to_string());
+            return lines;
+        }
+        Some(Some(until)) => {
+            let fitting = project(now, lengths, Some(until), usize::MAX);
+            lines.push(format!("{} more pomodoro{} fit before {}.", fitting.len(), if fitting.len() == 1 { "" } else { "s" }, clock(until)));
+            project(now, lengths, Some(until), limit)
+        }
+        None => project(now, lengths, None, limit),
+    };
+    for (n, (start, end)) in slots.iter().enumerate() {
+        lines.push(format!("  #{:<3} {} - {}", n + 1, clock(*start), clock(*end)));
+    }
+    if let Some(needed) = needed {
+        match slots.get(needed.saturating_sub(1)) {
+            Some((_, end)) if slots.len() >= needed => lines.push(format!("Goal reached at {} if you start now.", clock(*end))),
+            _ => lines.push(format!("Only {} of the {needed} pomodoros needed fit today.", slots.len())),
+        }
+    }
+    lines
+}
+
+pub fn print_status(config: &Config) -> Result<(), String> {
+    let plan = &config.plan;
+    if plan.daily_goal.is_none() && plan.end_of_day.is_none() {
+        return Err("Nothing to plan: set daily_goal and/or end_of_day in the [plan] config table.".to_string());
+    }
+    let session_name = plan.session.as_deref().unwrap_or("pomodoro");
+    let session = config
+        .resolve_session(session_name)
+        .ok_or_else(|| format!("Unknown session '{session_name}' in [plan]"))?;
+    let lengths = Lengths::resolve(session, config)?;
+    let goal = plan
+        .daily_goal
+        .as_deref()
+        .map(|goal| Duration::parse(goal).map(|d| d.total_secs).map_err(|e| format!("Invalid daily_goal '{goal}': {e}")))
+        .transpose()?;
+    let now = Local::now();
+    let until = plan.end_of_day.as_deref().map(|time| end_of_day(time, now)).transpose()?;
+    let entries = crate::log::read_entries();
+    for line in status_lines(&entries, session, lengths, goal, until, now, &config.clock_format()) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const LENGTHS: Lengths = Lengths { work: 1500, short_break: 300, long_break: 900, rounds: 2 };
+
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 2, h, m, 0).unwrap()
+    }
+
+    fn session() -> SessionConfig {
+        SessionConfig {
+            work: "pomodoro".to_string(),
+            break_preset: "break".to_string(),
+            long_break: "long-break".to_string(),
+            rounds: 2,
+        }
+    }
+
+    fn logged(name: &str, secs: u64, completed_at: DateTime<Local>) -> LogEntry {
+        LogEntry { name: name.to_string(), duration_secs: secs, completed_at, ..Default::default() }
+    }
+
+    #[test]
+    fn projection_takes_long_breaks_and_stops_at_end_of_day() {
+        let slots = project(at(14, 0), LENGTHS, Some(at(15, 40)), 10);
+        let ends: Vec<_> = slots.iter().map(|(_, end)| *end).collect();
+        // 14:00-14:25, break, 14:30-14:55, long break, 15:10-15:35
+        assert_eq!(ends, vec![at(14, 25), at(14, 55), at(15, 35)]);
+        assert_eq!(project(at(14, 0), LENGTHS, None, 2).len(), 2);
+    }
+
+    #[test]
+    fn breaks_do_not_count_toward_the_goal() {
+        let entries = vec![
+            logged("pomodoro", 1500, at(9, 25)),
+            logged("break", 300, at(9, 30)),
+            logged("pomodoro", 1500, at(9, 55) - chrono::Duration::days(1)),
+        ];
+        assert_eq!(done_today(&entries, &session(), at(12, 0)), 1500);
+    }
+
+    #[test]
+    fn status_with_goal_and_end_of_day() {
+        let entries = vec![logged("pomodoro", 3000, at(10, 0))];
+        let lines = status_lines(&entries, &session(), LENGTHS, Some(7200), Some(Some(at(15, 40))), at(14, 0), "%H:%M");
+        assert_eq!(lines, vec![
+            "Today: 50m of 2h00m goal done, 1h10m to go (3 x 25m).",
+            "3 more pomodoros fit before 15:40.",
+            "  #1   14:00 - 14:25",
+            "  #2   14:30 - 14:55",
+            "  #3   15:10 - 15:35",
+            "Goal reached at 15:35 if you start now.",
+        ]);
+    }
+
+    #[test]
+    fn status_when_goal_does_not_fit() {
+        let lines = status_lines(&[], &session(), LENGTHS, Some(7200), Some(Some(at(15, 0))), at(14, 0), "%H:%M");
+        assert_eq!(lines.last().unwrap(), "Only 2 of the 5 pomodoros needed fit today.");
+    }
+
+    #[test]
+    fn end_of_day_parsing() {
+        assert_eq!(end_of_day("18:00", at(14, 0)).unwrap(), Some(at(18, 0)));
+        assert_eq!(end_of_day("12:00", at(14, 0)).unwrap(), None);
+        assert!(end_of_day("6pm", at(14, 0)).is_err());
+    }
+}
@@ -1,357 +1,617 @@
 use crossterm::{
     cursor,
     execute,
-    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
-    terminal::{self, ClearType},
+    style::{Color, ResetColor},
+    terminal,
 };
-use std::io::{self, Write};
-
+use crate::frame::{self, BarCache, TimeText};
+use crate::surface::{Style, Surface, Terminal};
+use crate::text;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, IsTerminal};
+use std::sync::{Mutex, PoisonError};
+
+#[derive(Clone, Copy, Hash)]
 pub struct DrawParams<'a> {
     pub remaining_secs: u64,
     pub total_secs: u64,
     pub elapsed_secs: u64,
+    pub elapsed_millis: u64,  // moves the bar between whole seconds
+    pub remaining_tenths: Option<u64>,  // shown as mm:ss.t instead of remaining_secs
     pub paused: bool,
     pub title: Option<&'a str>,
     pub round_info: Option<(u32, u32)>,  // (current_round, total_rounds)
     pub context: crate::timer::TimerContext,
     pub todo: Option<&'a crate::todo::TodoSnapshot>,
+    pub ack_prompt: Option<&'a str>,  // shown once the countdown completes
+    pub task_input: Option<&'a str>,  // task being typed after pressing `n`
+    pub upcoming: Option<&'a str>,  // "next: ..." strip of the session's coming phases
+    pub session_left: Option<&'a str>,  // when the whole session ends
+    pub break_debt: Option<&'a str>,  // break time skipped today
+    pub footer: Option<&'a str>,  // the user's `[footer]` line
+    pub keys: bool,  // false with --no-input: nothing to hint at
+    pub flow_available: bool,
+    pub colors: PhaseColors,
+    pub flash: bool,  // completion animation frame: time shown inverted in the accent color
+}
+
+/// Per-phase colors from the `[theme]` config. The accent fills the progress
+/// bar until the yellow/red warning thresholds take over.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub struct PhaseColors {
+    pub accent: Color,
+    pub background: Option<Color>,
+    pub warning: Threshold,  // yellow from here
+    pub critical: Threshold,  // red from here
+}
+
+/// When the bar changes color: a share of the timer's length left ("20%")
+/// or a fixed time left ("60s").
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum Threshold {
+    Percent(u8),
+    Secs(u64),
+}
+
+impl Threshold {
+    pub const WARNING: Threshold = Threshold::Percent(20);
+    pub const CRITICAL: Threshold = Threshold::Secs(60);
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if let Some(percent) = value.strip_suffix('%') {
+            return match percent.trim().parse::<u8>() {
+                Ok(percent) if percent <= 100 => Ok(Threshold::Percent(percent)),
+                _ => Err(format!("'{value}': a percentage must be 0-100%")),
+            };
+        }
+        crate::duration::Duration::parse(value)
+            .map(|d| Threshold::Secs(d.total_secs))
+            .map_err(|e| format!("'{value}': expected a percentage (\"20%\") or a duration (\"60s\"): {e}"))
+    }
+
+    fn reached(self, remaining_secs: u64, total_secs: u64) -> bool {
+        match self {
+            Threshold::Percent(percent) => remaining_secs * 100 <= total_secs * u64::from(percent),
+            Threshold::Secs(secs) => remaining_secs <= secs,
+        }
+    }
+}
+
+/// Bar color: phase accent, then yellow and red as the thresholds are reached.
+fn bar_color(params: &DrawParams) -> Color {
+    let colors = params.colors;
+    if colors.critical.reached(params.remaining_secs, params.total_secs) {
+        Color::Red
+    } else if colors.warning.reached(params.remaining_secs, params.total_secs) {
+        Color::Yellow
+    } else {
+        colors.accent
+    }
+}
+
+/// Parse a color name (`red`, `dark_cyan`, ...) or `#rrggbb` hex value.
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let n = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb { r: (n >> 16) as u8, g: (n >> 8) as u8, b: n as u8 });
+    }
+    Color::try_from(value).ok()
+}
+
+/// One line of the `tik cook` screen.
+pub struct AlarmRow<'a> {
+    pub label: &'a str,
+    pub remaining_secs: u64,  // 0 once it has gone off
+}
+
+/// Smallest terminal the timer layout fits in. Below it, centered lines would
+/// overlap and column math would underflow.
+pub const MIN_COLS: u16 = 40;
+pub const MIN_ROWS: u16 = 10;
+
+/// Height of the graphical progress ring, and the rows it needs with the
+/// title and round above it and the hints below.
+#[cfg(feature = "graphics")]
+const RING_ROWS: u16 = 9;
+#[cfg(feature = "graphics")]
+const RING_MIN_ROWS: u16 = 16;
+
+/// Draw a "terminal too small" notice instead of the layout when the surface
+/// is below the minimum size. Returns whether it did; the next draw after a
+/// resize picks the layout back up.
+pub fn draw_too_small(surface: &mut impl Surface, cols: u16, rows: u16) -> io::Result<bool> {
+    if cols >= MIN_COLS && rows >= MIN_ROWS {
+        return Ok(false);
+    }
+    let lines = too_small_lines(cols);
+    let top = rows.saturating_sub(lines.len() as u16) / 2;
+    for (i, line) in lines.iter().enumerate() {
+        surface.print(text::center(line, cols), top + i as u16, line, Style::fg(Color::DarkGrey))?;
+    }
+    Ok(true)
+}
+
+/// The notice on one line if it fits, else split in two and cut to the width.
+fn too_small_lines(cols: u16) -> Vec<String> {
+    let need = format!("need {MIN_COLS}x{MIN_ROWS}");
+    let full = format!("terminal too small ({need})");
+    if full.len() <= cols as usize {
+        return vec![full];
+    }
+    ["terminal too small".to_string(), need]
+        .into_iter()
+        .map(|line| line.chars().take(cols as usize).collect())
+        .collect()
+}
+
+/// Whether a terminal of this type can't draw the full screen: `dumb`
+/// (Emacs shell buffers), or on Unix no type at all (bare serial consoles).
+pub fn is_dumb(term: Option<&str>) -> bool {
+    match term.map(str::trim) {
+        Some("dumb") => true,
+        Some("") | None => cfg!(unix),
+        Some(_) => false,
+    }
+}
+
+/// Whether stdout is a terminal that can't draw the full screen, so tik
+/// prints its plain `--a11y` lines there instead.
+pub fn dumb_terminal() -> bool {
+    io::stdout().is_terminal() && is_dumb(std::env::var("TERM").ok().as_deref())
+}
+
+/// The alternate screen, plus raw mode when keys are read, held for a whole
+/// run. A session enters it once, so the round header, countdown and prompts
+/// replace each other in place rather than dropping back to the shell between
+/// phases. Dropping it restores the terminal.
+pub struct Screen {
+    renderer: Renderer,
+}
+
+impl Screen {
+    pub fn enter(raw_mode: bool) -> io::Result<Self> {
+        if raw_mode {
+            terminal::enable_raw_mode()?;
+        }
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        crate::messages::hold();
+        Ok(Screen { renderer: Renderer::new() })
+    }
+
+    /// Draw a graphical progress ring in place of the bar when the terminal
+    /// can show one.
+    #[cfg(feature = "graphics")]
+    pub fn with_graphics(mut self, setting: crate::config::Graphics) -> Self {
+        self.renderer.ring = crate::ring::detect(setting);
+        self
+    }
+
+    /// Without the `graphics` feature there is only the text bar.
+    #[cfg(not(feature = "graphics"))]
+    pub fn with_graphics(self, _setting: crate::config::Graphics) -> Self {
+        self
+    }
+
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        #[cfg(feature = "graphics")]
+        if let Some(protocol) = self.renderer.ring {
+            let _ = crate::ring::clear(&mut io::stdout(), protocol);
+        }
+        let _ = execute!(io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        for message in crate::messages::release() {
+            eprintln!("{message}");
+        }
+    }
 }
 
 pub struct Renderer {
     bar_width: u16,
+    cache: Mutex<FrameCache>,
+    #[cfg(feature = "graphics")]
+    ring: Option<crate::ring::Protocol>,
+}
+
+/// Text kept between frames, plus a hash of the last frame drawn so an
+/// unchanged frame (most ticks between seconds) isn't redrawn at all.
+struct FrameCache {
+    bar: BarCache,
+    remaining: TimeText,
+    elapsed: TimeText,
+    last_frame: Option<u64>,
 }
 
 impl Renderer {
-    pub fn new() -> Self {
-        Renderer { bar_width: 30 }
+    fn new() -> Self {
+        let bar_width = 30;
+        Renderer {
+            bar_width,
+            cache: Mutex::new(FrameCache {
+                bar: BarCache::new(bar_width),
+                remaining: TimeText::default(),
+                elapsed: TimeText::default(),
+                last_frame: None,
+            }),
+            #[cfg(feature = "graphics")]
+            ring: None,
+        }
     }
 
-    pub fn setup(&self) -> io::Result<()> {
-        terminal::enable_raw_mode()?;
-        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
-        Ok(())
+    /// Forget the last countdown frame after drawing something else, so the
+    /// next `draw` repaints even if the countdown hasn't changed.
+    fn invalidate(&self, surface: &mut impl Surface) {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner).last_frame = None;
+        self.clear_ring(surface);
     }
 
-    pub fn teardown(&self) -> io::Result<()> {
-        execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
-        terminal::disable_raw_mode()?;
-        Ok(())
+    /// Take the progress ring off the screen; kitty keeps images through a
+    /// screen clear.
+    #[cfg(feature = "graphics")]
+    fn clear_ring(&self, surface: &mut impl Surface) {
+        if let Some(protocol) = self.ring {
+            let mut sequence = Vec::new();
+            let _ = crate::ring::clear(&mut sequence, protocol);
+            let _ = surface.graphics(0, 0, &sequence);
+        }
+    }
+
+    #[cfg(not(feature = "graphics"))]
+    fn clear_ring(&self, _surface: &mut impl Surface) {}
+
+    /// The ring's protocol when the ring is on and the terminal has room for
+    /// it and the lines around it.
+    #[cfg(feature = "graphics")]
+    fn ring_fits(&self, rows: u16) -> bool {
+        self.ring.is_some() && rows >= RING_MIN_ROWS
+    }
+
+    #[cfg(not(feature = "graphics"))]
+    fn ring_fits(&self, _rows: u16) -> bool {
+        false
+    }
+
+    /// The ring centered on `mid_row`, filled like the bar.
+    #[cfg(feature = "graphics")]
+    fn draw_ring(&self, surface: &mut impl Surface, params: &DrawParams, cols: u16, mid_row: u16) -> io::Result<()> {
+        let Some(protocol) = self.ring else {
+            return Ok(());
+        };
+        let width = crate::ring::width_in_cols(RING_ROWS);
+        let mut sequence = Vec::new();
+        crate::ring::draw(&mut sequence, protocol, width, RING_ROWS, bar_progress(params), bar_color(params))?;
+        surface.graphics(cols.saturating_sub(width) / 2, mid_row - RING_ROWS / 2, &sequence)
+    }
+
+    /// The round header shown before each session phase: an optional title,
+    /// "Round 2/4", the phase and its length, and the key hints when keys are read.
+    pub fn draw_header(
+        &self,
+        round: u32,
+        total: u32,
+        name: &str,
+        duration: &str,
+        hints: Option<&str>,
+        title: Option<&str>,
+    ) -> io::Result<()> {
+        self.draw_header_on(&mut Terminal::new(), round, total, name, duration, hints, title)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_header_on(
+        &self,
+        surface: &mut impl Surface,
+        round: u32,
+        total: u32,
+        name: &str,
+        duration: &str,
+        hints: Option<&str>,
+        title: Option<&str>,
+    ) -> io::Result<()> {
+        self.invalidate(surface);
+        let (cols, rows) = surface.size().unwrap_or((80, 24));
+        let mid_row = rows / 2;
+        // The last countdown's phase background doesn't carry over
+        surface.clear(Color::Reset)?;
+        if draw_too_small(surface, cols, rows)? {
+            return surface.flush();
+        }
+
+        if let Some(title) = title {
+            let title = text::truncate(title, cols);
+            surface.print(text::center(&title, cols), mid_row.saturating_sub(3), &title, Style::fg(Color::White).bold())?;
+        }
+
+        let line1 = format!("Round {round}/{total}");
+        let line2 = format!("{name} ({duration})");
+        let line2 = text::truncate(&line2, cols);
+        surface.print(text::center(&line1, cols), mid_row.saturating_sub(1), &line1, Style::fg(Color::Cyan).bold())?;
+        surface.print(text::center(&line2, cols), mid_row + 1, &line2, Style::fg(Color::DarkGrey))?;
+        if let Some(hints) = hints {
+            surface.print(text::center(hints, cols), mid_row + 3, hints, Style::fg(Color::DarkGrey))?;
+        }
+        surface.flush()
+    }
+
+    /// The `tik cook` alarms as an aligned list: label and time left, red in
+    /// the last minute and "done" once gone off.
+    pub fn draw_alarms(&self, rows: &[AlarmRow], paused: bool, keys: bool) -> io::Result<()> {
+        self.draw_alarms_on(&mut Terminal::new(), rows, paused, keys)
+    }
+
+    fn draw_alarms_on(&self, surface: &mut impl Surface, rows: &[AlarmRow], paused: bool, keys: bool) -> io::Result<()> {
+        self.invalidate(surface);
+        let (cols, term_rows) = surface.size().unwrap_or((80, 24));
+        surface.clear(Color::Reset)?;
+        if draw_too_small(surface, cols, term_rows)? {
+            return surface.flush();
+        }
+
+        let label_width = rows.iter().map(|r| text::width(r.label)).max().unwrap_or(0).min(cols / 2);
+        let lines: Vec<(String, Color)> = rows
+            .iter()
+            .map(|row| {
+                let label = text::truncate(row.label, label_width);
+                let pad = " ".repeat(label_width.saturating_sub(text::width(&label)) as usize);
+                let (time, color) = match row.remaining_secs {
+                    0 => ("done".to_string(), Color::Green),
+                    secs => (crate::duration::Duration::from_secs(secs).format_hms(), if secs <= 60 { Color::Red } else { Color::Reset }),
+                };
+                (format!("{label}{pad}  {time:>8}"), color)
+            })
+            .collect();
+        let block_width = lines.iter().map(|(line, _)| text::width(line)).max().unwrap_or(0);
+        let left = cols.saturating_sub(block_width) / 2;
+        let top = term_rows.saturating_sub(lines.len() as u16 + 2) / 2;
+
+        let heading = if paused { "PAUSED" } else { "Cooking" };
+        surface.print(text::center(heading, cols), top, heading, Style::PLAIN.bold())?;
+        for (i, (line, color)) in lines.iter().enumerate() {
+            surface.print(left, top + 2 + i as u16, line, Style::fg(*color))?;
+        }
+        if keys {
+            let hints = "[space] pause  [x] stop";
+            surface.print(text::center(hints, cols), term_rows.saturating_sub(2), hints, Style::fg(Color::DarkGrey))?;
+        }
+        surface.flush()
+    }
+
+    /// A bold question over its key hints, centered, for prompts between phases.
+    pub fn draw_prompt(&self, question: &str, hints: &str) -> io::Result<()> {
+        self.draw_prompt_on(&mut Terminal::new(), question, hints)
+    }
+
+    fn draw_prompt_on(&self, surface: &mut impl Surface, question: &str, hints: &str) -> io::Result<()> {
+        self.invalidate(surface);
+        let (cols, rows) = surface.size().unwrap_or((80, 24));
+        let mid_row = rows / 2;
+        surface.clear(Color::Reset)?;
+        if draw_too_small(surface, cols, rows)? {
+            return surface.flush();
+        }
+        let question = text::truncate(question, cols);
+        let hints = text::truncate(hints, cols);
+        surface.print(text::center(&question, cols), mid_row.saturating_sub(1), &question, Style::PLAIN.bold())?;
+        surface.print(text::center(&hints, cols), mid_row + 1, &hints, Style::fg(Color::DarkGrey))?;
+        surface.flush()
     }
 
     pub fn draw(&self, params: &DrawParams) -> io::Result<()> {
-        let (cols, rows) = terminal::size()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, terminal::Clear(ClearType::All))?;
+        self.draw_on(&mut Terminal::new(), params)
+    }
 
-        if let Some(todo_snap) = params.todo {
-            self.draw_with_sidebar(&mut stdout, params, todo_snap, cols, rows)?;
+    fn draw_on(&self, surface: &mut impl Surface, params: &DrawParams) -> io::Result<()> {
+        let (cols, rows) = surface.size()?;
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        // The bar's fill stands in for the exact milliseconds, which would make
+        // every frame look new
+        let bar_steps = cache.bar.steps(bar_progress(params));
+        let mut hasher = DefaultHasher::new();
+        (DrawParams { elapsed_millis: 0, ..*params }, bar_steps, cols, rows, crate::messages::current()).hash(&mut hasher);
+        let frame = hasher.finish();
+        if cache.last_frame == Some(frame) {
+            return Ok(());
+        }
+        cache.last_frame = Some(frame);
+
+        self.clear_ring(surface);
+        // Text only resets the foreground, so the phase background persists across the frame
+        surface.clear(params.colors.background.unwrap_or(Color::Reset))?;
+
+        if draw_too_small(surface, cols, rows)? {
+            // Nothing else to draw until the terminal grows
+        } else if let Some(todo_snap) = params.todo {
+            self.draw_with_sidebar(surface, &mut cache, params, todo_snap, cols, rows)?;
         } else {
-            self.draw_centered(&mut stdout, params, cols, rows)?;
+            self.draw_centered(surface, &mut cache, params, cols, rows)?;
         }
 
-        stdout.flush()?;
-        Ok(())
+        surface.flush()
     }
 
-    fn draw_centered(&self, stdout: &mut io::Stdout, params: &DrawParams, cols: u16, rows: u16) -> io::Result<()> {
-        let remaining_secs = params.remaining_secs;
-        let total_secs = params.total_secs;
-        let elapsed_secs = params.elapsed_secs;
+    fn draw_centered(&self, surface: &mut impl Surface, cache: &mut FrameCache, params: &DrawParams, cols: u16, rows: u16) -> io::Result<()> {
         let paused = params.paused;
+        draw_warning(surface, cols)?;
 
         let mid_row = rows / 2;
 
-        let remaining_str = format_time(remaining_secs);
-        let elapsed_str = format_time(elapsed_secs);
-        let progress = if total_secs > 0 {
-            1.0 - (remaining_secs as f64 / total_secs as f64)
-        } else {
-            1.0
-        };
+        let remaining_str = remaining_text(&mut cache.remaining, params);
+        let elapsed_str = cache.elapsed.get(params.elapsed_secs as i64);
+        let bar_color = bar_color(params);
 
-        let filled = (progress * self.bar_width as f64) as u16;
-        let empty = self.bar_width - filled;
+        let (bar_filled, bar_empty) = cache.bar.get(bar_progress(params));
 
-        // Color: green -> yellow (last 20%) -> red (last 60s)
-        let bar_color = if remaining_secs <= 60 {
-            Color::Red
-        } else if remaining_secs as f64 <= total_secs as f64 * 0.2 {
-            Color::Yellow
+        // A progress ring takes the bar's place with the time inside it, and
+        // the lines around move out to make room. It goes first, as sixel
+        // images cover text drawn before them
+        let ring = self.ring_fits(rows);
+        let (title_row, round_row, time_row, label_row, hints_row) = if ring {
+            (mid_row - 6, mid_row - 5, mid_row, mid_row + 5, mid_row + 6)
         } else {
-            Color::Green
+            (mid_row.saturating_sub(4), mid_row.saturating_sub(3), mid_row.saturating_sub(1), mid_row + 3, mid_row + 5)
         };
-
-        // Build progress bar string
-        let bar_filled: String = "\u{2588}".repeat(filled as usize);
-        let bar_empty: String = "\u{2591}".repeat(empty as usize);
+        #[cfg(feature = "graphics")]
+        if ring {
+            self.draw_ring(surface, params, cols, mid_row)?;
+        }
 
         // Title -- white, bold, centered
         if let Some(title) = params.title {
-            let title_row = mid_row.saturating_sub(4);
-            let title_col = cols.saturating_sub(title.len() as u16) / 2;
-            execute!(
-                stdout,
-                cursor::MoveTo(title_col, title_row),
-                SetForegroundColor(Color::White),
-                SetAttribute(Attribute::Bold),
-                Print(title),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+            let title = text::truncate(title, cols);
+            surface.print(text::center(&title, cols), title_row, &title, Style::fg(Color::White).bold())?;
         }
 
         // Round info -- cyan, bold, centered
         if let Some((current, total)) = params.round_info {
             let round_str = format!("Round {current}/{total}");
-            let round_col = cols.saturating_sub(round_str.len() as u16) / 2;
-            let round_row = mid_row.saturating_sub(3);
-            execute!(
-                stdout,
-                cursor::MoveTo(round_col, round_row),
-                SetForegroundColor(Color::Cyan),
-                SetAttribute(Attribute::Bold),
-                Print(&round_str),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+            surface.print(text::center(&round_str, cols), round_row, &round_str, Style::fg(Color::Cyan).bold())?;
         }
 
         // Remaining time -- bold, centered
-        let time_col = cols.saturating_sub(remaining_str.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(time_col, mid_row.saturating_sub(1)),
-            SetAttribute(Attribute::Bold),
-            Print(&remaining_str),
-            SetAttribute(Attribute::Reset),
-        )?;
+        print_time(surface, text::center(remaining_str, cols), time_row, remaining_str, params)?;
 
         // Progress bar -- centered, printed as single strings
-        let bar_col = cols.saturating_sub(self.bar_width) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(bar_col, mid_row + 1),
-            SetForegroundColor(bar_color),
-            Print(&bar_filled),
-            SetForegroundColor(Color::DarkGrey),
-            Print(&bar_empty),
-            ResetColor,
-        )?;
-
-        // Elapsed or "PAUSED" -- dim, centered
-        let label = if paused {
+        if !ring {
+            let bar_col = cols.saturating_sub(self.bar_width) / 2;
+            surface.print(bar_col, mid_row + 1, bar_filled, Style::fg(bar_color))?;
+            surface.print(bar_col + text::width(bar_filled), mid_row + 1, bar_empty, Style::fg(Color::DarkGrey))?;
+        }
+
+        // Elapsed, "PAUSED" or ack prompt -- dim, centered
+        let label = if let Some(prompt) = params.ack_prompt {
+            prompt.to_string()
+        } else if let Some(input) = params.task_input {
+            format!("Task: {input}_")
+        } else if paused {
             "PAUSED".to_string()
         } else {
             format!("{elapsed_str} elapsed")
         };
-        let label_col = cols.saturating_sub(label.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(label_col, mid_row + 3),
-            SetForegroundColor(Color::DarkGrey),
-            Print(&label),
-            ResetColor,
-        )?;
+        let label = text::truncate(&label, cols);
+        surface.print(text::center(&label, cols), label_row, &label, Style::fg(Color::DarkGrey))?;
 
         // Hint bar -- dark grey, centered
-        let is_last_round = params.round_info.is_some_and(|(cur, total)| cur >= total);
-        let hints = match params.context {
-            crate::timer::TimerContext::Standalone => {
-                "[space] pause  [s] skip  [x] stop".to_string()
-            }
-            _ if is_last_round => {
-                "[space] pause  [a/d] +/-round  [x] stop".to_string()
-            }
-            _ => {
-                "[space] pause  [s] skip  [a/d] +/-round  [x] stop".to_string()
-            }
-        };
-        let hints_col = cols.saturating_sub(hints.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(hints_col, mid_row + 5),
-            SetForegroundColor(Color::DarkGrey),
-            Print(hints),
-            ResetColor,
-        )?;
+        let hints = timer_hints(params, false);
+        let hints = text::truncate(&hints, cols);
+        surface.print(text::center(&hints, cols), hints_row, &hints, Style::fg(Color::DarkGrey))?;
+
+        draw_footer(surface, params, cols, rows, hints_row)?;
 
         Ok(())
     }
 
-    fn draw_with_sidebar(&self, stdout: &mut io::Stdout, params: &DrawParams, todo: &crate::todo::TodoSnapshot, cols: u16, rows: u16) -> io::Result<()> {
+    fn draw_with_sidebar(
+        &self,
+        surface: &mut impl Surface,
+        cache: &mut FrameCache,
+        params: &DrawParams,
+        todo: &crate::todo::TodoSnapshot,
+        cols: u16,
+        rows: u16,
+    ) -> io::Result<()> {
         // Fall back to centered if terminal too narrow
         if cols < 60 {
-            return self.draw_centered(stdout, params, cols, rows);
+            return self.draw_centered(surface, cache, params, cols, rows);
         }
 
         let sidebar_width: u16 = 32;
         let separator_col = cols.saturating_sub(sidebar_width);
         let left_width = separator_col.saturating_sub(1);
         let mid_row = rows / 2;
+        draw_warning(surface, left_width)?;
 
         // --- Left side: timer (centered within left_width) ---
 
         // Current task above title (first non-done item)
-        if let Some((_, text, _)) = todo.items.iter().find(|(_, _, done)| !done) {
-            let label = format!("> {text}");
-            let truncated = if label.len() > left_width as usize - 2 {
-                format!("{}...", &label[..left_width as usize - 5])
-            } else {
-                label
-            };
-            let col = left_width.saturating_sub(truncated.len() as u16) / 2;
-            execute!(
-                stdout,
-                cursor::MoveTo(col, mid_row.saturating_sub(5)),
-                SetForegroundColor(Color::White),
-                SetAttribute(Attribute::Bold),
-                Print(&truncated),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+        if let Some((_, task, _)) = todo.items.iter().find(|(_, _, done)| !done) {
+            let label = format!("> {task}");
+            let truncated = text::truncate(&label, left_width.saturating_sub(2));
+            let col = text::center(&truncated, left_width);
+            surface.print(col, mid_row.saturating_sub(5), &truncated, Style::fg(Color::White).bold())?;
         }
 
         // Title (optional)
         if let Some(title) = params.title {
-            let col = left_width.saturating_sub(title.len() as u16) / 2;
-            execute!(
-                stdout,
-                cursor::MoveTo(col, mid_row.saturating_sub(4)),
-                SetForegroundColor(Color::White),
-                SetAttribute(Attribute::Bold),
-                Print(title),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+            let title = text::truncate(title, left_width);
+            let col = text::center(&title, left_width);
+            surface.print(col, mid_row.saturating_sub(4), &title, Style::fg(Color::White).bold())?;
         }
 
         // Round info
         if let Some((current, total)) = params.round_info {
             let round_str = format!("Round {current}/{total}");
-            let col = left_width.saturating_sub(round_str.len() as u16) / 2;
-            execute!(
-                stdout,
-                cursor::MoveTo(col, mid_row.saturating_sub(3)),
-                SetForegroundColor(Color::Cyan),
-                SetAttribute(Attribute::Bold),
-                Print(&round_str),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+            let col = text::center(&round_str, left_width);
+            surface.print(col, mid_row.saturating_sub(3), &round_str, Style::fg(Color::Cyan).bold())?;
         }
 
         // Remaining time
-        let remaining_str = format_time(params.remaining_secs);
-        let time_col = left_width.saturating_sub(remaining_str.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(time_col, mid_row.saturating_sub(1)),
-            SetAttribute(Attribute::Bold),
-            Print(&remaining_str),
-            SetAttribute(Attribute::Reset),
-        )?;
+        let remaining_str = remaining_text(&mut cache.remaining, params);
+        let time_col = text::center(remaining_str, left_width);
+        print_time(surface, time_col, mid_row.saturating_sub(1), remaining_str, params)?;
 
         // Progress bar
-        let progress = if params.total_secs > 0 {
-            1.0 - (params.remaining_secs as f64 / params.total_secs as f64)
-        } else { 1.0 };
-        let filled = (progress * self.bar_width as f64) as u16;
-        let empty = self.bar_width - filled;
-        let bar_color = if params.remaining_secs <= 60 {
-            Color::Red
-        } else if params.remaining_secs as f64 <= params.total_secs as f64 * 0.2 {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
-        let bar_filled: String = "\u{2588}".repeat(filled as usize);
-        let bar_empty: String = "\u{2591}".repeat(empty as usize);
+        let bar_color = bar_color(params);
+        let (bar_filled, bar_empty) = cache.bar.get(bar_progress(params));
         let bar_col = left_width.saturating_sub(self.bar_width) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(bar_col, mid_row + 1),
-            SetForegroundColor(bar_color),
-            Print(&bar_filled),
-            SetForegroundColor(Color::DarkGrey),
-            Print(&bar_empty),
-            ResetColor,
-        )?;
+        surface.print(bar_col, mid_row + 1, bar_filled, Style::fg(bar_color))?;
+        surface.print(bar_col + text::width(bar_filled), mid_row + 1, bar_empty, Style::fg(Color::DarkGrey))?;
 
         // Elapsed / PAUSED
-        let elapsed_str = format_time(params.elapsed_secs);
-        let label = if params.paused { "PAUSED".to_string() } else { format!("{elapsed_str} elapsed") };
-        let label_col = left_width.saturating_sub(label.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(label_col, mid_row + 3),
-            SetForegroundColor(Color::DarkGrey),
-            Print(&label),
-            ResetColor,
-        )?;
+        let elapsed_str = cache.elapsed.get(params.elapsed_secs as i64);
+        let label = if let Some(prompt) = params.ack_prompt {
+            prompt.to_string()
+        } else if let Some(input) = params.task_input {
+            format!("Task: {input}_")
+        } else if params.paused {
+            "PAUSED".to_string()
+        } else {
+            format!("{elapsed_str} elapsed")
+        };
+        let label = text::truncate(&label, left_width);
+        let label_col = text::center(&label, left_width);
+        surface.print(label_col, mid_row + 3, &label, Style::fg(Color::DarkGrey))?;
 
         // Hint bar -- changes based on focus mode
-        let hints = if todo.focus {
+        let hints = if params.ack_prompt.is_some() {
+            String::new()
+        } else if todo.focus {
             "[tab] timer  [\u{2191}\u{2193}] select  [enter] done  [S-\u{2191}\u{2193}] move".to_string()
         } else {
-            let is_last_round = params.round_info.is_some_and(|(cur, total)| cur >= total);
-            match params.context {
-                crate::timer::TimerContext::Standalone => {
-                    "[space] pause  [s] skip  [tab] tasks  [x] stop".to_string()
-                }
-                _ if is_last_round => {
-                    "[space] pause  [a/d] +/-round  [tab] tasks  [x] stop".to_string()
-                }
-                _ => {
-                    "[space] pause  [s] skip  [a/d] +/-round  [tab] tasks  [x] stop".to_string()
-                }
-            }
+            timer_hints(params, true)
         };
-        let hints_col = left_width.saturating_sub(hints.len() as u16) / 2;
-        execute!(
-            stdout,
-            cursor::MoveTo(hints_col, mid_row + 5),
-            SetForegroundColor(Color::DarkGrey),
-            Print(&hints),
-            ResetColor,
-        )?;
+        let hints = text::truncate(&hints, left_width);
+        let hints_col = text::center(&hints, left_width);
+        surface.print(hints_col, mid_row + 5, &hints, Style::fg(Color::DarkGrey))?;
+        draw_footer(surface, params, left_width, rows, mid_row + 5)?;
 
         // --- Vertical separator ---
         for row in 0..rows {
-            execute!(
-                stdout,
-                cursor::MoveTo(separator_col, row),
-                SetForegroundColor(Color::DarkGrey),
-                Print("\u{2502}"),
-                ResetColor,
-            )?;
+            surface.print(separator_col, row, "\u{2502}", Style::fg(Color::DarkGrey))?;
         }
 
         // --- Right side: todo list ---
         let right_start = separator_col + 2;
-        let max_text_width = (sidebar_width - 4) as usize;
-
-        execute!(
-            stdout,
-            cursor::MoveTo(right_start, 1),
-            SetForegroundColor(Color::White),
-            SetAttribute(Attribute::Bold),
-            Print("Tasks:"),
-            SetAttribute(Attribute::Reset),
-            ResetColor,
-        )?;
+        let max_text_width = sidebar_width - 4;
+
+        surface.print(right_start, 1, "Tasks:", Style::fg(Color::White).bold())?;
 
         let first_pending_idx = todo.items.iter().position(|(_, _, done)| !done);
 
-        for (i, (_, text, done)) in todo.items.iter().enumerate() {
+        for (i, (_, task, done)) in todo.items.iter().enumerate() {
             let row = 3 + i as u16;
             if row >= rows - 1 { break; } // don't overflow terminal
 
             let is_selected = todo.focus && i == todo.selected_index;
-            let truncated = if text.len() > max_text_width {
-                format!("{}...", &text[..max_text_width - 3])
-            } else {
-                text.clone()
-            };
+            let truncated = text::truncate(task, max_text_width);
 
             // Determine prefix and color
             let (prefix, color) = if *done {
@@ -362,37 +622,327 @@ impl Renderer {
                 ("  ", Color::Grey) // other pending tasks
             };
 
-            let highlight_color = if is_selected { Color::Cyan } else { color };
-
-            execute!(stdout, cursor::MoveTo(right_start, row), SetForegroundColor(highlight_color))?;
-
+            let mut style = Style::fg(if is_selected { Color::Cyan } else { color });
             if is_selected {
-                execute!(stdout, SetAttribute(Attribute::Bold))?;
+                style = style.bold();
             }
             if *done {
-                execute!(stdout, SetAttribute(Attribute::CrossedOut))?;
+                style = style.crossed_out();
             }
-
-            execute!(
-                stdout,
-                Print(prefix),
-                Print(&truncated),
-                SetAttribute(Attribute::Reset),
-                ResetColor,
-            )?;
+            surface.print(right_start, row, &format!("{prefix}{truncated}"), style)?;
         }
 
         Ok(())
     }
 }
 
-fn format_time(secs: u64) -> String {
-    let h = secs / 3600;
-    let m = (secs % 3600) / 60;
-    let s = secs % 60;
-    if h > 0 {
-        format!("{h}:{m:02}:{s:02}")
+/// The newest held warning on the top row, if one came in recently.
+fn draw_warning(surface: &mut impl Surface, width: u16) -> io::Result<()> {
+    let Some(warning) = crate::messages::current() else {
+        return Ok(());
+    };
+    let warning = text::truncate(&warning, width);
+    surface.print(text::center(&warning, width), 0, &warning, Style::fg(Color::Yellow))
+}
+
+/// Session lines on the bottom rows: the "next: ..." strip last, the session
+/// end time and break debt above it. Lines that would run into the hints are
+/// left out.
+fn draw_footer(surface: &mut impl Surface, params: &DrawParams, width: u16, rows: u16, last_row: u16) -> io::Result<()> {
+    let lines = [params.footer, params.upcoming, params.session_left, params.break_debt];
+    for (offset, line) in lines.into_iter().flatten().enumerate() {
+        let row = rows.saturating_sub(1 + offset as u16);
+        if row <= last_row {
+            break;
+        }
+        let line = text::truncate(line, width);
+        surface.print(text::center(&line, width), row, &line, Style::fg(Color::DarkGrey))?;
+    }
+    Ok(())
+}
+
+/// Print the remaining time in bold at `col`, inverted on flash frames.
+fn print_time(surface: &mut impl Surface, col: u16, row: u16, text: &str, params: &DrawParams) -> io::Result<()> {
+    if params.flash {
+        surface.print(col, row, &format!(" {text} "), Style::fg(params.colors.accent).reverse().bold())
     } else {
-        format!("{m:02}:{s:02}")
+        surface.print(col, row, text, Style::PLAIN.bold())
+    }
+}
+
+fn remaining_text<'c>(text: &'c mut TimeText, params: &DrawParams) -> &'c str {
+    match params.remaining_tenths {
+        Some(tenths) => text.get_tenths(tenths),
+        None => text.get(params.remaining_secs as i64),
+    }
+}
+
+fn bar_progress(params: &DrawParams) -> f64 {
+    frame::progress_at(params.elapsed_millis, params.total_secs)
+}
+
+/// Hint bar for timer focus. Skip is hidden on the last round of a session.
+fn timer_hints(params: &DrawParams, with_tasks: bool) -> String {
+    if params.ack_prompt.is_some() || !params.keys {
+        return String::new();
+    }
+    if params.task_input.is_some() {
+        return "[enter] switch task  [esc] cancel".to_string();
+    }
+    let in_session = params.context != crate::timer::TimerContext::Standalone;
+    let is_last_round = params.round_info.is_some_and(|(cur, total)| cur >= total);
+
+    let mut hints = vec!["[space] pause"];
+    if !(in_session && is_last_round) {
+        hints.push("[s] skip");
+    }
+    if in_session {
+        hints.push("[a/d] +/-round");
+    }
+    if params.flow_available {
+        hints.push("[f] flow");
+    }
+    if params.context != crate::timer::TimerContext::Break {
+        hints.push("[n] task");
+    }
+    if with_tasks {
+        hints.push("[tab] tasks");
+    }
+    hints.push("[x] stop");
+    hints.join("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::surface::Buffer;
+
+    fn params() -> DrawParams<'static> {
+        DrawParams {
+            remaining_secs: 900,
+            total_secs: 1500,
+            elapsed_secs: 600,
+            elapsed_millis: 600_000,
+            remaining_tenths: None,
+            paused: false,
+            title: Some("Deep work"),
+            round_info: Some((2, 4)),
+            context: crate::timer::TimerContext::Work,
+            todo: None,
+            ack_prompt: None,
+            task_input: None,
+            upcoming: Some("next: break 5m"),
+            session_left: None,
+            break_debt: None,
+            footer: None,
+            keys: true,
+            flow_available: false,
+            colors: PhaseColors { accent: Color::Green, background: None, warning: Threshold::WARNING, critical: Threshold::CRITICAL },
+            flash: false,
+        }
+    }
+
+    /// The frame's rows, one per line and between newlines so the expected
+    /// frame can start on its own line. Trailing blank rows are dropped.
+    fn snapshot(buffer: &Buffer) -> String {
+        format!("\n{}\n", buffer.lines().join("\n").trim_end())
+    }
+
+    fn draw(params: &DrawParams, cols: u16, rows: u16) -> Buffer {
+        let mut buffer = Buffer::new(cols, rows);
+        Renderer::new().draw_on(&mut buffer, params).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn snapshot_timer_at_minimum_size() {
+        // The hints row is mid_row + 5, one past the last row at this height
+        let buffer = draw(&params(), MIN_COLS, MIN_ROWS);
+        assert_eq!(snapshot(&buffer), r"
+
+               Deep work
+               Round 2/4
+
+                 15:00
+
+     ████████████░░░░░░░░░░░░░░░░░░
+
+             10:00 elapsed
+");
+    }
+
+    #[test]
+    fn snapshot_timer_at_80x24() {
+        let buffer = draw(&params(), 80, 24);
+        assert_eq!(snapshot(&buffer), r"
+
+
+
+
+
+
+
+
+                                   Deep work
+                                   Round 2/4
+
+                                     15:00
+
+                         ████████████░░░░░░░░░░░░░░░░░░
+
+                                 10:00 elapsed
+
+          [space] pause  [s] skip  [a/d] +/-round  [n] task  [x] stop
+
+
+
+
+
+                                 next: break 5m
+");
+    }
+
+    #[test]
+    fn snapshot_timer_with_task_sidebar() {
+        let todo = crate::todo::TodoSnapshot {
+            items: vec![(1, "outline".to_string(), true), (2, "write the intro".to_string(), false), (3, "send draft".to_string(), false)],
+            selected_index: 2,
+            focus: true,
+        };
+        let buffer = draw(&DrawParams { todo: Some(&todo), ..params() }, 80, 16);
+        assert_eq!(snapshot(&buffer), r"
+                                                │
+                                                │ Tasks:
+                                                │
+               > write the intro                │ ✓ outline
+                   Deep work                    │ > write the intro
+                   Round 2/4                    │   send draft
+                                                │
+                     15:00                      │
+                                                │
+        ████████████░░░░░░░░░░░░░░░░░░          │
+                                                │
+                 10:00 elapsed                  │
+                                                │
+[tab] timer  [↑↓] select  [enter] done  [S-↑↓]… │
+                                                │
+                next: break 5m                  │
+");
+        let (col, row) = buffer.find("send draft").unwrap();
+        assert_eq!(buffer.cell(col, row).style, Style::fg(Color::Cyan).bold());
+        let (col, row) = buffer.find("outline").unwrap();
+        assert!(buffer.cell(col, row).style.crossed_out);
+    }
+
+    #[test]
+    fn bar_and_background_follow_the_phase_colors() {
+        let colors = PhaseColors { background: Some(Color::DarkBlue), ..params().colors };
+        let buffer = draw(&DrawParams { colors, ..params() }, 80, 24);
+        let (col, row) = buffer.find("\u{2588}").unwrap();
+        assert_eq!(buffer.cell(col, row).style.fg, Color::Green);
+        assert_eq!(buffer.cell(col + 29, row).style.fg, Color::DarkGrey);
+        assert_eq!(buffer.background(), Color::DarkBlue);
+
+        let buffer = draw(&DrawParams { remaining_secs: 30, elapsed_secs: 1470, elapsed_millis: 1_470_000, ..params() }, 80, 24);
+        let (col, row) = buffer.find("\u{2588}").unwrap();
+        assert_eq!(buffer.cell(col, row).style.fg, Color::Red);
+    }
+
+    #[test]
+    fn snapshot_too_small() {
+        let buffer = draw(&params(), 30, 6);
+        assert_eq!(snapshot(&buffer), r"
+
+
+      terminal too small
+          need 40x10
+");
+    }
+
+    #[test]
+    fn snapshot_header_and_prompt() {
+        let renderer = Renderer::new();
+        let mut buffer = Buffer::new(MIN_COLS, MIN_ROWS);
+        renderer.draw_header_on(&mut buffer, 3, 4, "work", "25:00", Some("[enter] start  [x] stop"), Some("Deep work")).unwrap();
+        assert_eq!(snapshot(&buffer), r"
+
+
+               Deep work
+
+               Round 3/4
+
+              work (25:00)
+
+        [enter] start  [x] stop
+");
+        renderer.draw_prompt_on(&mut buffer, "Take a long break?", "[y] yes  [n] no").unwrap();
+        assert_eq!(snapshot(&buffer), r"
+
+
+
+
+           Take a long break?
+
+            [y] yes  [n] no
+");
+    }
+
+    #[test]
+    fn snapshot_alarms() {
+        let rows = [AlarmRow { label: "pasta", remaining_secs: 45 }, AlarmRow { label: "sauce", remaining_secs: 0 }];
+        let mut buffer = Buffer::new(MIN_COLS, MIN_ROWS);
+        Renderer::new().draw_alarms_on(&mut buffer, &rows, false, true).unwrap();
+        assert_eq!(snapshot(&buffer), r"
+
+
+
+                Cooking
+
+            pasta      0:45
+            sauce      done
+
+        [space] pause  [x] stop
+");
+    }
+
+    #[test]
+    fn too_small_notice_fits_the_width() {
+        assert_eq!(too_small_lines(80), vec!["terminal too small (need 40x10)"]);
+        assert_eq!(too_small_lines(20), vec!["terminal too small", "need 40x10"]);
+        assert_eq!(too_small_lines(8), vec!["terminal", "need 40x"]);
+    }
+
+    #[test]
+    fn dumb_terminals_get_lines() {
+        assert!(is_dumb(Some("dumb")));
+        assert!(!is_dumb(Some("xterm-256color")));
+        assert!(!is_dumb(Some("vt220")));
+        assert_eq!(is_dumb(None), cfg!(unix));
+    }
+
+    #[test]
+    fn parse_color_names() {
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("dark_cyan"), Some(Color::DarkCyan));
+        assert_eq!(parse_color("nope"), None);
+    }
+
+    #[test]
+    fn thresholds_parse_and_apply() {
+        assert_eq!(Threshold::parse("20%"), Ok(Threshold::Percent(20)));
+        assert_eq!(Threshold::parse(" 2m "), Ok(Threshold::Secs(120)));
+        assert!(Threshold::parse("120%").is_err());
+        assert!(Threshold::parse("soon").is_err());
+        assert!(Threshold::WARNING.reached(300, 1500));
+        assert!(!Threshold::WARNING.reached(301, 1500));
+        assert!(Threshold::CRITICAL.reached(60, 1500));
+        assert!(!Threshold::CRITICAL.reached(61, 1500));
+    }
+
+    #[test]
+    fn parse_color_hex() {
+        assert_eq!(parse_color("#ff8000"), Some(Color::Rgb { r: 255, g: 128, b: 0 }));
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("#zzzzzz"), None);
     }
 }
@@ -0,0 +1,198 @@
+//! Recovering a timer whose process died: a crash, a closed terminal, a
+//! reboot. The timer loop checkpoints its progress to `resume.json` every few
+//! seconds and removes it when the timer returns; a checkpoint whose process
+//! is gone is what `tik resume` picks back up.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::duration::Duration;
+use crate::log::WorkLabels;
+use crate::timer::TimerContext;
+
+/// Time between checkpoints while the timer runs. Pausing and resuming are
+/// saved straight away.
+const CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Bumped whenever a field changes meaning; other versions aren't resumed.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// Process running the timer; resumable once it has exited.
+    pub pid: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub context: TimerContext,
+    pub started_at: DateTime<Local>,
+    pub total_secs: u64,
+    /// Time left as of `updated_at`.
+    pub remaining_secs: u64,
+    pub paused: bool,
+    /// Time spent paused since `started_at`.
+    pub paused_secs: u64,
+    pub updated_at: DateTime<Local>,
+    /// Task, tags and project, so the resumed part is logged like the rest.
+    #[serde(default)]
+    pub labels: WorkLabels,
+}
+
+impl Checkpoint {
+    /// "Resuming '25m': 12:30 of 25:00 left (started 14:05, paused 3m)"
+    pub fn summary(&self, clock_format: &str) -> String {
+        let left = Duration::from_secs(self.remaining_secs).format_hms();
+        let total = Duration::from_secs(self.total_secs).format_hms();
+        let mut details = format!("started {}", self.started_at.format(clock_format));
+        if self.paused_secs > 0 {
+            details.push_str(&format!(", paused {}", Duration::from_secs(self.paused_secs).format_compact()));
+        }
+        format!("Resuming '{}': {left} of {total} left ({details})", self.name)
+    }
+}
+
+pub fn checkpoint_path() -> PathBuf {
+    crate::log::log_path().with_file_name("resume.json")
+}
+
+/// Saves the running timer's progress when it's due.
+pub struct Checkpointer {
+    checkpoint: Checkpoint,
+    started: Instant,
+    /// When the last save happened and whether the timer was paused then.
+    saved: Option<(Instant, bool)>,
+}
+
+impl Checkpointer {
+    pub fn new(name: &str, title: Option<&str>, context: TimerContext, total_secs: u64, labels: &WorkLabels) -> Self {
+        let now = Local::now();
+        Checkpointer {
+            checkpoint: Checkpoint {
+                version: SCHEMA_VERSION,
+                pid: std::process::id(),
+                name: name.to_string(),
+                title: title.map(str::to_string),
+                context,
+                started_at: now,
+                total_secs,
+                remaining_secs: total_secs,
+                paused: false,
+                paused_secs: 0,
+                updated_at: now,
+                labels: labels.clone(),
+            },
+            started: Instant::now(),
+            saved: None,
+        }
+    }
+
+    /// Called every frame; writes the file every `CHECKPOINT_INTERVAL` and
+    /// on each pause or resume. `active` is the unpaused time so far.
+    pub fn update(&mut self, total_secs: u64, remaining_secs: u64, paused: bool, active: std::time::Duration) {
+        let now = Instant::now();
+        if !save_due(self.saved, now, paused) {
+            return;
+        }
+        self.saved = Some((now, paused));
+        self.checkpoint.total_secs = total_secs;
+        self.checkpoint.remaining_secs = remaining_secs;
+        self.checkpoint.paused = paused;
+        self.checkpoint.paused_secs = (now - self.started).saturating_sub(active).as_secs();
+        self.checkpoint.updated_at = Local::now();
+        if let Err(e) = write_to(&checkpoint_path(), &self.checkpoint) {
+            crate::debug::record(&format!("Failed to save the resume checkpoint: {e}"));
+        }
+    }
+}
+
+fn save_due(saved: Option<(Instant, bool)>, now: Instant, paused: bool) -> bool {
+    saved.is_none_or(|(at, was_paused)| was_paused != paused || now - at >= CHECKPOINT_INTERVAL)
+}
+
+/// Write to a temporary file and rename it over the old one, so a crash
+/// mid-write leaves the previous checkpoint.
+fn write_to(path: &Path, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string_pretty(checkpoint)?)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+fn read_from(path: &Path) -> Option<Checkpoint> {
+    let checkpoint: Checkpoint = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    (checkpoint.version == SCHEMA_VERSION).then_some(checkpoint)
+}
+
+/// Remove the checkpoint if this process wrote it: the timer returned, so
+/// there's nothing to recover.
+pub fn clear() {
+    clear_at(&checkpoint_path());
+}
+
+pub fn clear_at(path: &Path) {
+    if read_from(path).is_some_and(|c| c.pid == std::process::id()) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// The checkpoint an interrupted timer left behind, removed so it's only
+/// resumed once.
+pub fn take() -> Result<Checkpoint, String> {
+    take_from(&checkpoint_path(), crate::state::process_alive)
+}
+
+fn take_from(path: &Path, alive: impl Fn(u32) -> bool) -> Result<Checkpoint, String> {
+    let Some(checkpoint) = read_from(path) else {
+        return Err("Nothing to resume: no interrupted timer was found".to_string());
+    };
+    if checkpoint.pid == std::process::id() || alive(checkpoint.pid) {
+        return Err(format!("'{}' is still running (process {})", checkpoint.name, checkpoint.pid));
+    }
+    let _ = std::fs::remove_file(path);
+    if checkpoint.remaining_secs == 0 {
+        return Err(format!("Nothing to resume: '{}' had already finished", checkpoint.name));
+    }
+    Ok(checkpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_on_the_interval_and_on_pause_changes() {
+        let start = Instant::now();
+        assert!(save_due(None, start, false));
+        assert!(!save_due(Some((start, false)), start + std::time::Duration::from_secs(4), false));
+        assert!(save_due(Some((start, false)), start + CHECKPOINT_INTERVAL, false));
+        assert!(save_due(Some((start, false)), start + std::time::Duration::from_secs(1), true));
+    }
+
+    #[test]
+    fn taken_once_and_only_from_a_dead_process() {
+        let dir = std::env::temp_dir().join(format!("pomitik-resume-{}", std::process::id()));
+        let path = dir.join("resume.json");
+        let labels = WorkLabels { task: Some("report".to_string()), tags: vec!["deep".to_string()], ..Default::default() };
+        let mut checkpoint = Checkpointer::new("25m", Some("Deep work"), TimerContext::Standalone, 1500, &labels).checkpoint;
+        checkpoint.pid = u32::MAX;
+        checkpoint.remaining_secs = 750;
+        write_to(&path, &checkpoint).unwrap();
+
+        assert!(take_from(&path, |_| true).unwrap_err().contains("still running"));
+        // Labels included, so the resumed part is logged under the same task
+        assert_eq!(take_from(&path, |_| false), Ok(checkpoint.clone()));
+        assert!(take_from(&path, |_| false).unwrap_err().contains("Nothing to resume"));
+
+        checkpoint.paused_secs = 180;
+        let at = checkpoint.started_at.format("%H:%M");
+        assert_eq!(checkpoint.summary("%H:%M"), format!("Resuming '25m': 12:30 of 25:00 left (started {at}, paused 3m)"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,324 @@
+//! Feature `graphics`: the progress ring drawn as an image with the kitty
+//! graphics protocol or sixel, in place of the text bar. The ring is
+//! rasterized here (RGBA, anti-aliased edges) and sent whole each frame; the
+//! renderer only draws it when the terminal is known to support one.
+
+use crossterm::style::Color;
+use std::io::{self, Write};
+
+use crate::config::Graphics;
+
+/// Side of the image sent to kitty, which scales it to the cells it covers.
+const KITTY_SIZE: u32 = 128;
+/// Cell size assumed when the terminal doesn't report its pixel size.
+const DEFAULT_CELL: (u32, u32) = (10, 20);
+/// The ring's width as a share of its radius.
+const THICKNESS: f64 = 0.22;
+/// Kitty wants the image data in chunks of at most this many base64 bytes.
+const KITTY_CHUNK: usize = 4096;
+/// The unfilled part of the ring, like the bar's dark grey.
+const TRACK: [u8; 3] = [80, 80, 80];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+/// The protocol to draw with: the configured one, or for "auto" one the
+/// terminal is known to speak. Nothing inside tmux or screen, which don't
+/// pass images through.
+pub fn detect(setting: Graphics) -> Option<Protocol> {
+    detect_with(setting, |name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+fn detect_with(setting: Graphics, var: impl Fn(&str) -> Option<String>) -> Option<Protocol> {
+    match setting {
+        Graphics::Off => return None,
+        Graphics::Kitty => return Some(Protocol::Kitty),
+        Graphics::Sixel => return Some(Protocol::Sixel),
+        Graphics::Auto => {}
+    }
+    let term = var("TERM").unwrap_or_default();
+    if var("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+        return None;
+    }
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    if var("KITTY_WINDOW_ID").is_some()
+        || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+    {
+        return Some(Protocol::Kitty);
+    }
+    if term.starts_with("foot") || term.starts_with("mlterm") || term.contains("sixel") || var("WT_SESSION").is_some() {
+        return Some(Protocol::Sixel);
+    }
+    None
+}
+
+/// An RGB value for a terminal color, using the usual xterm palette.
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb { r, g, b } => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::DarkGrey => [128, 128, 128],
+        Color::Red => [255, 85, 85],
+        Color::DarkRed => [205, 0, 0],
+        Color::Green => [85, 255, 85],
+        Color::DarkGreen => [0, 205, 0],
+        Color::Yellow => [255, 255, 85],
+        Color::DarkYellow => [205, 205, 0],
+        Color::Blue => [92, 92, 255],
+        Color::DarkBlue => [0, 0, 238],
+        Color::Magenta => [255, 85, 255],
+        Color::DarkMagenta => [205, 0, 205],
+        Color::Cyan => [85, 255, 255],
+        Color::DarkCyan => [0, 205, 205],
+        Color::White => [255, 255, 255],
+        Color::Grey | Color::Reset => [229, 229, 229],
+        Color::AnsiValue(n) => ansi_rgb(n),
+    }
+}
+
+/// The 256-color palette: 16 system colors, a 6x6x6 cube, then greys.
+fn ansi_rgb(n: u8) -> [u8; 3] {
+    const SYSTEM: [Color; 16] = [
+        Color::Black,
+        Color::DarkRed,
+        Color::DarkGreen,
+        Color::DarkYellow,
+        Color::DarkBlue,
+        Color::DarkMagenta,
+        Color::DarkCyan,
+        Color::Grey,
+        Color::DarkGrey,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+    match n {
+        0..=15 => rgb(SYSTEM[n as usize]),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            [level(n / 36), level(n / 6 % 6), level(n % 6)]
+        }
+        _ => {
+            let grey = 8 + (n - 232) * 10;
+            [grey, grey, grey]
+        }
+    }
+}
+
+/// A `size` x `size` RGBA image of the ring, filled clockwise from the top
+/// up to `progress` (0.0 to 1.0). Outside the ring is transparent.
+fn pixels(size: u32, progress: f64, fill: [u8; 3]) -> Vec<[u8; 4]> {
+    let center = f64::from(size) / 2.0;
+    let outer = center - 1.0;
+    let inner = outer * (1.0 - THICKNESS);
+    let mut image = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (f64::from(x) + 0.5 - center, f64::from(y) + 0.5 - center);
+            let distance = dx.hypot(dy);
+            // How much of the pixel is inside the ring, for smooth edges
+            let coverage = (outer - distance + 0.5).min(distance - inner + 0.5).clamp(0.0, 1.0);
+            if coverage == 0.0 {
+                image.push([0; 4]);
+                continue;
+            }
+            let turn = dx.atan2(-dy).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+            let [r, g, b] = if turn < progress { fill } else { TRACK };
+            image.push([r, g, b, (coverage * 255.0).round() as u8]);
+        }
+    }
+    image
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Transmit and place an image over `cols` x `rows` cells at the cursor,
+/// under the text (so the time shows inside the ring) and without moving the
+/// cursor. `q=2` keeps the terminal from answering on stdin.
+fn kitty(image: &[[u8; 4]], size: u32, cols: u16, rows: u16) -> String {
+    let data = base64(image.as_flattened());
+    let chunks: Vec<&str> = data.as_bytes().chunks(KITTY_CHUNK).map(|c| std::str::from_utf8(c).unwrap_or_default()).collect();
+    let mut out = String::with_capacity(data.len() + chunks.len() * 16 + 64);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            out.push_str(&format!("\x1b_Ga=T,f=32,s={size},v={size},c={cols},r={rows},z=-1,C=1,q=2,m={more};{chunk}\x1b\\"));
+        } else {
+            out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    out
+}
+
+/// Encode an image as sixel with a two-color palette (fill and track).
+/// Pixels less than half covered are left as they are.
+fn sixel(image: &[[u8; 4]], width: u32, height: u32, fill: [u8; 3]) -> String {
+    let percent = |v: u8| u32::from(v) * 100 / 255;
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    for (index, [r, g, b]) in [fill, TRACK].into_iter().enumerate() {
+        out.push_str(&format!("#{index};2;{};{};{}", percent(r), percent(g), percent(b)));
+    }
+    let color_at = |x: u32, y: u32| {
+        let [r, g, b, a] = image[(y * width + x) as usize];
+        (a >= 128).then(|| if [r, g, b] == fill { 0 } else { 1 })
+    };
+    for band in (0..height).step_by(6) {
+        for index in 0..2 {
+            out.push_str(&format!("#{index}"));
+            let mut run: Option<(u8, u32)> = None;
+            let flush = |run: Option<(u8, u32)>, out: &mut String| {
+                if let Some((sixel, count)) = run {
+                    let c = char::from(63 + sixel);
+                    if count > 3 {
+                        out.push_str(&format!("!{count}{c}"));
+                    } else {
+                        out.extend(std::iter::repeat_n(c, count as usize));
+                    }
+                }
+            };
+            for x in 0..width {
+                let bits = (0..6)
+                    .filter(|row| band + row < height && color_at(x, band + row) == Some(index))
+                    .fold(0u8, |bits, row| bits | 1 << row);
+                run = match run {
+                    Some((sixel, count)) if sixel == bits => Some((sixel, count + 1)),
+                    previous => {
+                        flush(previous, &mut out);
+                        Some((bits, 1))
+                    }
+                };
+            }
+            flush(run, &mut out);
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// The size of a cell in pixels, as the terminal reports it.
+fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            (u32::from(size.width / size.columns).max(1), u32::from(size.height / size.rows).max(1))
+        }
+        _ => DEFAULT_CELL,
+    }
+}
+
+/// How many columns a ring `rows` tall takes, so it comes out round.
+pub fn width_in_cols(rows: u16) -> u16 {
+    let (cell_width, cell_height) = cell_size();
+    (u32::from(rows) * cell_height).div_ceil(cell_width) as u16
+}
+
+/// Draw the ring at the cursor, `rows` tall and `cols` wide.
+pub fn draw(stdout: &mut impl Write, protocol: Protocol, cols: u16, rows: u16, progress: f64, color: Color) -> io::Result<()> {
+    let fill = rgb(color);
+    let sequence = match protocol {
+        Protocol::Kitty => kitty(&pixels(KITTY_SIZE, progress, fill), KITTY_SIZE, cols, rows),
+        Protocol::Sixel => {
+            let size = u32::from(rows) * cell_size().1;
+            sixel(&pixels(size, progress, fill), size, size, fill)
+        }
+    };
+    stdout.write_all(sequence.as_bytes())
+}
+
+/// Remove the rings drawn so far. Clearing the screen removes sixels, but
+/// kitty keeps images until told otherwise.
+pub fn clear(stdout: &mut impl Write, protocol: Protocol) -> io::Result<()> {
+    match protocol {
+        Protocol::Kitty => stdout.write_all(b"\x1b_Ga=d,d=A,q=2\x1b\\"),
+        Protocol::Sixel => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect_in(setting: Graphics, vars: &[(&str, &str)]) -> Option<Protocol> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        detect_with(setting, |name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn detects_terminals_that_draw_images() {
+        assert_eq!(detect_in(Graphics::Auto, &[("TERM", "xterm-kitty")]), Some(Protocol::Kitty));
+        assert_eq!(detect_in(Graphics::Auto, &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")]), Some(Protocol::Kitty));
+        assert_eq!(detect_in(Graphics::Auto, &[("TERM", "foot")]), Some(Protocol::Sixel));
+        assert_eq!(detect_in(Graphics::Auto, &[("TERM", "xterm-256color")]), None);
+        assert_eq!(detect_in(Graphics::Auto, &[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")]), None);
+        // An explicit choice is trusted, and "off" always wins
+        assert_eq!(detect_in(Graphics::Sixel, &[("TERM", "xterm-256color")]), Some(Protocol::Sixel));
+        assert_eq!(detect_in(Graphics::Off, &[("TERM", "xterm-kitty")]), None);
+    }
+
+    #[test]
+    fn ring_fills_clockwise_from_the_top() {
+        let size = 40;
+        let image = pixels(size, 0.25, [255, 0, 0]);
+        let at = |x: u32, y: u32| image[(y * size + x) as usize];
+        // Center and corners are outside the ring
+        assert_eq!(at(20, 20)[3], 0);
+        assert_eq!(at(0, 0)[3], 0);
+        // Just right of the top is filled, the left side is still track
+        assert_eq!(at(22, 2), [255, 0, 0, 255]);
+        assert_eq!(at(1, 20)[..3], TRACK);
+    }
+
+    #[test]
+    fn base64_pads() {
+        assert_eq!(base64(b"tik"), "dGlr");
+        assert_eq!(base64(b"ti"), "dGk=");
+        assert_eq!(base64(b"t"), "dA==");
+    }
+
+    #[test]
+    fn kitty_image_sent_in_chunks() {
+        let image = vec![[1, 2, 3, 4]; 2048];
+        let out = kitty(&image, 32, 8, 4);
+        assert!(out.starts_with("\x1b_Ga=T,f=32,s=32,v=32,c=8,r=4,z=-1,C=1,q=2,m=1;AQIDBA"));
+        // 8192 bytes are 10924 in base64: three chunks
+        assert_eq!(out.matches("\x1b_G").count(), 3);
+        assert!(out.contains("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn sixel_bands_and_run_lengths() {
+        let fill = [255, 0, 0];
+        // 8 wide, 2 tall: left half fill, right half track
+        let image: Vec<[u8; 4]> = (0..16).map(|i| if i % 8 < 4 { [255, 0, 0, 255] } else { [80, 80, 80, 255] }).collect();
+        let out = sixel(&image, 8, 2, fill);
+        assert_eq!(out, "\x1bP0;1;0q\"1;1;8;2#0;2;100;0;0#1;2;31;31;31#0!4B!4?$#1!4?!4B$-\x1b\\");
+        assert_eq!(rgb(Color::AnsiValue(196)), [255, 0, 0]);
+        assert_eq!(rgb(Color::AnsiValue(244)), [128, 128, 128]);
+    }
+}
@@ -0,0 +1,246 @@
+//! `tik --msgpack-rpc`: the daemon's engine driven over stdio with
+//! msgpack-rpc, the protocol Neovim's `jobstart(..., {'rpc': v:true})`
+//! speaks. A plugin keeps one tik running and calls `start`, `pause`,
+//! `skip`, `stop` and `status` on it instead of spawning a process per call.
+//!
+//! Timers run headless as in `tik daemon`; their start and end lines go to
+//! stderr so they can't end up in the middle of a message. The running timer
+//! is stopped when the plugin closes stdin.
+
+use rmpv::Value;
+use std::io::{self, Write};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::ipc::{Request, Response};
+
+/// msgpack-rpc message types.
+const REQUEST: u64 = 0;
+const RESPONSE: u64 = 1;
+const NOTIFICATION: u64 = 2;
+
+/// Turn a method call into the daemon request it stands for.
+fn request(method: &str, params: &[Value]) -> Result<Request, String> {
+    match (method, params) {
+        ("start", [target]) => match target.as_str() {
+            Some(target) => Ok(Request::Start { target: target.to_string() }),
+            None => Err("start expects a duration, preset or session name".to_string()),
+        },
+        ("start", _) => Err("start expects one argument".to_string()),
+        ("pause", []) => Ok(Request::Pause),
+        ("skip", []) => Ok(Request::Skip),
+        ("stop", []) => Ok(Request::Stop),
+        ("status", []) => Ok(Request::Status),
+        ("pause" | "skip" | "stop" | "status", _) => Err(format!("{method} takes no arguments")),
+        _ => Err(format!("Unknown method '{method}'")),
+    }
+}
+
+/// The error and result of a response: a message string for `Ok`, the
+/// state (or nil) for `Status`. The state goes through JSON so it arrives as
+/// a map with the same keys as `tik status --json`; rmpv would encode the
+/// struct as a bare array.
+fn reply(response: Response) -> (Value, Value) {
+    match response {
+        Response::Ok { message } => (Value::Nil, Value::from(message)),
+        Response::Error { message } => (Value::from(message), Value::Nil),
+        Response::Status { state } => {
+            let json = serde_json::to_value(state).map_err(|e| e.to_string());
+            match json.and_then(|json| rmpv::ext::to_value(json).map_err(|e| e.to_string())) {
+                Ok(state) => (Value::Nil, state),
+                Err(e) => (Value::from(format!("Failed to encode the state: {e}")), Value::Nil),
+            }
+        }
+    }
+}
+
+/// Answer one message. Requests get a response; notifications are acted on
+/// without one.
+fn handle(message: Value, current: &mut Option<JoinHandle<()>>) -> Option<Value> {
+    let fields = message.as_array().map(Vec::as_slice).unwrap_or_default();
+    let (msgid, method, params) = match fields {
+        [kind, msgid, method, Value::Array(params)] if kind.as_u64() == Some(REQUEST) => (Some(msgid.clone()), method, params),
+        [kind, method, Value::Array(params)] if kind.as_u64() == Some(NOTIFICATION) => (None, method, params),
+        _ => {
+            eprintln!("Ignoring a message that isn't a msgpack-rpc request: {message}");
+            return None;
+        }
+    };
+    let response = match method.as_str().ok_or_else(|| "The method name must be a string".to_string()) {
+        Ok(method) => match request(method, params) {
+            Ok(request) => crate::daemon::handle(request, current),
+            Err(message) => Response::Error { message },
+        },
+        Err(message) => Response::Error { message },
+    };
+    let (error, result) = reply(response);
+    msgid.map(|msgid| Value::Array(vec![Value::from(RESPONSE), msgid, error, result]))
+}
+
+/// Read messages from stdin until it's closed or stops making sense. The
+/// thread ends with the stream.
+fn spawn_reader(tx: mpsc::UnboundedSender<Value>) {
+    std::thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        loop {
+            match rmpv::decode::read_value(&mut stdin) {
+                Ok(message) => {
+                    if tx.send(message).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    if !matches!(e.kind(), io::ErrorKind::UnexpectedEof) {
+                        eprintln!("Stopped reading requests: {e}");
+                    }
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Serve msgpack-rpc on stdin and stdout until stdin is closed.
+pub async fn run() -> Result<(), String> {
+    let mut out = platform::take_stdout().map_err(|e| format!("Failed to set up stdout for msgpack-rpc: {e}"))?;
+    let config = crate::config::Config::load();
+    crate::spawn_integrations(&config);
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    spawn_reader(tx);
+    let mut current = None;
+    let shutdown = crate::timer::shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        let message = tokio::select! {
+            message = rx.recv() => message,
+            _ = &mut shutdown => None,
+        };
+        let Some(message) = message else {
+            break;
+        };
+        if let Some(response) = handle(message, &mut current) {
+            let written = rmpv::encode::write_value(&mut out, &response).map_err(io::Error::from);
+            // The plugin went away
+            if written.and_then(|()| out.flush()).is_err() {
+                break;
+            }
+        }
+    }
+    if let Some(job) = current.take() {
+        job.abort();
+        crate::state::clear();
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::io::{self, Write};
+    use std::os::fd::FromRawFd;
+
+    /// Keep the real stdout for responses and point fd 1 at stderr, so
+    /// everything else tik prints goes there.
+    pub fn take_stdout() -> io::Result<Box<dyn Write + Send>> {
+        // SAFETY: dup and dup2 on our own standard descriptors; the duplicate
+        // is owned by the returned File and nothing else
+        unsafe {
+            let fd = libc::dup(libc::STDOUT_FILENO);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let out = std::fs::File::from_raw_fd(fd);
+            if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Box::new(out))
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::io::{self, Write};
+    use std::os::windows::io::FromRawHandle;
+    use windows_sys::Win32::System::Console::{GetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE, SetStdHandle};
+
+    /// Keep the real stdout for responses and make the process's standard
+    /// output handle stderr's, which `println!` looks up on every write.
+    pub fn take_stdout() -> io::Result<Box<dyn Write + Send>> {
+        // SAFETY: swaps our own standard handles; the old stdout handle is
+        // owned by the returned File and nothing else
+        unsafe {
+            let out = GetStdHandle(STD_OUTPUT_HANDLE);
+            if SetStdHandle(STD_OUTPUT_HANDLE, GetStdHandle(STD_ERROR_HANDLE)) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Box::new(std::fs::File::from_raw_handle(out)))
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io::{self, Write};
+
+    pub fn take_stdout() -> io::Result<Box<dyn Write + Send>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform yet"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(method: &str, params: Vec<Value>) -> Value {
+        Value::Array(vec![Value::from(REQUEST), Value::from(7), Value::from(method), Value::Array(params)])
+    }
+
+    #[test]
+    fn methods_map_to_daemon_requests() {
+        assert_eq!(request("start", &[Value::from("pomodoro")]), Ok(Request::Start { target: "pomodoro".to_string() }));
+        assert_eq!(request("pause", &[]), Ok(Request::Pause));
+        assert_eq!(request("status", &[]), Ok(Request::Status));
+        assert!(request("start", &[]).is_err());
+        assert!(request("start", &[Value::from(25)]).is_err());
+        assert_eq!(request("pause", &[Value::Nil]), Err("pause takes no arguments".to_string()));
+        assert_eq!(request("reset", &[]), Err("Unknown method 'reset'".to_string()));
+    }
+
+    #[tokio::test]
+    async fn requests_get_responses_with_their_id() {
+        let mut current = None;
+        assert_eq!(
+            handle(call("stop", vec![]), &mut current),
+            Some(Value::Array(vec![Value::from(RESPONSE), Value::from(7), Value::from("No timer running."), Value::Nil]))
+        );
+        let unknown = handle(call("reset", vec![]), &mut current).unwrap();
+        assert_eq!(unknown[3], Value::Nil);
+        // Notifications are never answered, and other messages are dropped
+        let notification = Value::Array(vec![Value::from(NOTIFICATION), Value::from("stop"), Value::Array(vec![])]);
+        assert_eq!(handle(notification, &mut current), None);
+        assert_eq!(handle(Value::from("hello"), &mut current), None);
+    }
+
+    #[test]
+    fn status_result_is_the_state_map() {
+        let state = crate::state::RuntimeState {
+            version: crate::state::SCHEMA_VERSION,
+            pid: 1,
+            name: "pomodoro".to_string(),
+            phase: crate::timer::TimerContext::Work,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 1440,
+            paused: false,
+            updated_at: chrono::Local::now(),
+        };
+        let (error, result) = reply(Response::Status { state: Some(state) });
+        assert_eq!(error, Value::Nil);
+        let field = |key: &str| result.as_map().unwrap().iter().find(|(k, _)| k.as_str() == Some(key)).map(|(_, v)| v.clone());
+        assert_eq!(field("phase"), Some(Value::from("work")));
+        assert_eq!(field("remaining_secs"), Some(Value::from(1440)));
+        assert_eq!(reply(Response::Status { state: None }), (Value::Nil, Value::Nil));
+        assert_eq!(reply(Response::Ok { message: "Paused.".to_string() }), (Value::Nil, Value::from("Paused.")));
+    }
+}
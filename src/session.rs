@@ -1,19 +1,301 @@
-use crate::config::{Config, SessionConfig};
+use crate::config::{Config, Journal, SessionConfig, SkipLogging};
 use crate::duration::Duration;
-use crate::log::LogEntry;
+use crate::events::{EventBus, TikEvent};
+use crate::footer::Footer;
+use crate::input::Input;
+use crate::log::{LogEntry, SessionEnd, SessionRecord};
+use crate::render::Screen;
+use crate::sound::Ticker;
 use crate::timer;
 use chrono::Local;
-use crossterm::{
-    cursor, execute,
-    style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor},
-    terminal::{self, ClearType},
-};
-use std::io::{self, Write};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-pub async fn run_session(session: &SessionConfig, config: &Config, silent: bool, title: Option<&str>) {
-    let total_rounds = Arc::new(AtomicU32::new(session.rounds));
+/// Command-line options that apply to every phase of a session.
+pub struct SessionOptions<'a> {
+    pub silent: bool,
+    pub title: Option<&'a str>,
+    pub ack: bool,
+    pub headless: bool,
+    /// Never read the keyboard: no raw mode, no key handling, no ack prompts.
+    pub no_input: bool,
+    /// Show tenths of a second on phases under a minute.
+    pub tenths: bool,
+    /// Headless lines for screen readers: time left announced in words (`--a11y`).
+    pub a11y: bool,
+    /// Time left, in seconds, at which each phase also notifies (`--notify-at`).
+    pub notify_at: &'a [u64],
+    /// Tags, task and estimate recorded on work phases.
+    pub labels: &'a crate::log::WorkLabels,
+    /// Write blocks and the session record to the log. Off when another
+    /// running timer already counts this time.
+    pub log: bool,
+}
+
+/// Kinds of session phase. The last round's break is the long break.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhaseKind {
+    Work,
+    Break,
+    LongBreak,
+}
+
+impl PhaseKind {
+    pub fn context(self) -> timer::TimerContext {
+        match self {
+            PhaseKind::Work => timer::TimerContext::Work,
+            PhaseKind::Break | PhaseKind::LongBreak => timer::TimerContext::Break,
+        }
+    }
+}
+
+/// One phase of a session, as planned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phase {
+    pub kind: PhaseKind,
+    pub name: String,
+    pub duration: Duration,
+    pub round: u32,
+}
+
+/// A session's phases with their lengths resolved. Iterating yields the
+/// phases in order: work then break for each round, with the long break after
+/// the last. Rounds can be added or removed mid-session, so the round count is
+/// read from the shared total at each step, and the upcoming phases are worked
+/// out from the current round and total each time. A `[sequences]` entry
+/// instead yields its steps as listed, and its rounds stay fixed.
+#[derive(Debug, Clone)]
+pub struct SessionPlan {
+    phases: Phases,
+    total_rounds: Arc<AtomicU32>,
+    /// Round and kind of the phase last yielded.
+    last: Option<(u32, PhaseKind)>,
+}
+
+#[derive(Debug, Clone)]
+enum Phases {
+    Rounds { work: (String, u64), short_break: (String, u64), long_break: (String, u64) },
+    /// Steps still to come, in order.
+    Sequence(std::collections::VecDeque<Phase>),
+}
+
+impl SessionPlan {
+    pub fn new(session: &SessionConfig, config: &Config) -> Result<Self, String> {
+        let phase = |name: &String, what: &str| {
+            let spec = config.resolve_preset(name).unwrap_or(name);
+            Duration::parse(spec)
+                .map(|d| (name.clone(), d.total_secs))
+                .map_err(|e| format!("Invalid {what} duration '{name}': {e}"))
+        };
+        Ok(SessionPlan {
+            phases: Phases::Rounds {
+                work: phase(&session.work, "work")?,
+                short_break: phase(&session.break_preset, "break")?,
+                long_break: phase(&session.long_break, "break")?,
+            },
+            total_rounds: Arc::new(AtomicU32::new(session.rounds)),
+            last: None,
+        })
+    }
+
+    /// A plan running `steps` (presets or durations) in order. Steps named
+    /// like a break ("break", "long-break", "coffee-break") run as breaks;
+    /// each other step is a round of work.
+    pub fn sequence(steps: &[String], config: &Config) -> Result<Self, String> {
+        if steps.is_empty() {
+            return Err("A sequence needs at least one step".to_string());
+        }
+        let mut round = 0;
+        let mut phases = std::collections::VecDeque::new();
+        for step in steps {
+            let kind = step_kind(step);
+            if kind == PhaseKind::Work {
+                round += 1;
+            }
+            let spec = config.resolve_preset(step).unwrap_or(step);
+            let duration = Duration::parse(spec).map_err(|e| format!("Invalid step '{step}': {e}"))?;
+            phases.push_back(Phase { kind, name: step.clone(), duration, round: round.max(1) });
+        }
+        Ok(SessionPlan {
+            phases: Phases::Sequence(phases),
+            total_rounds: Arc::new(AtomicU32::new(round.max(1))),
+            last: None,
+        })
+    }
+
+    /// The round count, shared with the timer's add/remove round keys.
+    pub fn total_rounds(&self) -> &Arc<AtomicU32> {
+        &self.total_rounds
+    }
+
+    /// Length of each work round, for round-based plans.
+    pub fn work_secs(&self) -> Option<u64> {
+        match &self.phases {
+            Phases::Rounds { work, .. } => Some(work.1),
+            Phases::Sequence(_) => None,
+        }
+    }
+
+    /// Change the length of every work round still to come.
+    pub fn set_work_secs(&mut self, secs: u64) {
+        if let Phases::Rounds { work, .. } = &mut self.phases {
+            work.1 = secs;
+        }
+    }
+
+    /// Whether rounds can be added or removed: not for a fixed sequence.
+    pub fn adjustable(&self) -> bool {
+        matches!(self.phases, Phases::Rounds { .. })
+    }
+
+    /// Work time the plan holds at its current round count.
+    pub fn planned_work_secs(&self) -> u64 {
+        match &self.phases {
+            Phases::Rounds { work, .. } => work.1 * u64::from(self.total_rounds.load(Ordering::Relaxed)),
+            Phases::Sequence(phases) => {
+                phases.iter().filter(|p| p.kind == PhaseKind::Work).map(|p| p.duration.total_secs).sum()
+            }
+        }
+    }
+
+    /// Phases after the current one, as (name, seconds). The last round ends
+    /// with the long break. A sequence lists its remaining steps.
+    pub fn upcoming(&self, round: u32, total_rounds: u32, in_break: bool) -> Vec<(&str, u64)> {
+        let (work, short_break, long_break) = match &self.phases {
+            Phases::Rounds { work, short_break, long_break } => (work, short_break, long_break),
+            Phases::Sequence(phases) => {
+                return phases.iter().map(|p| (p.name.as_str(), p.duration.total_secs)).collect();
+            }
+        };
+        let break_after = |round: u32| if round >= total_rounds { long_break } else { short_break };
+        let mut phases = Vec::new();
+        if !in_break {
+            phases.push(break_after(round));
+        }
+        for r in round + 1..=total_rounds {
+            phases.push(work);
+            phases.push(break_after(r));
+        }
+        phases.into_iter().map(|(name, secs)| (name.as_str(), *secs)).collect()
+    }
+
+    /// The phase right after the current one, if there is one.
+    pub fn next_phase(&self, round: u32, total_rounds: u32, in_break: bool) -> Option<(String, u64)> {
+        self.upcoming(round, total_rounds, in_break).first().map(|(name, secs)| (name.to_string(), *secs))
+    }
+
+    /// Length of everything after the current phase.
+    pub fn upcoming_secs(&self, round: u32, total_rounds: u32, in_break: bool) -> u64 {
+        self.upcoming(round, total_rounds, in_break).iter().map(|(_, secs)| secs).sum()
+    }
+
+    /// "next: break 5m → work 25m → long-break 15m", up to three phases ahead.
+    pub fn strip(&self, round: u32, total_rounds: u32, in_break: bool) -> Option<String> {
+        let upcoming = self.upcoming(round, total_rounds, in_break);
+        if upcoming.is_empty() {
+            return None;
+        }
+        let phases: Vec<String> = upcoming
+            .iter()
+            .take(3)
+            .map(|(name, secs)| format!("{name} {}", Duration::from_secs(*secs).format_compact()))
+            .collect();
+        Some(format!("next: {}", phases.join(" \u{2192} ")))
+    }
+}
+
+impl Iterator for SessionPlan {
+    type Item = Phase;
+
+    fn next(&mut self) -> Option<Phase> {
+        let (work, short_break, long_break) = match &mut self.phases {
+            Phases::Rounds { work, short_break, long_break } => (work, short_break, long_break),
+            Phases::Sequence(phases) => return phases.pop_front(),
+        };
+        let total_rounds = self.total_rounds.load(Ordering::Relaxed);
+        let (round, kind) = match self.last {
+            None => (1, PhaseKind::Work),
+            Some((round, PhaseKind::Work)) if round >= total_rounds => (round, PhaseKind::LongBreak),
+            Some((round, PhaseKind::Work)) => (round, PhaseKind::Break),
+            Some((round, _)) => (round + 1, PhaseKind::Work),
+        };
+        if kind == PhaseKind::Work && round > total_rounds {
+            return None;
+        }
+        self.last = Some((round, kind));
+        let (name, secs) = match kind {
+            PhaseKind::Work => work,
+            PhaseKind::Break => short_break,
+            PhaseKind::LongBreak => long_break,
+        };
+        Some(Phase { kind, name: name.clone(), duration: Duration::from_secs(*secs), round })
+    }
+}
+
+fn step_kind(name: &str) -> PhaseKind {
+    let name = name.to_lowercase();
+    if !name.contains("break") {
+        PhaseKind::Work
+    } else if name.contains("long") {
+        PhaseKind::LongBreak
+    } else {
+        PhaseKind::Break
+    }
+}
+
+/// Rounds and focus time so far, for the end summary and the session log. A
+/// work phase counts as a completed round only if it ran to the end; skipped
+/// ones are counted apart, and ones stopped or quit midway not at all.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SessionState {
+    pub rounds_completed: u32,
+    pub rounds_skipped: u32,
+    pub focus_secs: u64,
+}
+
+impl SessionState {
+    /// Count a work phase that ended with `outcome`, with the seconds logged for it.
+    pub fn work_ended(&mut self, outcome: timer::TimerOutcome, logged_secs: u64) {
+        if outcome.is_completed() {
+            self.rounds_completed += 1;
+        } else if outcome == timer::TimerOutcome::Skipped {
+            self.rounds_skipped += 1;
+        } else {
+            return;
+        }
+        self.focus_secs += logged_secs;
+    }
+
+    /// The session record. Rounds removed mid-session never leave fewer
+    /// planned than were worked.
+    pub fn record(&self, name: &str, rounds_planned: u32, started_at: chrono::DateTime<Local>, end: SessionEnd) -> SessionRecord {
+        SessionRecord {
+            work_secs: 0,
+            note: None,
+            name: name.to_string(),
+            rounds_planned: rounds_planned.max(self.rounds_completed + self.rounds_skipped),
+            rounds_completed: self.rounds_completed,
+            rounds_skipped: self.rounds_skipped,
+            focus_secs: self.focus_secs,
+            started_at,
+            ended_at: Local::now(),
+            end,
+        }
+    }
+}
+
+pub async fn run_session(name: &str, mut plan: SessionPlan, config: &Config, options: SessionOptions<'_>, events: &EventBus) {
+    let SessionOptions { silent, title, ack, headless, no_input, tenths, a11y, notify_at, labels, log: _ } = options;
+    let ack = ack && !headless && !no_input;
+    // One keyboard reader for the whole session, so keys pressed as a phase
+    // ends go to the next screen rather than a reader that's already done
+    let reader = (!headless && !no_input).then(Input::start);
+    let input = reader.as_ref();
+    let work_ack_prompt = ack.then(|| timer::ack_prompt_for("for break"));
+    let break_ack_prompt = ack.then(|| timer::ack_prompt_for("to continue"));
+    let total_rounds = Arc::clone(plan.total_rounds());
     let todos = {
         let list = crate::todo::TodoList::load();
         if list.items.is_empty() {
@@ -22,212 +304,553 @@ pub async fn run_session(session: &SessionConfig, config: &Config, silent: bool,
             Some(Arc::new(Mutex::new(list)))
         }
     };
-    let mut round: u32 = 1;
-    let mut in_alt_screen = false;
-
-    loop {
-        let current_total = total_rounds.load(Ordering::Relaxed);
-        if round > current_total {
-            break;
-        }
-
-        // --- Work phase ---
-        let work_duration_str = config
-            .resolve_preset(&session.work)
-            .unwrap_or(&session.work);
-        let work_dur = match Duration::parse(work_duration_str) {
-            Ok(d) => d,
+    let clock_format = config.clock_format();
+    let sounds = events.sounds();
+    let ticker = Ticker::new(config, sounds, silent);
+    // Entered once: headers, countdowns and prompts all draw into it
+    let screen = if headless {
+        None
+    } else {
+        match Screen::enter(input.is_some()) {
+            Ok(screen) => Some(screen.with_graphics(config.graphics)),
             Err(e) => {
-                eprintln!("Invalid work duration '{}': {e}", session.work);
+                eprintln!("Failed to setup terminal: {e}");
                 return;
             }
+        }
+    };
+    let footer = screen.as_ref().and_then(|_| Footer::start(&config.footer));
+    let mut state = SessionState::default();
+    let mut break_debt = crate::log::break_debt(&crate::log::read_entries(), Local::now());
+    let started_at = Local::now();
+    let work_secs = plan.work_secs().unwrap_or(0);
+    // Leaves the screen first so the summary lands in the shell
+    let finish = |end: SessionEnd, state: &SessionState, screen: Option<Screen>, note: Option<String>| {
+        drop(screen);
+        let record = SessionRecord {
+            work_secs,
+            note,
+            ..state.record(name, total_rounds.load(Ordering::Relaxed), started_at, end)
         };
+        println!("{}", record.summary());
+        events.emit(TikEvent::SessionEnded(record));
+    };
 
-        // Show header: if previous phase was skipped, we're already in alternate screen
-        if in_alt_screen {
-            draw_round_header_content(round, current_total, &session.work, &work_dur.format_hms(), title);
-        } else {
-            show_round_header(round, current_total, &session.work, &work_dur.format_hms(), title);
-        }
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    while let Some(phase) = plan.next() {
+        let Phase { kind, name: phase_name, duration: planned, round } = phase;
+        let current_total = total_rounds.load(Ordering::Relaxed);
+        let debt_line = crate::log::break_debt_line(break_debt);
 
-        let outcome = timer::run(
-            work_dur.total_secs,
-            &session.work,
-            timer::TimerContext::Work,
-            title,
-            Some((round, Arc::clone(&total_rounds))),
-            todos.clone(),
-        ).await;
+        if kind == PhaseKind::Work {
+            let round_labels = labels.for_round(round);
+            let work_title = work_title(title, round_labels.task.as_deref());
+            let work_title = work_title.as_deref();
 
-        in_alt_screen = outcome == timer::TimerOutcome::Skipped;
+            let work_dur = match &screen {
+                Some(screen) => show_round_header(screen, input, round, current_total, &phase_name, planned, work_title).await,
+                None => {
+                    let task = round_labels.task.as_deref().map(|t| format!(": {t}")).unwrap_or_default();
+                    let left = planned.total_secs + plan.upcoming_secs(round, current_total, false);
+                    let ends_at = Local::now() + chrono::Duration::seconds(left as i64);
+                    println!("Round {round}/{current_total}{task} (session ends at {})", ends_at.format(&clock_format));
+                    planned
+                }
+            };
 
-        match outcome {
-            timer::TimerOutcome::Quit => {
-                println!("Session cancelled.");
-                return;
+            let result = timer::run(timer::TimerParams {
+                name: &phase_name,
+                total_secs: work_dur.total_secs,
+                context: kind.context(),
+                title: work_title,
+                round_info: Some((round, Arc::clone(&total_rounds))),
+                todos: todos.clone(),
+                ack_prompt: work_ack_prompt.as_deref(),
+                flow_extend_secs: config.flow_extend_secs(),
+                colors: config.theme.colors_for(kind.context()),
+                animate_completion: config.completion_animation(),
+                inhibit_sleep: config.inhibit_sleep,
+                screen: screen.as_ref(),
+                input,
+                blocker: Some(&config.blocker),
+                clock_format: &clock_format,
+                frame_interval: config.frame_interval(),
+                tenths,
+                announce: a11y,
+                notify_at,
+                sounds,
+                ticker: ticker.as_ref(),
+                plan: Some(&plan),
+                talk: None,
+                break_debt: debt_line.as_deref(),
+                footer: footer.as_ref(),
+                events,
+                labels: &round_labels,
+            }).await;
+            let outcome = result.outcome;
+
+            match outcome {
+                timer::TimerOutcome::Quit => {
+                    finish(SessionEnd::Cancelled, &state, screen, None);
+                    return;
+                }
+                timer::TimerOutcome::StoppedEarly => {
+                    finish(SessionEnd::StoppedEarly, &state, screen, None);
+                    return;
+                }
+                _ => {} // Completed or Skipped — continue to break
             }
-            timer::TimerOutcome::StoppedEarly => {
-                cleanup_alt_screen();
-                println!("Session stopped early after {} round{}.", round.saturating_sub(1), if round.saturating_sub(1) == 1 { "" } else { "s" });
+
+            let worked = work_dur + Duration::from_secs(result.extended_secs);
+            let logged_secs = if outcome == timer::TimerOutcome::Skipped {
+                match (config.skip_logging, &screen, input) {
+                    (SkipLogging::Prompt, Some(screen), Some(input)) => {
+                        prompt_skip_log(screen, input, &phase_name, result.active_secs, worked.total_secs).await
+                    }
+                    (SkipLogging::None, ..) => Some(0),
+                    // Without a keyboard the prompt falls back to partial
+                    (SkipLogging::Partial | SkipLogging::Prompt, ..) => Some(result.active_secs),
+                }
+            } else {
+                Some(worked.total_secs)
+            };
+            // Ctrl+C at the prompt quits like it does in the timer: nothing logged
+            let Some(logged_secs) = logged_secs else {
+                finish(SessionEnd::Cancelled, &state, screen, None);
                 return;
-            }
-            _ => {} // Completed or Skipped — continue to break
-        }
+            };
+            let entry = LogEntry {
+                extended_secs: result.extended_secs.min(logged_secs.saturating_sub(work_dur.total_secs)),
+                ..LogEntry::work(&phase_name, logged_secs, &round_labels)
+            };
+            let entries = if logged_secs > 0 { entry.split_at_task_switches(&result.task_switches) } else { Vec::new() };
+            events.emit(TikEvent::Completed {
+                name: phase_name,
+                context: kind.context(),
+                duration_secs: worked.total_secs,
+                skipped: outcome == timer::TimerOutcome::Skipped,
+                next: plan.next_phase(round, total_rounds.load(Ordering::Relaxed), false),
+                entries,
+            });
+            state.work_ended(outcome, logged_secs);
 
-        if !in_alt_screen {
-            crate::notify::send_completion(&session.work, &work_dur.format_hms(), silent);
+            if outcome == timer::TimerOutcome::CompletedThenQuit {
+                finish(SessionEnd::Cancelled, &state, screen, None);
+                return;
+            }
+            continue;
         }
-        log_entry(&session.work, work_dur.total_secs);
 
         // --- Break phase ---
-        let current_total = total_rounds.load(Ordering::Relaxed);
-        let (break_name, break_duration_str) = if round == current_total {
-            let dur_str = config
-                .resolve_preset(&session.long_break)
-                .unwrap_or(&session.long_break);
-            (&session.long_break, dur_str.to_string())
-        } else {
-            let dur_str = config
-                .resolve_preset(&session.break_preset)
-                .unwrap_or(&session.break_preset);
-            (&session.break_preset, dur_str.to_string())
-        };
+        // The long break pays back breaks skipped earlier today
+        let repaying = if config.repay_break_debt && kind == PhaseKind::LongBreak { break_debt } else { 0 };
+        let break_dur = planned + Duration::from_secs(repaying);
 
-        let break_dur = match Duration::parse(&break_duration_str) {
-            Ok(d) => d,
-            Err(e) => {
-                eprintln!("Invalid break duration '{break_name}': {e}");
-                return;
-            }
+        let break_dur = match &screen {
+            Some(screen) => show_round_header(screen, input, round, current_total, &phase_name, break_dur, title).await,
+            None => break_dur,
         };
 
-        if in_alt_screen {
-            draw_round_header_content(round, current_total, break_name, &break_dur.format_hms(), title);
-        } else {
-            show_round_header(round, current_total, break_name, &break_dur.format_hms(), title);
-        }
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-
-        let outcome = timer::run(
-            break_dur.total_secs,
-            break_name,
-            timer::TimerContext::Break,
+        let result = timer::run(timer::TimerParams {
+            name: &phase_name,
+            total_secs: break_dur.total_secs,
+            context: kind.context(),
             title,
-            Some((round, Arc::clone(&total_rounds))),
-            todos.clone(),
-        ).await;
-
-        in_alt_screen = outcome == timer::TimerOutcome::Skipped;
+            round_info: Some((round, Arc::clone(&total_rounds))),
+            todos: todos.clone(),
+            ack_prompt: break_ack_prompt.as_deref(),
+            flow_extend_secs: 0,
+            colors: config.theme.colors_for(kind.context()),
+            animate_completion: config.completion_animation(),
+            inhibit_sleep: false,
+            screen: screen.as_ref(),
+            input,
+            blocker: None,
+            clock_format: &clock_format,
+            frame_interval: config.frame_interval(),
+            tenths,
+            announce: a11y,
+            notify_at,
+            sounds,
+            ticker: ticker.as_ref(),
+            plan: Some(&plan),
+            talk: None,
+            break_debt: debt_line.as_deref(),
+            footer: footer.as_ref(),
+            events,
+            labels: &crate::log::WorkLabels::default(),
+        }).await;
+        let outcome = result.outcome;
 
         match outcome {
             timer::TimerOutcome::Quit => {
-                println!("Session cancelled.");
+                finish(SessionEnd::Cancelled, &state, screen, None);
                 return;
             }
             timer::TimerOutcome::StoppedEarly => {
-                cleanup_alt_screen();
-                println!("Session stopped early after {} round{}.", round, if round == 1 { "" } else { "s" });
+                finish(SessionEnd::StoppedEarly, &state, screen, None);
                 return;
             }
             _ => {} // Completed or Skipped — continue
         }
 
-        if !in_alt_screen {
-            crate::notify::send_completion(break_name, &break_dur.format_hms(), silent);
-        }
-        log_entry(break_name, break_dur.total_secs);
+        let taken_secs = if outcome == timer::TimerOutcome::Skipped { result.active_secs } else { break_dur.total_secs };
+        let skipped_secs = break_dur.total_secs - taken_secs;
+        // A long break shortened in the round header repays only what's left of the extension
+        let repaid_secs = break_dur.total_secs.saturating_sub(planned.total_secs).min(repaying);
+        break_debt = (break_debt + skipped_secs).saturating_sub(repaid_secs);
+        let entry = LogEntry {
+            name: phase_name.clone(),
+            duration_secs: taken_secs,
+            completed_at: Local::now(),
+            skipped_secs,
+            repaid_secs,
+            ..Default::default()
+        };
+        events.emit(TikEvent::Completed {
+            name: phase_name,
+            context: kind.context(),
+            duration_secs: break_dur.total_secs,
+            skipped: outcome == timer::TimerOutcome::Skipped,
+            next: plan.next_phase(round, total_rounds.load(Ordering::Relaxed), true),
+            entries: vec![entry],
+        });
 
-        round += 1;
+        if outcome == timer::TimerOutcome::CompletedThenQuit {
+            finish(SessionEnd::Cancelled, &state, screen, None);
+            return;
+        }
     }
 
-    if in_alt_screen {
-        cleanup_alt_screen();
+    // Save todos if they were modified during session
+    if let Some(ref todos) = todos
+        && let Ok(list) = todos.lock()
+        && let Err(e) = list.save()
+    {
+        crate::messages::warn(format!("Failed to save todos: {e}"));
     }
 
-    // Save todos if they were modified during session
-    if let Some(ref todos) = todos {
-        if let Ok(list) = todos.lock() {
-            if let Err(e) = list.save() {
-                eprintln!("Failed to save todos: {e}");
+    let (note, screen) = match (config.journal, screen, input) {
+        (Journal::Inline, Some(screen), Some(input)) => (prompt_note(&screen, input).await, Some(screen)),
+        (Journal::Editor, screen, Some(_)) => {
+            // The editor needs the terminal to itself
+            drop(screen);
+            drop(reader);
+            (crate::hooks::edit_note(), None)
+        }
+        (_, screen, _) => (None, screen),
+    };
+    finish(SessionEnd::Completed, &state, screen, note);
+}
+
+/// Ask for a note on the finished session, with `journal = "inline"`. None
+/// when it's left empty or skipped with esc.
+async fn prompt_note(screen: &Screen, input: &Input) -> Option<String> {
+    let mut text = String::new();
+    let draw = |text: &str| {
+        let _ = screen.renderer().draw_prompt(&format!("Session note: {text}_"), NOTE_HINTS);
+    };
+    draw(&text);
+    while let Some(event) = input.next().await {
+        let key = match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            Event::Resize(..) => {
+                draw(&text);
+                continue;
+            }
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Enter => break,
+            KeyCode::Esc => return None,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return None,
+            KeyCode::Backspace => {
+                text.pop();
             }
+            KeyCode::Char(c) => text.push(c),
+            _ => continue,
         }
+        draw(&text);
     }
+    let note = text.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+const NOTE_HINTS: &str = "[enter] save  [esc] skip";
 
-    let final_total = total_rounds.load(Ordering::Relaxed);
-    println!("Session complete! {} rounds finished.", final_total);
+/// Title for a work phase: the round's task, after the user's title if both are set.
+fn work_title(title: Option<&str>, task: Option<&str>) -> Option<String> {
+    match (title, task) {
+        (Some(title), Some(task)) => Some(format!("{title}: {task}")),
+        (title, task) => title.or(task).map(str::to_string),
+    }
 }
 
-fn cleanup_alt_screen() {
-    let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
-    let _ = terminal::disable_raw_mode();
+/// Show the round header for ~2s before a phase. Digit keys change the
+/// upcoming phase's length (see `quick_duration`), which restarts the wait;
+/// enter starts the phase right away. Returns the length to run. Without
+/// `input` the header just shows for its ~2s.
+async fn show_round_header(
+    screen: &Screen,
+    input: Option<&Input>,
+    round: u32,
+    total: u32,
+    name: &str,
+    planned: Duration,
+    title: Option<&str>,
+) -> Duration {
+    let planned_secs = planned.total_secs;
+    let mut dur = planned;
+    let mut deadline = Instant::now() + std::time::Duration::from_secs(2);
+    let hints = input.map(|_| HEADER_HINTS);
+    let draw = |dur: Duration| {
+        let _ = screen.renderer().draw_header(round, total, name, &dur.format_hms(), hints, title);
+    };
+    draw(dur);
+    let Some(input) = input else {
+        tokio::time::sleep_until(deadline.into()).await;
+        return dur;
+    };
+    // Keys left unread when the header times out stay queued for the timer
+    while let Ok(Some(event)) = tokio::time::timeout_at(deadline.into(), input.next()).await {
+        let key = match event {
+            Event::Key(key) => key,
+            Event::Resize(..) => {
+                draw(dur);
+                continue;
+            }
+            _ => continue,
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Enter => break,
+            KeyCode::Char(c) => {
+                if let Some(secs) = quick_duration(c, planned_secs) {
+                    dur = Duration::from_secs(secs);
+                    deadline = Instant::now() + std::time::Duration::from_secs(2);
+                    draw(dur);
+                }
+            }
+            _ => {}
+        }
+    }
+    dur
 }
 
-fn show_round_header(round: u32, total: u32, name: &str, duration: &str, title: Option<&str>) {
-    let _ = terminal::enable_raw_mode();
-    let _ = execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide);
+/// Quick-duration keys on the round header: `1`-`9` set the phase to 5-45
+/// minutes, `0` restores the configured length.
+fn quick_duration(key: char, planned_secs: u64) -> Option<u64> {
+    match key.to_digit(10)? {
+        0 => Some(planned_secs),
+        n => Some(u64::from(n) * 5 * 60),
+    }
+}
 
-    draw_round_header_content(round, total, name, duration, title);
+const HEADER_HINTS: &str = "[1-9] 5-45m  [0] reset  [enter] start";
 
-    let _ = io::stdout().flush();
-    let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
-    let _ = terminal::disable_raw_mode();
+/// Ask how much of a skipped work phase to log, with `skip_logging = "prompt"`.
+/// Draws over the skipped timer in the session's screen. Returns the
+/// seconds to log: the time worked (enter or p), the full block (f) or
+/// nothing (n or esc). None when Ctrl+C ends the session instead.
+async fn prompt_skip_log(screen: &Screen, input: &Input, name: &str, active_secs: u64, full_secs: u64) -> Option<u64> {
+    let worked = Duration::from_secs(active_secs).format_compact();
+    let full = Duration::from_secs(full_secs).format_compact();
+    let question = format!("Skipped {name} after {worked}. Log it?");
+    let hints = format!("[p] {worked}  [f] {full}  [n] don't log");
+    let draw = || {
+        let _ = screen.renderer().draw_prompt(&question, &hints);
+    };
+    draw();
+    while let Some(event) = input.next().await {
+        let key = match event {
+            Event::Key(key) => key,
+            Event::Resize(..) => {
+                draw();
+                continue;
+            }
+            _ => continue,
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return None,
+            KeyCode::Enter | KeyCode::Char('p') => return Some(active_secs),
+            KeyCode::Char('f') => return Some(full_secs),
+            KeyCode::Esc | KeyCode::Char('n') => return Some(0),
+            _ => {}
+        }
+    }
+    // The reader stopped: log what was worked, as without the prompt
+    Some(active_secs)
 }
 
-/// Draw round header content without managing alternate screen.
-/// Used both by show_round_header (first phase entry) and for smooth
-/// transitions when skipping (alternate screen already active).
-fn draw_round_header_content(round: u32, total: u32, name: &str, duration: &str, title: Option<&str>) {
-    let (cols, rows) = terminal::size().unwrap_or((80, 24));
-    let mid_row = rows / 2;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let line1 = format!("Round {round}/{total}");
-    let line2 = format!("{name} ({duration})");
+    fn plan(rounds: u32) -> SessionPlan {
+        SessionPlan {
+            phases: Phases::Rounds {
+                work: ("work".to_string(), 1500),
+                short_break: ("break".to_string(), 300),
+                long_break: ("long-break".to_string(), 900),
+            },
+            total_rounds: Arc::new(AtomicU32::new(rounds)),
+            last: None,
+        }
+    }
+
+    fn kinds(plan: &mut SessionPlan, n: usize) -> Vec<(PhaseKind, u32)> {
+        plan.take(n).map(|p| (p.kind, p.round)).collect()
+    }
+
+    #[test]
+    fn plan_yields_work_and_breaks_then_long_break() {
+        use PhaseKind::*;
+        let mut p = plan(2);
+        let phases: Vec<Phase> = p.by_ref().collect();
+        let summary: Vec<_> = phases.iter().map(|p| (p.kind, p.name.as_str(), p.duration.total_secs, p.round)).collect();
+        assert_eq!(
+            summary,
+            [(Work, "work", 1500, 1), (Break, "break", 300, 1), (Work, "work", 1500, 2), (LongBreak, "long-break", 900, 2)]
+        );
+        assert_eq!(p.next(), None);
+    }
 
-    let col1 = cols.saturating_sub(line1.len() as u16) / 2;
-    let col2 = cols.saturating_sub(line2.len() as u16) / 2;
+    #[test]
+    fn plan_single_round_is_work_and_long_break() {
+        assert_eq!(kinds(&mut plan(1), 5), [(PhaseKind::Work, 1), (PhaseKind::LongBreak, 1)]);
+    }
 
-    let _ = execute!(
-        io::stdout(),
-        terminal::Clear(ClearType::All),
-    );
+    #[test]
+    fn plan_without_rounds_is_empty() {
+        assert_eq!(plan(0).next(), None);
+    }
+
+    #[test]
+    fn plan_follows_rounds_added_and_removed() {
+        use PhaseKind::*;
+        let mut p = plan(2);
+        assert_eq!(kinds(&mut p, 1), [(Work, 1)]);
+        p.total_rounds.store(1, Ordering::Relaxed);
+        assert_eq!(kinds(&mut p, 1), [(LongBreak, 1)]);
+        // A round added during the long break still runs
+        p.total_rounds.store(2, Ordering::Relaxed);
+        assert_eq!(kinds(&mut p, 5), [(Work, 2), (LongBreak, 2)]);
+        p.total_rounds.store(4, Ordering::Relaxed);
+        assert_eq!(kinds(&mut p, 2), [(Work, 3), (Break, 3)]);
+    }
 
-    if let Some(title) = title {
-        let title_col = cols.saturating_sub(title.len() as u16) / 2;
-        let _ = execute!(
-            io::stdout(),
-            cursor::MoveTo(title_col, mid_row.saturating_sub(3)),
-            SetForegroundColor(Color::White),
-            SetAttribute(Attribute::Bold),
-            Print(title),
-            SetAttribute(Attribute::Reset),
-            ResetColor,
+    #[test]
+    fn plan_rejects_bad_durations() {
+        let config = Config::default();
+        let session = SessionConfig {
+            work: "25m".to_string(),
+            break_preset: "soon".to_string(),
+            long_break: "15m".to_string(),
+            rounds: 4,
+        };
+        let err = SessionPlan::new(&session, &config).unwrap_err();
+        assert!(err.starts_with("Invalid break duration 'soon'"), "{err}");
+    }
+
+    #[test]
+    fn sequence_runs_steps_in_order() {
+        use PhaseKind::*;
+        let mut config = Config::default();
+        config.presets.insert("warmup".to_string(), "10m".to_string());
+        config.presets.insert("pomodoro".to_string(), "25m".to_string());
+        config.presets.insert("break".to_string(), "5m".to_string());
+        let steps = ["warmup", "pomodoro", "break", "pomodoro", "20m"].map(String::from);
+        let mut p = SessionPlan::sequence(&steps, &config).unwrap();
+        assert!(!p.adjustable());
+        assert_eq!(p.total_rounds().load(Ordering::Relaxed), 4);
+        assert_eq!(p.planned_work_secs(), 4800);
+        assert_eq!(kinds(&mut p, 2), [(Work, 1), (Work, 2)]);
+        assert_eq!(p.upcoming(2, 4, false), vec![("break", 300), ("pomodoro", 1500), ("20m", 1200)]);
+        assert_eq!(kinds(&mut p, 5), [(Break, 2), (Work, 3), (Work, 4)]);
+        assert!(SessionPlan::sequence(&[], &config).is_err());
+        assert!(SessionPlan::sequence(&["nap".to_string()], &config).unwrap_err().starts_with("Invalid step 'nap'"));
+    }
+
+    #[test]
+    fn step_kind_from_name() {
+        assert_eq!(step_kind("pomodoro"), PhaseKind::Work);
+        assert_eq!(step_kind("coffee-break"), PhaseKind::Break);
+        assert_eq!(step_kind("long-break"), PhaseKind::LongBreak);
+    }
+
+    #[test]
+    fn phase_kind_context() {
+        assert_eq!(PhaseKind::Work.context(), timer::TimerContext::Work);
+        assert_eq!(PhaseKind::LongBreak.context(), timer::TimerContext::Break);
+    }
+
+    #[test]
+    fn session_state_counts_completed_and_skipped_rounds() {
+        use timer::TimerOutcome::*;
+        let mut state = SessionState::default();
+        state.work_ended(Completed, 1500);
+        state.work_ended(Skipped, 120);
+        state.work_ended(CompletedThenQuit, 1500);
+        assert_eq!(state, SessionState { rounds_completed: 2, rounds_skipped: 1, focus_secs: 3120 });
+    }
+
+    #[test]
+    fn session_state_ignores_stopped_and_quit_work() {
+        let mut state = SessionState::default();
+        state.work_ended(timer::TimerOutcome::StoppedEarly, 0);
+        state.work_ended(timer::TimerOutcome::Quit, 0);
+        assert_eq!(state, SessionState::default());
+        let record = state.record("pomodoro", 4, Local::now(), SessionEnd::StoppedEarly);
+        assert_eq!(record.summary(), "Session stopped early after 0 of 4 rounds (0m focus).");
+    }
+
+    #[test]
+    fn session_record_plans_at_least_the_rounds_worked() {
+        let state = SessionState { rounds_completed: 3, rounds_skipped: 1, focus_secs: 4620 };
+        let record = state.record("pomodoro", 2, Local::now(), SessionEnd::Completed);
+        assert_eq!((record.rounds_planned, record.rounds_completed, record.rounds_skipped), (4, 3, 1));
+    }
+
+    #[test]
+    fn upcoming_phases_from_work_and_break() {
+        let p = plan(4);
+        assert_eq!(p.upcoming(1, 2, false), vec![("break", 300), ("work", 1500), ("long-break", 900)]);
+        assert_eq!(p.upcoming(1, 2, true), vec![("work", 1500), ("long-break", 900)]);
+        assert_eq!(p.upcoming(2, 2, false), vec![("long-break", 900)]);
+        assert!(p.upcoming(2, 2, true).is_empty());
+        assert_eq!(p.next_phase(1, 2, true), Some(("work".to_string(), 1500)));
+        assert_eq!(p.next_phase(2, 2, true), None);
+        assert_eq!(p.upcoming_secs(1, 2, false), 2700);
+        assert_eq!(p.upcoming_secs(2, 2, true), 0);
+    }
+
+    #[test]
+    fn strip_shows_three_phases_ahead() {
+        let p = plan(4);
+        assert_eq!(
+            p.strip(1, 4, false).as_deref(),
+            Some("next: break 5m \u{2192} work 25m \u{2192} break 5m")
         );
+        assert_eq!(p.strip(3, 4, true).as_deref(), Some("next: work 25m \u{2192} long-break 15m"));
+        assert_eq!(p.strip(4, 4, true), None);
     }
 
-    let _ = execute!(
-        io::stdout(),
-        cursor::MoveTo(col1, mid_row.saturating_sub(1)),
-        SetForegroundColor(Color::Cyan),
-        SetAttribute(Attribute::Bold),
-        Print(&line1),
-        SetAttribute(Attribute::Reset),
-        ResetColor,
-        cursor::MoveTo(col2, mid_row + 1),
-        SetForegroundColor(Color::DarkGrey),
-        Print(&line2),
-        ResetColor,
-    );
-    let _ = io::stdout().flush();
-}
-
-fn log_entry(name: &str, duration_secs: u64) {
-    let entry = LogEntry {
-        name: name.to_string(),
-        duration_secs,
-        completed_at: Local::now(),
-    };
-    if let Err(e) = crate::log::append_entry(&entry) {
-        eprintln!("Failed to write log: {e}");
+    #[test]
+    fn quick_duration_digits() {
+        assert_eq!(quick_duration('1', 1500), Some(300));
+        assert_eq!(quick_duration('9', 1500), Some(2700));
+        assert_eq!(quick_duration('0', 1500), Some(1500));
+        assert_eq!(quick_duration('x', 1500), None);
+    }
+
+    #[test]
+    fn work_title_combines_title_and_task() {
+        assert_eq!(work_title(Some("Deep Work"), Some("spec")).as_deref(), Some("Deep Work: spec"));
+        assert_eq!(work_title(None, Some("spec")).as_deref(), Some("spec"));
+        assert_eq!(work_title(Some("Deep Work"), None).as_deref(), Some("Deep Work"));
     }
 }
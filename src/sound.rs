@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Moments a sound pack has a file for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cue {
+    WorkEnd,
+    BreakEnd,
+    /// One minute left in a work block.
+    Warning,
+    /// Kitchen-timer tick, once a second while working.
+    Tick,
+}
+
+impl Cue {
+    fn file_stem(self) -> &'static str {
+        match self {
+            Cue::WorkEnd => "work-end",
+            Cue::BreakEnd => "break-end",
+            Cue::Warning => "warning",
+            Cue::Tick => "tick",
+        }
+    }
+}
+
+/// A bundled pack: its name and (file stem, wav bytes) for each sound.
+type BundledPack = (&'static str, &'static [(&'static str, &'static [u8])]);
+
+const BUNDLED: &[BundledPack] = &[
+    (
+        "classic",
+        &[
+            ("work-end", include_bytes!("../assets/sounds/classic/work-end.wav")),
+            ("break-end", include_bytes!("../assets/sounds/classic/break-end.wav")),
+            ("warning", include_bytes!("../assets/sounds/classic/warning.wav")),
+            ("tick", include_bytes!("../assets/sounds/classic/tick.wav")),
+        ],
+    ),
+    (
+        "soft",
+        &[
+            ("work-end", include_bytes!("../assets/sounds/soft/work-end.wav")),
+            ("break-end", include_bytes!("../assets/sounds/soft/break-end.wav")),
+            ("warning", include_bytes!("../assets/sounds/soft/warning.wav")),
+            ("tick", include_bytes!("../assets/sounds/soft/tick.wav")),
+        ],
+    ),
+];
+
+/// A named set of sounds: `~/.config/pomitik/sounds/<name>/` with
+/// `work-end`, `break-end`, `warning` and `tick` as `.wav` or `.ogg`, falling
+/// back per file to the bundled pack of the same name.
+///
+/// Sounds are played with the platform's own player (`afplay`, `paplay` or
+/// `aplay`, PowerShell's `SoundPlayer`). A missing player or file is silent.
+#[derive(Debug, Clone)]
+pub struct SoundPack {
+    name: String,
+    /// Nothing plays during these hours.
+    quiet_hours: Option<crate::config::QuietHours>,
+}
+
+impl SoundPack {
+    /// The configured pack, unless sounds are silenced or no pack is set.
+    pub fn from_config(config: &crate::config::Config, silent: bool) -> Option<Self> {
+        let name = config.sound_pack.as_deref()?.trim();
+        if silent || name.is_empty() {
+            return None;
+        }
+        if !is_bundled(name) && !user_dir(name).is_dir() {
+            crate::messages::warn(format!("Unknown sound pack '{name}' (bundled: {})", bundled_names().join(", ")));
+            return None;
+        }
+        Some(SoundPack { name: name.to_string(), quiet_hours: config.notify.quiet_hours.clone() })
+    }
+
+    pub fn play(&self, cue: Cue) {
+        if self.quiet_hours.as_ref().is_some_and(|quiet| quiet.is_now()) {
+            return;
+        }
+        if let Some(path) = self.path(cue) {
+            play_file(&path);
+        }
+    }
+
+    fn path(&self, cue: Cue) -> Option<PathBuf> {
+        user_file(&user_dir(&self.name), cue).or_else(|| bundled_file(&self.name, cue))
+    }
+}
+
+/// The ticking during work phases. Uses the active pack's tick, or the classic
+/// one when no pack is set. Shared by every round of a session so toggling it
+/// with `m` carries over.
+pub struct Ticker {
+    pack: SoundPack,
+    on: AtomicBool,
+}
+
+impl Ticker {
+    /// None when silenced; otherwise available for `m` even if it starts off
+    /// (on with `tick_sound`).
+    pub fn new(config: &crate::config::Config, sounds: Option<&SoundPack>, silent: bool) -> Option<Self> {
+        if silent {
+            return None;
+        }
+        let pack = sounds.cloned().unwrap_or_else(|| SoundPack {
+            name: "classic".to_string(),
+            quiet_hours: config.notify.quiet_hours.clone(),
+        });
+        Some(Ticker { pack, on: AtomicBool::new(config.tick_sound) })
+    }
+
+    pub fn toggle(&self) {
+        self.on.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    pub fn tick(&self) {
+        if self.on.load(Ordering::Relaxed) {
+            self.pack.play(Cue::Tick);
+        }
+    }
+}
+
+fn bundled_names() -> Vec<&'static str> {
+    BUNDLED.iter().map(|(name, _)| *name).collect()
+}
+
+fn is_bundled(name: &str) -> bool {
+    BUNDLED.iter().any(|(pack, _)| *pack == name)
+}
+
+fn user_dir(name: &str) -> PathBuf {
+    crate::config::Config::config_path()
+        .with_file_name("sounds")
+        .join(name)
+}
+
+fn user_file(dir: &Path, cue: Cue) -> Option<PathBuf> {
+    ["wav", "ogg"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{ext}", cue.file_stem())))
+        .find(|path| path.is_file())
+}
+
+/// Players need a file, so bundled sounds are written to the cache dir once.
+fn bundled_file(name: &str, cue: Cue) -> Option<PathBuf> {
+    let (_, files) = BUNDLED.iter().find(|(pack, _)| *pack == name)?;
+    let (_, bytes) = files.iter().find(|(stem, _)| *stem == cue.file_stem())?;
+    let path = dirs::cache_dir()?
+        .join("pomitik")
+        .join("sounds")
+        .join(name)
+        .join(format!("{}.wav", cue.file_stem()));
+    let current = std::fs::metadata(&path).is_ok_and(|m| m.len() == bytes.len() as u64);
+    if !current {
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        std::fs::write(&path, bytes).ok()?;
+    }
+    Some(path)
+}
+
+/// Start playback and reap the player on a background thread.
+fn play_file(path: &Path) {
+    for mut command in player_commands(path) {
+        let spawned = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = spawned {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+            return;
+        }
+    }
+}
+
+/// Programs `play` tries, in order.
+#[cfg(target_os = "macos")]
+pub const PLAYERS: &[&str] = &["afplay"];
+#[cfg(windows)]
+pub const PLAYERS: &[&str] = &["powershell"];
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const PLAYERS: &[&str] = &["paplay", "aplay"];
+
+/// The first player found on PATH, for `tik doctor`.
+pub fn available_player() -> Option<&'static str> {
+    let path = std::env::var_os("PATH")?;
+    let dirs: Vec<_> = std::env::split_paths(&path).collect();
+    PLAYERS.iter().copied().find(|player| {
+        dirs.iter().any(|dir| dir.join(player).is_file() || dir.join(format!("{player}.exe")).is_file())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn player_commands(path: &Path) -> Vec<Command> {
+    let mut afplay = Command::new("afplay");
+    afplay.arg(path);
+    vec![afplay]
+}
+
+#[cfg(windows)]
+fn player_commands(path: &Path) -> Vec<Command> {
+    let mut powershell = Command::new("powershell");
+    powershell.args([
+        "-NoProfile",
+        "-Command",
+        &format!("(New-Object Media.SoundPlayer '{}').PlaySync()", path.display()),
+    ]);
+    vec![powershell]
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn player_commands(path: &Path) -> Vec<Command> {
+    // PulseAudio/PipeWire first, then plain ALSA
+    PLAYERS
+        .iter()
+        .map(|player| {
+            let mut command = Command::new(player);
+            if *player == "aplay" {
+                command.arg("-q");
+            }
+            command.arg(path);
+            command
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_packs_have_every_cue() {
+        for (pack, files) in BUNDLED {
+            for cue in [Cue::WorkEnd, Cue::BreakEnd, Cue::Warning, Cue::Tick] {
+                let bytes = files.iter().find(|(stem, _)| *stem == cue.file_stem());
+                assert!(bytes.is_some_and(|(_, b)| b.starts_with(b"RIFF")), "{pack} is missing {cue:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn ticker_toggles_and_respects_silent() {
+        let config = crate::config::Config { tick_sound: true, ..Default::default() };
+        assert!(Ticker::new(&config, None, true).is_none());
+        let ticker = Ticker::new(&crate::config::Config::default(), None, false).unwrap();
+        assert_eq!(ticker.pack.name, "classic");
+        ticker.toggle();
+        assert!(ticker.on.load(Ordering::Relaxed));
+        ticker.toggle();
+        assert!(!ticker.on.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn user_file_prefers_wav_then_ogg() {
+        let dir = std::env::temp_dir().join(format!("pomitik-sound-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(user_file(&dir, Cue::Warning), None);
+        std::fs::write(dir.join("warning.ogg"), b"").unwrap();
+        assert_eq!(user_file(&dir, Cue::Warning), Some(dir.join("warning.ogg")));
+        std::fs::write(dir.join("warning.wav"), b"").unwrap();
+        assert_eq!(user_file(&dir, Cue::Warning), Some(dir.join("warning.wav")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
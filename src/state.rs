@@ -0,0 +1,283 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::duration::TimeStyle;
+use crate::frame::{mini_bar, MiniBar};
+use crate::timer::TimerContext;
+
+/// Bumped whenever a field changes meaning or a required field is added.
+/// Readers ignore files written with a different version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Snapshot of the running timer, written whenever it changes so that
+/// `tik status` and other processes can see what's going on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeState {
+    #[serde(default)]
+    pub version: u32,
+    /// Process that owns the timer; the file is stale once it has exited.
+    pub pid: u32,
+    pub name: String,
+    pub phase: TimerContext,
+    /// (current round, total rounds) for session phases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round: Option<(u32, u32)>,
+    pub total_secs: u64,
+    /// Remaining time as of `updated_at`.
+    pub remaining_secs: u64,
+    pub paused: bool,
+    pub updated_at: DateTime<Local>,
+}
+
+impl RuntimeState {
+    /// Remaining seconds at `now`, counting down from the last write unless paused.
+    pub fn remaining_at(&self, now: DateTime<Local>) -> u64 {
+        if self.paused {
+            return self.remaining_secs;
+        }
+        let elapsed = (now - self.updated_at).num_seconds().max(0) as u64;
+        self.remaining_secs.saturating_sub(elapsed)
+    }
+}
+
+pub fn state_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pomitik")
+        .join("state.json")
+}
+
+pub fn write(state: &RuntimeState) -> std::io::Result<()> {
+    write_to(&state_path(), state)
+}
+
+/// Write to a temporary file and rename it over the old one, so readers see
+/// either the previous state or the new one, never a partial write.
+fn write_to(path: &Path, state: &RuntimeState) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    let json = serde_json::to_string_pretty(state)?;
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+/// The running timer, if any. A state file left behind by a process that has
+/// since exited is removed and reported as no timer.
+pub fn read() -> Option<RuntimeState> {
+    let path = state_path();
+    let state = read_from(&path)?;
+    if !process_alive(state.pid) {
+        let _ = std::fs::remove_file(&path);
+        return None;
+    }
+    Some(state)
+}
+
+fn read_from(path: &Path) -> Option<RuntimeState> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    parse(&contents)
+}
+
+fn parse(contents: &str) -> Option<RuntimeState> {
+    let state: RuntimeState = serde_json::from_str(contents).ok()?;
+    (state.version == SCHEMA_VERSION).then_some(state)
+}
+
+/// Remove the state file if this process owns it.
+pub fn clear() {
+    let path = state_path();
+    if read_from(&path).is_some_and(|s| s.pid == std::process::id()) {
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/// Another process's running work timer, if any. Breaks don't count: starting
+/// a timer during someone else's break doesn't double-count focus time.
+pub fn running_work() -> Option<RuntimeState> {
+    read().filter(|s| s.pid != std::process::id() && s.phase != TimerContext::Break)
+}
+
+/// Warning shown before starting a timer alongside `other`.
+pub fn overlap_warning(other: &RuntimeState, now: DateTime<Local>) -> String {
+    let total = crate::duration::Duration::from_secs(other.total_secs).format_compact();
+    let remaining = TimeStyle::Clock.format(other.remaining_at(now) as i64);
+    let paused = if other.paused { ", paused" } else { "" };
+    format!("a {total} '{}' timer is already running ({remaining} left{paused})", other.name)
+}
+
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks for existence; EPERM means it exists but isn't ours
+    let exists = unsafe { libc::kill(pid, 0) } == 0;
+    exists || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+pub fn process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+/// Characters in the `--bar` progress indicator.
+const MINI_BAR_WIDTH: usize = 4;
+
+pub fn print_status(json: bool, style: TimeStyle, bar: Option<MiniBar>) {
+    let Some(state) = read() else {
+        if json {
+            println!("null");
+        } else {
+            println!("No timer running.");
+        }
+        return;
+    };
+    if json {
+        match serde_json::to_string_pretty(&state) {
+            Ok(s) => println!("{s}"),
+            Err(e) => eprintln!("Failed to serialize state: {e}"),
+        }
+        return;
+    }
+    println!("{}", status_line(&state, Local::now(), style, bar));
+}
+
+/// One-line status; the compact style is short enough for a status bar,
+/// with an optional mini progress bar before the time.
+pub fn status_line(state: &RuntimeState, now: DateTime<Local>, style: TimeStyle, bar: Option<MiniBar>) -> String {
+    let left = state.remaining_at(now);
+    let remaining = style.format(left as i64);
+    if style == TimeStyle::Compact {
+        let paused = if state.paused { " paused" } else { "" };
+        let bar = bar
+            .map(|bar| {
+                let done = if state.total_secs > 0 { 1.0 - left as f64 / state.total_secs as f64 } else { 1.0 };
+                format!("{} ", mini_bar(done, MINI_BAR_WIDTH, bar))
+            })
+            .unwrap_or_default();
+        return format!("{} {bar}{remaining}{paused}", state.phase.label());
+    }
+    let round = state
+        .round
+        .map(|(current, total)| format!(", round {current}/{total}"))
+        .unwrap_or_default();
+    let paused = if state.paused { " (paused)" } else { "" };
+    format!("{} [{}{round}] {remaining} remaining{paused}", state.name, state.phase.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(paused: bool) -> RuntimeState {
+        RuntimeState {
+            version: SCHEMA_VERSION,
+            pid: std::process::id(),
+            name: "pomodoro".to_string(),
+            phase: TimerContext::Work,
+            round: Some((2, 4)),
+            total_secs: 1500,
+            remaining_secs: 600,
+            paused,
+            updated_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn remaining_counts_down_from_last_write() {
+        let state = sample(false);
+        let later = state.updated_at + chrono::Duration::seconds(90);
+        assert_eq!(state.remaining_at(later), 510);
+    }
+
+    #[test]
+    fn remaining_frozen_while_paused() {
+        let state = sample(true);
+        let later = state.updated_at + chrono::Duration::seconds(90);
+        assert_eq!(state.remaining_at(later), 600);
+    }
+
+    #[test]
+    fn status_line_includes_round_and_pause() {
+        let state = sample(true);
+        assert_eq!(
+            status_line(&state, state.updated_at, TimeStyle::Clock, None),
+            "pomodoro [work, round 2/4] 10:00 remaining (paused)"
+        );
+        assert_eq!(status_line(&state, state.updated_at, TimeStyle::Compact, None), "work 10m paused");
+    }
+
+    #[test]
+    fn compact_status_with_mini_bar() {
+        // 900 of 1500 seconds done: 60% of four cells
+        let state = sample(false);
+        assert_eq!(
+            status_line(&state, state.updated_at, TimeStyle::Compact, Some(MiniBar::Braille)),
+            "work \u{28FF}\u{28FF}\u{2846}\u{2800} 10m"
+        );
+    }
+
+    #[test]
+    fn overlap_warning_names_the_other_timer() {
+        let state = sample(false);
+        assert_eq!(
+            overlap_warning(&state, state.updated_at),
+            "a 25m 'pomodoro' timer is already running (10:00 left)"
+        );
+    }
+
+    #[test]
+    fn other_schema_versions_are_ignored() {
+        let mut state = sample(false);
+        state.version = SCHEMA_VERSION + 1;
+        assert!(parse(&serde_json::to_string(&state).unwrap()).is_none());
+        state.version = SCHEMA_VERSION;
+        assert_eq!(parse(&serde_json::to_string(&state).unwrap()), Some(state));
+    }
+
+    #[test]
+    fn write_replaces_file_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("pomitik-state-test-{}", std::process::id()));
+        let path = dir.join("state.json");
+        let first = sample(false);
+        let second = RuntimeState { remaining_secs: 10, ..first.clone() };
+        write_to(&path, &first).unwrap();
+        write_to(&path, &second).unwrap();
+        assert_eq!(read_from(&path), Some(second));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn own_process_is_alive() {
+        assert!(process_alive(std::process::id()));
+        assert!(!process_alive(u32::MAX));
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let state = sample(false);
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"phase\":\"work\""));
+        assert_eq!(serde_json::from_str::<RuntimeState>(&json).unwrap(), state);
+    }
+}
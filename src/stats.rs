@@ -0,0 +1,493 @@
+//! Figures computed from the log and session records: per-day totals and the
+//! daily focus score, shown by `tik log` and printed by `tik log --json`, and
+//! the longer view of `tik stats`.
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::config::Config;
+use crate::dates::DateRange;
+use crate::log::{LogEntry, SessionRecord};
+
+/// How much each part counts towards the focus score. Parts without data
+/// that day (no sessions, no breaks, no goal) are left out and the rest
+/// scaled up.
+const COMPLETION_WEIGHT: f64 = 0.5;
+const BREAKS_WEIGHT: f64 = 0.25;
+const GOAL_WEIGHT: f64 = 0.25;
+
+/// Breaks looked back on for the skipped-breaks nudge: the most recent ones
+/// from the last week, and how many of them must have been skipped.
+const RECENT_BREAKS: usize = 10;
+const MIN_RECENT_BREAKS: usize = 5;
+const RECENT_DAYS: i64 = 7;
+const SKIPPED_SHARE: f64 = 0.6;
+
+/// What the score needs from the config: which blocks are breaks, and the
+/// daily goal from `[plan]`.
+pub struct Scoring<'a> {
+    pub breaks: HashSet<&'a str>,
+    pub goal_secs: Option<u64>,
+}
+
+impl<'a> Scoring<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        let goal_secs = config
+            .plan
+            .daily_goal
+            .as_deref()
+            .and_then(|goal| crate::duration::Duration::parse(goal).ok())
+            .map(|d| d.total_secs)
+            .filter(|&secs| secs > 0);
+        Scoring { breaks: break_names(config), goal_secs }
+    }
+
+    /// Whether the block was a break, taken or skipped.
+    pub fn is_break(&self, entry: &LogEntry) -> bool {
+        entry.skipped_secs > 0 || self.breaks.contains(entry.name.as_str())
+    }
+}
+
+/// Names of the break presets of every session.
+pub fn break_names(config: &Config) -> HashSet<&str> {
+    config
+        .sessions
+        .values()
+        .flat_map(|s| [s.break_preset.as_str(), s.long_break.as_str()])
+        .collect()
+}
+
+/// A day's focus score out of 100, with the parts it's made of (each 0.0
+/// to 1.0, None when there was nothing to measure).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FocusScore {
+    pub score: u32,
+    /// Session rounds finished out of rounds planned.
+    pub completion: Option<f64>,
+    /// Breaks taken out of breaks due.
+    pub breaks: Option<f64>,
+    /// Focus time out of the daily goal, at most 1.
+    pub goal: Option<f64>,
+}
+
+impl FocusScore {
+    /// "Focus score 82/100: 75% of rounds finished, 100% of breaks taken".
+    pub fn line(&self) -> String {
+        let percent = |part: f64| (part * 100.0).round() as u32;
+        let parts: Vec<String> = [
+            self.completion.map(|p| format!("{}% of rounds finished", percent(p))),
+            self.breaks.map(|p| format!("{}% of breaks taken", percent(p))),
+            self.goal.map(|p| format!("{}% of the daily goal", percent(p))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        format!("Focus score {}/100: {}", self.score, parts.join(", "))
+    }
+}
+
+fn on_day(at: &DateTime<Local>, day: NaiveDate) -> bool {
+    at.date_naive() == day
+}
+
+/// The score for `day`, or None if nothing that day can be scored.
+pub fn focus_score(entries: &[LogEntry], sessions: &[SessionRecord], scoring: &Scoring, day: NaiveDate) -> Option<FocusScore> {
+    let (planned, completed) = sessions
+        .iter()
+        .filter(|s| on_day(&s.ended_at, day))
+        .fold((0, 0), |(planned, completed), s| (planned + s.rounds_planned, completed + s.rounds_completed));
+    let completion = (planned > 0).then(|| f64::from(completed.min(planned)) / f64::from(planned));
+
+    let day_entries: Vec<&LogEntry> = entries.iter().filter(|e| on_day(&e.completed_at, day)).collect();
+    let (taken, skipped) = day_entries
+        .iter()
+        .filter(|e| scoring.is_break(e))
+        .fold((0u32, 0u32), |(taken, skipped), e| if e.skipped_secs > 0 { (taken, skipped + 1) } else { (taken + 1, skipped) });
+    let breaks = (taken + skipped > 0).then(|| f64::from(taken) / f64::from(taken + skipped));
+
+    let focus_secs: u64 = day_entries.iter().filter(|e| !scoring.is_break(e)).map(|e| e.duration_secs).sum();
+    let goal = scoring
+        .goal_secs
+        .filter(|_| focus_secs > 0 || completion.is_some())
+        .map(|goal| (focus_secs as f64 / goal as f64).min(1.0));
+
+    let weighted = [(completion, COMPLETION_WEIGHT), (breaks, BREAKS_WEIGHT), (goal, GOAL_WEIGHT)];
+    let weight: f64 = weighted.iter().filter(|(part, _)| part.is_some()).map(|(_, w)| w).sum();
+    if weight == 0.0 {
+        return None;
+    }
+    let total: f64 = weighted.iter().filter_map(|(part, w)| part.map(|p| p * w)).sum();
+    Some(FocusScore { score: (total / weight * 100.0).round() as u32, completion, breaks, goal })
+}
+
+/// A nudge for the start of a session when most recent breaks were skipped.
+pub fn skipped_breaks_nudge(entries: &[LogEntry], scoring: &Scoring, now: DateTime<Local>) -> Option<String> {
+    let since = now - chrono::Duration::days(RECENT_DAYS);
+    let mut recent: Vec<&LogEntry> =
+        entries.iter().filter(|e| e.completed_at >= since && scoring.is_break(e)).collect();
+    recent.sort_by_key(|e| std::cmp::Reverse(e.completed_at));
+    recent.truncate(RECENT_BREAKS);
+    let skipped = recent.iter().filter(|e| e.skipped_secs > 0).count();
+    if recent.len() < MIN_RECENT_BREAKS || (skipped as f64) < recent.len() as f64 * SKIPPED_SHARE {
+        return None;
+    }
+    Some(format!("you skipped {skipped} of your last {} breaks; even a short one helps you keep going", recent.len()))
+}
+
+/// One day's totals, as printed by `tik log --json`.
+#[derive(Debug, Serialize)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub pomodoros: usize,
+    pub focus_secs: u64,
+    pub break_secs: u64,
+    pub focus_score: Option<FocusScore>,
+}
+
+pub fn day_stats(entries: &[LogEntry], sessions: &[SessionRecord], scoring: &Scoring, day: NaiveDate) -> DayStats {
+    let (breaks, blocks): (Vec<&LogEntry>, Vec<&LogEntry>) =
+        entries.iter().filter(|e| on_day(&e.completed_at, day)).partition(|e| scoring.is_break(e));
+    DayStats {
+        date: day,
+        pomodoros: blocks.len(),
+        focus_secs: blocks.iter().map(|e| e.duration_secs).sum(),
+        break_secs: breaks.iter().map(|e| e.duration_secs).sum(),
+        focus_score: focus_score(entries, sessions, scoring, day),
+    }
+}
+
+/// The days `tik log --json` covers: `period` from its start, the `--since`
+/// and `--until` days, or just today when neither was given.
+pub fn json_range(period: Option<crate::log::Period>, dates: DateRange, now: DateTime<Local>) -> DateRange {
+    match period {
+        Some(period) => dates.starting(period.starts(now).0),
+        None if dates.is_all() => dates.starting(now.date_naive()),
+        None => dates,
+    }
+}
+
+/// Print a JSON array of `DayStats`, one per day of `range` up to today.
+pub fn print_json(config: &Config, range: &DateRange) {
+    let scoring = Scoring::new(config);
+    let entries = crate::log::read_entries();
+    let sessions = crate::log::read_sessions();
+    let days: Vec<DayStats> = json_days(&entries, range, Local::now().date_naive())
+        .into_iter()
+        .map(|day| day_stats(&entries, &sessions, &scoring, day))
+        .collect();
+    match serde_json::to_string_pretty(&days) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Failed to serialize stats: {e}"),
+    }
+}
+
+/// The days of `range` up to today, from the first logged day when it has no
+/// start.
+fn json_days(entries: &[LogEntry], range: &DateRange, today: NaiveDate) -> Vec<NaiveDate> {
+    let first = range.since.or_else(|| entries.iter().map(|e| e.completed_at.date_naive()).min()).unwrap_or(today);
+    first.iter_days().take_while(|day| *day <= today && range.contains(*day)).collect()
+}
+
+/// Longest run of consecutive days in `days` (sorted): first day, last day.
+pub fn longest_streak(days: &[NaiveDate]) -> Option<(NaiveDate, NaiveDate)> {
+    let mut streak: Option<(NaiveDate, NaiveDate)> = None;
+    let mut run_start = None;
+    for (i, day) in days.iter().enumerate() {
+        let start = match (run_start, i.checked_sub(1).map(|p| days[p])) {
+            (Some(start), Some(previous)) if previous.succ_opt() == Some(*day) => start,
+            _ => *day,
+        };
+        run_start = Some(start);
+        if streak.is_none_or(|(first, last)| (*day - start) > (last - first)) {
+            streak = Some((start, *day));
+        }
+    }
+    streak
+}
+
+/// "3 days (Mar 1 to Mar 3)".
+pub fn streak_line((first, last): (NaiveDate, NaiveDate)) -> String {
+    let count = (last - first).num_days() + 1;
+    let days = if first == last {
+        first.format("%b %-d").to_string()
+    } else {
+        format!("{} to {}", first.format("%b %-d"), last.format("%b %-d"))
+    };
+    format!("{count} day{} ({days})", if count == 1 { "" } else { "s" })
+}
+
+/// How far back `tik stats` looks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    Week,
+    Month,
+    All,
+}
+
+impl Range {
+    /// First day counted, or None for the whole log.
+    fn since(self, now: DateTime<Local>) -> Option<NaiveDate> {
+        match self {
+            Range::Week => Some(crate::log::Period::Week.starts(now).0),
+            Range::Month => now.date_naive().with_day(1),
+            Range::All => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Range::Week => "This week",
+            Range::Month => "This month",
+            Range::All => "All time",
+        }
+    }
+}
+
+/// The figures `tik stats` shows for a range.
+#[derive(Debug, PartialEq)]
+struct Summary {
+    pomodoros: usize,
+    focus_secs: u64,
+    sessions: usize,
+    rounds_planned: u32,
+    rounds_completed: u32,
+    /// Wall-clock time of all sessions, breaks included.
+    session_secs: u64,
+    streak: Option<(NaiveDate, NaiveDate)>,
+    /// Pomodoros and focus time per weekday, Monday first.
+    weekdays: [(usize, u64); 7],
+}
+
+fn summarize(entries: &[LogEntry], sessions: &[SessionRecord], scoring: &Scoring, range: &DateRange) -> Summary {
+    let counted = |at: &DateTime<Local>| range.contains(at.date_naive());
+    let blocks: Vec<&LogEntry> = entries.iter().filter(|e| counted(&e.completed_at) && !scoring.is_break(e)).collect();
+    let sessions: Vec<&SessionRecord> = sessions.iter().filter(|s| counted(&s.ended_at)).collect();
+
+    let mut weekdays = [(0, 0); 7];
+    for block in &blocks {
+        let day = &mut weekdays[block.completed_at.weekday().num_days_from_monday() as usize];
+        day.0 += 1;
+        day.1 += block.duration_secs;
+    }
+    let mut days: Vec<NaiveDate> = blocks.iter().map(|b| b.completed_at.date_naive()).collect();
+    days.sort();
+    days.dedup();
+
+    Summary {
+        pomodoros: blocks.len(),
+        focus_secs: blocks.iter().map(|b| b.duration_secs).sum(),
+        sessions: sessions.len(),
+        rounds_planned: sessions.iter().map(|s| s.rounds_planned).sum(),
+        rounds_completed: sessions.iter().map(|s| s.rounds_completed.min(s.rounds_planned)).sum(),
+        session_secs: sessions.iter().map(|s| (s.ended_at - s.started_at).num_seconds().max(0) as u64).sum(),
+        streak: longest_streak(&days),
+        weekdays,
+    }
+}
+
+/// Width of the longest weekday bar.
+const WEEKDAY_BAR: usize = 20;
+
+/// The figures, then a table of the weekdays.
+fn summary_lines(summary: &Summary) -> Vec<String> {
+    let human = crate::log::format_duration_human;
+    let mut facts = vec![("Focus time", format!("{} in {} pomodoros", human(summary.focus_secs), summary.pomodoros))];
+    if summary.rounds_planned > 0 {
+        let rate = (f64::from(summary.rounds_completed) / f64::from(summary.rounds_planned) * 100.0).round();
+        facts.push((
+            "Completion rate",
+            format!(
+                "{rate}% ({} of {} rounds in {} session{})",
+                summary.rounds_completed,
+                summary.rounds_planned,
+                summary.sessions,
+                if summary.sessions == 1 { "" } else { "s" }
+            ),
+        ));
+    }
+    if summary.sessions > 0 {
+        facts.push(("Average session", human(summary.session_secs / summary.sessions as u64)));
+    }
+    if summary.pomodoros > 0 {
+        facts.push(("Average block", human(summary.focus_secs / summary.pomodoros as u64)));
+    }
+    if let Some(streak) = summary.streak {
+        facts.push(("Longest streak", streak_line(streak)));
+    }
+    let mut lines: Vec<String> = facts.into_iter().map(|(label, value)| format!("  {label:<17}{value}")).collect();
+
+    lines.push(String::new());
+    lines.push(format!("  {:<5}{:>9}{:>10}", "Day", "Blocks", "Focus"));
+    let most = summary.weekdays.iter().map(|(_, secs)| *secs).max().unwrap_or(0).max(1);
+    for (i, (blocks, secs)) in summary.weekdays.iter().enumerate() {
+        let day = Weekday::try_from(i as u8).map_or(String::new(), |day| day.to_string());
+        let bar = "█".repeat((*secs as usize * WEEKDAY_BAR).div_ceil(most as usize));
+        lines.push(format!("  {day:<5}{blocks:>9}{:>10}  {bar}", human(*secs)).trim_end().to_string());
+    }
+    lines
+}
+
+/// Print `tik stats` for the range.
+/// `tik stats` for `range`, cut to the days in `dates`.
+pub fn print(config: &Config, range: Range, dates: &DateRange) {
+    let scoring = Scoring::new(config);
+    let since = range.since(Local::now());
+    let counted = since.map_or(*dates, |since| dates.starting(since));
+    let summary = summarize(&crate::log::read_entries(), &crate::log::read_sessions(), &scoring, &counted);
+    let title = match since {
+        _ if !dates.is_all() => counted.title(),
+        Some(since) => format!("{} (since {})", range.title(), since.format("%a %b %-d")),
+        None => range.title().to_string(),
+    };
+    if summary.pomodoros == 0 && summary.sessions == 0 {
+        println!("No focus logged: {}.", title.to_lowercase());
+        return;
+    }
+    println!("{title}\n");
+    for line in summary_lines(&summary) {
+        println!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::SessionEnd;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap()
+    }
+
+    fn block(name: &str, hour: u32, skipped_secs: u64) -> LogEntry {
+        LogEntry { name: name.to_string(), duration_secs: 1500, completed_at: at(hour), skipped_secs, ..Default::default() }
+    }
+
+    fn session(completed: u32, planned: u32) -> SessionRecord {
+        SessionRecord {
+            name: "pomodoro".to_string(),
+            rounds_planned: planned,
+            rounds_completed: completed,
+            rounds_skipped: 0,
+            focus_secs: 0,
+            work_secs: 1500,
+            note: None,
+            started_at: at(9),
+            ended_at: at(12),
+            end: SessionEnd::Completed,
+        }
+    }
+
+    fn scoring(goal_secs: Option<u64>) -> Scoring<'static> {
+        Scoring { breaks: HashSet::from(["break", "long-break"]), goal_secs }
+    }
+
+    #[test]
+    fn parts_weighted_into_score() {
+        let entries = [block("pomodoro", 9, 0), block("break", 9, 0), block("pomodoro", 10, 0), block("break", 10, 300)];
+        let day = at(9).date_naive();
+        let score = focus_score(&entries, &[session(3, 4)], &scoring(Some(6000)), day).unwrap();
+        assert_eq!((score.completion, score.breaks, score.goal), (Some(0.75), Some(0.5), Some(0.5)));
+        // 0.75 * 0.5 + 0.5 * 0.25 + 0.5 * 0.25
+        assert_eq!(score.score, 63);
+        assert_eq!(score.line(), "Focus score 63/100: 75% of rounds finished, 50% of breaks taken, 50% of the daily goal");
+    }
+
+    #[test]
+    fn missing_parts_left_out() {
+        let day = at(9).date_naive();
+        let score = focus_score(&[block("pomodoro", 9, 0)], &[session(2, 2)], &scoring(None), day).unwrap();
+        assert_eq!((score.score, score.breaks, score.goal), (100, None, None));
+        let score = focus_score(&[block("pomodoro", 9, 0)], &[], &scoring(Some(3000)), day).unwrap();
+        assert_eq!(score.score, 50);
+        assert_eq!(focus_score(&[block("pomodoro", 9, 0)], &[], &scoring(None), day), None);
+        assert_eq!(focus_score(&[], &[], &scoring(Some(3000)), day), None);
+    }
+
+    #[test]
+    fn nudge_when_most_recent_breaks_skipped() {
+        let now = at(18);
+        let mut entries: Vec<LogEntry> = (9..13).map(|hour| block("break", hour, 300)).collect();
+        assert_eq!(skipped_breaks_nudge(&entries, &scoring(None), now), None);
+        entries.push(block("break", 13, 0));
+        entries.push(block("pomodoro", 14, 0));
+        assert_eq!(
+            skipped_breaks_nudge(&entries, &scoring(None), now).unwrap(),
+            "you skipped 4 of your last 5 breaks; even a short one helps you keep going"
+        );
+        entries.extend((14..17).map(|hour| block("long-break", hour, 0)));
+        assert_eq!(skipped_breaks_nudge(&entries, &scoring(None), now), None);
+        // Breaks from over a week ago don't count
+        assert_eq!(skipped_breaks_nudge(&entries[..5], &scoring(None), now + chrono::Duration::days(8)), None);
+    }
+
+    #[test]
+    fn day_stats_split_breaks_from_focus() {
+        let entries = [block("pomodoro", 9, 0), block("break", 9, 0), block("pomodoro", 10, 0)];
+        let stats = day_stats(&entries, &[], &scoring(None), at(9).date_naive());
+        assert_eq!((stats.pomodoros, stats.focus_secs, stats.break_secs), (2, 3000, 1500));
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.starts_with(r#"{"date":"2026-03-02","pomodoros":2"#));
+    }
+
+    #[test]
+    fn json_is_today_unless_days_are_given() {
+        let now = at(18);
+        let today = now.date_naive();
+        let day = |month, d| NaiveDate::from_ymd_opt(2026, month, d).unwrap();
+        assert_eq!(json_range(None, DateRange::default(), now), DateRange { since: Some(today), until: None });
+        assert_eq!(json_range(Some(crate::log::Period::Week), DateRange::default(), now).since, Some(day(3, 2)));
+
+        // Only --until: the days up to it, not today onwards
+        let until = DateRange { since: None, until: Some(day(3, 1)) };
+        assert_eq!(json_range(None, until, now), until);
+        let earlier = LogEntry { completed_at: now - chrono::Duration::days(3), ..block("pomodoro", 9, 0) };
+        assert_eq!(json_days(&[earlier], &until, today), [day(2, 27), day(2, 28), day(3, 1)]);
+        assert_eq!(json_days(&[], &until, today), Vec::<NaiveDate>::new());
+    }
+
+    #[test]
+    fn streaks_are_runs_of_consecutive_days() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        assert_eq!(longest_streak(&[]), None);
+        assert_eq!(longest_streak(&[day(1), day(2), day(4), day(5), day(6), day(9)]), Some((day(4), day(6))));
+        assert_eq!(streak_line((day(4), day(6))), "3 days (Mar 4 to Mar 6)");
+        assert_eq!(streak_line((day(9), day(9))), "1 day (Mar 9)");
+    }
+
+    #[test]
+    fn summary_counts_the_range_only() {
+        let mut entries = vec![block("pomodoro", 9, 0), block("break", 9, 0), block("pomodoro", 10, 0)];
+        // The day before, a Sunday
+        entries.push(LogEntry { completed_at: at(9) - chrono::Duration::days(1), ..block("pomodoro", 9, 0) });
+        let sessions = [session(3, 4)];
+        let all = summarize(&entries, &sessions, &scoring(None), &DateRange::default());
+        assert_eq!((all.pomodoros, all.focus_secs), (3, 4500));
+        assert_eq!((all.sessions, all.rounds_completed, all.rounds_planned, all.session_secs), (1, 3, 4, 3 * 3600));
+        assert_eq!(all.streak.map(|(first, last)| (last - first).num_days()), Some(1));
+        assert_eq!((all.weekdays[0], all.weekdays[6]), ((2, 3000), (1, 1500)));
+
+        let since = summarize(&entries, &sessions, &scoring(None), &DateRange { since: Some(at(9).date_naive()), until: None });
+        assert_eq!((since.pomodoros, since.weekdays[6]), (2, (0, 0)));
+    }
+
+    #[test]
+    fn summary_lines_align() {
+        let summary = summarize(&[block("pomodoro", 9, 0), block("pomodoro", 10, 0)], &[session(3, 4)], &scoring(None), &DateRange::default());
+        let lines = summary_lines(&summary);
+        assert_eq!(
+            lines[..5],
+            [
+                "  Focus time       50m in 2 pomodoros",
+                "  Completion rate  75% (3 of 4 rounds in 1 session)",
+                "  Average session  3h 0m",
+                "  Average block    25m",
+                "  Longest streak   1 day (Mar 2)",
+            ]
+        );
+        assert_eq!(lines[6], "  Day     Blocks     Focus");
+        assert_eq!(lines[7], format!("  Mon          2       50m  {}", "█".repeat(WEEKDAY_BAR)));
+        assert_eq!(lines[8], "  Tue          0        0m");
+    }
+}
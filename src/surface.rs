@@ -0,0 +1,226 @@
+//! Where the renderer draws. The terminal is one `Surface`; a `Buffer` is
+//! another, a grid of cells that tests read back to check a frame's layout
+//! at any terminal size without a terminal.
+
+use crossterm::{
+    cursor,
+    queue,
+    style::{Attribute, Color, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+use std::io::{self, Write};
+
+/// How a run of text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub fg: Color,
+    pub bold: bool,
+    pub crossed_out: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style { fg: Color::Reset, bold: false, crossed_out: false, reverse: false };
+
+    pub fn fg(fg: Color) -> Self {
+        Style { fg, ..Style::PLAIN }
+    }
+
+    pub fn bold(self) -> Self {
+        Style { bold: true, ..self }
+    }
+
+    pub fn crossed_out(self) -> Self {
+        Style { crossed_out: true, ..self }
+    }
+
+    pub fn reverse(self) -> Self {
+        Style { reverse: true, ..self }
+    }
+}
+
+pub trait Surface {
+    /// Columns and rows.
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    /// Blank the whole surface in `background`, which text drawn after keeps.
+    fn clear(&mut self, background: Color) -> io::Result<()>;
+
+    /// `text` from `col` on `row`; whatever runs past the edge is lost.
+    fn print(&mut self, col: u16, row: u16, text: &str, style: Style) -> io::Result<()>;
+
+    /// Escape sequences that draw an image with its top left at `col`, `row`.
+    /// Surfaces that can't show images drop them.
+    #[cfg_attr(not(feature = "graphics"), allow(dead_code))]
+    fn graphics(&mut self, col: u16, row: u16, sequence: &[u8]) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// The terminal on stdout. Output is queued and goes out on `flush`, so a
+/// frame is written in one go.
+pub struct Terminal {
+    stdout: io::Stdout,
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        Terminal { stdout: io::stdout() }
+    }
+}
+
+impl Surface for Terminal {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn clear(&mut self, background: Color) -> io::Result<()> {
+        queue!(self.stdout, SetForegroundColor(Color::Reset), SetBackgroundColor(background), terminal::Clear(ClearType::All))
+    }
+
+    fn print(&mut self, col: u16, row: u16, text: &str, style: Style) -> io::Result<()> {
+        queue!(self.stdout, cursor::MoveTo(col, row), SetForegroundColor(style.fg))?;
+        if style.bold {
+            queue!(self.stdout, SetAttribute(Attribute::Bold))?;
+        }
+        if style.crossed_out {
+            queue!(self.stdout, SetAttribute(Attribute::CrossedOut))?;
+        }
+        if style.reverse {
+            queue!(self.stdout, SetAttribute(Attribute::Reverse))?;
+        }
+        // Only the foreground and attributes are reset, so the background stays
+        queue!(
+            self.stdout,
+            Print(text),
+            SetAttribute(Attribute::NormalIntensity),
+            SetAttribute(Attribute::NotCrossedOut),
+            SetAttribute(Attribute::NoReverse),
+            SetForegroundColor(Color::Reset),
+        )
+    }
+
+    fn graphics(&mut self, col: u16, row: u16, sequence: &[u8]) -> io::Result<()> {
+        queue!(self.stdout, cursor::MoveTo(col, row))?;
+        self.stdout.write_all(sequence)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+
+/// One cell of a `Buffer`. The right half of a wide character is `None`.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub symbol: Option<char>,
+    pub style: Style,
+}
+
+/// A surface in memory, for reading frames back.
+#[cfg(test)]
+pub struct Buffer {
+    cols: u16,
+    rows: u16,
+    background: Color,
+    cells: Vec<Cell>,
+}
+
+#[cfg(test)]
+impl Buffer {
+    const BLANK: Cell = Cell { symbol: Some(' '), style: Style::PLAIN };
+
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Buffer { cols, rows, background: Color::Reset, cells: vec![Buffer::BLANK; cols as usize * rows as usize] }
+    }
+
+    pub fn cell(&self, col: u16, row: u16) -> Cell {
+        self.cells[row as usize * self.cols as usize + col as usize]
+    }
+
+    /// The background the surface was last cleared in.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// The text of each row, without trailing blanks.
+    pub fn lines(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.cols.max(1) as usize)
+            .map(|row| row.iter().filter_map(|cell| cell.symbol).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    /// Where `text` first appears, as (column, row).
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        self.lines().iter().enumerate().find_map(|(row, line)| {
+            let byte = line.find(text)?;
+            Some((crate::text::width(&line[..byte]), row as u16))
+        })
+    }
+}
+
+#[cfg(test)]
+impl Surface for Buffer {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok((self.cols, self.rows))
+    }
+
+    fn clear(&mut self, background: Color) -> io::Result<()> {
+        self.background = background;
+        self.cells.fill(Buffer::BLANK);
+        Ok(())
+    }
+
+    fn print(&mut self, col: u16, row: u16, text: &str, style: Style) -> io::Result<()> {
+        if row >= self.rows {
+            return Ok(());
+        }
+        let start = row as usize * self.cols as usize;
+        let mut col = col as usize;
+        for c in text.chars() {
+            let width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+            if width == 0 {
+                continue;
+            }
+            if col + width > self.cols as usize {
+                break;
+            }
+            self.cells[start + col] = Cell { symbol: Some(c), style };
+            if width == 2 {
+                self.cells[start + col + 1] = Cell { symbol: None, style };
+            }
+            col += width;
+        }
+        Ok(())
+    }
+
+    fn graphics(&mut self, _col: u16, _row: u16, _sequence: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_keeps_text_styles_and_wide_characters() {
+        let mut buffer = Buffer::new(10, 2);
+        buffer.print(1, 0, "ab", Style::fg(Color::Red).bold()).unwrap();
+        buffer.print(6, 1, "日本語", Style::PLAIN).unwrap();
+        assert_eq!(buffer.lines(), [" ab", "      日本"]);
+        assert_eq!(buffer.cell(2, 0).style, Style { fg: Color::Red, bold: true, crossed_out: false, reverse: false });
+        assert_eq!(buffer.cell(7, 1).symbol, None);
+        assert_eq!(buffer.find("本"), Some((8, 1)));
+
+        buffer.clear(Color::DarkBlue).unwrap();
+        assert_eq!(buffer.lines(), ["", ""]);
+        assert_eq!(buffer.background(), Color::DarkBlue);
+    }
+}
@@ -0,0 +1,189 @@
+//! `tik talk "intro 5m, demo 10m, Q&A 5m"`: one countdown for a whole talk,
+//! split into sections. The timer shows the section being given and the next
+//! cue, and flashes when a section's time is up. Talks aren't logged.
+
+use crate::config::Config;
+use crate::duration::Duration;
+use crate::events::{EventBus, TikEvent};
+use crate::footer::Footer;
+use crate::input::Input;
+use crate::render::Screen;
+use crate::session::SessionOptions;
+use crate::timer::{self, TimerContext};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub name: String,
+    pub secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Talk {
+    sections: Vec<Section>,
+}
+
+impl Talk {
+    /// Parse comma-separated `name duration` pairs. Names may contain spaces;
+    /// the duration is the last word.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let sections = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let (name, duration) = part
+                    .rsplit_once(char::is_whitespace)
+                    .ok_or_else(|| format!("Invalid section '{part}': expected a name and a duration, e.g. intro 5m"))?;
+                let duration = Duration::parse(duration).map_err(|e| format!("Invalid duration for '{}': {e}", name.trim()))?;
+                if duration.total_secs == 0 {
+                    return Err(format!("Section '{}' has no time", name.trim()));
+                }
+                Ok(Section { name: name.trim().to_string(), secs: duration.total_secs })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if sections.is_empty() {
+            return Err("No sections given, e.g. \"intro 5m, demo 10m, Q&A 5m\"".to_string());
+        }
+        Ok(Talk { sections })
+    }
+
+    pub fn total_secs(&self) -> u64 {
+        self.sections.iter().map(|s| s.secs).sum()
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// The section running `elapsed_secs` into the talk and the seconds left
+    /// in it. Past the end this is the last section with none left.
+    pub fn section_at(&self, elapsed_secs: u64) -> (usize, u64) {
+        let mut end = 0;
+        for (i, section) in self.sections.iter().enumerate() {
+            end += section.secs;
+            if elapsed_secs < end {
+                return (i, end - elapsed_secs);
+            }
+        }
+        (self.sections.len() - 1, 0)
+    }
+
+    /// The next cue and what follows it, e.g. "demo in 2:13 → Q&A 5m".
+    pub fn cue_line(&self, index: usize, section_left_secs: u64) -> String {
+        let left = Duration::from_secs(section_left_secs).format_hms();
+        let Some(next) = self.sections.get(index + 1) else {
+            return format!("wrap up in {left}");
+        };
+        let mut line = format!("{} in {left}", next.name);
+        for section in self.sections.iter().skip(index + 2).take(2) {
+            line.push_str(&format!(" \u{2192} {} {}", section.name, Duration::from_secs(section.secs).format_compact()));
+        }
+        line
+    }
+}
+
+/// Run the talk as a standalone timer with section cues. Nothing is logged.
+pub async fn run(config: &Config, talk: &Talk, options: SessionOptions<'_>) {
+    let events = EventBus::start(config, options.silent, false);
+    let input = (!options.headless && !options.no_input).then(Input::start);
+    let screen = if options.headless {
+        None
+    } else {
+        match Screen::enter(input.is_some()) {
+            Ok(screen) => Some(screen.with_graphics(config.graphics)),
+            Err(e) => {
+                eprintln!("Failed to setup terminal: {e}");
+                return;
+            }
+        }
+    };
+    let footer = screen.as_ref().and_then(|_| Footer::start(&config.footer));
+    let ack_prompt = (options.ack && !options.headless && !options.no_input).then_some(timer::ACK_PROMPT);
+    let total_secs = talk.total_secs();
+    let result = timer::run(timer::TimerParams {
+        name: "talk",
+        total_secs,
+        context: TimerContext::Standalone,
+        title: options.title,
+        round_info: None,
+        todos: None,
+        ack_prompt,
+        flow_extend_secs: 0,
+        colors: config.theme.colors_for(TimerContext::Standalone),
+        animate_completion: config.completion_animation(),
+        inhibit_sleep: config.inhibit_sleep,
+        screen: screen.as_ref(),
+        input: input.as_ref(),
+        blocker: None,
+        clock_format: &config.clock_format(),
+        frame_interval: config.frame_interval(),
+        tenths: options.tenths,
+        announce: options.a11y,
+        notify_at: options.notify_at,
+        sounds: events.sounds(),
+        ticker: None,
+        plan: None,
+        talk: Some(talk),
+        break_debt: None,
+        footer: footer.as_ref(),
+        events: &events,
+        labels: &crate::log::WorkLabels::default(),
+    })
+    .await;
+    drop(screen);
+
+    if result.outcome.is_completed() {
+        events.emit(TikEvent::Completed {
+            name: "talk".to_string(),
+            context: TimerContext::Standalone,
+            duration_secs: total_secs,
+            skipped: false,
+            next: None,
+            entries: Vec::new(),
+        });
+        println!("Talk over: {}", Duration::from_secs(total_secs).format_hms());
+    } else {
+        let (index, _) = talk.section_at(result.active_secs);
+        println!("Talk stopped during {} after {}", talk.sections()[index].name, Duration::from_secs(result.active_secs).format_hms());
+    }
+    events.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn talk() -> Talk {
+        Talk::parse("intro 5m, live demo 10m, Q&A 5m").unwrap()
+    }
+
+    #[test]
+    fn parse_sections() {
+        let talk = talk();
+        assert_eq!(talk.sections().len(), 3);
+        assert_eq!(talk.sections()[1], Section { name: "live demo".to_string(), secs: 600 });
+        assert_eq!(talk.total_secs(), 1200);
+        assert!(Talk::parse("intro").is_err());
+        assert!(Talk::parse("intro 5m, demo soon").is_err());
+        assert!(Talk::parse(" , ").is_err());
+        assert!(Talk::parse("intro 0s").is_err());
+    }
+
+    #[test]
+    fn section_at_boundaries() {
+        let talk = talk();
+        assert_eq!(talk.section_at(0), (0, 300));
+        assert_eq!(talk.section_at(299), (0, 1));
+        assert_eq!(talk.section_at(300), (1, 600));
+        assert_eq!(talk.section_at(1199), (2, 1));
+        assert_eq!(talk.section_at(5000), (2, 0));
+    }
+
+    #[test]
+    fn cue_line_names_the_next_sections() {
+        let talk = talk();
+        assert_eq!(talk.cue_line(0, 133), "live demo in 2:13 \u{2192} Q&A 5m");
+        assert_eq!(talk.cue_line(1, 60), "Q&A in 1:00");
+        assert_eq!(talk.cue_line(2, 30), "wrap up in 0:30");
+    }
+}
@@ -0,0 +1,60 @@
+//! Column math for terminal text. Emoji and CJK characters take two columns
+//! and most non-ASCII characters take more than one byte, so widths come from
+//! unicode-width rather than `len()`.
+
+use std::borrow::Cow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns `s` takes up on screen.
+pub fn width(s: &str) -> u16 {
+    s.width().min(u16::MAX as usize) as u16
+}
+
+/// First column that centers `s` in a span of `cols` columns.
+pub fn center(s: &str, cols: u16) -> u16 {
+    cols.saturating_sub(width(s)) / 2
+}
+
+/// `s` cut to at most `max` columns, ending in an ellipsis when anything was cut.
+pub fn truncate(s: &str, max: u16) -> Cow<'_, str> {
+    if width(s) <= max {
+        return Cow::Borrowed(s);
+    }
+    let Some(room) = (max as usize).checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > room {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out.push('\u{2026}');
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_count_two_columns() {
+        assert_eq!(width("focus"), 5);
+        assert_eq!(width("\u{96c6}\u{4e2d}"), 4);
+        assert_eq!(width("\u{1f345} tomato"), 9);
+        assert_eq!(center("\u{96c6}\u{4e2d}", 10), 3);
+    }
+
+    #[test]
+    fn truncate_respects_columns() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a longer title", 8), "a longe\u{2026}");
+        // A wide character that would straddle the limit is dropped whole
+        assert_eq!(truncate("\u{96c6}\u{4e2d}\u{529b}", 4), "\u{96c6}\u{2026}");
+        assert_eq!(truncate("abc", 0), "");
+    }
+}
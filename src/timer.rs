@@ -1,40 +1,223 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Instant;
 use tokio::sync::watch;
 
+use crate::events::TikEvent;
 use crate::render::Renderer;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TimerContext {
+    #[serde(rename = "timer")]
     Standalone,
     Work,
     Break,
 }
 
+impl TimerContext {
+    pub fn label(self) -> &'static str {
+        match self {
+            TimerContext::Standalone => "timer",
+            TimerContext::Work => "work",
+            TimerContext::Break => "break",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TimerOutcome {
     Completed,
     Skipped,
     StoppedEarly,
     Quit,
+    /// The countdown finished, but quit was requested before the completion
+    /// state ended. The phase counts as done; the caller should not continue.
+    CompletedThenQuit,
 }
 
-pub async fn run(
-    total_secs: u64,
-    _name: &str,
-    context: TimerContext,
-    title: Option<&str>,
-    round_info: Option<(u32, Arc<AtomicU32>)>,
-    todos: Option<Arc<Mutex<crate::todo::TodoList>>>,
-) -> TimerOutcome {
-    let renderer = Renderer::new();
-    if let Err(e) = renderer.setup() {
-        eprintln!("Failed to setup terminal: {e}");
-        return TimerOutcome::Quit;
+impl TimerOutcome {
+    pub fn is_completed(self) -> bool {
+        matches!(self, TimerOutcome::Completed | TimerOutcome::CompletedThenQuit)
     }
+}
+
+/// Prompt shown in the completion state when acknowledgment is required.
+pub const ACK_PROMPT: &str = "\u{2713} done \u{2014} press any key to continue";
+
+/// Ack prompt for a session phase, naming what comes next.
+pub fn ack_prompt_for(next: &str) -> String {
+    format!("\u{2713} done \u{2014} press any key {next}")
+}
+
+pub struct TimerParams<'a> {
+    pub name: &'a str,
+    pub total_secs: u64,
+    pub context: TimerContext,
+    pub title: Option<&'a str>,
+    pub round_info: Option<(u32, Arc<AtomicU32>)>,  // (current_round, shared total_rounds)
+    pub todos: Option<Arc<Mutex<crate::todo::TodoList>>>,
+    pub ack_prompt: Option<&'a str>,
+    pub flow_extend_secs: u64,  // 0 disables the flow key
+    pub colors: crate::render::PhaseColors,
+    pub animate_completion: bool,
+    pub inhibit_sleep: bool,  // keep the screen awake while running (released on pause)
+    pub screen: Option<&'a crate::render::Screen>,  // None runs headless: start/end lines and a sleep
+    pub input: Option<&'a crate::input::Input>,  // None draws the UI but never reads the keyboard or enables raw mode
+    pub blocker: Option<&'a crate::config::BlockerConfig>,  // block distractions for the phase
+    pub clock_format: &'a str,  // strftime for wall-clock times
+    pub frame_interval: std::time::Duration,  // time between redraws
+    pub tenths: bool,  // mm:ss.t while under a minute long
+    pub announce: bool,  // headless: time left in words at intervals (--a11y)
+    pub notify_at: &'a [u64],  // seconds left that get a heads-up notification
+    pub sounds: Option<&'a crate::sound::SoundPack>,  // plays the one-minute warning
+    pub ticker: Option<&'a crate::sound::Ticker>,  // ticks through work, toggled with `m`
+    pub plan: Option<&'a crate::session::SessionPlan>,  // session phases, for the "next:" strip
+    pub talk: Option<&'a crate::talk::Talk>,  // sections shown as title and next cue
+    pub break_debt: Option<&'a str>,  // "skipped 15m of breaks today"
+    pub footer: Option<&'a crate::footer::Footer>,  // the configured `[footer]` line
+    pub events: &'a crate::events::EventBus,  // phase start, ticks and pauses go out here
+    pub labels: &'a crate::log::WorkLabels,  // what the block is logged with, for autosaves
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimerResult {
+    pub outcome: TimerOutcome,
+    /// Seconds added to the countdown with the flow key.
+    pub extended_secs: u64,
+    /// Tasks switched to with the `n` key, as (active seconds elapsed, new task).
+    pub task_switches: Vec<(u64, String)>,
+    /// Unpaused time the countdown ran, at most its (extended) length.
+    pub active_secs: u64,
+}
+
+/// The warning cue sounds once, when a work countdown longer than a minute
+/// enters its last minute.
+fn warning_due(context: TimerContext, total_secs: u64, remaining_secs: u64) -> bool {
+    context != TimerContext::Break && total_secs > 60 && remaining_secs <= 60 && remaining_secs > 0
+}
+
+/// The `--notify-at` mark just reached, if any: the smallest mark the
+/// countdown is now at or under that's below the last one announced. Marks
+/// passed together (say, after a suspend) announce once, and marks at or
+/// beyond the timer's length never do.
+fn milestone_due(marks: &[u64], total_secs: u64, remaining_secs: u64, last: Option<u64>) -> Option<u64> {
+    if remaining_secs == 0 {
+        return None;
+    }
+    marks
+        .iter()
+        .copied()
+        .filter(|&mark| remaining_secs <= mark && mark < total_secs && last.is_none_or(|last| mark < last))
+        .min()
+}
+
+/// Whether `--a11y` reads out the time left at this second: every 15 minutes
+/// above half an hour, every 5 below, then at 2 and 1 minutes, 30 and 10
+/// seconds. The start line already gives the full length.
+fn announcement_due(total_secs: u64, remaining_secs: u64) -> bool {
+    let every = if remaining_secs > 1800 { 900 } else { 300 };
+    remaining_secs < total_secs
+        && remaining_secs > 0
+        && (remaining_secs.is_multiple_of(every) || matches!(remaining_secs, 120 | 60 | 30 | 10))
+}
+
+/// Ticks once per countdown second of a running, non-break timer.
+fn tick_due(context: TimerContext, is_paused: bool, remaining_secs: u64, last_tick: Option<u64>) -> bool {
+    context != TimerContext::Break && !is_paused && remaining_secs > 0 && last_tick != Some(remaining_secs)
+}
+
+/// When the whole session will be over, if nothing is paused or skipped.
+fn session_left_line(left_secs: u64, ends_at: chrono::DateTime<chrono::Local>, clock_format: &str) -> String {
+    let left = crate::duration::Duration::from_secs(left_secs).format_hms();
+    format!("session ends at {} ({left} left)", ends_at.format(clock_format))
+}
+
+/// Remaining time in tenths of a second, rounded up like the whole-second
+/// display so it reads 00:00.0 only at the end.
+fn remaining_tenths(total_secs: u64, elapsed: std::time::Duration) -> u64 {
+    let remaining_millis = (total_secs * 1000).saturating_sub(elapsed.as_millis() as u64);
+    remaining_millis.div_ceil(100)
+}
+
+/// Alternate flash frames every 250ms until `until`.
+fn flash_frame(until: Instant) -> bool {
+    let left = until.saturating_duration_since(Instant::now());
+    !left.is_zero() && (left.as_millis() / 250).is_multiple_of(2)
+}
+
+/// Redraw interval while tenths are shown: fast enough to land on each tenth.
+const TENTHS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// The flow key is offered in the final stretch of a non-break timer, once the
+/// remaining time has dropped to a single extension increment.
+fn flow_available(context: TimerContext, flow_extend_secs: u64, remaining_secs: u64) -> bool {
+    context != TimerContext::Break && flow_extend_secs > 0 && remaining_secs <= flow_extend_secs
+}
+
+pub async fn run(params: TimerParams<'_>) -> TimerResult {
+    let _focus_block = params
+        .blocker
+        .filter(|b| b.is_enabled())
+        .map(crate::blocker::FocusBlock::start);
+    let events = params.events;
+    events.emit(TikEvent::PhaseStarted {
+        name: params.name.to_string(),
+        context: params.context,
+        total_secs: params.total_secs,
+        round: params.round_info.as_ref().map(|(current, total)| (*current, total.load(Ordering::Relaxed))),
+        labels: params.labels.clone(),
+    });
+    let result = match params.screen {
+        Some(screen) => run_tui(params, screen).await,
+        None => run_headless(params).await,
+    };
+    // However it ended, it ended here: only a process that dies leaves a
+    // checkpoint for `tik resume`
+    crate::resume::clear();
+    events.emit(TikEvent::TimerEnded);
+    result
+}
+
+async fn run_tui(params: TimerParams<'_>, screen: &crate::render::Screen) -> TimerResult {
+    let TimerParams {
+        name,
+        mut total_secs,
+        context,
+        title,
+        round_info,
+        todos,
+        ack_prompt,
+        flow_extend_secs,
+        colors,
+        animate_completion,
+        inhibit_sleep,
+        screen: _,
+        input,
+        blocker: _,
+        clock_format,
+        frame_interval,
+        tenths,
+        announce: _,
+        notify_at,
+        sounds,
+        ticker,
+        plan,
+        talk,
+        break_debt,
+        footer,
+        events,
+        labels,
+    } = params;
+    let no_input = input.is_none();
+    let mut extended_secs = 0;
+    let mut task_switches = Vec::new();
+
+    // The caller owns the screen, so it stays up between a session's phases
+    let renderer = screen.renderer();
 
     let (pause_tx, pause_rx) = watch::channel(false);
     let (quit_tx, quit_rx) = watch::channel(false);
@@ -42,245 +225,794 @@ pub async fn run(
     let (stop_tx, stop_rx) = watch::channel(false);
     let (todo_focus_tx, todo_focus_rx) = watch::channel(false);
     let (todo_selected_tx, todo_selected_rx) = watch::channel(0usize);
+    let (finished_tx, finished_rx) = watch::channel(false);
+    let (ack_tx, ack_rx) = watch::channel(false);
+    let (flow_tx, flow_rx) = watch::channel(false);
+    let (task_input_tx, task_input_rx) = watch::channel(None::<String>);
+    // The receiver keeps the channel open so the input thread's sends land
+    let (task_switch_tx, _task_switch_rx) = watch::channel(None::<String>);
+    let (tick_toggle_tx, _tick_toggle_rx) = watch::channel(false);
+    let _remote = RemoteGuard::register(&pause_tx, &skip_tx, &stop_tx, round_info.clone());
 
-    // Spawn a thread for keyboard input (crossterm events are blocking)
+    // Keys from the run's input reader; dropped when the timer returns, which
+    // leaves any unread keys queued for the next phase
     let pause_tx_clone = pause_tx.clone();
     let quit_tx_clone = quit_tx.clone();
     let skip_tx_clone = skip_tx.clone();
     let stop_tx_clone = stop_tx.clone();
+    let flow_tx_clone = flow_tx.clone();
+    let task_switch_tx_clone = task_switch_tx.clone();
+    let tick_toggle_tx_clone = tick_toggle_tx.clone();
     let todo_focus_tx_clone = todo_focus_tx.clone();
     let todo_selected_tx_clone = todo_selected_tx.clone();
     let round_info_clone = round_info.clone();
+    let rounds_adjustable = plan.is_none_or(crate::session::SessionPlan::adjustable);
     let todos_clone = todos.clone();
     let context_clone = context;
-    std::thread::spawn(move || {
-        loop {
-            if event::poll(std::time::Duration::from_millis(50)).unwrap_or(false) {
-                if let Ok(Event::Key(key)) = event::read() {
-                    let in_todo_focus = *todo_focus_tx_clone.borrow();
-
-                    if in_todo_focus {
-                        // === TODO FOCUS MODE ===
-                        match key {
-                            KeyEvent { code: KeyCode::Tab, .. } => {
-                                let _ = todo_focus_tx_clone.send(false);
-                            }
-                            KeyEvent { code: KeyCode::Up, modifiers, .. }
-                                if modifiers.contains(KeyModifiers::SHIFT) =>
-                            {
-                                if let Some(ref todos) = todos_clone {
-                                    let sel = *todo_selected_tx_clone.borrow();
-                                    if let Ok(mut list) = todos.lock() {
-                                        if list.move_up(sel).is_ok() && sel > 0 {
-                                            let _ = todo_selected_tx_clone.send(sel - 1);
-                                        }
-                                        let _ = list.save();
-                                    }
-                                }
-                            }
-                            KeyEvent { code: KeyCode::Down, modifiers, .. }
-                                if modifiers.contains(KeyModifiers::SHIFT) =>
-                            {
-                                if let Some(ref todos) = todos_clone {
-                                    let sel = *todo_selected_tx_clone.borrow();
-                                    if let Ok(mut list) = todos.lock() {
-                                        let len = list.items.len();
-                                        if list.move_down(sel).is_ok() && sel + 1 < len {
-                                            let _ = todo_selected_tx_clone.send(sel + 1);
-                                        }
-                                        let _ = list.save();
-                                    }
-                                }
-                            }
-                            KeyEvent { code: KeyCode::Up, .. } => {
-                                let sel = *todo_selected_tx_clone.borrow();
-                                if sel > 0 {
+    let key_loop = async move {
+        let Some(input) = input else {
+            return;
+        };
+        while let Some(event) = input.next().await {
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if *finished_rx.borrow() {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                // Completion state: any key acknowledges, Ctrl+C still quits
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    let _ = quit_tx_clone.send(true);
+                } else {
+                    let _ = ack_tx.send(true);
+                }
+                break;
+            }
+
+            // Task input line opened with `n`: edit until enter or esc
+            let task_input = task_input_tx.borrow().clone();
+            if let Some(mut text) = task_input {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let _ = quit_tx_clone.send(true);
+                        break;
+                    }
+                    KeyCode::Enter => {
+                        let task = text.trim();
+                        if !task.is_empty() {
+                            let _ = task_switch_tx_clone.send(Some(task.to_string()));
+                        }
+                        let _ = task_input_tx.send(None);
+                    }
+                    KeyCode::Esc => {
+                        let _ = task_input_tx.send(None);
+                    }
+                    KeyCode::Backspace => {
+                        text.pop();
+                        let _ = task_input_tx.send(Some(text));
+                    }
+                    KeyCode::Char(c) => {
+                        text.push(c);
+                        let _ = task_input_tx.send(Some(text));
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let in_todo_focus = *todo_focus_tx_clone.borrow();
+
+            if in_todo_focus {
+                // === TODO FOCUS MODE ===
+                match key {
+                    KeyEvent { code: KeyCode::Tab, .. } => {
+                        let _ = todo_focus_tx_clone.send(false);
+                    }
+                    KeyEvent { code: KeyCode::Up, modifiers, .. }
+                        if modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        if let Some(ref todos) = todos_clone {
+                            let sel = *todo_selected_tx_clone.borrow();
+                            if let Ok(mut list) = todos.lock() {
+                                if list.move_up(sel).is_ok() && sel > 0 {
                                     let _ = todo_selected_tx_clone.send(sel - 1);
                                 }
+                                let _ = list.save();
                             }
-                            KeyEvent { code: KeyCode::Down, .. } => {
-                                let sel = *todo_selected_tx_clone.borrow();
-                                if let Some(ref todos) = todos_clone {
-                                    if let Ok(list) = todos.lock() {
-                                        if sel + 1 < list.items.len() {
-                                            let _ = todo_selected_tx_clone.send(sel + 1);
-                                        }
-                                    }
-                                }
-                            }
-                            KeyEvent { code: KeyCode::Enter, .. } => {
-                                if let Some(ref todos) = todos_clone {
-                                    let sel = *todo_selected_tx_clone.borrow();
-                                    if let Ok(mut list) = todos.lock() {
-                                        if let Some(todo) = list.items.get(sel) {
-                                            let id = todo.id;
-                                            let _ = list.toggle_done(id);
-                                            let _ = list.save();
-                                        }
-                                    }
-                                }
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char('c'),
-                                modifiers,
-                                ..
-                            } if modifiers.contains(KeyModifiers::CONTROL) => {
-                                let _ = quit_tx_clone.send(true);
-                                break;
-                            }
-                            _ => {}
                         }
-                    } else {
-                        // === TIMER FOCUS MODE ===
-                        match key {
-                            KeyEvent { code: KeyCode::Tab, .. } => {
-                                if todos_clone.is_some() {
-                                    let _ = todo_focus_tx_clone.send(true);
-                                }
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char(' '),
-                                ..
-                            } => {
-                                let current = *pause_tx_clone.borrow();
-                                let _ = pause_tx_clone.send(!current);
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char('c'),
-                                modifiers,
-                                ..
-                            } if modifiers.contains(KeyModifiers::CONTROL) => {
-                                let _ = quit_tx_clone.send(true);
-                                break;
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char('s'),
-                                ..
-                            } => {
-                                let is_last_round = round_info_clone.as_ref().is_some_and(|ri| {
-                                    ri.0 >= ri.1.load(Ordering::Relaxed)
-                                });
-                                if !is_last_round {
-                                    let _ = skip_tx_clone.send(true);
-                                    break;
-                                }
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char('x'),
-                                ..
-                            } => {
-                                let _ = stop_tx_clone.send(true);
-                                break;
-                            }
-                            KeyEvent {
-                                code: KeyCode::Char('a'),
-                                ..
-                            } => {
-                                if matches!(context_clone, TimerContext::Work | TimerContext::Break) {
-                                    if let Some(ref ri) = round_info_clone {
-                                        ri.1.fetch_add(1, Ordering::Relaxed);
-                                    }
+                    }
+                    KeyEvent { code: KeyCode::Down, modifiers, .. }
+                        if modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        if let Some(ref todos) = todos_clone {
+                            let sel = *todo_selected_tx_clone.borrow();
+                            if let Ok(mut list) = todos.lock() {
+                                let len = list.items.len();
+                                if list.move_down(sel).is_ok() && sel + 1 < len {
+                                    let _ = todo_selected_tx_clone.send(sel + 1);
                                 }
+                                let _ = list.save();
                             }
-                            KeyEvent {
-                                code: KeyCode::Char('d'),
-                                ..
-                            } => {
-                                if matches!(context_clone, TimerContext::Work | TimerContext::Break) {
-                                    if let Some(ref ri) = round_info_clone {
-                                        let current_round = ri.0;
-                                        let _ = ri.1.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |val| {
-                                            if val > current_round { Some(val - 1) } else { None }
-                                        });
-                                    }
-                                }
+                        }
+                    }
+                    KeyEvent { code: KeyCode::Up, .. } => {
+                        let sel = *todo_selected_tx_clone.borrow();
+                        if sel > 0 {
+                            let _ = todo_selected_tx_clone.send(sel - 1);
+                        }
+                    }
+                    KeyEvent { code: KeyCode::Down, .. } => {
+                        let sel = *todo_selected_tx_clone.borrow();
+                        if let Some(ref todos) = todos_clone
+                            && let Ok(list) = todos.lock()
+                            && sel + 1 < list.items.len()
+                        {
+                            let _ = todo_selected_tx_clone.send(sel + 1);
+                        }
+                    }
+                    KeyEvent { code: KeyCode::Enter, .. } => {
+                        if let Some(ref todos) = todos_clone {
+                            let sel = *todo_selected_tx_clone.borrow();
+                            if let Ok(mut list) = todos.lock()
+                                && let Some(todo) = list.items.get(sel)
+                            {
+                                let id = todo.id;
+                                let _ = list.toggle_done(id);
+                                let _ = list.save();
                             }
-                            _ => {}
                         }
                     }
+                    KeyEvent {
+                        code: KeyCode::Char('c'),
+                        modifiers,
+                        ..
+                    } if modifiers.contains(KeyModifiers::CONTROL) => {
+                        let _ = quit_tx_clone.send(true);
+                        break;
+                    }
+                    _ => {}
+                }
+            } else {
+                // === TIMER FOCUS MODE ===
+                match key {
+                    KeyEvent { code: KeyCode::Tab, .. } if todos_clone.is_some() => {
+                        let _ = todo_focus_tx_clone.send(true);
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char(' '),
+                        ..
+                    } => {
+                        let current = *pause_tx_clone.borrow();
+                        let _ = pause_tx_clone.send(!current);
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('c'),
+                        modifiers,
+                        ..
+                    } if modifiers.contains(KeyModifiers::CONTROL) => {
+                        let _ = quit_tx_clone.send(true);
+                        break;
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('s'),
+                        ..
+                    } => {
+                        let is_last_round = round_info_clone.as_ref().is_some_and(|ri| {
+                            ri.0 >= ri.1.load(Ordering::Relaxed)
+                        });
+                        if !is_last_round {
+                            let _ = skip_tx_clone.send(true);
+                            break;
+                        }
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('x'),
+                        ..
+                    } => {
+                        let _ = stop_tx_clone.send(true);
+                        break;
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('f'),
+                        ..
+                    } => {
+                        let _ = flow_tx_clone.send(true);
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('n'),
+                        ..
+                    } if context_clone != TimerContext::Break => {
+                        let _ = task_input_tx.send(Some(String::new()));
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('m'),
+                        ..
+                    } => {
+                        let _ = tick_toggle_tx_clone.send(true);
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('a'),
+                        ..
+                    } => {
+                        if matches!(context_clone, TimerContext::Work | TimerContext::Break)
+                            && rounds_adjustable
+                            && let Some(ref ri) = round_info_clone
+                        {
+                            ri.1.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                    KeyEvent {
+                        code: KeyCode::Char('d'),
+                        ..
+                    } => {
+                        if matches!(context_clone, TimerContext::Work | TimerContext::Break)
+                            && rounds_adjustable
+                            && let Some(ref ri) = round_info_clone
+                        {
+                            let current_round = ri.0;
+                            let _ = ri.1.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |val| {
+                                if val > current_round { Some(val - 1) } else { None }
+                            });
+                        }
+                    }
+                    _ => {}
                 }
             }
-            if *quit_tx_clone.borrow() {
+        }
+    };
+
+    let body = async {
+        let mut clock = ActiveClock::new();
+        let mut outcome = TimerOutcome::Quit;
+        let mut inhibitor = crate::inhibit::SleepInhibitor::new(inhibit_sleep);
+        let mut last_tick_event = None;
+        let mut last_paused = false;
+        let mut current_task: Option<String> = None;
+        let mut warned = false;
+        let mut milestone = None;
+        let mut last_tick = None;
+        let mut upcoming: Option<((u32, u32), Option<String>, u64)> = None;
+        let mut section = None;
+        let mut flash_until: Option<Instant> = None;
+        let mut checkpoint = crate::resume::Checkpointer::new(name, title, context, total_secs, labels);
+        // Without raw mode Ctrl+C arrives as a signal rather than a key
+        let shutdown = shutdown_signal();
+        tokio::pin!(shutdown);
+
+        loop {
+            // Check quit
+            if *quit_rx.borrow() {
                 break;
             }
-        }
-    });
+            if *skip_rx.borrow() {
+                let active_secs = clock.active_elapsed(*pause_rx.borrow()).as_secs().min(total_secs);
+                return TimerResult { outcome: TimerOutcome::Skipped, extended_secs, task_switches, active_secs };
+            }
+            if *stop_rx.borrow() {
+                let active_secs = clock.active_elapsed(*pause_rx.borrow()).as_secs().min(total_secs);
+                return TimerResult { outcome: TimerOutcome::StoppedEarly, extended_secs, task_switches, active_secs };
+            }
 
-    let start = Instant::now();
-    let mut paused_duration = std::time::Duration::ZERO;
-    let mut pause_start: Option<Instant> = None;
-    let mut completed = false;
+            let is_paused = *pause_rx.borrow();
+            inhibitor.set_active(!is_paused);
 
-    loop {
-        // Check quit
-        if *quit_rx.borrow() {
-            break;
+            let elapsed = clock.active_elapsed(is_paused);
+            let elapsed_secs = elapsed.as_secs();
+            let mut remaining_secs = total_secs.saturating_sub(elapsed_secs);
+
+            if *flow_rx.borrow() {
+                let _ = flow_tx.send(false);
+                if flow_available(context, flow_extend_secs, remaining_secs) {
+                    total_secs += flow_extend_secs;
+                    extended_secs += flow_extend_secs;
+                    remaining_secs += flow_extend_secs;
+                }
+            }
+            let show_tenths = tenths && total_secs < 60;
+            checkpoint.update(total_secs, remaining_secs, is_paused, elapsed);
+
+            // A talk flashes the time and sounds the warning as each section runs out
+            let talk_cue = talk.map(|talk| {
+                let (index, left) = talk.section_at(elapsed_secs);
+                if section.is_some_and(|s| s != index) {
+                    flash_until = Some(Instant::now() + std::time::Duration::from_secs(2));
+                    if let Some(sounds) = sounds {
+                        sounds.play(crate::sound::Cue::Warning);
+                    }
+                }
+                section = Some(index);
+                (talk.sections()[index].name.as_str(), talk.cue_line(index, left))
+            });
+
+            if let Some(task) = task_switch_tx.send_replace(None) {
+                task_switches.push((elapsed_secs, task.clone()));
+                current_task = Some(task);
+            }
+            let task_input = task_input_rx.borrow().clone();
+
+            if !warned && warning_due(context, total_secs, remaining_secs) {
+                warned = true;
+                if let Some(sounds) = sounds {
+                    sounds.play(crate::sound::Cue::Warning);
+                }
+            }
+            if let Some(mark) = milestone_due(notify_at, total_secs, remaining_secs, milestone) {
+                milestone = Some(mark);
+                events.emit(TikEvent::Milestone { name: name.to_string(), remaining_secs: mark });
+            }
+            if let Some(ticker) = ticker {
+                if tick_toggle_tx.send_replace(false) {
+                    ticker.toggle();
+                }
+                if tick_due(context, is_paused, remaining_secs, last_tick) {
+                    last_tick = Some(remaining_secs);
+                    ticker.tick();
+                }
+            }
+
+            let current_round_info = round_info
+                .as_ref()
+                .map(|(current, total_arc)| (*current, total_arc.load(Ordering::Relaxed)));
+
+            // Rebuilt only when rounds are added or removed
+            if let (Some(plan), Some(round)) = (plan, current_round_info)
+                && upcoming.as_ref().is_none_or(|(key, _, _)| *key != round)
+            {
+                let in_break = context == TimerContext::Break;
+                upcoming = Some((
+                    round,
+                    plan.strip(round.0, round.1, in_break),
+                    plan.upcoming_secs(round.0, round.1, in_break),
+                ));
+            }
+            // The end time comes from the unrounded elapsed time so it doesn't
+            // jitter between seconds
+            let session_left = upcoming.as_ref().map(|(_, _, secs)| {
+                let left = std::time::Duration::from_secs(total_secs + secs).saturating_sub(elapsed);
+                let ends_at = chrono::Local::now() + chrono::Duration::from_std(left).unwrap_or_default();
+                session_left_line(remaining_secs + secs, ends_at, clock_format)
+            });
+
+            let tick_key = (total_secs, remaining_secs, current_round_info);
+            if last_tick_event != Some(tick_key) {
+                last_tick_event = Some(tick_key);
+                events.emit(TikEvent::Tick { total_secs, remaining_secs, round: current_round_info });
+            }
+            if last_paused != is_paused {
+                last_paused = is_paused;
+                events.emit(TikEvent::Paused { paused: is_paused });
+            }
+
+            let todo_snapshot = todos.as_ref().and_then(|t| {
+                let list = t.lock().ok()?;
+                if list.items.is_empty() {
+                    return None;
+                }
+                Some(crate::todo::TodoSnapshot {
+                    items: list.items.iter().map(|t| (t.id, t.text.clone(), t.done)).collect(),
+                    selected_index: *todo_selected_rx.borrow(),
+                    focus: *todo_focus_rx.borrow(),
+                })
+            });
+
+            let footer_line = footer.and_then(|footer| {
+                footer.line(current_task.as_deref().or(title), &chrono::Local::now().format(clock_format).to_string())
+            });
+
+            let params = crate::render::DrawParams {
+                remaining_secs,
+                total_secs,
+                elapsed_secs,
+                elapsed_millis: elapsed.as_millis() as u64,
+                remaining_tenths: show_tenths.then(|| remaining_tenths(total_secs, elapsed)),
+                paused: is_paused,
+                title: talk_cue.as_ref().map(|(name, _)| *name).or(current_task.as_deref()).or(title),
+                round_info: current_round_info,
+                context,
+                todo: todo_snapshot.as_ref(),
+                ack_prompt: None,
+                task_input: task_input.as_deref(),
+                upcoming: talk_cue
+                    .as_ref()
+                    .map(|(_, cue)| cue.as_str())
+                    .or_else(|| upcoming.as_ref().and_then(|(_, strip, _)| strip.as_deref())),
+                session_left: session_left.as_deref(),
+                break_debt,
+                footer: footer_line.as_deref(),
+                keys: !no_input,
+                flow_available: flow_available(context, flow_extend_secs, remaining_secs),
+                colors,
+                flash: flash_until.is_some_and(flash_frame),
+            };
+            if renderer.draw(&params).is_err() {
+                break;
+            }
+
+            if remaining_secs == 0 {
+                inhibitor.set_active(false);
+                let _ = finished_tx.send(true);
+                let params = crate::render::DrawParams { ack_prompt, task_input: None, flow_available: false, ..params };
+                outcome = wait_for_completion(renderer, &params, animate_completion, &quit_rx, &ack_rx).await;
+                break;
+            }
+
+            tokio::select! {
+                _ = &mut shutdown, if no_input => break,
+                _ = tokio::time::sleep(if show_tenths { frame_interval.min(TENTHS_INTERVAL) } else { frame_interval }) => {}
+            }
         }
+
+        let active_secs = clock.active_elapsed(*pause_rx.borrow()).as_secs().min(total_secs);
+        TimerResult { outcome, extended_secs, task_switches, active_secs }
+    };
+
+    // The key loop never decides the result; it stays alive until the body
+    // returns, even after its last key
+    tokio::select! {
+        result = body => result,
+        _ = async {
+            key_loop.await;
+            std::future::pending::<()>().await
+        } => unreachable!(),
+    }
+}
+
+/// Headless mode: no terminal UI and no keyboard. Prints a line when the timer
+/// starts, pauses, resumes and ends, keeps the state file current, and stops on
+/// Ctrl+C/SIGTERM. `tik pause`, `tik skip` and `tik stop` still work.
+async fn run_headless(params: TimerParams<'_>) -> TimerResult {
+    let round = params
+        .round_info
+        .as_ref()
+        .map(|(current, total_arc)| (*current, total_arc.load(Ordering::Relaxed)));
+    let (pause_tx, pause_rx) = watch::channel(false);
+    let (skip_tx, skip_rx) = watch::channel(false);
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let _remote = RemoteGuard::register(&pause_tx, &skip_tx, &stop_tx, params.round_info.clone());
+    let mut inhibitor = crate::inhibit::SleepInhibitor::new(params.inhibit_sleep);
+
+    let length = crate::duration::Duration::from_secs(params.total_secs);
+    let display = if params.announce { length.format_words() } else { length.format_hms() };
+    let ends_at = chrono::Local::now() + chrono::Duration::seconds(params.total_secs as i64);
+    println!(
+        "[{}] {} started ({display}, {}), ends at {}",
+        chrono::Local::now().format(params.clock_format),
+        params.name,
+        params.context.label(),
+        ends_at.format(params.clock_format),
+    );
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut clock = ActiveClock::new();
+    let mut last_paused = false;
+    let mut last_remaining = None;
+    let mut warned = false;
+    let mut milestone = None;
+    let mut last_tick = None;
+    let mut section = None;
+    let mut checkpoint = crate::resume::Checkpointer::new(params.name, params.title, params.context, params.total_secs, params.labels);
+    let outcome = loop {
         if *skip_rx.borrow() {
-            // Don't teardown — session stays in alternate screen for smooth transition
-            return TimerOutcome::Skipped;
+            break TimerOutcome::Skipped;
         }
         if *stop_rx.borrow() {
-            let _ = renderer.teardown();
-            return TimerOutcome::StoppedEarly;
+            break TimerOutcome::StoppedEarly;
         }
 
         let is_paused = *pause_rx.borrow();
+        inhibitor.set_active(!is_paused);
+        let elapsed = clock.active_elapsed(is_paused);
+        let elapsed_secs = elapsed.as_secs();
+        let remaining_secs = params.total_secs.saturating_sub(elapsed_secs);
+        if remaining_secs == 0 {
+            break TimerOutcome::Completed;
+        }
+        checkpoint.update(params.total_secs, remaining_secs, is_paused, elapsed);
+        if !warned && warning_due(params.context, params.total_secs, remaining_secs) {
+            warned = true;
+            if let Some(sounds) = params.sounds {
+                sounds.play(crate::sound::Cue::Warning);
+            }
+        }
+        if let Some(mark) = milestone_due(params.notify_at, params.total_secs, remaining_secs, milestone) {
+            milestone = Some(mark);
+            let left = crate::duration::Duration::from_secs(mark).format_compact();
+            println!("[{}] {} {left} left", chrono::Local::now().format(params.clock_format), params.name);
+            params.events.emit(TikEvent::Milestone { name: params.name.to_string(), remaining_secs: mark });
+        }
+        if let Some(ticker) = params.ticker
+            && tick_due(params.context, is_paused, remaining_secs, last_tick)
+        {
+            last_tick = Some(remaining_secs);
+            ticker.tick();
+        }
 
-        // Track pause duration
-        if is_paused {
-            if pause_start.is_none() {
-                pause_start = Some(Instant::now());
+        if let Some(talk) = params.talk {
+            let (index, _) = talk.section_at(elapsed_secs);
+            if section != Some(index) {
+                section = Some(index);
+                let current = &talk.sections()[index];
+                let length = crate::duration::Duration::from_secs(current.secs).format_compact();
+                println!("[{}] {} ({length})", chrono::Local::now().format(params.clock_format), current.name);
             }
-        } else if let Some(ps) = pause_start.take() {
-            paused_duration += ps.elapsed();
+        }
+        if last_remaining != Some(remaining_secs) {
+            last_remaining = Some(remaining_secs);
+            if params.announce && !is_paused && announcement_due(params.total_secs, remaining_secs) {
+                let left = crate::duration::Duration::from_secs(remaining_secs).format_words();
+                println!("[{}] {} {left} remaining", chrono::Local::now().format(params.clock_format), params.name);
+            }
+            params.events.emit(TikEvent::Tick { total_secs: params.total_secs, remaining_secs, round });
+        }
+        if last_paused != is_paused {
+            let verb = if is_paused { "paused" } else { "resumed" };
+            let left = if params.announce {
+                format!(", {} remaining", crate::duration::Duration::from_secs(remaining_secs).format_words())
+            } else {
+                String::new()
+            };
+            println!("[{}] {} {verb}{left}", chrono::Local::now().format(params.clock_format), params.name);
+            last_paused = is_paused;
+            params.events.emit(TikEvent::Paused { paused: is_paused });
         }
 
-        let current_pause = pause_start.map_or(std::time::Duration::ZERO, |ps| ps.elapsed());
-        let active_elapsed = start.elapsed() - paused_duration - current_pause;
+        tokio::select! {
+            _ = &mut shutdown => break TimerOutcome::Quit,
+            _ = tokio::time::sleep(std::time::Duration::from_millis(250)) => {}
+        }
+    };
+    let verb = match outcome {
+        TimerOutcome::Completed => "finished",
+        TimerOutcome::Skipped => "skipped",
+        TimerOutcome::StoppedEarly => "stopped",
+        _ => "cancelled",
+    };
+    println!("[{}] {} {verb}", chrono::Local::now().format(params.clock_format), params.name);
+    let active_secs = clock.active_elapsed(*pause_rx.borrow()).as_secs().min(params.total_secs);
+    TimerResult { outcome, extended_secs: 0, task_switches: Vec::new(), active_secs }
+}
 
-        let elapsed_secs = active_elapsed.as_secs();
-        let remaining_secs = total_secs.saturating_sub(elapsed_secs);
+/// Countdown time that excludes pauses.
+pub struct ActiveClock {
+    start: Instant,
+    paused: std::time::Duration,
+    pause_start: Option<Instant>,
+}
 
-        let current_round_info = round_info
-            .as_ref()
-            .map(|(current, total_arc)| (*current, total_arc.load(Ordering::Relaxed)));
+impl ActiveClock {
+    pub fn new() -> Self {
+        ActiveClock { start: Instant::now(), paused: std::time::Duration::ZERO, pause_start: None }
+    }
 
-        let todo_snapshot = todos.as_ref().and_then(|t| {
-            let list = t.lock().ok()?;
-            if list.items.is_empty() {
-                return None;
-            }
-            Some(crate::todo::TodoSnapshot {
-                items: list.items.iter().map(|t| (t.id, t.text.clone(), t.done)).collect(),
-                selected_index: *todo_selected_rx.borrow(),
-                focus: *todo_focus_rx.borrow(),
-            })
-        });
-
-        let params = crate::render::DrawParams {
-            remaining_secs,
-            total_secs,
-            elapsed_secs,
-            paused: is_paused,
-            title,
-            round_info: current_round_info,
-            context,
-            todo: todo_snapshot.as_ref(),
-        };
-        if renderer.draw(&params).is_err() {
-            break;
+    /// Time spent unpaused so far. Call every tick with the current pause state.
+    pub fn active_elapsed(&mut self, is_paused: bool) -> std::time::Duration {
+        let now = Instant::now();
+        if is_paused {
+            self.pause_start.get_or_insert(now);
+        } else if let Some(ps) = self.pause_start.take() {
+            self.paused += now - ps;
         }
+        let current_pause = self.pause_start.map_or(std::time::Duration::ZERO, |ps| now - ps);
+        (now - self.start).saturating_sub(self.paused + current_pause)
+    }
+}
 
-        if remaining_secs == 0 {
-            completed = true;
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            break;
+/// Channels of the running timer, reachable from IPC requests (`tik pause`,
+/// `tik skip`, `tik stop`) handled elsewhere in the process.
+struct RemoteHandle {
+    pause: watch::Sender<bool>,
+    skip: watch::Sender<bool>,
+    stop: watch::Sender<bool>,
+    round_info: Option<(u32, Arc<AtomicU32>)>,
+}
+
+static REMOTE: Mutex<Option<RemoteHandle>> = Mutex::new(None);
+
+/// Keeps the running timer registered for remote control until dropped.
+struct RemoteGuard;
+
+impl RemoteGuard {
+    fn register(
+        pause: &watch::Sender<bool>,
+        skip: &watch::Sender<bool>,
+        stop: &watch::Sender<bool>,
+        round_info: Option<(u32, Arc<AtomicU32>)>,
+    ) -> Self {
+        if let Ok(mut remote) = REMOTE.lock() {
+            *remote = Some(RemoteHandle { pause: pause.clone(), skip: skip.clone(), stop: stop.clone(), round_info });
+        }
+        RemoteGuard
+    }
+}
+
+impl Drop for RemoteGuard {
+    fn drop(&mut self) {
+        if let Ok(mut remote) = REMOTE.lock() {
+            *remote = None;
+        }
+    }
+}
+
+fn with_remote<T>(f: impl FnOnce(&RemoteHandle) -> Result<T, String>) -> Result<T, String> {
+    let remote = REMOTE.lock().map_err(|_| "Timer state unavailable".to_string())?;
+    match remote.as_ref() {
+        Some(handle) => f(handle),
+        None => Err("No timer running.".to_string()),
+    }
+}
+
+/// Toggle pause on the running timer. Returns true if it is now paused.
+pub fn remote_toggle_pause() -> Result<bool, String> {
+    with_remote(|handle| {
+        let paused = !*handle.pause.borrow();
+        let _ = handle.pause.send(paused);
+        Ok(paused)
+    })
+}
+
+/// Skip to the next session phase. Like the `s` key, not on the last round.
+pub fn remote_skip() -> Result<(), String> {
+    with_remote(|handle| match &handle.round_info {
+        Some((current, total)) if *current < total.load(Ordering::Relaxed) => {
+            let _ = handle.skip.send(true);
+            Ok(())
+        }
+        Some(_) => Err("Can't skip the last round.".to_string()),
+        None => Err("Only session phases can be skipped.".to_string()),
+    })
+}
+
+/// Stop the running timer early, like the `x` key.
+pub fn remote_stop() -> Result<(), String> {
+    with_remote(|handle| {
+        let _ = handle.stop.send(true);
+        Ok(())
+    })
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM (e.g. `systemctl stop`) on Unix.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+            return;
         }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
 
+/// Completion state: the countdown has reached zero. Without an ack prompt the
+/// final frame lingers for a second (or for the 2s flash animation); with one
+/// we wait for a key press. Quit is honored either way.
+async fn wait_for_completion(
+    renderer: &Renderer,
+    params: &crate::render::DrawParams<'_>,
+    animate: bool,
+    quit_rx: &watch::Receiver<bool>,
+    ack_rx: &watch::Receiver<bool>,
+) -> TimerOutcome {
+    let start = Instant::now();
+    let animate_until = start + std::time::Duration::from_secs(if animate { 2 } else { 0 });
+    let linger_until = start + std::time::Duration::from_secs(if animate { 2 } else { 1 });
+    let mut flash_frame = true;
+    loop {
+        if *quit_rx.borrow() {
+            return TimerOutcome::CompletedThenQuit;
+        }
+        let done = match params.ack_prompt {
+            Some(_) => *ack_rx.borrow(),
+            None => Instant::now() >= linger_until,
+        };
+        if done {
+            return TimerOutcome::Completed;
+        }
+        let flash = flash_frame && Instant::now() < animate_until;
+        let _ = renderer.draw(&crate::render::DrawParams { flash, ..*params });
+        flash_frame = !flash_frame;
         tokio::time::sleep(std::time::Duration::from_millis(250)).await;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flow_available_in_final_increment() {
+        assert!(flow_available(TimerContext::Work, 300, 300));
+        assert!(flow_available(TimerContext::Standalone, 300, 10));
+        assert!(!flow_available(TimerContext::Work, 300, 301));
+    }
+
+    #[test]
+    fn flow_unavailable_in_breaks_or_when_disabled() {
+        assert!(!flow_available(TimerContext::Break, 300, 10));
+        assert!(!flow_available(TimerContext::Work, 0, 0));
+    }
+
+    #[test]
+    fn warning_due_in_last_minute_of_work() {
+        assert!(warning_due(TimerContext::Work, 1500, 60));
+        assert!(!warning_due(TimerContext::Work, 1500, 61));
+        assert!(!warning_due(TimerContext::Break, 300, 30));
+        assert!(!warning_due(TimerContext::Standalone, 45, 30));
+    }
+
+    #[test]
+    fn announcements_at_meaningful_marks() {
+        let due: Vec<u64> = (0..=3600).rev().filter(|&left| announcement_due(3600, left)).collect();
+        assert_eq!(due, [2700, 1800, 1500, 1200, 900, 600, 300, 120, 60, 30, 10]);
+        // Nothing at the start, which already says how long it is
+        assert!(!announcement_due(1500, 1500));
+        assert_eq!((0..=45).filter(|&left| announcement_due(45, left)).collect::<Vec<_>>(), [10, 30]);
+    }
+
+    #[test]
+    fn tick_due_once_per_running_work_second() {
+        assert!(tick_due(TimerContext::Work, false, 90, None));
+        assert!(!tick_due(TimerContext::Work, false, 90, Some(90)));
+        assert!(tick_due(TimerContext::Work, false, 89, Some(90)));
+        assert!(!tick_due(TimerContext::Work, true, 89, Some(90)));
+        assert!(!tick_due(TimerContext::Break, false, 89, Some(90)));
+        assert!(!tick_due(TimerContext::Work, false, 0, Some(1)));
+    }
+
+    #[test]
+    fn session_left_line_shows_end_time() {
+        use chrono::TimeZone;
+        let ends_at = chrono::Local.with_ymd_and_hms(2026, 3, 2, 15, 30, 0).unwrap();
+        assert_eq!(
+            session_left_line(5400, ends_at, "%H:%M"),
+            "session ends at 15:30 (1:30:00 left)"
+        );
+    }
 
-    let _ = renderer.teardown();
-    if completed { TimerOutcome::Completed } else { TimerOutcome::Quit }
+    #[test]
+    fn milestones_fire_once_each_in_order() {
+        let marks = [1800, 600, 300];
+        assert_eq!(milestone_due(&marks, 3600, 1801, None), None);
+        assert_eq!(milestone_due(&marks, 3600, 1800, None), Some(1800));
+        assert_eq!(milestone_due(&marks, 3600, 1700, Some(1800)), None);
+        // Two marks passed at once announce the nearer one
+        assert_eq!(milestone_due(&marks, 3600, 550, Some(1800)), Some(600));
+        assert_eq!(milestone_due(&marks, 3600, 0, Some(300)), None);
+        // Marks as long as the timer itself never fire
+        assert_eq!(milestone_due(&marks, 1200, 1200, None), None);
+        assert_eq!(milestone_due(&marks, 1200, 600, None), Some(600));
+    }
+
+    #[test]
+    fn remaining_tenths_round_up() {
+        let ms = std::time::Duration::from_millis;
+        assert_eq!(remaining_tenths(10, ms(650)), 94);
+        assert_eq!(remaining_tenths(10, ms(0)), 100);
+        assert_eq!(remaining_tenths(10, ms(9_999)), 1);
+        assert_eq!(remaining_tenths(10, ms(12_000)), 0);
+    }
+
+    #[test]
+    fn active_clock_excludes_pauses() {
+        let mut clock = ActiveClock::new();
+        clock.start -= std::time::Duration::from_secs(10);
+        assert_eq!(clock.active_elapsed(true).as_secs(), 10);
+        clock.pause_start = Some(Instant::now() - std::time::Duration::from_secs(4));
+        assert_eq!(clock.active_elapsed(false).as_secs(), 6);
+        assert!(clock.pause_start.is_none());
+    }
+
+    #[test]
+    fn completed_then_quit_counts_as_completed() {
+        assert!(TimerOutcome::CompletedThenQuit.is_completed());
+        assert!(!TimerOutcome::Skipped.is_completed());
+    }
 }
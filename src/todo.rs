@@ -18,7 +18,7 @@ pub struct TodoList {
 }
 
 /// Lightweight snapshot of todo state for rendering.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 pub struct TodoSnapshot {
     pub items: Vec<(u32, String, bool)>, // (id, text, done)
     pub selected_index: usize,
@@ -314,9 +314,9 @@ mod tests {
     fn toggle_done() {
         let mut list = TodoList::new();
         list.add("Task".to_string());
-        assert_eq!(list.toggle_done(1).unwrap(), true);
+        assert!(list.toggle_done(1).unwrap());
         assert!(list.items[0].done);
-        assert_eq!(list.toggle_done(1).unwrap(), false);
+        assert!(!list.toggle_done(1).unwrap());
         assert!(!list.items[0].done);
         assert!(list.items[0].completed_at.is_none());
     }
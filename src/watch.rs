@@ -0,0 +1,278 @@
+//! `tik watch-file PATH`: a countdown whose time comes from a file (or FIFO)
+//! another program keeps up to date, e.g. a CI deadline or an auction clock.
+//! tik only draws it and notifies at zero. Nothing is logged.
+//!
+//! The last non-empty line is read as the time left: seconds ("90"), a
+//! duration ("4m30s"), a clock ("4:30", "1:04:30") or an RFC 3339 deadline.
+//! "left/total" also sets the bar's full length, e.g. "90/300".
+
+use chrono::{DateTime, Local};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::duration::Duration;
+use crate::events::{EventBus, TikEvent};
+use crate::input::Input;
+use crate::render::{DrawParams, Screen};
+use crate::timer::TimerContext;
+
+/// How often a regular file is read again.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The time left as of `read_at`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Reading {
+    remaining_secs: u64,
+    total_secs: Option<u64>,
+    read_at: Instant,
+}
+
+impl Reading {
+    /// Seconds left now, counting down from the reading between updates.
+    fn remaining_millis(&self) -> u64 {
+        (self.remaining_secs * 1000).saturating_sub(self.read_at.elapsed().as_millis() as u64)
+    }
+}
+
+/// "4:30" or "1:04:30".
+fn parse_clock(text: &str) -> Option<u64> {
+    let parts: Vec<u64> = text.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [m, s] if s < 60 => Some(m * 60 + s),
+        [h, m, s] if m < 60 && s < 60 => Some(h * 3600 + m * 60 + s),
+        _ => None,
+    }
+}
+
+fn parse_secs(text: &str, now: DateTime<Local>) -> Result<u64, String> {
+    let text = text.trim();
+    if let Ok(secs) = text.parse::<u64>() {
+        return Ok(secs);
+    }
+    if let Some(secs) = parse_clock(text) {
+        return Ok(secs);
+    }
+    if let Ok(deadline) = DateTime::parse_from_rfc3339(text) {
+        return Ok((deadline.with_timezone(&Local) - now).num_seconds().max(0) as u64);
+    }
+    Duration::parse(text)
+        .map(|d| d.total_secs)
+        .map_err(|_| format!("Can't read a time from '{text}': expected seconds, 4m30s, 4:30 or an RFC 3339 deadline"))
+}
+
+/// Parse what the file holds: its last non-empty line.
+fn parse_reading(contents: &str, now: DateTime<Local>) -> Result<Reading, String> {
+    let line = contents.lines().rev().map(str::trim).find(|l| !l.is_empty()).ok_or("The file is empty")?;
+    let (remaining, total) = match line.split_once('/') {
+        Some((remaining, total)) => (remaining, Some(parse_secs(total, now)?)),
+        None => (line, None),
+    };
+    Ok(Reading { remaining_secs: parse_secs(remaining, now)?, total_secs: total, read_at: Instant::now() })
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+/// Forward the file's contents whenever they change. A FIFO is read a line
+/// at a time and reopened when the writer closes it; a regular file is read
+/// again every `POLL_INTERVAL`. The thread is left blocked on the FIFO when
+/// the command ends, and goes with the process.
+fn spawn_reader(path: PathBuf, tx: mpsc::UnboundedSender<String>) {
+    std::thread::spawn(move || {
+        if is_fifo(&path) {
+            use std::io::BufRead;
+            loop {
+                let Ok(file) = std::fs::File::open(&path) else {
+                    return;
+                };
+                for line in std::io::BufReader::new(file).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    if tx.send(line).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+        let mut last = None;
+        loop {
+            let contents = std::fs::read_to_string(&path).ok();
+            if contents.is_some() && contents != last {
+                if tx.send(contents.clone().unwrap_or_default()).is_err() {
+                    return;
+                }
+                last = contents;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Draw the time from `path` until it reaches zero, or until stopped with
+/// `q`, `x` or Ctrl+C.
+pub async fn run(config: &Config, path: PathBuf, silent: bool, headless: bool, no_input: bool) {
+    let events = EventBus::start(config, silent, false);
+    let input = (!headless && !no_input).then(Input::start);
+    let screen = if headless {
+        None
+    } else {
+        match Screen::enter(input.is_some()) {
+            Ok(screen) => Some(screen.with_graphics(config.graphics)),
+            Err(e) => {
+                eprintln!("Failed to setup terminal: {e}");
+                return;
+            }
+        }
+    };
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    spawn_reader(path.clone(), tx);
+    let title = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    let waiting = format!("Waiting for a time in {}", path.display());
+    let clock_format = config.clock_format();
+    let colors = config.theme.colors_for(TimerContext::Standalone);
+
+    let shutdown = crate::timer::shutdown_signal();
+    tokio::pin!(shutdown);
+    let next_key = || async {
+        match &input {
+            Some(input) => input.next().await,
+            None => std::future::pending().await,
+        }
+    };
+    let mut reading: Option<Reading> = None;
+    let mut error: Option<String> = None;
+    let mut longest = 0;
+    let mut finished = false;
+    // The waiting prompt clears the screen, so it's only drawn when it changes
+    let mut prompt_drawn = false;
+    loop {
+        if let Some(reading) = reading {
+            let remaining_millis = reading.remaining_millis();
+            if remaining_millis == 0 {
+                finished = true;
+                break;
+            }
+            if let Some(screen) = &screen {
+                let total_secs = reading.total_secs.unwrap_or(longest).max(reading.remaining_secs).max(1);
+                let remaining_secs = remaining_millis.div_ceil(1000);
+                let elapsed_millis = (total_secs * 1000).saturating_sub(remaining_millis);
+                let _ = screen.renderer().draw(&DrawParams {
+                    remaining_secs,
+                    total_secs,
+                    elapsed_secs: elapsed_millis / 1000,
+                    elapsed_millis,
+                    remaining_tenths: None,
+                    paused: false,
+                    title: Some(error.as_deref().unwrap_or(&title)),
+                    round_info: None,
+                    context: TimerContext::Standalone,
+                    todo: None,
+                    ack_prompt: None,
+                    task_input: None,
+                    upcoming: None,
+                    session_left: None,
+                    break_debt: None,
+                    footer: None,
+                    keys: false,
+                    flow_available: false,
+                    colors,
+                    flash: false,
+                });
+            }
+        } else if let Some(screen) = &screen
+            && !prompt_drawn
+        {
+            let _ = screen.renderer().draw_prompt(error.as_deref().unwrap_or(&waiting), "[q] quit");
+            prompt_drawn = true;
+        }
+
+        tokio::select! {
+            _ = &mut shutdown => break,
+            contents = rx.recv() => match contents.map(|c| parse_reading(&c, Local::now())) {
+                Some(Ok(read)) => {
+                    if headless {
+                        println!("[{}] {} left", Local::now().format(&clock_format), Duration::from_secs(read.remaining_secs).format_hms());
+                    }
+                    longest = longest.max(read.remaining_secs);
+                    reading = Some(read);
+                    error = None;
+                }
+                Some(Err(e)) => {
+                    if headless {
+                        eprintln!("{e}");
+                    }
+                    error = Some(e);
+                    prompt_drawn = false;
+                }
+                None => {
+                    crate::messages::warn(format!("Stopped reading {}", path.display()));
+                    break;
+                }
+            },
+            event = next_key() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('x') | KeyCode::Char('q') => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    _ => {}
+                },
+                Some(Event::Resize(..)) => prompt_drawn = false,
+                Some(_) => {}
+                None => break,
+            },
+            _ = tokio::time::sleep(config.frame_interval()) => {}
+        }
+    }
+
+    drop(screen);
+    if finished {
+        events.emit(TikEvent::Completed {
+            name: title.clone(),
+            context: TimerContext::Standalone,
+            duration_secs: longest,
+            skipped: false,
+            next: None,
+            entries: Vec::new(),
+        });
+        println!("{title}: time's up");
+    }
+    events.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_seconds_durations_clocks_and_deadlines() {
+        let now = Local::now();
+        let secs = |text: &str| parse_reading(text, now).map(|r| r.remaining_secs);
+        assert_eq!(secs("90"), Ok(90));
+        assert_eq!(secs("4m30s\n"), Ok(270));
+        assert_eq!(secs("4:30"), Ok(270));
+        assert_eq!(secs("1:04:30"), Ok(3870));
+        assert_eq!(secs(&(now + chrono::Duration::seconds(120)).to_rfc3339()), Ok(120));
+        assert_eq!(secs("2020-01-01T00:00:00Z"), Ok(0));
+        assert!(secs("4:75").is_err());
+        assert!(secs("soon").is_err());
+        assert!(secs(" \n").is_err());
+    }
+
+    #[test]
+    fn last_line_wins_and_total_sets_the_bar() {
+        let reading = parse_reading("300\n120/10m\n\n", Local::now()).unwrap();
+        assert_eq!((reading.remaining_secs, reading.total_secs), (120, Some(600)));
+        assert!(parse_reading("120/later", Local::now()).is_err());
+    }
+}
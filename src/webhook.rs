@@ -0,0 +1,185 @@
+use serde::Deserialize;
+use std::io;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::ipc::{Request, Response};
+
+/// Requests larger than this are rejected; real payloads are a few bytes.
+const MAX_BODY: usize = 16 * 1024;
+const MAX_HEADER_LINES: usize = 64;
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The parts of an HTTP request the routes care about.
+#[derive(Debug, Default, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct StartBody {
+    #[serde(alias = "target", alias = "session")]
+    preset: String,
+}
+
+/// Accept webhooks forever. `token`, when set, must be sent as `Authorization: Bearer <token>`.
+pub async fn serve<F>(listener: TcpListener, token: Option<String>, mut handler: F) -> io::Result<()>
+where
+    F: FnMut(Request) -> Response,
+{
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let result = tokio::time::timeout(REQUEST_TIMEOUT, handle_connection(stream, token.as_deref(), &mut handler)).await;
+        if let Ok(Err(e)) = result {
+            eprintln!("Webhook connection error: {e}");
+        }
+    }
+}
+
+async fn handle_connection<F>(mut stream: TcpStream, token: Option<&str>, handler: &mut F) -> io::Result<()>
+where
+    F: FnMut(Request) -> Response,
+{
+    let (status, body) = match read_request(&mut stream).await {
+        Ok(request) => match route(&request, token) {
+            Ok(ipc_request) => {
+                let response = handler(ipc_request);
+                let status = if matches!(response, Response::Error { .. }) { 409 } else { 200 };
+                (status, serde_json::to_string(&response)?)
+            }
+            Err((status, message)) => (status, serde_json::to_string(&Response::Error { message })?),
+        },
+        Err(e) => (400, serde_json::to_string(&Response::Error { message: e.to_string() })?),
+    };
+    write_response(&mut stream, status, "application/json", &body).await
+}
+
+pub async fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        reason(status),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.flush().await
+}
+
+pub async fn read_request(stream: &mut TcpStream) -> io::Result<HttpRequest> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(invalid("Malformed request line"));
+    };
+    let mut request = HttpRequest { method: method.to_string(), path: path.to_string(), ..Default::default() };
+
+    let mut content_length = 0;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        reader.read_line(&mut line).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(invalid("Malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| invalid("Invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            request.authorization = Some(value.to_string());
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(invalid("Request body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    request.body = String::from_utf8(body).map_err(|_| invalid("Request body is not UTF-8"))?;
+    Ok(request)
+}
+
+/// Map an HTTP request to a daemon request, or an error status and message.
+fn route(request: &HttpRequest, token: Option<&str>) -> Result<Request, (u16, String)> {
+    if let Some(token) = token
+        && request.authorization.as_deref() != Some(&format!("Bearer {token}"))
+    {
+        return Err((401, "Missing or invalid bearer token".to_string()));
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    let expected_method = if path == "/status" { "GET" } else { "POST" };
+    let ipc_request = match path {
+        "/start" => {
+            let body: StartBody = serde_json::from_str(&request.body)
+                .map_err(|e| (400, format!("Expected {{\"preset\": \"<name>\"}}: {e}")))?;
+            Request::Start { target: body.preset }
+        }
+        "/stop" => Request::Stop,
+        "/pause" => Request::Pause,
+        "/skip" => Request::Skip,
+        "/status" => Request::Status,
+        _ => return Err((404, format!("Unknown endpoint: {path}"))),
+    };
+    if request.method != expected_method {
+        return Err((405, format!("{path} expects {expected_method}")));
+    }
+    Ok(ipc_request)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(path: &str, body: &str) -> HttpRequest {
+        HttpRequest { method: "POST".to_string(), path: path.to_string(), body: body.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn start_route_reads_preset() {
+        let request = post("/start", r#"{"preset":"pomodoro"}"#);
+        assert_eq!(route(&request, None), Ok(Request::Start { target: "pomodoro".to_string() }));
+    }
+
+    #[test]
+    fn start_route_rejects_missing_preset() {
+        assert_eq!(route(&post("/start", "{}"), None).unwrap_err().0, 400);
+    }
+
+    #[test]
+    fn token_is_required_when_configured() {
+        let mut request = post("/stop", "");
+        assert_eq!(route(&request, Some("s3cret")).unwrap_err().0, 401);
+        request.authorization = Some("Bearer s3cret".to_string());
+        assert_eq!(route(&request, Some("s3cret")), Ok(Request::Stop));
+    }
+
+    #[test]
+    fn status_is_get_and_others_are_post() {
+        let get = HttpRequest { method: "GET".to_string(), path: "/status".to_string(), ..Default::default() };
+        assert_eq!(route(&get, None), Ok(Request::Status));
+        assert_eq!(route(&post("/status", ""), None).unwrap_err().0, 405);
+        assert_eq!(route(&post("/nope", ""), None).unwrap_err().0, 404);
+    }
+}
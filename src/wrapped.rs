@@ -0,0 +1,233 @@
+//! `tik wrapped`: a year in review from the log. Pomodoros, focus time, the
+//! longest run of days with focus in it, the busiest day and the top tasks,
+//! printed in color or as markdown to paste somewhere.
+
+use chrono::{Datelike, Local, NaiveDate, Timelike};
+use crossterm::style::Stylize;
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+
+use crate::config::Config;
+use crate::dates::DateRange;
+use crate::log::{self, LogEntry};
+
+/// How many tasks make the top list.
+const TOP_TASKS: usize = 5;
+
+#[derive(Debug, PartialEq)]
+struct Wrapped {
+    year: i32,
+    pomodoros: usize,
+    focus_secs: u64,
+    days: usize,
+    /// Longest run of consecutive days with focus: first day, last day.
+    streak: Option<(NaiveDate, NaiveDate)>,
+    busiest_day: Option<(NaiveDate, u64)>,
+    /// Task, pomodoros and focus time, most time first.
+    top_tasks: Vec<(String, usize, u64)>,
+    top_tag: Option<(String, u64)>,
+    /// Hour of the day most pomodoros finished in.
+    peak_hour: Option<u32>,
+}
+
+/// The key with the largest value; ties go to the smallest key so the result
+/// doesn't depend on hash order.
+fn max_by_value<K: Ord + Clone>(totals: &HashMap<K, u64>) -> Option<(K, u64)> {
+    totals
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(k, v)| (k.clone(), *v))
+}
+
+fn compute(entries: &[LogEntry], breaks: &HashSet<&str>, year: i32) -> Wrapped {
+    let blocks: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|e| e.completed_at.year() == year && !breaks.contains(e.name.as_str()) && e.skipped_secs == 0)
+        .collect();
+
+    let mut per_day: HashMap<NaiveDate, u64> = HashMap::new();
+    let mut per_tag: HashMap<String, u64> = HashMap::new();
+    let mut per_hour: HashMap<u32, u64> = HashMap::new();
+    let mut per_task: HashMap<&str, (usize, u64)> = HashMap::new();
+    for block in &blocks {
+        *per_day.entry(block.completed_at.date_naive()).or_default() += block.duration_secs;
+        *per_hour.entry(block.completed_at.hour()).or_default() += 1;
+        for tag in &block.tags {
+            *per_tag.entry(tag.clone()).or_default() += block.duration_secs;
+        }
+        if let Some(task) = &block.task {
+            let totals = per_task.entry(task).or_default();
+            totals.0 += 1;
+            totals.1 += block.duration_secs;
+        }
+    }
+
+    let mut days: Vec<NaiveDate> = per_day.keys().copied().collect();
+    days.sort();
+    let streak = crate::stats::longest_streak(&days);
+
+    let mut top_tasks: Vec<(String, usize, u64)> =
+        per_task.into_iter().map(|(task, (count, secs))| (task.to_string(), count, secs)).collect();
+    top_tasks.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+    top_tasks.truncate(TOP_TASKS);
+
+    Wrapped {
+        year,
+        pomodoros: blocks.len(),
+        focus_secs: blocks.iter().map(|b| b.duration_secs).sum(),
+        days: days.len(),
+        streak,
+        busiest_day: max_by_value(&per_day),
+        top_tasks,
+        top_tag: max_by_value(&per_tag),
+        peak_hour: max_by_value(&per_hour).map(|(hour, _)| hour),
+    }
+}
+
+fn plural(n: impl Into<u64>, word: &str) -> String {
+    let n = n.into();
+    format!("{n} {word}{}", if n == 1 { "" } else { "s" })
+}
+
+/// Label and value pairs, in the order they're shown.
+fn facts(wrapped: &Wrapped) -> Vec<(&'static str, String)> {
+    let mut facts = vec![
+        ("Pomodoros", wrapped.pomodoros.to_string()),
+        ("Focus time", log::format_duration_human(wrapped.focus_secs)),
+        ("Days with focus", wrapped.days.to_string()),
+    ];
+    if let Some(streak) = wrapped.streak {
+        facts.push(("Longest streak", crate::stats::streak_line(streak)));
+    }
+    if let Some((day, secs)) = wrapped.busiest_day {
+        facts.push(("Busiest day", format!("{} ({})", day.format("%a %b %-d"), log::format_duration_human(secs))));
+    }
+    if let Some(hour) = wrapped.peak_hour {
+        facts.push(("Favorite hour", format!("{hour:02}:00")));
+    }
+    if let Some((tag, secs)) = &wrapped.top_tag {
+        facts.push(("Top tag", format!("#{tag} ({})", log::format_duration_human(*secs))));
+    }
+    facts
+}
+
+fn task_line((task, count, secs): &(String, usize, u64)) -> String {
+    format!("{task} ({}, {})", plural(*count as u64, "pomodoro"), log::format_duration_human(*secs))
+}
+
+fn terminal_lines(wrapped: &Wrapped, color: bool) -> Vec<String> {
+    let paint = |text: String, style: fn(String) -> crossterm::style::StyledContent<String>| {
+        if color { style(text).to_string() } else { text }
+    };
+    let mut lines = vec![paint(format!("Your {} in tik", wrapped.year), |t| t.bold().magenta()), String::new()];
+    for (label, value) in facts(wrapped) {
+        lines.push(format!("  {label:<16}{}", paint(value, |t| t.bold().cyan())));
+    }
+    if !wrapped.top_tasks.is_empty() {
+        lines.push(String::new());
+        lines.push(paint("Top tasks".to_string(), |t| t.bold()));
+        for (i, task) in wrapped.top_tasks.iter().enumerate() {
+            lines.push(format!("  {}. {}", i + 1, paint(task_line(task), |t| t.yellow())));
+        }
+    }
+    lines
+}
+
+fn markdown(wrapped: &Wrapped) -> String {
+    let mut out = format!("# My {} in tik\n\n", wrapped.year);
+    for (label, value) in facts(wrapped) {
+        out.push_str(&format!("- **{label}:** {value}\n"));
+    }
+    if !wrapped.top_tasks.is_empty() {
+        out.push_str("\n## Top tasks\n\n");
+        for (i, task) in wrapped.top_tasks.iter().enumerate() {
+            out.push_str(&format!("{}. {}\n", i + 1, task_line(task)));
+        }
+    }
+    out
+}
+
+/// Print the review for `year` (this year by default), in color on a
+/// terminal or as markdown.
+pub fn print(config: &Config, year: Option<i32>, as_markdown: bool, range: &DateRange) {
+    let year = year.unwrap_or_else(|| Local::now().year());
+    let mut entries = log::read_entries();
+    entries.retain(|e| range.contains(e.completed_at.date_naive()));
+    let wrapped = compute(&entries, &crate::stats::break_names(config), year);
+    if wrapped.pomodoros == 0 {
+        match range.is_all() {
+            true => println!("No pomodoros logged in {year}."),
+            false => println!("No pomodoros logged in {year}: {}.", range.title().to_lowercase()),
+        }
+        return;
+    }
+    if as_markdown {
+        print!("{}", markdown(&wrapped));
+        return;
+    }
+    for line in terminal_lines(&wrapped, std::io::stdout().is_terminal()) {
+        println!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn block(name: &str, month: u32, day: u32, hour: u32, task: Option<&str>, tags: &[&str]) -> LogEntry {
+        LogEntry {
+            name: name.to_string(),
+            duration_secs: 1500,
+            completed_at: Local.with_ymd_and_hms(2025, month, day, hour, 0, 0).unwrap(),
+            task: task.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Vec<LogEntry> {
+        vec![
+            block("pomodoro", 3, 1, 9, Some("spec"), &["docs"]),
+            block("break", 3, 1, 9, None, &[]),
+            block("pomodoro", 3, 2, 9, Some("spec"), &["docs"]),
+            block("pomodoro", 3, 2, 14, Some("review"), &[]),
+            block("pomodoro", 3, 3, 9, None, &["docs"]),
+            block("pomodoro", 3, 10, 9, Some("review"), &[]),
+            block("pomodoro", 3, 11, 9, None, &[]),
+            LogEntry { completed_at: Local.with_ymd_and_hms(2024, 12, 31, 9, 0, 0).unwrap(), ..block("pomodoro", 1, 1, 9, None, &[]) },
+        ]
+    }
+
+    #[test]
+    fn year_summed_without_breaks() {
+        let wrapped = compute(&sample(), &HashSet::from(["break", "long-break"]), 2025);
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        assert_eq!((wrapped.pomodoros, wrapped.focus_secs, wrapped.days), (6, 6 * 1500, 5));
+        assert_eq!(wrapped.streak, Some((day(1), day(3))));
+        assert_eq!(wrapped.busiest_day, Some((day(2), 3000)));
+        assert_eq!(wrapped.top_tasks, [("review".to_string(), 2, 3000), ("spec".to_string(), 2, 3000)]);
+        assert_eq!(wrapped.top_tag, Some(("docs".to_string(), 4500)));
+        assert_eq!(wrapped.peak_hour, Some(9));
+    }
+
+    #[test]
+    fn markdown_lists_facts_and_tasks() {
+        let wrapped = compute(&sample(), &HashSet::from(["break"]), 2025);
+        let md = markdown(&wrapped);
+        assert!(md.starts_with("# My 2025 in tik\n\n- **Pomodoros:** 6\n- **Focus time:** 2h 30m\n"));
+        assert!(md.contains("- **Longest streak:** 3 days (Mar 1 to Mar 3)\n"));
+        assert!(md.contains("- **Busiest day:** Sun Mar 2 (50m)\n"));
+        assert!(md.ends_with("## Top tasks\n\n1. review (2 pomodoros, 50m)\n2. spec (2 pomodoros, 50m)\n"));
+    }
+
+    #[test]
+    fn plain_without_color() {
+        let wrapped = compute(&sample(), &HashSet::new(), 2024);
+        let lines = terminal_lines(&wrapped, false);
+        assert_eq!(lines[0], "Your 2024 in tik");
+        assert_eq!(lines[2], "  Pomodoros       1");
+        assert_eq!(lines[5], "  Longest streak  1 day (Dec 31)");
+        assert!(terminal_lines(&wrapped, true)[0].contains("\u{1b}["));
+    }
+}